@@ -0,0 +1,17 @@
+-- 蔵書の全文検索用の列を追加
+-- 日本語の形態素解析器は標準で提供されていないため、`simple`設定で語を分割する。
+-- ISBNはハイフンなどを取り除いた値も検索できるように、正規化した値も含める。
+ALTER TABLE books
+    ADD COLUMN search_vector TSVECTOR GENERATED ALWAYS AS (
+        setweight(to_tsvector('simple', title), 'A')
+        || setweight(to_tsvector('simple', author), 'B')
+        || setweight(to_tsvector('simple', isbn), 'A')
+        || setweight(to_tsvector('simple', regexp_replace(isbn, '[^0-9Xx]', '', 'g')), 'A')
+        || setweight(to_tsvector('simple', description), 'C')
+    ) STORED;
+
+-- 全文検索用のGINインデックスを作成
+CREATE INDEX IF NOT EXISTS books_search_vector_idx ON books USING GIN (search_vector);
+
+-- 所有者による絞り込み用のインデックスを作成
+CREATE INDEX IF NOT EXISTS books_user_id_idx ON books (user_id);
@@ -0,0 +1,12 @@
+-- 貸出に返却期限を追加
+-- 既存の貸出の返却期限は、貸出日時から標準の貸出期間である14日後とする。
+ALTER TABLE checkouts ADD COLUMN IF NOT EXISTS due_at TIMESTAMP(3) WITH TIME ZONE;
+UPDATE checkouts SET due_at = checked_out_at + INTERVAL '14 days' WHERE due_at IS NULL;
+ALTER TABLE checkouts ALTER COLUMN due_at SET NOT NULL;
+
+ALTER TABLE returned_checkouts ADD COLUMN IF NOT EXISTS due_at TIMESTAMP(3) WITH TIME ZONE;
+UPDATE returned_checkouts SET due_at = checked_out_at + INTERVAL '14 days' WHERE due_at IS NULL;
+ALTER TABLE returned_checkouts ALTER COLUMN due_at SET NOT NULL;
+
+-- 延滞している貸出を検索するためのインデックスを作成
+CREATE INDEX IF NOT EXISTS checkouts_due_at_idx ON checkouts (due_at);
@@ -0,0 +1,2 @@
+ALTER TABLE returned_checkouts DROP COLUMN IF EXISTS renewal_count;
+ALTER TABLE checkouts DROP COLUMN IF EXISTS renewal_count;
@@ -0,0 +1,3 @@
+-- 貸出に延長回数を追加
+ALTER TABLE checkouts ADD COLUMN IF NOT EXISTS renewal_count INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE returned_checkouts ADD COLUMN IF NOT EXISTS renewal_count INTEGER NOT NULL DEFAULT 0;
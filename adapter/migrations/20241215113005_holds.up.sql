@@ -0,0 +1,21 @@
+-- 蔵書予約テーブル
+-- 予約は作成日時の順に並んだ待ち行列を構成する。
+-- 蔵書が返却されると、待ち行列の先頭の予約に取り置き開始日時(ready_at)と
+-- 取り置き期限(expires_at)が設定され、期限までは予約したユーザーのみが借りられる。
+CREATE TABLE IF NOT EXISTS holds (
+    hold_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    book_id UUID NOT NULL,
+    user_id UUID NOT NULL,
+    created_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+    ready_at TIMESTAMP(3) WITH TIME ZONE,
+    expires_at TIMESTAMP(3) WITH TIME ZONE,
+    CONSTRAINT uq_holds_book_id_user_id UNIQUE (book_id, user_id),
+    CONSTRAINT fk_holds_book_id__books_book_id FOREIGN KEY (book_id) REFERENCES books (book_id)
+        ON UPDATE CASCADE
+        ON DELETE CASCADE,
+    CONSTRAINT fk_holds_user_id__users_user_id FOREIGN KEY (user_id) REFERENCES users (user_id)
+        ON UPDATE CASCADE
+        ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS holds_book_id_created_at_idx ON holds (book_id, created_at);
@@ -0,0 +1,2 @@
+DROP TRIGGER IF EXISTS loan_policies_updated_at_trigger ON loan_policies;
+DROP TABLE IF EXISTS loan_policies;
@@ -0,0 +1,20 @@
+-- ロールごとの貸出ポリシーテーブル
+-- ロールに貸出ポリシーが登録されていない場合、貸出期間と延長回数の上限は設定値を使用し、
+-- 同時に借りられる蔵書の数は制限しない。
+CREATE TABLE IF NOT EXISTS loan_policies (
+    role_id UUID PRIMARY KEY,
+    -- 同時に借りられる蔵書の数の上限 (NULLの場合は制限しない)
+    max_checkouts INTEGER CHECK (max_checkouts >= 0),
+    loan_period_days INTEGER NOT NULL CHECK (loan_period_days > 0),
+    max_renewals INTEGER NOT NULL CHECK (max_renewals >= 0),
+    created_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+    updated_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+    CONSTRAINT fk_loan_policies_role_id__roles_role_id FOREIGN KEY (role_id) REFERENCES roles (role_id)
+        ON UPDATE CASCADE
+        ON DELETE CASCADE
+);
+
+-- loan_policiesテーブルのupdated_at列を自動更新するトリガーを登録
+CREATE TRIGGER loan_policies_updated_at_trigger
+    BEFORE UPDATE ON loan_policies FOR EACH ROW
+    EXECUTE PROCEDURE set_updated_at();
@@ -0,0 +1,2 @@
+ALTER TABLE returned_checkouts DROP CONSTRAINT IF EXISTS returned_checkouts_outcome_check;
+ALTER TABLE returned_checkouts DROP COLUMN IF EXISTS outcome;
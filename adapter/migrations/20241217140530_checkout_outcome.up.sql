@@ -0,0 +1,4 @@
+-- 貸出の終わり方 (返却、紛失、破損) を返却済みの貸出に追加
+ALTER TABLE returned_checkouts ADD COLUMN IF NOT EXISTS outcome VARCHAR(32) NOT NULL DEFAULT 'Returned';
+ALTER TABLE returned_checkouts ADD CONSTRAINT returned_checkouts_outcome_check
+    CHECK (outcome IN ('Returned', 'Lost', 'Damaged'));
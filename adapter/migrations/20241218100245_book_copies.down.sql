@@ -0,0 +1,19 @@
+ALTER TABLE returned_checkouts
+    DROP CONSTRAINT IF EXISTS fk_returned_checkouts_copy_id__book_copies_copy_id;
+ALTER TABLE returned_checkouts DROP COLUMN IF EXISTS copy_id;
+
+-- 同じ蔵書の複数の複本が貸出中の場合、最初の貸出以外は元のスキーマで表現できないため削除する。
+DELETE FROM checkouts c
+WHERE EXISTS (
+    SELECT 1 FROM checkouts o
+    WHERE o.book_id = c.book_id
+        AND (o.checked_out_at, o.checkout_id) < (c.checked_out_at, c.checkout_id)
+);
+DROP INDEX IF EXISTS checkouts_book_id_idx;
+ALTER TABLE checkouts DROP CONSTRAINT IF EXISTS fk_checkouts_copy_id__book_copies_copy_id;
+ALTER TABLE checkouts DROP CONSTRAINT IF EXISTS uq_checkouts_copy_id;
+ALTER TABLE checkouts ADD CONSTRAINT checkouts_book_id_key UNIQUE (book_id);
+ALTER TABLE checkouts DROP COLUMN IF EXISTS copy_id;
+
+DROP TRIGGER IF EXISTS book_copies_updated_at_trigger ON book_copies;
+DROP TABLE IF EXISTS book_copies;
@@ -0,0 +1,48 @@
+-- 蔵書の複本テーブル
+-- booksテーブルは書誌情報を、book_copiesテーブルは書誌情報に対応する現物を記録する。
+-- 紛失した複本(condition = 'Lost')は貸出できず、複本の数にも含めない。
+CREATE TABLE IF NOT EXISTS book_copies (
+    copy_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    book_id UUID NOT NULL,
+    barcode VARCHAR(255) NOT NULL UNIQUE,
+    condition VARCHAR(32) NOT NULL DEFAULT 'Good'
+        CHECK (condition IN ('Good', 'Fair', 'Damaged', 'Lost')),
+    location VARCHAR(255) NOT NULL DEFAULT '',
+    created_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+    updated_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+    CONSTRAINT fk_book_copies_book_id__books_book_id FOREIGN KEY (book_id) REFERENCES books (book_id)
+        ON UPDATE CASCADE
+        ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS book_copies_book_id_idx ON book_copies (book_id);
+
+-- book_copiesテーブルのupdated_at列を自動更新するトリガーを登録
+CREATE TRIGGER book_copies_updated_at_trigger
+    BEFORE UPDATE ON book_copies FOR EACH ROW
+    EXECUTE PROCEDURE set_updated_at();
+
+-- 既存の蔵書ごとに複本を1冊登録する。バーコードには蔵書IDを使用する。
+INSERT INTO book_copies (book_id, barcode)
+SELECT book_id, REPLACE(book_id::text, '-', '') FROM books;
+
+-- 貸出は蔵書ではなく複本を対象とする。
+-- 同じ蔵書の複本は同時に貸出できるため、蔵書IDの一意制約を複本IDの一意制約に置き換える。
+ALTER TABLE checkouts ADD COLUMN IF NOT EXISTS copy_id UUID;
+UPDATE checkouts c SET copy_id = bc.copy_id FROM book_copies bc WHERE c.book_id = bc.book_id;
+ALTER TABLE checkouts ALTER COLUMN copy_id SET NOT NULL;
+ALTER TABLE checkouts DROP CONSTRAINT IF EXISTS checkouts_book_id_key;
+ALTER TABLE checkouts ADD CONSTRAINT uq_checkouts_copy_id UNIQUE (copy_id);
+ALTER TABLE checkouts ADD CONSTRAINT fk_checkouts_copy_id__book_copies_copy_id
+    FOREIGN KEY (copy_id) REFERENCES book_copies (copy_id)
+    ON UPDATE CASCADE
+    ON DELETE CASCADE;
+CREATE INDEX IF NOT EXISTS checkouts_book_id_idx ON checkouts (book_id);
+
+-- 返却済みの貸出は、複本を削除しても履歴として残す。
+ALTER TABLE returned_checkouts ADD COLUMN IF NOT EXISTS copy_id UUID;
+UPDATE returned_checkouts rc SET copy_id = bc.copy_id FROM book_copies bc WHERE rc.book_id = bc.book_id;
+ALTER TABLE returned_checkouts ADD CONSTRAINT fk_returned_checkouts_copy_id__book_copies_copy_id
+    FOREIGN KEY (copy_id) REFERENCES book_copies (copy_id)
+    ON UPDATE CASCADE
+    ON DELETE SET NULL;
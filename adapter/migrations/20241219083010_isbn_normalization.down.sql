@@ -0,0 +1,2 @@
+-- 正規化する前のISBNは記録していないため、インデックスのみを削除する。
+DROP INDEX IF EXISTS books_isbn_idx;
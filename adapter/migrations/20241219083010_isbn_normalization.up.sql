@@ -0,0 +1,25 @@
+-- 登録済みのISBNからハイフンと空白を取り除く
+-- ISBNの形式でない値は、蔵書を更新するときに正しいISBNを指定してもらうため、そのまま残す。
+UPDATE books
+SET isbn = upper(regexp_replace(isbn, '[-\s]', '', 'g'))
+WHERE upper(regexp_replace(isbn, '[-\s]', '', 'g')) ~ '^([0-9]{13}|[0-9]{9}[0-9X])$';
+
+-- チェックディジットが正しいISBN-10は、先頭に978を付け、チェックディジットを計算し直して
+-- ISBN-13に変換する。チェックディジットが正しくない値は、変換せずにそのまま残す。
+UPDATE books
+SET isbn = '978' || left(isbn, 9) || (
+    (
+        10 - (
+            SELECT SUM(substr('978' || left(isbn, 9), i, 1)::INTEGER * CASE WHEN i % 2 = 1 THEN 1 ELSE 3 END)
+            FROM generate_series(1, 12) AS i
+        ) % 10
+    ) % 10
+)::TEXT
+WHERE isbn ~ '^[0-9]{9}[0-9X]$'
+    AND (
+        SELECT SUM(CASE WHEN substr(isbn, i, 1) = 'X' THEN 10 ELSE substr(isbn, i, 1)::INTEGER END * (11 - i))
+        FROM generate_series(1, 10) AS i
+    ) % 11 = 0;
+
+-- 同じISBNを持つ蔵書を探すためのインデックスを作成
+CREATE INDEX IF NOT EXISTS books_isbn_idx ON books (isbn);
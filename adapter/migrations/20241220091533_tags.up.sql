@@ -0,0 +1,33 @@
+-- 蔵書を分類するタグ
+-- タグ名は大文字と小文字を区別せずに一意とする。
+CREATE TABLE IF NOT EXISTS tags (
+    tag_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(64) NOT NULL,
+    created_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+    updated_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS tags_lower_name_key ON tags (lower(name));
+
+CREATE TRIGGER tags_updated_at_trigger
+    BEFORE UPDATE ON tags FOR EACH ROW
+    EXECUTE PROCEDURE set_updated_at();
+
+-- 蔵書に付けたタグ
+CREATE TABLE IF NOT EXISTS book_tags (
+    book_id UUID NOT NULL,
+    tag_id UUID NOT NULL,
+    created_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+    PRIMARY KEY (book_id, tag_id),
+    CONSTRAINT fk_book_tags_book_id__books_book_id
+        FOREIGN KEY (book_id) REFERENCES books (book_id)
+        ON UPDATE CASCADE
+        ON DELETE CASCADE,
+    CONSTRAINT fk_book_tags_tag_id__tags_tag_id
+        FOREIGN KEY (tag_id) REFERENCES tags (tag_id)
+        ON UPDATE CASCADE
+        ON DELETE CASCADE
+);
+
+-- タグによる絞り込み用のインデックスを作成
+CREATE INDEX IF NOT EXISTS book_tags_tag_id_idx ON book_tags (tag_id);
@@ -0,0 +1,20 @@
+-- 蔵書の表紙画像
+--
+-- 画像自体はBlobStoreに保存し、このテーブルには画像の形式とETagのみを記録する。
+CREATE TABLE IF NOT EXISTS book_covers (
+    book_id UUID PRIMARY KEY,
+    content_type VARCHAR(32) NOT NULL,
+    thumbnail_content_type VARCHAR(32) NOT NULL,
+    etag VARCHAR(64) NOT NULL,
+    created_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+    updated_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+
+    CONSTRAINT fk_book_covers_book_id__books_book_id
+        FOREIGN KEY (book_id) REFERENCES books (book_id)
+        ON UPDATE CASCADE
+        ON DELETE CASCADE
+);
+
+CREATE TRIGGER book_covers_updated_at_trigger
+    BEFORE UPDATE ON book_covers FOR EACH ROW
+    EXECUTE PROCEDURE set_updated_at();
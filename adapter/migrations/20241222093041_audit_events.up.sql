@@ -0,0 +1,35 @@
+-- 変更を記録する監査ログ
+--
+-- 操作したユーザーが削除されても記録を残すため、actor_idには外部キー制約を付けない。
+-- entity_idは、ロール名で識別する貸出ポリシーも記録できるように文字列とする。
+CREATE TABLE IF NOT EXISTS audit_events (
+    audit_event_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    actor_id UUID,
+    action VARCHAR(64) NOT NULL,
+    entity_type VARCHAR(32) NOT NULL,
+    entity_id VARCHAR(255) NOT NULL,
+    before JSONB,
+    after JSONB,
+    occurred_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3)
+);
+
+-- 操作したユーザー、対象及び日時による絞り込み用のインデックスを作成
+CREATE INDEX IF NOT EXISTS audit_events_occurred_at_idx ON audit_events (occurred_at);
+CREATE INDEX IF NOT EXISTS audit_events_actor_id_idx ON audit_events (actor_id, occurred_at);
+CREATE INDEX IF NOT EXISTS audit_events_entity_idx
+    ON audit_events (entity_type, entity_id, occurred_at);
+
+-- 監査ログは追記のみを許可し、更新と削除を拒否する関数
+CREATE OR REPLACE FUNCTION reject_audit_event_modification() RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'audit events are append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER audit_events_append_only_trigger
+    BEFORE UPDATE OR DELETE ON audit_events FOR EACH ROW
+    EXECUTE PROCEDURE reject_audit_event_modification();
+
+CREATE TRIGGER audit_events_no_truncate_trigger
+    BEFORE TRUNCATE ON audit_events FOR EACH STATEMENT
+    EXECUTE PROCEDURE reject_audit_event_modification();
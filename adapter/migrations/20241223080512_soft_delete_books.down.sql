@@ -0,0 +1,6 @@
+DROP INDEX IF EXISTS books_deleted_at_idx;
+
+-- ゴミ箱にある蔵書は、論理削除を導入する前と同様に削除する。
+DELETE FROM books WHERE deleted_at IS NOT NULL;
+
+ALTER TABLE books DROP COLUMN IF EXISTS deleted_at;
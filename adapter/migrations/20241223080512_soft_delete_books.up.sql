@@ -0,0 +1,11 @@
+-- 蔵書の論理削除
+--
+-- 蔵書を削除したときは`deleted_at`に削除した日時を記録し、行は残す。
+-- 行を削除すると、貸出履歴などの蔵書に関連する行も削除されるため、
+-- 行を削除するのは、ゴミ箱にある蔵書を完全に削除したときのみとする。
+ALTER TABLE books ADD COLUMN deleted_at TIMESTAMP(3) WITH TIME ZONE;
+
+-- ゴミ箱にある蔵書を、削除した日時の順に一覧する。
+CREATE INDEX IF NOT EXISTS books_deleted_at_idx
+    ON books (deleted_at DESC)
+    WHERE deleted_at IS NOT NULL;
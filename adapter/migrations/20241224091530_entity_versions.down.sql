@@ -0,0 +1,7 @@
+DROP TRIGGER IF EXISTS users_version_trigger ON users;
+ALTER TABLE users DROP COLUMN IF EXISTS version;
+
+DROP TRIGGER IF EXISTS books_version_trigger ON books;
+ALTER TABLE books DROP COLUMN IF EXISTS version;
+
+DROP FUNCTION IF EXISTS increment_version;
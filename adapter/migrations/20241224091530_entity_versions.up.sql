@@ -0,0 +1,22 @@
+-- 楽観的排他制御に使用するバージョン
+--
+-- 行を更新するたびに`version`を1つ増やす。クライアントが取得した時点から
+-- バージョンが変わっている場合、その行は他のリクエストによって更新されている。
+CREATE OR REPLACE FUNCTION increment_version() RETURNS TRIGGER AS $$
+BEGIN
+    NEW.version := OLD.version + 1;
+    return NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+ALTER TABLE books ADD COLUMN version BIGINT NOT NULL DEFAULT 1;
+
+CREATE TRIGGER books_version_trigger
+    BEFORE UPDATE ON books FOR EACH ROW
+    EXECUTE PROCEDURE increment_version();
+
+ALTER TABLE users ADD COLUMN version BIGINT NOT NULL DEFAULT 1;
+
+CREATE TRIGGER users_version_trigger
+    BEFORE UPDATE ON users FOR EACH ROW
+    EXECUTE PROCEDURE increment_version();
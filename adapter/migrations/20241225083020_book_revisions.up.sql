@@ -0,0 +1,22 @@
+-- 蔵書の更新履歴
+--
+-- 蔵書を更新するたびに、更新する前の内容を記録する。
+-- revision_numberは蔵書ごとに1から始まる連番で、revised_byとrevised_atはその内容を更新したユーザーと日時である。
+-- 更新したユーザーが削除されても履歴を残すため、監査ログと同様にrevised_byには外部キー制約を付けない。
+CREATE TABLE IF NOT EXISTS book_revisions (
+    book_revision_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    book_id UUID NOT NULL,
+    revision_number INTEGER NOT NULL,
+    title VARCHAR(255) NOT NULL,
+    author VARCHAR(255) NOT NULL,
+    isbn VARCHAR(255) NOT NULL,
+    description VARCHAR(1024) NOT NULL,
+    revised_by UUID NOT NULL,
+    revised_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+    CONSTRAINT book_revisions_book_id_revision_number_key
+        UNIQUE (book_id, revision_number),
+    CONSTRAINT fk_book_revisions_book_id__books_book_id
+        FOREIGN KEY (book_id) REFERENCES books (book_id)
+        ON UPDATE CASCADE
+        ON DELETE CASCADE
+);
@@ -0,0 +1,8 @@
+ALTER TABLE books
+    DROP CONSTRAINT fk_books_user_id__users_user_id,
+    ADD CONSTRAINT fk_books_user_id__users_user_id
+        FOREIGN KEY (user_id) REFERENCES users (user_id)
+        ON UPDATE CASCADE
+        ON DELETE CASCADE;
+
+DROP TABLE IF EXISTS book_transfers;
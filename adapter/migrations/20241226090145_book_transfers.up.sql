@@ -0,0 +1,35 @@
+-- 蔵書の所有権の譲渡
+--
+-- 蔵書の所有者が譲渡を申し込み、譲渡先のユーザーが承諾すると所有者が変わる。
+-- 承諾されるか、辞退または取り消されるまでの申し込みを記録し、蔵書ごとに1件のみとする。
+CREATE TABLE IF NOT EXISTS book_transfers (
+    book_transfer_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    book_id UUID NOT NULL UNIQUE,
+    from_user_id UUID NOT NULL,
+    to_user_id UUID NOT NULL,
+    created_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+    CONSTRAINT fk_book_transfers_book_id__books_book_id
+        FOREIGN KEY (book_id) REFERENCES books (book_id)
+        ON UPDATE CASCADE
+        ON DELETE CASCADE,
+    CONSTRAINT fk_book_transfers_from_user_id__users_user_id
+        FOREIGN KEY (from_user_id) REFERENCES users (user_id)
+        ON UPDATE CASCADE
+        ON DELETE CASCADE,
+    CONSTRAINT fk_book_transfers_to_user_id__users_user_id
+        FOREIGN KEY (to_user_id) REFERENCES users (user_id)
+        ON UPDATE CASCADE
+        ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS book_transfers_to_user_id_idx ON book_transfers (to_user_id);
+CREATE INDEX IF NOT EXISTS book_transfers_from_user_id_idx ON book_transfers (from_user_id);
+
+-- ユーザーを削除したときに所有する蔵書が削除されないように、
+-- 所有する蔵書を他のユーザーに譲渡してからでなければ削除できないようにする。
+ALTER TABLE books
+    DROP CONSTRAINT fk_books_user_id__users_user_id,
+    ADD CONSTRAINT fk_books_user_id__users_user_id
+        FOREIGN KEY (user_id) REFERENCES users (user_id)
+        ON UPDATE CASCADE
+        ON DELETE RESTRICT;
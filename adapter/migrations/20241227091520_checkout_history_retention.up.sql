@@ -0,0 +1,3 @@
+-- 返却した貸出の履歴を残すかどうかのユーザーごとの設定
+-- 残さない場合、返却した貸出はreturned_checkoutsテーブルに記録しない。
+ALTER TABLE users ADD COLUMN IF NOT EXISTS retain_checkout_history BOOLEAN NOT NULL DEFAULT TRUE;
@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS reviewable_books;
+DROP TRIGGER IF EXISTS reviews_updated_at_trigger ON reviews;
+DROP TABLE IF EXISTS reviews;
@@ -0,0 +1,46 @@
+-- 蔵書のレビュー
+-- ユーザーは1冊の蔵書につき1件だけレビューを書ける。評価は1から5の星の数とし、本文は省略できる。
+-- レビューを書けるのは、その蔵書を借りて返却したことのあるユーザーのみとする。
+CREATE TABLE IF NOT EXISTS reviews (
+    review_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    book_id UUID NOT NULL,
+    user_id UUID NOT NULL,
+    rating INTEGER NOT NULL,
+    comment TEXT,
+    created_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+    updated_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+    CONSTRAINT uq_reviews_book_id_user_id UNIQUE (book_id, user_id),
+    CONSTRAINT ck_reviews_rating CHECK (rating BETWEEN 1 AND 5),
+    CONSTRAINT fk_reviews_book_id__books_book_id FOREIGN KEY (book_id) REFERENCES books (book_id)
+        ON UPDATE CASCADE
+        ON DELETE CASCADE,
+    CONSTRAINT fk_reviews_user_id__users_user_id FOREIGN KEY (user_id) REFERENCES users (user_id)
+        ON UPDATE CASCADE
+        ON DELETE CASCADE
+);
+
+CREATE TRIGGER reviews_updated_at_trigger
+    BEFORE UPDATE ON reviews FOR EACH ROW
+    EXECUTE PROCEDURE set_updated_at();
+
+-- まだレビューを書いていない、レビューを書ける蔵書
+-- 貸出の履歴を残さない設定のユーザーも返却した蔵書のレビューを書けるように、
+-- 返却した貸出の履歴とは別に記録する。借りた蔵書の一覧として残らないように、
+-- レビューを書いたときと、貸出の履歴を残さない設定にしたときに削除する。
+CREATE TABLE IF NOT EXISTS reviewable_books (
+    user_id UUID NOT NULL,
+    book_id UUID NOT NULL,
+    PRIMARY KEY (user_id, book_id),
+    CONSTRAINT fk_reviewable_books_user_id__users_user_id FOREIGN KEY (user_id)
+        REFERENCES users (user_id)
+        ON UPDATE CASCADE
+        ON DELETE CASCADE,
+    CONSTRAINT fk_reviewable_books_book_id__books_book_id FOREIGN KEY (book_id)
+        REFERENCES books (book_id)
+        ON UPDATE CASCADE
+        ON DELETE CASCADE
+);
+
+INSERT INTO reviewable_books (user_id, book_id)
+SELECT DISTINCT user_id, book_id FROM returned_checkouts
+ON CONFLICT DO NOTHING;
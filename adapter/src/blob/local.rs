@@ -0,0 +1,126 @@
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use kernel::repository::blob::BlobStore;
+use shared::config::BlobStoreConfig;
+use shared::error::{AppError, AppResult};
+
+/// ローカルファイルシステムにデータを保存する`BlobStore`
+///
+/// キーを`/`で区切ったものを、ルートディレクトリからの相対パスとして扱う。
+pub struct LocalBlobStore {
+    root: PathBuf,
+}
+
+impl LocalBlobStore {
+    pub fn new(config: &BlobStoreConfig) -> Self {
+        Self {
+            root: PathBuf::from(&config.local_root),
+        }
+    }
+
+    /// キーをファイルのパスに変換する。ルートディレクトリの外を指すキーは受け付けない。
+    fn path(&self, key: &str) -> AppResult<PathBuf> {
+        let mut path = self.root.clone();
+        for segment in key.split('/') {
+            if segment.is_empty() || segment == "." || segment == ".." || segment.contains('\\') {
+                return Err(AppError::UnprocessableEntity(format!(
+                    "invalid blob key: {key}"
+                )));
+            }
+            path.push(segment);
+        }
+        Ok(path)
+    }
+}
+
+#[async_trait]
+impl BlobStore for LocalBlobStore {
+    async fn put(&self, key: &str, data: Vec<u8>) -> AppResult<()> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        // 読み出し中のファイルを壊さないように、一時ファイルに書き込んでから置き換える。
+        let temporary = path.with_extension(format!("{}.tmp", Uuid::new_v4().simple()));
+        tokio::fs::write(&temporary, data).await?;
+        if let Err(e) = tokio::fs::rename(&temporary, &path).await {
+            let _ = tokio::fs::remove_file(&temporary).await;
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> AppResult<Option<Vec<u8>>> {
+        match tokio::fs::read(self.path(key)?).await {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> AppResult<()> {
+        match tokio::fs::remove_file(self.path(key)?).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> AppResult<()> {
+        match tokio::fs::remove_dir_all(self.path(prefix)?).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_local_blob_store() -> anyhow::Result<()> {
+        let root = std::env::temp_dir().join(format!("blob-store-{}", Uuid::new_v4()));
+        let store = LocalBlobStore::new(&BlobStoreConfig {
+            local_root: root.to_string_lossy().into_owned(),
+        });
+
+        assert_eq!(store.get("covers/a/original").await?, None);
+        store.put("covers/a/original", b"first".to_vec()).await?;
+        store.put("covers/a/original", b"second".to_vec()).await?;
+        assert_eq!(
+            store.get("covers/a/original").await?,
+            Some(b"second".to_vec())
+        );
+
+        store.delete("covers/a/original").await?;
+        assert_eq!(store.get("covers/a/original").await?, None);
+        // 存在しないデータを削除してもエラーにならない
+        store.delete("covers/a/original").await?;
+
+        store.put("covers/b/original", b"original".to_vec()).await?;
+        store
+            .put("covers/b/thumbnail", b"thumbnail".to_vec())
+            .await?;
+        store.put("covers/bb/original", b"other".to_vec()).await?;
+        store.delete_prefix("covers/b").await?;
+        assert_eq!(store.get("covers/b/original").await?, None);
+        assert_eq!(store.get("covers/b/thumbnail").await?, None);
+        assert_eq!(
+            store.get("covers/bb/original").await?,
+            Some(b"other".to_vec())
+        );
+        store.delete_prefix("covers/b").await?;
+
+        // ルートディレクトリの外を指すキーは受け付けない
+        for key in ["../escape", "covers//a", "/etc/passwd", "covers/./a", ""] {
+            assert!(store.put(key, vec![]).await.is_err(), "{key}");
+        }
+
+        tokio::fs::remove_dir_all(&root).await?;
+        Ok(())
+    }
+}
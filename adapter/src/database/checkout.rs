@@ -1,7 +1,10 @@
+use std::str::FromStr;
+
 use sqlx::types::chrono::{DateTime, Utc};
 
-use kernel::model::checkout::{Checkout, CheckoutBook};
-use kernel::model::id::{BookId, CheckoutId, UserId};
+use kernel::model::checkout::{Checkout, CheckoutBook, CheckoutOutcome};
+use kernel::model::id::{BookCopyId, BookId, CheckoutId, UserId};
+use shared::error::AppError;
 
 pub struct CheckoutStateRow {
     pub book_id: BookId,
@@ -12,8 +15,11 @@ pub struct CheckoutStateRow {
 pub struct CheckoutRow {
     pub checkout_id: CheckoutId,
     pub book_id: BookId,
+    pub copy_id: BookCopyId,
     pub user_id: UserId,
     pub checked_out_at: DateTime<Utc>,
+    pub due_at: DateTime<Utc>,
+    pub renewal_count: i32,
     pub title: String,
     pub author: String,
     pub isbn: String,
@@ -30,37 +36,60 @@ impl From<CheckoutRow> for Checkout {
         Self {
             id: value.checkout_id,
             book,
+            copy_id: Some(value.copy_id),
             checked_out_by: value.user_id,
             checked_out_at: value.checked_out_at,
+            due_at: value.due_at,
+            renewal_count: value.renewal_count,
             returned_at: None,
+            outcome: None,
         }
     }
 }
 
-pub struct ReturnedCheckoutRow {
+/// 未返却の貸出と返却済みの貸出をまとめて取得した行
+pub struct CheckoutListRow {
+    pub total: i64,
     pub checkout_id: CheckoutId,
     pub book_id: BookId,
+    pub copy_id: Option<BookCopyId>,
     pub user_id: UserId,
     pub checked_out_at: DateTime<Utc>,
-    pub returned_at: DateTime<Utc>,
+    pub due_at: DateTime<Utc>,
+    pub renewal_count: i32,
+    /// 返却日時 (未返却の場合は`None`)
+    pub returned_at: Option<DateTime<Utc>>,
+    /// 貸出の終わり方 (未返却の場合は`None`)
+    pub outcome: Option<String>,
     pub title: String,
     pub author: String,
     pub isbn: String,
 }
 
-impl From<ReturnedCheckoutRow> for Checkout {
-    fn from(value: ReturnedCheckoutRow) -> Self {
-        let ReturnedCheckoutRow {
+impl TryFrom<CheckoutListRow> for Checkout {
+    type Error = AppError;
+
+    fn try_from(value: CheckoutListRow) -> Result<Self, Self::Error> {
+        let CheckoutListRow {
             checkout_id,
             book_id,
+            copy_id,
             user_id,
             checked_out_at,
+            due_at,
+            renewal_count,
             returned_at,
+            outcome,
             title,
             author,
             isbn,
+            ..
         } = value;
-        Self {
+        let outcome = outcome
+            .map(|outcome| CheckoutOutcome::from_str(outcome.as_str()))
+            .transpose()
+            .map_err(|e| AppError::ConversionEntityError(e.to_string()))?;
+        Ok(Self {
             id: checkout_id,
             book: CheckoutBook {
                 book_id,
@@ -68,9 +97,13 @@ impl From<ReturnedCheckoutRow> for Checkout {
                 author,
                 isbn,
             },
+            copy_id,
             checked_out_by: user_id,
             checked_out_at,
-            returned_at: Some(returned_at),
-        }
+            due_at,
+            renewal_count,
+            returned_at,
+            outcome,
+        })
     }
 }
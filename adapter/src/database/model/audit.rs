@@ -0,0 +1,49 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+
+use kernel::model::audit::{AuditAction, AuditEntityType, AuditEvent};
+use kernel::model::id::{AuditEventId, UserId};
+use shared::error::AppError;
+
+pub struct AuditEventRow {
+    pub total: i64,
+    pub audit_event_id: AuditEventId,
+    pub actor_id: Option<UserId>,
+    pub action: String,
+    pub entity_type: String,
+    pub entity_id: String,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+impl TryFrom<AuditEventRow> for AuditEvent {
+    type Error = AppError;
+
+    fn try_from(value: AuditEventRow) -> Result<Self, Self::Error> {
+        let AuditEventRow {
+            audit_event_id,
+            actor_id,
+            action,
+            entity_type,
+            entity_id,
+            before,
+            after,
+            occurred_at,
+            ..
+        } = value;
+        Ok(Self {
+            id: audit_event_id,
+            actor: actor_id,
+            action: AuditAction::from_str(&action)
+                .map_err(|e| AppError::ConversionEntityError(e.to_string()))?,
+            entity_type: AuditEntityType::from_str(&entity_type)
+                .map_err(|e| AppError::ConversionEntityError(e.to_string()))?,
+            entity_id,
+            before,
+            after,
+            occurred_at,
+        })
+    }
+}
@@ -1,8 +1,14 @@
+use std::str::FromStr;
+
 use chrono::{DateTime, Utc};
 
-use kernel::model::book::{Book, Checkout};
-use kernel::model::id::{BookId, CheckoutId, UserId};
-use kernel::model::user::{BookOwner, CheckoutUser};
+use kernel::model::book::{
+    Book, BookCopy, BookCopyCondition, BookFields, BookRevision, Checkout, DeletedBook,
+};
+use kernel::model::id::{BookCopyId, BookId, CheckoutId, TagId, UserId};
+use kernel::model::tag::Tag;
+use kernel::model::user::{BookOwner, BookReviser, CheckoutUser};
+use shared::error::AppError;
 
 pub struct BookRow {
     pub book_id: BookId,
@@ -12,10 +18,16 @@ pub struct BookRow {
     pub description: String,
     pub owned_by: UserId,
     pub owner_name: String,
+    pub total_copies: i64,
+    pub available_copies: i64,
+    pub cover_etag: Option<String>,
+    pub version: i64,
+    pub average_rating: Option<f64>,
+    pub review_count: i64,
 }
 
 impl BookRow {
-    pub fn into_book(self, checkout: Option<Checkout>) -> Book {
+    pub fn into_book(self, checkouts: Vec<Checkout>, tags: Vec<Tag>) -> Book {
         let BookRow {
             book_id,
             title,
@@ -24,6 +36,12 @@ impl BookRow {
             description,
             owned_by,
             owner_name,
+            total_copies,
+            available_copies,
+            cover_etag,
+            version,
+            average_rating,
+            review_count,
         } = self;
         Book {
             id: book_id,
@@ -35,40 +53,211 @@ impl BookRow {
                 id: owned_by,
                 name: owner_name,
             },
-            checkout,
+            total_copies,
+            available_copies,
+            checkouts,
+            tags,
+            cover_etag,
+            average_rating,
+            review_count,
+            version,
+        }
+    }
+}
+
+pub struct DeletedBookRow {
+    pub total: i64,
+    pub book_id: BookId,
+    pub title: String,
+    pub author: String,
+    pub isbn: String,
+    pub description: String,
+    pub owned_by: UserId,
+    pub owner_name: String,
+    pub deleted_at: DateTime<Utc>,
+}
+
+impl From<DeletedBookRow> for DeletedBook {
+    fn from(value: DeletedBookRow) -> Self {
+        let DeletedBookRow {
+            total: _,
+            book_id,
+            title,
+            author,
+            isbn,
+            description,
+            owned_by,
+            owner_name,
+            deleted_at,
+        } = value;
+        Self {
+            id: book_id,
+            title,
+            author,
+            isbn,
+            description,
+            owner: BookOwner {
+                id: owned_by,
+                name: owner_name,
+            },
+            deleted_at,
+        }
+    }
+}
+
+pub struct BookRevisionRow {
+    pub revision_number: i32,
+    pub title: String,
+    pub author: String,
+    pub isbn: String,
+    pub description: String,
+    /// 次の更新履歴の値 (最新の更新履歴の場合は蔵書の現在の値)
+    pub next_title: String,
+    pub next_author: String,
+    pub next_isbn: String,
+    pub next_description: String,
+    pub revised_by: UserId,
+    pub reviser_name: Option<String>,
+    pub revised_at: DateTime<Utc>,
+}
+
+impl From<BookRevisionRow> for BookRevision {
+    fn from(value: BookRevisionRow) -> Self {
+        let BookRevisionRow {
+            revision_number,
+            title,
+            author,
+            isbn,
+            description,
+            next_title,
+            next_author,
+            next_isbn,
+            next_description,
+            revised_by,
+            reviser_name,
+            revised_at,
+        } = value;
+        let fields = BookFields {
+            title,
+            author,
+            isbn,
+            description,
+        };
+        let changes = fields.diff(&BookFields {
+            title: next_title,
+            author: next_author,
+            isbn: next_isbn,
+            description: next_description,
+        });
+        Self {
+            revision_number,
+            fields,
+            changes,
+            revised_by: BookReviser {
+                id: revised_by,
+                name: reviser_name,
+            },
+            revised_at,
         }
     }
 }
 
+#[derive(sqlx::FromRow)]
 pub struct PaginatedBookRow {
     pub total: i64,
     pub id: BookId,
+    /// カーソルに格納する並べ替えキーの値
+    pub sort_value: String,
 }
 
 pub struct BookCheckoutRow {
     pub checkout_id: CheckoutId,
     pub book_id: BookId,
+    pub copy_id: BookCopyId,
     pub user_id: UserId,
     pub user_name: String,
     pub checked_out_at: DateTime<Utc>,
+    pub due_at: DateTime<Utc>,
+    pub renewal_count: i32,
 }
 
 impl From<BookCheckoutRow> for Checkout {
     fn from(value: BookCheckoutRow) -> Self {
         let BookCheckoutRow {
             checkout_id,
+            copy_id,
             user_id,
             user_name,
             checked_out_at,
+            due_at,
+            renewal_count,
             ..
         } = value;
         Self {
             checkout_id,
+            copy_id,
             checked_out_by: CheckoutUser {
                 id: user_id,
                 name: user_name,
             },
             checked_out_at,
+            due_at,
+            renewal_count,
         }
     }
 }
+
+pub struct BookCopyRow {
+    pub copy_id: BookCopyId,
+    pub book_id: BookId,
+    pub barcode: String,
+    pub condition: String,
+    pub location: String,
+    pub checked_out: bool,
+}
+
+impl TryFrom<BookCopyRow> for BookCopy {
+    type Error = AppError;
+
+    fn try_from(value: BookCopyRow) -> Result<Self, Self::Error> {
+        let BookCopyRow {
+            copy_id,
+            book_id,
+            barcode,
+            condition,
+            location,
+            checked_out,
+        } = value;
+        Ok(Self {
+            id: copy_id,
+            book_id,
+            barcode,
+            condition: BookCopyCondition::from_str(condition.as_str())
+                .map_err(|e| AppError::ConversionEntityError(e.to_string()))?,
+            location,
+            checked_out,
+        })
+    }
+}
+
+pub struct BookTagRow {
+    pub book_id: BookId,
+    pub tag_id: TagId,
+    pub name: String,
+}
+
+impl From<BookTagRow> for Tag {
+    fn from(value: BookTagRow) -> Self {
+        Self {
+            id: value.tag_id,
+            name: value.name,
+        }
+    }
+}
+
+pub struct BookCoverRow {
+    pub content_type: String,
+    pub thumbnail_content_type: String,
+    pub etag: String,
+    pub updated_at: DateTime<Utc>,
+}
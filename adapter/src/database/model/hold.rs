@@ -0,0 +1,43 @@
+use chrono::{DateTime, Utc};
+
+use kernel::model::hold::Hold;
+use kernel::model::id::{BookId, HoldId, UserId};
+use kernel::model::user::HoldUser;
+
+pub struct HoldRow {
+    pub hold_id: HoldId,
+    pub book_id: BookId,
+    pub user_id: UserId,
+    pub user_name: String,
+    pub position: i64,
+    pub created_at: DateTime<Utc>,
+    pub ready_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl From<HoldRow> for Hold {
+    fn from(value: HoldRow) -> Self {
+        let HoldRow {
+            hold_id,
+            book_id,
+            user_id,
+            user_name,
+            position,
+            created_at,
+            ready_at,
+            expires_at,
+        } = value;
+        Self {
+            id: hold_id,
+            book_id,
+            held_by: HoldUser {
+                id: user_id,
+                name: user_name,
+            },
+            position,
+            held_at: created_at,
+            ready_at,
+            expires_at,
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use std::str::FromStr;
+
+use kernel::model::loan_policy::LoanPolicy;
+use kernel::model::role::Role;
+use shared::config::CheckoutConfig;
+use shared::error::AppError;
+
+pub struct LoanPolicyRow {
+    pub role_name: String,
+    pub max_checkouts: Option<i32>,
+    pub loan_period_days: Option<i32>,
+    pub max_renewals: Option<i32>,
+}
+
+impl LoanPolicyRow {
+    /// 貸出ポリシーが登録されていない項目を設定値で補って、貸出ポリシーに変換する。
+    pub fn into_loan_policy(self, config: &CheckoutConfig) -> Result<LoanPolicy, AppError> {
+        let LoanPolicyRow {
+            role_name,
+            max_checkouts,
+            loan_period_days,
+            max_renewals,
+        } = self;
+        Ok(LoanPolicy {
+            role: Role::from_str(role_name.as_str())
+                .map_err(|e| AppError::ConversionEntityError(e.to_string()))?,
+            max_checkouts,
+            loan_period_days: loan_period_days.unwrap_or(config.loan_period_days),
+            max_renewals: max_renewals.unwrap_or(config.max_renewals),
+        })
+    }
+}
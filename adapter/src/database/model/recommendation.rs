@@ -0,0 +1,64 @@
+use kernel::model::id::BookId;
+use kernel::model::recommendation::{RecommendationCandidate, RecommendationReason};
+
+pub struct BorrowedTogetherRow {
+    pub book_id: BookId,
+    pub title: String,
+    pub author: String,
+    pub isbn: String,
+    pub borrower_count: i64,
+}
+
+impl From<BorrowedTogetherRow> for RecommendationCandidate {
+    fn from(value: BorrowedTogetherRow) -> Self {
+        let BorrowedTogetherRow {
+            book_id,
+            title,
+            author,
+            isbn,
+            borrower_count,
+        } = value;
+        Self {
+            book_id,
+            title,
+            author,
+            isbn,
+            reason: RecommendationReason::BorrowedTogether,
+            strength: borrower_count,
+        }
+    }
+}
+
+pub struct SimilarBookRow {
+    pub book_id: BookId,
+    pub title: String,
+    pub author: String,
+    pub isbn: String,
+    pub same_author: bool,
+    pub shared_tag_count: i64,
+}
+
+impl SimilarBookRow {
+    /// 著者が同じ場合とタグが共通する場合で、それぞれ推薦の候補にする。
+    pub fn into_candidates(self) -> Vec<RecommendationCandidate> {
+        let candidate = |reason, strength| RecommendationCandidate {
+            book_id: self.book_id,
+            title: self.title.clone(),
+            author: self.author.clone(),
+            isbn: self.isbn.clone(),
+            reason,
+            strength,
+        };
+        let mut candidates = Vec::new();
+        if self.same_author {
+            candidates.push(candidate(RecommendationReason::SameAuthor, 1));
+        }
+        if self.shared_tag_count > 0 {
+            candidates.push(candidate(
+                RecommendationReason::SharedTags,
+                self.shared_tag_count,
+            ));
+        }
+        candidates
+    }
+}
@@ -0,0 +1,68 @@
+use kernel::model::id::{BookId, UserId};
+use kernel::model::report::{BookCirculation, BorrowerActivity, MonthlyCheckouts};
+
+pub struct BookCirculationRow {
+    pub book_id: BookId,
+    pub title: String,
+    pub author: String,
+    pub isbn: String,
+    pub checkout_count: i64,
+}
+
+impl From<BookCirculationRow> for BookCirculation {
+    fn from(value: BookCirculationRow) -> Self {
+        let BookCirculationRow {
+            book_id,
+            title,
+            author,
+            isbn,
+            checkout_count,
+        } = value;
+        Self {
+            book_id,
+            title,
+            author,
+            isbn,
+            checkout_count,
+        }
+    }
+}
+
+pub struct BorrowerActivityRow {
+    pub user_id: UserId,
+    pub name: String,
+    pub checkout_count: i64,
+}
+
+impl From<BorrowerActivityRow> for BorrowerActivity {
+    fn from(value: BorrowerActivityRow) -> Self {
+        let BorrowerActivityRow {
+            user_id,
+            name,
+            checkout_count,
+        } = value;
+        Self {
+            user_id,
+            name,
+            checkout_count,
+        }
+    }
+}
+
+pub struct MonthlyCheckoutsRow {
+    pub month: String,
+    pub checkout_count: i64,
+}
+
+impl From<MonthlyCheckoutsRow> for MonthlyCheckouts {
+    fn from(value: MonthlyCheckoutsRow) -> Self {
+        let MonthlyCheckoutsRow {
+            month,
+            checkout_count,
+        } = value;
+        Self {
+            month,
+            checkout_count,
+        }
+    }
+}
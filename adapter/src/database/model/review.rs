@@ -0,0 +1,43 @@
+use chrono::{DateTime, Utc};
+
+use kernel::model::id::{BookId, ReviewId, UserId};
+use kernel::model::review::Review;
+use kernel::model::user::ReviewUser;
+
+pub struct ReviewRow {
+    pub review_id: ReviewId,
+    pub book_id: BookId,
+    pub user_id: UserId,
+    pub user_name: String,
+    pub rating: i32,
+    pub comment: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<ReviewRow> for Review {
+    fn from(value: ReviewRow) -> Self {
+        let ReviewRow {
+            review_id,
+            book_id,
+            user_id,
+            user_name,
+            rating,
+            comment,
+            created_at,
+            updated_at,
+        } = value;
+        Self {
+            id: review_id,
+            book_id,
+            reviewed_by: ReviewUser {
+                id: user_id,
+                name: user_name,
+            },
+            rating,
+            comment,
+            created_at,
+            updated_at,
+        }
+    }
+}
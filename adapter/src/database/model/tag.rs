@@ -0,0 +1,14 @@
+use kernel::model::id::TagId;
+use kernel::model::tag::Tag;
+
+pub struct TagRow {
+    pub tag_id: TagId,
+    pub name: String,
+}
+
+impl From<TagRow> for Tag {
+    fn from(value: TagRow) -> Self {
+        let TagRow { tag_id, name } = value;
+        Self { id: tag_id, name }
+    }
+}
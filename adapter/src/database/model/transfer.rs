@@ -0,0 +1,45 @@
+use chrono::{DateTime, Utc};
+
+use kernel::model::id::{BookId, BookTransferId, UserId};
+use kernel::model::transfer::BookTransfer;
+use kernel::model::user::TransferUser;
+
+pub struct BookTransferRow {
+    pub book_transfer_id: BookTransferId,
+    pub book_id: BookId,
+    pub title: String,
+    pub from_user_id: UserId,
+    pub from_user_name: String,
+    pub to_user_id: UserId,
+    pub to_user_name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<BookTransferRow> for BookTransfer {
+    fn from(value: BookTransferRow) -> Self {
+        let BookTransferRow {
+            book_transfer_id,
+            book_id,
+            title,
+            from_user_id,
+            from_user_name,
+            to_user_id,
+            to_user_name,
+            created_at,
+        } = value;
+        Self {
+            id: book_transfer_id,
+            book_id,
+            book_title: title,
+            from: TransferUser {
+                id: from_user_id,
+                name: from_user_name,
+            },
+            to: TransferUser {
+                id: to_user_id,
+                name: to_user_name,
+            },
+            requested_at: created_at,
+        }
+    }
+}
@@ -14,6 +14,8 @@ pub struct UserRow {
     pub role_name: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub retain_checkout_history: bool,
+    pub version: i64,
 }
 
 impl TryFrom<UserRow> for User {
@@ -24,6 +26,8 @@ impl TryFrom<UserRow> for User {
             name,
             email,
             role_name,
+            retain_checkout_history,
+            version,
             ..
         } = value;
         Ok(User {
@@ -32,6 +36,8 @@ impl TryFrom<UserRow> for User {
             email,
             role: Role::from_str(role_name.as_str())
                 .map_err(|e| AppError::ConversionEntityError(e.to_string()))?,
+            retain_checkout_history,
+            version,
         })
     }
 }
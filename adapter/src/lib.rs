@@ -1,3 +1,4 @@
+pub mod blob;
 pub mod database;
 pub mod redis;
 pub mod repository;
@@ -0,0 +1,544 @@
+use async_trait::async_trait;
+use derive_new::new;
+use serde_json::Value;
+use sqlx::Postgres;
+
+use kernel::model::audit::{AuditAction, AuditEntityType, AuditEvent, AuditEventListOptions};
+use kernel::model::id::{
+    BookCopyId, BookId, BookTransferId, CheckoutId, HoldId, ReviewId, TagId, UserId,
+};
+use kernel::model::list::PaginatedList;
+use kernel::model::role::Role;
+use kernel::repository::audit::AuditEventRepository;
+use shared::error::{AppError, AppResult};
+
+use crate::database::model::audit::AuditEventRow;
+use crate::database::ConnectionPool;
+
+#[derive(new)]
+pub struct AuditEventRepositoryImpl {
+    db: ConnectionPool,
+}
+
+#[async_trait]
+impl AuditEventRepository for AuditEventRepositoryImpl {
+    async fn find_all(
+        &self,
+        options: AuditEventListOptions,
+    ) -> AppResult<PaginatedList<AuditEvent>> {
+        let AuditEventListOptions {
+            limit,
+            offset,
+            actor,
+            entity_type,
+            entity_id,
+            since,
+            until,
+        } = options;
+        // UUIDはハイフンの有無を問わず照合できるように、記録した形式 (ハイフンなし) に揃える。
+        let entity_id =
+            entity_id.map(|id| uuid::Uuid::parse_str(&id).map_or(id, |u| u.simple().to_string()));
+
+        let rows = sqlx::query_as!(
+            AuditEventRow,
+            r#"
+                SELECT
+                    COUNT(*) OVER() "total!",
+                    audit_event_id,
+                    actor_id "actor_id: UserId",
+                    action,
+                    entity_type,
+                    entity_id,
+                    before,
+                    after,
+                    occurred_at
+                FROM audit_events
+                WHERE ($1::UUID IS NULL OR actor_id = $1)
+                    AND ($2::TEXT IS NULL OR entity_type = $2)
+                    AND ($3::TEXT IS NULL OR entity_id = $3)
+                    AND ($4::TIMESTAMPTZ IS NULL OR occurred_at >= $4)
+                    AND ($5::TIMESTAMPTZ IS NULL OR occurred_at < $5)
+                ORDER BY occurred_at DESC, audit_event_id
+                LIMIT $6
+                OFFSET $7
+            "#,
+            actor as _,
+            entity_type.as_ref().map(AsRef::<str>::as_ref),
+            entity_id,
+            since,
+            until,
+            limit,
+            offset
+        )
+        .fetch_all(self.db.inner_ref())
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        let total = rows.first().map(|r| r.total).unwrap_or_default();
+        let items = rows
+            .into_iter()
+            .map(AuditEvent::try_from)
+            .collect::<AppResult<Vec<_>>>()?;
+
+        Ok(PaginatedList {
+            total,
+            limit,
+            offset,
+            next_cursor: None,
+            items,
+        })
+    }
+}
+
+/// 監査ログに記録する変更
+#[derive(new)]
+pub(crate) struct AuditRecord {
+    actor: Option<UserId>,
+    action: AuditAction,
+    entity_type: AuditEntityType,
+    entity_id: String,
+    before: Option<Value>,
+    after: Option<Value>,
+}
+
+/// 監査ログに変更を追記する。
+///
+/// 変更が取り消されたときに記録も取り消されるように、変更するトランザクションの中で呼び出す。
+pub(crate) async fn record_audit_event(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    record: AuditRecord,
+) -> AppResult<()> {
+    let AuditRecord {
+        actor,
+        action,
+        entity_type,
+        entity_id,
+        before,
+        after,
+    } = record;
+    sqlx::query!(
+        r#"
+            INSERT INTO audit_events (actor_id, action, entity_type, entity_id, before, after)
+            VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+        actor as _,
+        action.as_ref(),
+        entity_type.as_ref(),
+        entity_id,
+        before,
+        after
+    )
+    .execute(&mut **tx)
+    .await
+    .map_err(AppError::SpecificOperationError)?;
+    Ok(())
+}
+
+// 以下は、監査ログに記録する変更前後の対象をJSONで取得する関数である。
+// 対象が存在しない場合は`None`を返す。
+
+pub(crate) async fn book_snapshot(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    book_id: BookId,
+) -> AppResult<Option<Value>> {
+    // 検索用の列は蔵書の内容から生成されるため記録しない。
+    sqlx::query_scalar!(
+        r#"
+            SELECT to_jsonb(b) - 'search_vector' "snapshot!"
+            FROM books b
+            WHERE b.book_id = $1
+        "#,
+        book_id as _
+    )
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(AppError::SpecificOperationError)
+}
+
+pub(crate) async fn book_cover_snapshot(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    book_id: BookId,
+) -> AppResult<Option<Value>> {
+    sqlx::query_scalar!(
+        r#"
+            SELECT to_jsonb(bcv) "snapshot!"
+            FROM book_covers bcv
+            WHERE bcv.book_id = $1
+        "#,
+        book_id as _
+    )
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(AppError::SpecificOperationError)
+}
+
+pub(crate) async fn book_copy_snapshot(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    copy_id: BookCopyId,
+) -> AppResult<Option<Value>> {
+    sqlx::query_scalar!(
+        r#"
+            SELECT to_jsonb(bc) "snapshot!"
+            FROM book_copies bc
+            WHERE bc.copy_id = $1
+        "#,
+        copy_id as _
+    )
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(AppError::SpecificOperationError)
+}
+
+pub(crate) async fn tag_snapshot(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    tag_id: TagId,
+) -> AppResult<Option<Value>> {
+    sqlx::query_scalar!(
+        r#"
+            SELECT to_jsonb(t) "snapshot!"
+            FROM tags t
+            WHERE t.tag_id = $1
+        "#,
+        tag_id as _
+    )
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(AppError::SpecificOperationError)
+}
+
+pub(crate) async fn user_snapshot(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    user_id: UserId,
+) -> AppResult<Option<Value>> {
+    // パスワードのハッシュ値は記録せず、ロールはIDの代わりに名前を記録する。
+    sqlx::query_scalar!(
+        r#"
+            SELECT
+                to_jsonb(u) - 'password_hash' - 'role_id'
+                    || jsonb_build_object('role', r.name) "snapshot!"
+            FROM users u
+            INNER JOIN roles r ON u.role_id = r.role_id
+            WHERE u.user_id = $1
+        "#,
+        user_id as _
+    )
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(AppError::SpecificOperationError)
+}
+
+pub(crate) async fn checkout_snapshot(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    checkout_id: CheckoutId,
+) -> AppResult<Option<Value>> {
+    // 返却した貸出は、returned_checkoutsテーブルに移動している。
+    // 借りたユーザーが履歴を残さない設定の場合は、借りたユーザーを記録しない。
+    sqlx::query_scalar!(
+        r#"
+            SELECT
+                CASE
+                    WHEN COALESCE(u.retain_checkout_history, TRUE) THEN s.snapshot
+                    ELSE s.snapshot - 'user_id'
+                END "snapshot!"
+            FROM (
+                SELECT c.user_id, to_jsonb(c) snapshot
+                FROM checkouts c
+                WHERE c.checkout_id = $1
+                UNION ALL
+                SELECT rc.user_id, to_jsonb(rc)
+                FROM returned_checkouts rc
+                WHERE rc.checkout_id = $1
+            ) s
+            LEFT OUTER JOIN users u ON s.user_id = u.user_id
+        "#,
+        checkout_id as _
+    )
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(AppError::SpecificOperationError)
+}
+
+/// 貸出の監査ログに記録する操作者を返す。
+///
+/// 履歴を残さない設定のユーザーが自身の貸出を操作した場合は、操作者から借りたユーザーが
+/// 分からないように、操作者を記録しない。
+pub(crate) async fn checkout_actor(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    checkout_id: CheckoutId,
+    actor: UserId,
+) -> AppResult<Option<UserId>> {
+    let hidden = sqlx::query_scalar!(
+        r#"
+            SELECT EXISTS (
+                SELECT 1
+                FROM users u
+                WHERE u.user_id = $2
+                    AND NOT u.retain_checkout_history
+                    AND u.user_id IN (
+                        SELECT c.user_id FROM checkouts c WHERE c.checkout_id = $1
+                        UNION ALL
+                        SELECT rc.user_id FROM returned_checkouts rc WHERE rc.checkout_id = $1
+                    )
+            ) "hidden!"
+        "#,
+        checkout_id as _,
+        actor as _
+    )
+    .fetch_one(&mut **tx)
+    .await
+    .map_err(AppError::SpecificOperationError)?;
+    Ok((!hidden).then_some(actor))
+}
+
+pub(crate) async fn hold_snapshot(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    hold_id: HoldId,
+) -> AppResult<Option<Value>> {
+    sqlx::query_scalar!(
+        r#"
+            SELECT to_jsonb(h) "snapshot!"
+            FROM holds h
+            WHERE h.hold_id = $1
+        "#,
+        hold_id as _
+    )
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(AppError::SpecificOperationError)
+}
+
+pub(crate) async fn review_snapshot(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    review_id: ReviewId,
+) -> AppResult<Option<Value>> {
+    sqlx::query_scalar!(
+        r#"
+            SELECT to_jsonb(r) "snapshot!"
+            FROM reviews r
+            WHERE r.review_id = $1
+        "#,
+        review_id as _
+    )
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(AppError::SpecificOperationError)
+}
+
+pub(crate) async fn book_transfer_snapshot(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    transfer_id: BookTransferId,
+) -> AppResult<Option<Value>> {
+    sqlx::query_scalar!(
+        r#"
+            SELECT to_jsonb(bt) "snapshot!"
+            FROM book_transfers bt
+            WHERE bt.book_transfer_id = $1
+        "#,
+        transfer_id as _
+    )
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(AppError::SpecificOperationError)
+}
+
+pub(crate) async fn loan_policy_snapshot(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    role: Role,
+) -> AppResult<Option<Value>> {
+    sqlx::query_scalar!(
+        r#"
+            SELECT to_jsonb(lp) - 'role_id' || jsonb_build_object('role', r.name) "snapshot!"
+            FROM loan_policies lp
+            INNER JOIN roles r ON lp.role_id = r.role_id
+            WHERE r.name = $1
+        "#,
+        role.as_ref()
+    )
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(AppError::SpecificOperationError)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use chrono::{Duration, Utc};
+    use sqlx::PgPool;
+
+    use kernel::model::book::event::{CreateBook, DeleteBook, UpdateBook};
+    use kernel::model::user::event::UpdateUserRole;
+    use kernel::repository::book::BookRepository;
+    use kernel::repository::user::UserRepository;
+
+    use super::*;
+    use crate::repository::book::BookRepositoryImpl;
+    use crate::repository::user::UserRepositoryImpl;
+
+    const ADMIN: &str = "5b4c96ac-316a-4bee-8e69-cac5eb84ff4c";
+    const USER: &str = "9582f9de-0fd1-4892-b20c-70139a7eb95b";
+
+    fn options() -> AuditEventListOptions {
+        AuditEventListOptions {
+            limit: 20,
+            offset: 0,
+            actor: None,
+            entity_type: None,
+            entity_id: None,
+            since: None,
+            until: None,
+        }
+    }
+
+    #[sqlx::test(fixtures("common"))]
+    async fn test_record_book_changes(pool: PgPool) -> anyhow::Result<()> {
+        let repo = AuditEventRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+        let book_repo = BookRepositoryImpl::new(ConnectionPool::new(pool));
+        let admin = UserId::from_str(ADMIN)?;
+        let user = UserId::from_str(USER)?;
+
+        book_repo
+            .create(
+                CreateBook {
+                    title: "Old Title".into(),
+                    author: "Author".into(),
+                    isbn: "978-4-7981-6044-3".parse()?,
+                    description: "".into(),
+                    allow_duplicate: false,
+                },
+                user,
+            )
+            .await?;
+        let created = repo
+            .find_all(AuditEventListOptions {
+                actor: Some(user),
+                ..options()
+            })
+            .await?;
+        assert_eq!(created.total, 1);
+        let book_id = BookId::from_str(&created.items[0].entity_id)?;
+
+        let update = |title: &str, requested_user| UpdateBook {
+            book_id,
+            title: title.into(),
+            author: "Author".into(),
+            isbn: "978-4-7981-6044-3".parse().unwrap(),
+            description: "".into(),
+            allow_duplicate: false,
+            requested_user,
+            requested_role: Role::User,
+            expected_version: None,
+        };
+        // 失敗した変更は記録されない
+        assert!(book_repo
+            .update(update("Other", UserId::new()))
+            .await
+            .is_err());
+        book_repo.update(update("New Title", user)).await?;
+        book_repo
+            .delete(DeleteBook {
+                book_id,
+                requested_user: admin,
+                requested_role: Role::Admin,
+                expected_version: None,
+            })
+            .await?;
+
+        // UUIDはハイフンを含めて指定してもよい
+        let events = repo
+            .find_all(AuditEventListOptions {
+                entity_type: Some(AuditEntityType::Book),
+                entity_id: Some(book_id.raw().hyphenated().to_string()),
+                ..options()
+            })
+            .await?;
+        let actions: Vec<_> = events.items.iter().map(|e| e.action).collect();
+        assert_eq!(
+            actions,
+            vec![
+                AuditAction::DeleteBook,
+                AuditAction::UpdateBook,
+                AuditAction::CreateBook
+            ]
+        );
+        let (deleted, updated, created) = (&events.items[0], &events.items[1], &events.items[2]);
+        assert_eq!(deleted.actor, Some(admin));
+        assert_eq!(deleted.before.as_ref().unwrap()["title"], "New Title");
+        assert!(deleted.before.as_ref().unwrap()["deleted_at"].is_null());
+        // 削除した蔵書はゴミ箱に移るため、削除後の蔵書には削除した日時が記録される
+        assert!(!deleted.after.as_ref().unwrap()["deleted_at"].is_null());
+        assert_eq!(updated.actor, Some(user));
+        assert_eq!(updated.before.as_ref().unwrap()["title"], "Old Title");
+        assert_eq!(updated.after.as_ref().unwrap()["title"], "New Title");
+        assert!(updated
+            .after
+            .as_ref()
+            .unwrap()
+            .get("search_vector")
+            .is_none());
+        assert!(created.before.is_none());
+
+        // 記録した日時で絞り込める
+        let now = Utc::now();
+        let events = repo
+            .find_all(AuditEventListOptions {
+                since: Some(now - Duration::hours(1)),
+                until: Some(now + Duration::hours(1)),
+                ..options()
+            })
+            .await?;
+        assert_eq!(events.total, 3);
+        let events = repo
+            .find_all(AuditEventListOptions {
+                since: Some(now + Duration::hours(1)),
+                ..options()
+            })
+            .await?;
+        assert_eq!(events.total, 0);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common"))]
+    async fn test_audit_events_are_append_only(pool: PgPool) -> anyhow::Result<()> {
+        let repo = AuditEventRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+        let user_repo = UserRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+        let admin = UserId::from_str(ADMIN)?;
+        let user = UserId::from_str(USER)?;
+
+        user_repo
+            .update_role(UpdateUserRole {
+                user_id: user,
+                role: Role::Admin,
+                requested_user: admin,
+                expected_version: None,
+            })
+            .await?;
+        let events = repo
+            .find_all(AuditEventListOptions {
+                entity_type: Some(AuditEntityType::User),
+                ..options()
+            })
+            .await?;
+        assert_eq!(events.total, 1);
+        let event = &events.items[0];
+        assert_eq!(event.action, AuditAction::UpdateUserRole);
+        assert_eq!(event.before.as_ref().unwrap()["role"], "User");
+        assert_eq!(event.after.as_ref().unwrap()["role"], "Admin");
+        // パスワードのハッシュ値は記録しない
+        assert!(event.after.as_ref().unwrap().get("password_hash").is_none());
+
+        // 監査ログは更新も削除もできない
+        let res = sqlx::query!("UPDATE audit_events SET action = 'login'")
+            .execute(&pool)
+            .await;
+        assert!(res.is_err());
+        let res = sqlx::query!("DELETE FROM audit_events")
+            .execute(&pool)
+            .await;
+        assert!(res.is_err());
+        assert_eq!(repo.find_all(options()).await?.total, 1);
+
+        Ok(())
+    }
+}
@@ -3,6 +3,7 @@ use std::sync::Arc;
 use async_trait::async_trait;
 use derive_new::new;
 
+use kernel::model::audit::{AuditAction, AuditEntityType};
 use kernel::model::auth::event::CreateToken;
 use kernel::model::auth::AccessToken;
 use kernel::model::id::UserId;
@@ -12,6 +13,7 @@ use shared::error::{AppError, AppResult};
 use crate::database::model::auth::{from, AuthorizationKey, AuthorizedUserId, UserItem};
 use crate::database::ConnectionPool;
 use crate::redis::RedisClient;
+use crate::repository::audit::{record_audit_event, AuditRecord};
 
 #[derive(new)]
 pub struct AuthRepositoryImpl {
@@ -20,6 +22,31 @@ pub struct AuthRepositoryImpl {
     ttl: u64,
 }
 
+impl AuthRepositoryImpl {
+    /// ログインとログアウトを監査ログに記録する。
+    async fn record_auth_event(
+        &self,
+        actor: Option<UserId>,
+        action: AuditAction,
+        user_id: UserId,
+    ) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+        record_audit_event(
+            &mut tx,
+            AuditRecord::new(
+                actor,
+                action,
+                AuditEntityType::User,
+                user_id.to_string(),
+                None,
+                None,
+            ),
+        )
+        .await?;
+        tx.commit().await.map_err(AppError::TransactionError)
+    }
+}
+
 #[async_trait]
 impl AuthRepository for AuthRepositoryImpl {
     /// `create_token`で保存したアクセストークンがRedis内にキーとして存在すれば、その値である`UserId`を返す。
@@ -38,7 +65,7 @@ impl AuthRepository for AuthRepositoryImpl {
 
     /// メールアドレスとパスワードから、該当するユーザーが存在することを確認する。
     /// パスワードはbcryptでハッシュ化されてデータベースに記録されているため、ハッシュ化前のパスワードと
-    /// 一致するか確認する。
+    /// 一致するか確認する。パスワードが一致しなかった場合は、ログインの失敗を監査ログに記録する。
     async fn verify_user(&self, email: &str, password: &str) -> AppResult<UserId> {
         let user_item = sqlx::query_as!(
             UserItem,
@@ -55,21 +82,35 @@ impl AuthRepository for AuthRepositoryImpl {
 
         let valid = bcrypt::verify(password, &user_item.password_hash)?;
         if !valid {
+            self.record_auth_event(None, AuditAction::LoginFailed, user_item.user_id)
+                .await?;
             return Err(AppError::UnauthorizedError);
         }
         Ok(user_item.user_id)
     }
 
     /// アクセストークンを生成してRedisに保存して、アクセストークンを返す。
+    /// アクセストークンを発行したユーザーのログインを監査ログに記録する。
     async fn create_token(&self, event: CreateToken) -> AppResult<AccessToken> {
+        let user_id = event.user_id;
+        // 記録されていないアクセストークンが使われることがないように、先に記録する。
+        self.record_auth_event(Some(user_id), AuditAction::Login, user_id)
+            .await?;
         let (key, value) = from(event);
         self.kv.set_ex(&key, &value, self.ttl).await?;
         Ok(key.into())
     }
 
-    /// Redisに登録されているアクセストークンを削除する。
+    /// Redisに登録されているアクセストークンを削除して、ログアウトを監査ログに記録する。
     async fn delete_token(&self, access_token: AccessToken) -> AppResult<()> {
         let key: AuthorizationKey = access_token.into();
-        self.kv.delete(&key).await
+        let user_id = self.kv.get(&key).await?.map(AuthorizedUserId::into_inner);
+        self.kv.delete(&key).await?;
+        // 有効期限が切れたアクセストークンは、誰のものか分からないため記録しない。
+        if let Some(user_id) = user_id {
+            self.record_auth_event(Some(user_id), AuditAction::Logout, user_id)
+                .await?;
+        }
+        Ok(())
     }
 }
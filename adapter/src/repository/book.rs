@@ -2,124 +2,353 @@ use std::collections::HashMap;
 
 use async_trait::async_trait;
 use derive_new::new;
+use futures::stream::{self, BoxStream};
+use futures::{StreamExt, TryStreamExt};
+use sqlx::{Acquire, Postgres, QueryBuilder};
 
-use kernel::model::book::event::{CreateBook, DeleteBook, UpdateBook};
-use kernel::model::book::BookListOptions;
-use kernel::model::book::{Book, Checkout};
-use kernel::model::id::{BookId, UserId};
-use kernel::model::list::PaginatedList;
+use kernel::model::audit::{AuditAction, AuditEntityType};
+use kernel::model::book::event::{
+    CreateBook, CreateBookCopy, DeleteBook, DeleteBookCopy, PatchBook, PurgeBook, RestoreBook,
+    RevertBook, UpdateBook, UpdateBookCopy,
+};
+use kernel::model::book::{
+    Book, BookCopy, BookRevision, Checkout, DeletedBook, DeletedBookListOptions, Isbn,
+};
+use kernel::model::book::{
+    BookCheckoutState, BookImportMode, BookImportOutcome, BookListCursor, BookListOptions,
+    BookSortKey,
+};
+use kernel::model::id::{BookCopyId, BookId, HoldId, UserId};
+use kernel::model::list::{PaginatedList, SortDirection};
+use kernel::model::role::Role;
+use kernel::model::tag::{Tag, TagFilter, TagMatch};
 use kernel::repository::book::BookRepository;
 use shared::error::{AppError, AppResult};
 
-use crate::database::model::book::{BookCheckoutRow, BookRow, PaginatedBookRow};
+use crate::database::model::book::{
+    BookCheckoutRow, BookCopyRow, BookRevisionRow, BookRow, BookTagRow, DeletedBookRow,
+    PaginatedBookRow,
+};
 use crate::database::ConnectionPool;
+use crate::repository::audit::{
+    book_copy_snapshot, book_snapshot, record_audit_event, AuditRecord,
+};
 
 #[derive(new)]
 pub struct BookRepositoryImpl {
     db: ConnectionPool,
 }
 
+/// 蔵書を書き出すときに、一度にデータベースから取得する蔵書の数
+const EXPORT_BATCH_SIZE: i64 = 500;
+
 impl BookRepositoryImpl {
-    async fn find_checkouts(&self, book_ids: &[BookId]) -> AppResult<HashMap<BookId, Checkout>> {
-        let results = sqlx::query_as!(
+    /// 書き出す蔵書を、蔵書IDが`after`より大きいものから蔵書IDの順に取得する。
+    async fn find_export_batch(&self, after: Option<BookId>) -> AppResult<Vec<Book>> {
+        let rows: Vec<BookRow> = sqlx::query_as!(
+            BookRow,
+            r#"
+                SELECT
+                    b.book_id, b.title, b.author, b.isbn, b.description,
+                    u.user_id owned_by, u.name owner_name,
+                    cp.total_copies "total_copies!", cp.available_copies "available_copies!",
+                    bcv.etag "cover_etag?", b.version,
+                    rv.average_rating, rv.review_count "review_count!"
+                FROM
+                    books b
+                INNER JOIN users u ON b.user_id = u.user_id
+                LEFT OUTER JOIN book_covers bcv ON b.book_id = bcv.book_id
+                CROSS JOIN LATERAL (
+                    SELECT
+                        COUNT(*) FILTER (WHERE bc.condition <> 'Lost') total_copies,
+                        COUNT(*) FILTER (
+                            WHERE bc.condition <> 'Lost' AND c.checkout_id IS NULL
+                        ) available_copies
+                    FROM book_copies bc
+                    LEFT OUTER JOIN checkouts c ON bc.copy_id = c.copy_id
+                    WHERE bc.book_id = b.book_id
+                ) cp
+                CROSS JOIN LATERAL (
+                    SELECT AVG(r.rating)::FLOAT8 average_rating, COUNT(*) review_count
+                    FROM reviews r
+                    WHERE r.book_id = b.book_id
+                ) rv
+                WHERE ($1::UUID IS NULL OR b.book_id > $1) AND b.deleted_at IS NULL
+                ORDER BY b.book_id
+                LIMIT $2
+            "#,
+            after as _,
+            EXPORT_BATCH_SIZE
+        )
+        .fetch_all(self.db.inner_ref())
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        let book_ids = rows.iter().map(|r| r.book_id).collect::<Vec<_>>();
+        let mut checkouts = self.find_checkouts(&book_ids).await?;
+        let mut tags = self.find_tags(&book_ids).await?;
+        Ok(rows
+            .into_iter()
+            .map(|r| {
+                let checkouts = checkouts.remove(&r.book_id).unwrap_or_default();
+                let tags = tags.remove(&r.book_id).unwrap_or_default();
+                r.into_book(checkouts, tags)
+            })
+            .collect())
+    }
+
+    async fn find_checkouts(
+        &self,
+        book_ids: &[BookId],
+    ) -> AppResult<HashMap<BookId, Vec<Checkout>>> {
+        let rows = sqlx::query_as!(
             BookCheckoutRow,
             r#"
                 SELECT
                     c.checkout_id,
                     c.book_id,
+                    c.copy_id,
                     u.user_id,
                     u.name user_name,
-                    c.checked_out_at
+                    c.checked_out_at,
+                    c.due_at,
+                    c.renewal_count
                 FROM
                     checkouts c
                 INNER JOIN users u ON c.user_id = u.user_id
                 WHERE c.book_id = ANY($1)
+                ORDER BY c.checked_out_at, c.checkout_id
             "#,
             book_ids as _
         )
         .fetch_all(self.db.inner_ref())
         .await
-        .map_err(AppError::SpecificOperationError)?
-        .into_iter()
-        .map(|checkout| (checkout.book_id, Checkout::from(checkout)))
-        .collect();
+        .map_err(AppError::SpecificOperationError)?;
+
+        let mut results: HashMap<BookId, Vec<Checkout>> = HashMap::new();
+        for row in rows {
+            results
+                .entry(row.book_id)
+                .or_default()
+                .push(Checkout::from(row));
+        }
         Ok(results)
     }
-}
 
-#[async_trait]
-impl BookRepository for BookRepositoryImpl {
-    async fn create(&self, event: CreateBook, user_id: UserId) -> AppResult<()> {
-        sqlx::query!(
+    async fn find_tags(&self, book_ids: &[BookId]) -> AppResult<HashMap<BookId, Vec<Tag>>> {
+        let rows = sqlx::query_as!(
+            BookTagRow,
             r#"
-                INSERT INTO books (
-                    title, author, isbn, description, user_id
-                ) VALUES (
-                    $1, $2, $3, $4, $5
-                )
+                SELECT bt.book_id, t.tag_id, t.name
+                FROM book_tags bt
+                INNER JOIN tags t ON bt.tag_id = t.tag_id
+                WHERE bt.book_id = ANY($1)
+                ORDER BY lower(t.name)
             "#,
-            event.title,
-            event.author,
-            event.isbn,
-            event.description,
-            user_id as _,
+            book_ids as _
         )
-        .execute(self.db.inner_ref())
+        .fetch_all(self.db.inner_ref())
         .await
         .map_err(AppError::SpecificOperationError)?;
+
+        let mut results: HashMap<BookId, Vec<Tag>> = HashMap::new();
+        for row in rows {
+            results.entry(row.book_id).or_default().push(Tag::from(row));
+        }
+        Ok(results)
+    }
+}
+
+#[async_trait]
+impl BookRepository for BookRepositoryImpl {
+    async fn create(&self, event: CreateBook, user_id: UserId) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+        insert_book(&mut tx, event, user_id).await?;
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
         Ok(())
     }
 
+    async fn import(
+        &self,
+        events: Vec<CreateBook>,
+        user_id: UserId,
+        mode: BookImportMode,
+    ) -> AppResult<Vec<BookImportOutcome>> {
+        let mut tx = self.db.begin().await?;
+
+        let mut outcomes = Vec::with_capacity(events.len());
+        for event in events {
+            let outcome = match mode {
+                BookImportMode::Atomic => match insert_book(&mut tx, event, user_id).await {
+                    Ok(book_id) => BookImportOutcome::Created(book_id),
+                    Err(AppError::Conflict(message)) => BookImportOutcome::Skipped(message),
+                    Err(e) => return Err(e),
+                },
+                // 登録に失敗した蔵書だけを取り消せるように、蔵書ごとにセーブポイントを作成する。
+                BookImportMode::BestEffort => {
+                    let mut savepoint = (&mut tx)
+                        .begin()
+                        .await
+                        .map_err(AppError::TransactionError)?;
+                    match insert_book(&mut savepoint, event, user_id).await {
+                        Ok(book_id) => {
+                            savepoint
+                                .commit()
+                                .await
+                                .map_err(AppError::TransactionError)?;
+                            BookImportOutcome::Created(book_id)
+                        }
+                        Err(e) => {
+                            savepoint
+                                .rollback()
+                                .await
+                                .map_err(AppError::TransactionError)?;
+                            match e {
+                                AppError::Conflict(message) => BookImportOutcome::Skipped(message),
+                                e => BookImportOutcome::Failed(e.to_string()),
+                            }
+                        }
+                    }
+                }
+            };
+            outcomes.push(outcome);
+        }
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(outcomes)
+    }
+
     async fn find_all(&self, options: BookListOptions) -> AppResult<PaginatedList<Book>> {
-        let BookListOptions { limit, offset } = options;
+        let BookListOptions {
+            limit,
+            offset,
+            sort,
+            cursor,
+            query,
+            owner,
+            checkout_state,
+            tags,
+        } = options;
+        // カーソルが指定された場合は、カーソルが示す蔵書の次から取得するため、開始位置は使用しない。
+        let offset = if cursor.is_some() { 0 } else { offset };
 
-        let rows: Vec<PaginatedBookRow> = sqlx::query_as!(
-            PaginatedBookRow,
+        // 並べ替えキーや絞り込み条件によってSQL文が変わるため、`QueryBuilder`でSQL文を組み立てる。
+        // 副問い合わせで絞り込んだ蔵書の総数を数えてから、カーソルによる絞り込みをすることで、
+        // キーセットページネーションでも絞り込み条件に一致するすべての蔵書の数を`total`とする。
+        let mut builder = QueryBuilder::<Postgres>::new(
             r#"
-                SELECT
-                    COUNT(*) OVER() "total!",
-                    b.book_id id
-                FROM
-                    books b
-                ORDER BY b.created_at DESC
-                LIMIT $1
-                OFFSET $2
+                SELECT t.total, t.id, t.sort_key::text sort_value
+                FROM (
+                    SELECT
+                        COUNT(*) OVER() total,
+                        b.book_id id,
             "#,
-            limit,
-            offset
-        )
-        .fetch_all(self.db.inner_ref())
-        .await
-        .map_err(AppError::SpecificOperationError)?;
+        );
+        push_sort_key(&mut builder, sort.key, query.as_deref());
+        builder.push(
+            r#"
+                        sort_key
+                    FROM books b
+                    WHERE b.deleted_at IS NULL
+            "#,
+        );
+        push_book_filters(
+            &mut builder,
+            query.as_deref(),
+            owner,
+            checkout_state,
+            tags.as_ref(),
+        );
+        builder.push(") t");
+
+        let (comparison, direction) = match sort.direction {
+            SortDirection::Asc => (">", "ASC"),
+            SortDirection::Desc => ("<", "DESC"),
+        };
+        if let Some(cursor) = cursor {
+            builder
+                .push(format!(" WHERE (t.sort_key, t.id) {comparison} (CAST("))
+                .push_bind(cursor.value)
+                .push(format!(" AS {}), ", sort_key_type(sort.key)))
+                .push_bind(cursor.id)
+                .push(")");
+        }
+        // 次のページが存在するか確認するため、上限値より1件多く取得する。
+        builder
+            .push(format!(
+                " ORDER BY t.sort_key {direction}, t.id {direction} LIMIT "
+            ))
+            .push_bind(limit + 1)
+            .push(" OFFSET ")
+            .push_bind(offset);
+
+        let mut rows: Vec<PaginatedBookRow> = builder
+            .build_query_as()
+            .fetch_all(self.db.inner_ref())
+            .await
+            .map_err(AppError::SpecificOperationError)?;
+
+        let next_cursor = if rows.len() as i64 > limit {
+            rows.truncate(limit as usize);
+            rows.last().map(|r| {
+                String::from(BookListCursor {
+                    sort,
+                    value: r.sort_value.clone(),
+                    id: r.id,
+                })
+            })
+        } else {
+            None
+        };
 
         // 上記クエリで行が得られた場合は最初の行のtotalカラムの値を、
         // 行が得られなかった場合は`i64`のデフォルト値、つまり0を`total`に割り当てる。
         let total = rows.first().map(|r| r.total).unwrap_or_default();
         let book_ids = rows.into_iter().map(|r| r.id).collect::<Vec<BookId>>();
         let mut checkouts = self.find_checkouts(&book_ids).await?;
+        let mut tags = self.find_tags(&book_ids).await?;
 
         // UNNEST: 配列を行集合に展開する。
         // $1::uuid[]: クエリパラメーター$1をuuidの配列と認識させる。
+        // WITH ORDINALITY: 展開した各行に配列内の位置を付与する。
         // 次のSQLは、上記で得られた蔵書IDのベクタをSQL文のパラメーターとして与え、
-        // 上記で得られた蔵書IDのベクタに含まれた蔵書IDを持つ蔵書のみを選択する。
-        // 蔵書数が膨大にならない限り非効率と考える。次のSQL文を実行して、必要な行を選択するべきではないか。
-        // * すべての蔵書の冊数を選択するSQL文
-        // * `created_at`の降順でソートした結果に対して、`OFFSET`と`LIMIT`で
-        //   ページにレンダリングする蔵書を選択するSQL文
+        // 上記で得られた蔵書IDのベクタに含まれた蔵書IDを持つ蔵書のみを、ベクタ内の順番を保って選択する。
         // SQL文の実行に時間がかかるようになった場合、クエリの実行計画を確認して、SQL文を見直すべきである。
         let rows: Vec<BookRow> = sqlx::query_as!(
             BookRow,
             r#"
                 SELECT
                     b.book_id, b.title, b.author, b.isbn, b.description,
-                    u.user_id owned_by, u.name owner_name
+                    u.user_id owned_by, u.name owner_name,
+                    cp.total_copies "total_copies!", cp.available_copies "available_copies!",
+                    bcv.etag "cover_etag?", b.version,
+                    rv.average_rating, rv.review_count "review_count!"
                 FROM
-                    books b
+                    UNNEST($1::uuid[]) WITH ORDINALITY AS ids (book_id, ordinality)
+                INNER JOIN
+                    books b ON ids.book_id = b.book_id
                 INNER JOIN
                     users u ON b.user_id = u.user_id
-                WHERE
-                    b.book_id IN (SELECT * FROM UNNEST($1::uuid[]))
-                ORDER BY b.created_at
+                LEFT OUTER JOIN
+                    book_covers bcv ON b.book_id = bcv.book_id
+                CROSS JOIN LATERAL (
+                    SELECT
+                        COUNT(*) FILTER (WHERE bc.condition <> 'Lost') total_copies,
+                        COUNT(*) FILTER (
+                            WHERE bc.condition <> 'Lost' AND c.checkout_id IS NULL
+                        ) available_copies
+                    FROM book_copies bc
+                    LEFT OUTER JOIN checkouts c ON bc.copy_id = c.copy_id
+                    WHERE bc.book_id = b.book_id
+                ) cp
+                CROSS JOIN LATERAL (
+                    SELECT AVG(r.rating)::FLOAT8 average_rating, COUNT(*) review_count
+                    FROM reviews r
+                    WHERE r.book_id = b.book_id
+                ) rv
+                ORDER BY ids.ordinality
             "#,
             &book_ids as _
         )
@@ -130,8 +359,9 @@ impl BookRepository for BookRepositoryImpl {
         let items = rows
             .into_iter()
             .map(|row| {
-                let checkout = checkouts.remove(&row.book_id);
-                row.into_book(checkout)
+                let checkouts = checkouts.remove(&row.book_id).unwrap_or_default();
+                let tags = tags.remove(&row.book_id).unwrap_or_default();
+                row.into_book(checkouts, tags)
             })
             .collect();
 
@@ -139,6 +369,7 @@ impl BookRepository for BookRepositoryImpl {
             total,
             limit,
             offset,
+            next_cursor,
             items,
         })
     }
@@ -149,11 +380,30 @@ impl BookRepository for BookRepositoryImpl {
             r#"
                 SELECT
                     b.book_id, b.title, b.author, b.isbn, b.description,
-                    u.user_id owned_by, u.name owner_name
+                    u.user_id owned_by, u.name owner_name,
+                    cp.total_copies "total_copies!", cp.available_copies "available_copies!",
+                    bcv.etag "cover_etag?", b.version,
+                    rv.average_rating, rv.review_count "review_count!"
                 FROM
                     books b
                 INNER JOIN users u ON b.user_id = u.user_id
-                WHERE b.book_id = $1
+                LEFT OUTER JOIN book_covers bcv ON b.book_id = bcv.book_id
+                CROSS JOIN LATERAL (
+                    SELECT
+                        COUNT(*) FILTER (WHERE bc.condition <> 'Lost') total_copies,
+                        COUNT(*) FILTER (
+                            WHERE bc.condition <> 'Lost' AND c.checkout_id IS NULL
+                        ) available_copies
+                    FROM book_copies bc
+                    LEFT OUTER JOIN checkouts c ON bc.copy_id = c.copy_id
+                    WHERE bc.book_id = b.book_id
+                ) cp
+                CROSS JOIN LATERAL (
+                    SELECT AVG(r.rating)::FLOAT8 average_rating, COUNT(*) review_count
+                    FROM reviews r
+                    WHERE r.book_id = b.book_id
+                ) rv
+                WHERE b.book_id = $1 AND b.deleted_at IS NULL
             "#,
             book_id as _
         )
@@ -163,16 +413,56 @@ impl BookRepository for BookRepositoryImpl {
 
         match row {
             Some(r) => {
-                let checkout = self.find_checkouts(&[r.book_id]).await?.remove(&r.book_id);
-                Ok(Some(r.into_book(checkout)))
+                let checkouts = self
+                    .find_checkouts(&[r.book_id])
+                    .await?
+                    .remove(&r.book_id)
+                    .unwrap_or_default();
+                let tags = self
+                    .find_tags(&[r.book_id])
+                    .await?
+                    .remove(&r.book_id)
+                    .unwrap_or_default();
+                Ok(Some(r.into_book(checkouts, tags)))
             }
             None => Ok(None),
         }
     }
 
+    fn export(&self) -> BoxStream<'static, AppResult<Book>> {
+        // ストリームがリポジトリを借用しないように、コネクションプールを複製して渡す。
+        let repo = BookRepositoryImpl::new(self.db.clone());
+        stream::try_unfold((repo, None), |(repo, after)| async move {
+            let books = repo.find_export_batch(after).await?;
+            let Some(last) = books.last().map(|b| b.id) else {
+                return AppResult::Ok(None);
+            };
+            let batch = stream::iter(books.into_iter().map(Ok));
+            AppResult::Ok(Some((batch, (repo, Some(last)))))
+        })
+        .try_flatten()
+        .boxed()
+    }
+
     async fn update(&self, event: UpdateBook) -> AppResult<()> {
-        // 蔵書の所有者のみが更新できるように`user_id`を更新条件に含められている。
-        let result = sqlx::query!(
+        let mut tx = self.db.begin().await?;
+
+        ensure_book_is_modifiable(
+            &mut tx,
+            event.book_id,
+            event.requested_user,
+            event.requested_role,
+            event.expected_version,
+        )
+        .await?;
+
+        if !event.allow_duplicate {
+            ensure_isbn_is_unique(&mut tx, &event.isbn, Some(event.book_id)).await?;
+        }
+
+        record_book_revision(&mut tx, event.book_id, event.requested_user).await?;
+        let before = book_snapshot(&mut tx, event.book_id).await?;
+        sqlx::query!(
             r#"
                 UPDATE books
                 SET
@@ -180,97 +470,1086 @@ impl BookRepository for BookRepositoryImpl {
                     author = $2,
                     isbn = $3,
                     description = $4
-                WHERE
-                    book_id = $5
-                    AND user_id = $6
+                WHERE book_id = $5
             "#,
             event.title,
             event.author,
-            event.isbn,
+            event.isbn.as_str(),
             event.description,
             event.book_id as _,
-            event.requested_user as _,
         )
-        .execute(self.db.inner_ref())
+        .execute(&mut *tx)
         .await
         .map_err(AppError::SpecificOperationError)?;
 
-        if result.rows_affected() < 1 {
-            return Err(AppError::EntityNotFound("specified book not found".into()));
+        let after = book_snapshot(&mut tx, event.book_id).await?;
+        record_audit_event(
+            &mut tx,
+            AuditRecord::new(
+                Some(event.requested_user),
+                AuditAction::UpdateBook,
+                AuditEntityType::Book,
+                event.book_id.to_string(),
+                before,
+                after,
+            ),
+        )
+        .await?;
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(())
+    }
+
+    async fn patch(&self, event: PatchBook) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        ensure_book_is_modifiable(
+            &mut tx,
+            event.book_id,
+            event.requested_user,
+            event.requested_role,
+            event.expected_version,
+        )
+        .await?;
+
+        // 更新する項目がない場合は、蔵書を変更せず、監査ログにも記録しない。
+        if event.is_empty() {
+            return Ok(());
+        }
+
+        if let Some(isbn) = event.isbn.as_ref().filter(|_| !event.allow_duplicate) {
+            ensure_isbn_is_unique(&mut tx, isbn, Some(event.book_id)).await?;
+        }
+
+        record_book_revision(&mut tx, event.book_id, event.requested_user).await?;
+
+        // 指定された項目によってSET句が変わるため、`QueryBuilder`でSQL文を組み立てる。
+        let before = book_snapshot(&mut tx, event.book_id).await?;
+        let mut builder = QueryBuilder::<Postgres>::new("UPDATE books SET ");
+        let mut assignments = builder.separated(", ");
+        if let Some(title) = event.title {
+            assignments.push("title = ").push_bind_unseparated(title);
+        }
+        if let Some(author) = event.author {
+            assignments.push("author = ").push_bind_unseparated(author);
+        }
+        if let Some(isbn) = event.isbn {
+            assignments
+                .push("isbn = ")
+                .push_bind_unseparated(isbn.as_str().to_string());
+        }
+        if let Some(description) = event.description {
+            assignments
+                .push("description = ")
+                .push_bind_unseparated(description);
         }
+        builder
+            .push(" WHERE book_id = ")
+            .push_bind(event.book_id)
+            .build()
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::SpecificOperationError)?;
+
+        let after = book_snapshot(&mut tx, event.book_id).await?;
+        record_audit_event(
+            &mut tx,
+            AuditRecord::new(
+                Some(event.requested_user),
+                AuditAction::UpdateBook,
+                AuditEntityType::Book,
+                event.book_id.to_string(),
+                before,
+                after,
+            ),
+        )
+        .await?;
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
 
         Ok(())
     }
 
-    async fn delete(&self, event: DeleteBook) -> AppResult<()> {
-        let result = sqlx::query!(
+    async fn find_revisions(&self, book_id: BookId) -> AppResult<Vec<BookRevision>> {
+        let exists = sqlx::query_scalar!(
             r#"
-                DELETE FROM books
-                WHERE book_id = $1
+                SELECT EXISTS (
+                    SELECT 1 FROM books WHERE book_id = $1 AND deleted_at IS NULL
+                ) "exists!"
             "#,
-            event.book_id as _
+            book_id as _
         )
-        .execute(self.db.inner_ref())
+        .fetch_one(self.db.inner_ref())
         .await
         .map_err(AppError::SpecificOperationError)?;
+        if !exists {
+            return Err(AppError::EntityNotFound(format!(
+                "the book ({book_id}) doesn't exist"
+            )));
+        }
 
-        if result.rows_affected() < 1 {
-            return Err(AppError::EntityNotFound("specified book not found".into()));
+        // 更新履歴には更新する前の値を記録しているため、更新した後の値は次の更新履歴の値、
+        // 最新の更新履歴の場合は蔵書の現在の値になる。
+        let rows: Vec<BookRevisionRow> = sqlx::query_as!(
+            BookRevisionRow,
+            r#"
+                SELECT
+                    r.revision_number, r.title, r.author, r.isbn, r.description,
+                    COALESCE(LEAD(r.title) OVER w, b.title) "next_title!",
+                    COALESCE(LEAD(r.author) OVER w, b.author) "next_author!",
+                    COALESCE(LEAD(r.isbn) OVER w, b.isbn) "next_isbn!",
+                    COALESCE(LEAD(r.description) OVER w, b.description) "next_description!",
+                    r.revised_by, u.name "reviser_name?", r.revised_at
+                FROM book_revisions r
+                INNER JOIN books b ON r.book_id = b.book_id
+                LEFT OUTER JOIN users u ON r.revised_by = u.user_id
+                WHERE r.book_id = $1
+                WINDOW w AS (ORDER BY r.revision_number)
+                ORDER BY r.revision_number DESC
+            "#,
+            book_id as _
+        )
+        .fetch_all(self.db.inner_ref())
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        Ok(rows.into_iter().map(BookRevision::from).collect())
+    }
+
+    async fn revert(&self, event: RevertBook) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        ensure_book_is_modifiable(
+            &mut tx,
+            event.book_id,
+            event.requested_user,
+            event.requested_role,
+            event.expected_version,
+        )
+        .await?;
+
+        let revision = sqlx::query!(
+            r#"
+                SELECT r.title, r.author, r.isbn, r.description, r.isbn <> b.isbn "isbn_changed!"
+                FROM book_revisions r
+                INNER JOIN books b ON r.book_id = b.book_id
+                WHERE r.book_id = $1 AND r.revision_number = $2
+            "#,
+            event.book_id as _,
+            event.revision_number
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?
+        .ok_or_else(|| {
+            AppError::EntityNotFound(format!(
+                "the revision ({}) of the book ({}) doesn't exist",
+                event.revision_number, event.book_id
+            ))
+        })?;
+
+        // ISBNを戻す場合のみ、他の蔵書と重複しないか確認する。
+        if revision.isbn_changed {
+            ensure_isbn_is_unique(&mut tx, &revision.isbn.parse()?, Some(event.book_id)).await?;
         }
 
+        record_book_revision(&mut tx, event.book_id, event.requested_user).await?;
+        let before = book_snapshot(&mut tx, event.book_id).await?;
+        sqlx::query!(
+            r#"
+                UPDATE books
+                SET
+                    title = $1,
+                    author = $2,
+                    isbn = $3,
+                    description = $4
+                WHERE book_id = $5
+            "#,
+            revision.title,
+            revision.author,
+            revision.isbn,
+            revision.description,
+            event.book_id as _,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        let after = book_snapshot(&mut tx, event.book_id).await?;
+        record_audit_event(
+            &mut tx,
+            AuditRecord::new(
+                Some(event.requested_user),
+                AuditAction::RevertBook,
+                AuditEntityType::Book,
+                event.book_id.to_string(),
+                before,
+                after,
+            ),
+        )
+        .await?;
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
         Ok(())
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use sqlx::PgPool;
+    async fn find_deleted_all(
+        &self,
+        options: DeletedBookListOptions,
+    ) -> AppResult<PaginatedList<DeletedBook>> {
+        let DeletedBookListOptions { limit, offset } = options;
 
-    use kernel::model::user::event::CreateUser;
-    use kernel::repository::user::UserRepository;
+        let rows: Vec<DeletedBookRow> = sqlx::query_as!(
+            DeletedBookRow,
+            r#"
+                SELECT
+                    COUNT(*) OVER() "total!",
+                    b.book_id, b.title, b.author, b.isbn, b.description,
+                    u.user_id owned_by, u.name owner_name,
+                    b.deleted_at "deleted_at!"
+                FROM books b
+                INNER JOIN users u ON b.user_id = u.user_id
+                WHERE b.deleted_at IS NOT NULL
+                ORDER BY b.deleted_at DESC, b.book_id
+                LIMIT $1
+                OFFSET $2
+            "#,
+            limit,
+            offset
+        )
+        .fetch_all(self.db.inner_ref())
+        .await
+        .map_err(AppError::SpecificOperationError)?;
 
-    use super::*;
-    use crate::repository::user::UserRepositoryImpl;
+        let total = rows.first().map(|r| r.total).unwrap_or_default();
+        let items = rows.into_iter().map(DeletedBook::from).collect();
 
-    #[sqlx::test]
-    async fn test_register_book(pool: PgPool) -> anyhow::Result<()> {
-        // TODO: ロールを追加（フィクスチャーに変更）
+        Ok(PaginatedList {
+            total,
+            limit,
+            offset,
+            next_cursor: None,
+            items,
+        })
+    }
+
+    async fn delete(&self, event: DeleteBook) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        ensure_book_is_modifiable(
+            &mut tx,
+            event.book_id,
+            event.requested_user,
+            event.requested_role,
+            event.expected_version,
+        )
+        .await?;
+
+        // 貸出中の蔵書を削除すると返却できなくなるため、貸出中の蔵書は削除できない。
+        // 蔵書をロックしているため、確認してから削除するまでの間に貸出されることはない。
+        let checked_out = sqlx::query_scalar!(
+            r#"
+                SELECT EXISTS (
+                    SELECT 1 FROM checkouts WHERE book_id = $1
+                ) "checked_out!"
+            "#,
+            event.book_id as _
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+        if checked_out {
+            return Err(AppError::UnprocessableEntity(format!(
+                "the book ({}) is checked out",
+                event.book_id
+            )));
+        }
+
+        // ゴミ箱にある蔵書は借りられないため、待ち行列の予約と取り置き中の予約を取り消す。
+        let holds = sqlx::query!(
+            r#"
+                DELETE FROM holds h
+                WHERE h.book_id = $1
+                RETURNING h.hold_id "hold_id: HoldId", to_jsonb(h) "snapshot!"
+            "#,
+            event.book_id as _
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+        for hold in holds {
+            record_audit_event(
+                &mut tx,
+                AuditRecord::new(
+                    Some(event.requested_user),
+                    AuditAction::DeleteHold,
+                    AuditEntityType::Hold,
+                    hold.hold_id.to_string(),
+                    Some(hold.snapshot),
+                    None,
+                ),
+            )
+            .await?;
+        }
+
+        // 貸出履歴を残すため、蔵書の行は削除せずに削除した日時を記録する。
+        let before = book_snapshot(&mut tx, event.book_id).await?;
         sqlx::query!(
             r#"
-                INSERT INTO roles (name)
-                VALUES
-                    ('Admin'),
-                    ('User')
+                UPDATE books
+                SET deleted_at = CURRENT_TIMESTAMP(3)
+                WHERE book_id = $1
             "#,
+            event.book_id as _
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        let after = book_snapshot(&mut tx, event.book_id).await?;
+        record_audit_event(
+            &mut tx,
+            AuditRecord::new(
+                Some(event.requested_user),
+                AuditAction::DeleteBook,
+                AuditEntityType::Book,
+                event.book_id.to_string(),
+                before,
+                after,
+            ),
         )
-        .execute(&pool)
         .await?;
-        // TODO: テスト用のユーザーを登録（フィクスチャーに変更）
-        let user_repo = UserRepositoryImpl::new(ConnectionPool::new(pool.clone()));
-        let user = user_repo
-            .create(CreateUser {
-                name: "Test User".into(),
-                email: "test@example.com".into(),
-                password: "test_password".into(),
-            })
-            .await?;
-        // TODO: テスト用の蔵書を登録（フィクスチャーに変更）
-        let book_repo = BookRepositoryImpl::new(ConnectionPool::new(pool.clone()));
-        let book = CreateBook {
-            title: "Test Title".into(),
-            author: "Test Author".into(),
-            isbn: "Test ISBN".into(),
-            description: "Test Description".into(),
-        };
-        book_repo.create(book, user.id).await?;
 
-        // 蔵書リストを取得
-        let options = BookListOptions {
-            limit: 20,
-            offset: 0,
-        };
-        let books = book_repo.find_all(options).await?;
-        assert_eq!(1, books.items.len());
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(())
+    }
+
+    async fn restore(&self, event: RestoreBook) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        let isbn = sqlx::query_scalar!(
+            r#"
+                SELECT isbn
+                FROM books
+                WHERE book_id = $1 AND deleted_at IS NOT NULL
+                FOR UPDATE
+            "#,
+            event.book_id as _
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?
+        .ok_or_else(|| AppError::EntityNotFound("specified book not found in the trash".into()))?;
+
+        // ゴミ箱にある間に同じISBNの蔵書が登録された場合は、元に戻すと重複するため元に戻さない。
+        // 正規化できない古いISBNは、重複しているか判断できないため確認しない。
+        if let Ok(isbn) = isbn.parse::<Isbn>() {
+            ensure_isbn_is_unique(&mut tx, &isbn, Some(event.book_id)).await?;
+        }
+
+        let before = book_snapshot(&mut tx, event.book_id).await?;
+        let result = sqlx::query!(
+            r#"
+                UPDATE books
+                SET deleted_at = NULL
+                WHERE book_id = $1 AND deleted_at IS NOT NULL
+            "#,
+            event.book_id as _
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        if result.rows_affected() < 1 {
+            return Err(AppError::EntityNotFound(
+                "specified book not found in the trash".into(),
+            ));
+        }
+
+        let after = book_snapshot(&mut tx, event.book_id).await?;
+        record_audit_event(
+            &mut tx,
+            AuditRecord::new(
+                Some(event.requested_user),
+                AuditAction::RestoreBook,
+                AuditEntityType::Book,
+                event.book_id.to_string(),
+                before,
+                after,
+            ),
+        )
+        .await?;
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(())
+    }
+
+    async fn purge(&self, event: PurgeBook) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        // 完全に削除できるのは、ゴミ箱にある蔵書のみである。
+        // 貸出中の蔵書を削除すると未返却の貸出も削除されるため、貸出中の蔵書は削除できない。
+        let checked_out = sqlx::query_scalar!(
+            r#"
+                SELECT EXISTS (
+                    SELECT 1 FROM checkouts c WHERE c.book_id = b.book_id
+                ) "checked_out!"
+                FROM books b
+                WHERE b.book_id = $1 AND b.deleted_at IS NOT NULL
+                FOR UPDATE OF b
+            "#,
+            event.book_id as _
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        match checked_out {
+            None => {
+                return Err(AppError::EntityNotFound(
+                    "specified book not found in the trash".into(),
+                ))
+            }
+            Some(true) => {
+                return Err(AppError::UnprocessableEntity(format!(
+                    "the book ({}) is checked out",
+                    event.book_id
+                )))
+            }
+            Some(false) => {}
+        }
+
+        let before = book_snapshot(&mut tx, event.book_id).await?;
+        sqlx::query!(
+            r#"
+                DELETE FROM books
+                WHERE book_id = $1
+            "#,
+            event.book_id as _
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        record_audit_event(
+            &mut tx,
+            AuditRecord::new(
+                Some(event.requested_user),
+                AuditAction::PurgeBook,
+                AuditEntityType::Book,
+                event.book_id.to_string(),
+                before,
+                None,
+            ),
+        )
+        .await?;
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(())
+    }
+
+    async fn find_copies_by_book_id(&self, book_id: BookId) -> AppResult<Vec<BookCopy>> {
+        sqlx::query_as!(
+            BookCopyRow,
+            r#"
+                SELECT
+                    bc.copy_id,
+                    bc.book_id,
+                    bc.barcode,
+                    bc.condition,
+                    bc.location,
+                    EXISTS (
+                        SELECT 1 FROM checkouts c WHERE c.copy_id = bc.copy_id
+                    ) "checked_out!"
+                FROM book_copies bc
+                INNER JOIN books b ON bc.book_id = b.book_id
+                WHERE bc.book_id = $1 AND b.deleted_at IS NULL
+                ORDER BY bc.barcode
+            "#,
+            book_id as _
+        )
+        .fetch_all(self.db.inner_ref())
+        .await
+        .map_err(AppError::SpecificOperationError)?
+        .into_iter()
+        .map(BookCopy::try_from)
+        .collect()
+    }
+
+    async fn create_copy(&self, event: CreateBookCopy) -> AppResult<()> {
+        // 複本の追加で借りられるようになった予約は、次に蔵書の貸出や予約が操作されたときに取り置かれる。
+        let mut tx = self.db.begin().await?;
+
+        ensure_book_is_modifiable(
+            &mut tx,
+            event.book_id,
+            event.requested_user,
+            event.requested_role,
+            None,
+        )
+        .await?;
+
+        let copy_id = BookCopyId::new();
+        sqlx::query!(
+            r#"
+                INSERT INTO book_copies (copy_id, book_id, barcode, condition, location)
+                VALUES ($1, $2, $3, $4, $5)
+            "#,
+            copy_id as _,
+            event.book_id as _,
+            event.barcode,
+            event.condition.as_ref(),
+            event.location,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(barcode_error(&event.barcode))?;
+
+        let after = book_copy_snapshot(&mut tx, copy_id).await?;
+        record_audit_event(
+            &mut tx,
+            AuditRecord::new(
+                Some(event.requested_user),
+                AuditAction::CreateBookCopy,
+                AuditEntityType::BookCopy,
+                copy_id.to_string(),
+                None,
+                after,
+            ),
+        )
+        .await?;
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(())
+    }
+
+    async fn update_copy(&self, event: UpdateBookCopy) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        ensure_book_is_modifiable(
+            &mut tx,
+            event.book_id,
+            event.requested_user,
+            event.requested_role,
+            None,
+        )
+        .await?;
+
+        let before = book_copy_snapshot(&mut tx, event.copy_id).await?;
+        let result = sqlx::query!(
+            r#"
+                UPDATE book_copies
+                SET
+                    barcode = $3,
+                    condition = $4,
+                    location = $5
+                WHERE copy_id = $1 AND book_id = $2
+            "#,
+            event.copy_id as _,
+            event.book_id as _,
+            event.barcode,
+            event.condition.as_ref(),
+            event.location,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(barcode_error(&event.barcode))?;
+
+        if result.rows_affected() < 1 {
+            return Err(AppError::EntityNotFound("specified copy not found".into()));
+        }
+
+        let after = book_copy_snapshot(&mut tx, event.copy_id).await?;
+        record_audit_event(
+            &mut tx,
+            AuditRecord::new(
+                Some(event.requested_user),
+                AuditAction::UpdateBookCopy,
+                AuditEntityType::BookCopy,
+                event.copy_id.to_string(),
+                before,
+                after,
+            ),
+        )
+        .await?;
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(())
+    }
+
+    async fn delete_copy(&self, event: DeleteBookCopy) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        ensure_book_is_modifiable(
+            &mut tx,
+            event.book_id,
+            event.requested_user,
+            event.requested_role,
+            None,
+        )
+        .await?;
+
+        // 貸出中の複本を削除すると貸出も削除されるため、貸出中の複本は削除できない。
+        let checked_out = sqlx::query_scalar!(
+            r#"
+                SELECT EXISTS (
+                    SELECT 1 FROM checkouts c WHERE c.copy_id = bc.copy_id
+                ) "checked_out!"
+                FROM book_copies bc
+                WHERE bc.copy_id = $1 AND bc.book_id = $2
+                FOR UPDATE OF bc
+            "#,
+            event.copy_id as _,
+            event.book_id as _,
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        match checked_out {
+            None => return Err(AppError::EntityNotFound("specified copy not found".into())),
+            Some(true) => {
+                return Err(AppError::UnprocessableEntity(format!(
+                    "the copy ({}) is checked out",
+                    event.copy_id
+                )))
+            }
+            Some(false) => {}
+        }
+
+        let before = book_copy_snapshot(&mut tx, event.copy_id).await?;
+        sqlx::query!(
+            r#"
+                DELETE FROM book_copies
+                WHERE copy_id = $1
+            "#,
+            event.copy_id as _
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        record_audit_event(
+            &mut tx,
+            AuditRecord::new(
+                Some(event.requested_user),
+                AuditAction::DeleteBookCopy,
+                AuditEntityType::BookCopy,
+                event.copy_id.to_string(),
+                before,
+                None,
+            ),
+        )
+        .await?;
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(())
+    }
+}
+
+/// 蔵書を登録して、貸出できるように複本を1冊追加する。登録した蔵書は監査ログに記録する。
+///
+/// 重複が許可されていない場合に同じISBNを持つ蔵書が存在すれば、`AppError::Conflict`を返す。
+async fn insert_book(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    event: CreateBook,
+    user_id: UserId,
+) -> AppResult<BookId> {
+    if !event.allow_duplicate {
+        ensure_isbn_is_unique(tx, &event.isbn, None).await?;
+    }
+
+    let book_id = BookId::new();
+    sqlx::query!(
+        r#"
+                INSERT INTO books (
+                    book_id, title, author, isbn, description, user_id
+                ) VALUES (
+                    $1, $2, $3, $4, $5, $6
+                )
+            "#,
+        book_id as _,
+        event.title,
+        event.author,
+        event.isbn.as_str(),
+        event.description,
+        user_id as _,
+    )
+    .execute(&mut **tx)
+    .await
+    .map_err(AppError::SpecificOperationError)?;
+
+    // 登録した蔵書をすぐに貸出できるように、複本を1冊登録する。
+    // バーコードが決まっていないため、複本IDをバーコードとする。
+    let copy_id = BookCopyId::new();
+    sqlx::query!(
+        r#"
+                INSERT INTO book_copies (copy_id, book_id, barcode)
+                VALUES ($1, $2, $3)
+            "#,
+        copy_id as _,
+        book_id as _,
+        copy_id.to_string(),
+    )
+    .execute(&mut **tx)
+    .await
+    .map_err(AppError::SpecificOperationError)?;
+
+    let after = book_snapshot(tx, book_id).await?;
+    record_audit_event(
+        tx,
+        AuditRecord::new(
+            Some(user_id),
+            AuditAction::CreateBook,
+            AuditEntityType::Book,
+            book_id.to_string(),
+            None,
+            after,
+        ),
+    )
+    .await?;
+
+    Ok(book_id)
+}
+
+/// 蔵書を変更できるのは、蔵書の所有者と管理者のみである。
+///
+/// 蔵書が存在しない場合は`AppError::EntityNotFound`を、変更する権限がない場合は
+/// `AppError::ForbiddenOperation`を返す。ゴミ箱にある蔵書は存在しないものとして扱う。
+/// `expected_version`を指定した場合、蔵書のバージョンが異なれば他のリクエストによって
+/// 更新されているため、`AppError::PreconditionFailed`を返す。
+/// 確認してから変更するまでの間に蔵書が削除されたり貸出されたりしないように、
+/// トランザクションが終わるまで蔵書をロックする。
+async fn ensure_book_is_modifiable(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    book_id: BookId,
+    requested_user: UserId,
+    requested_role: Role,
+    expected_version: Option<i64>,
+) -> AppResult<()> {
+    let row = sqlx::query!(
+        r#"
+            SELECT user_id "user_id: UserId", version
+            FROM books
+            WHERE book_id = $1 AND deleted_at IS NULL
+            FOR UPDATE
+        "#,
+        book_id as _
+    )
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(AppError::SpecificOperationError)?;
+
+    let Some(row) = row else {
+        return Err(AppError::EntityNotFound(format!(
+            "the book ({book_id}) doesn't exist"
+        )));
+    };
+    if row.user_id != requested_user && requested_role != Role::Admin {
+        return Err(AppError::ForbiddenOperation);
+    }
+    match expected_version {
+        Some(expected) if expected != row.version => Err(AppError::PreconditionFailed(format!(
+            "the book ({book_id}) has been modified by another request"
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// 蔵書の現在の内容を、次の番号の更新履歴として記録する。
+///
+/// 蔵書を更新する前に呼び出す。更新履歴の番号が重複しないように、
+/// `ensure_book_is_modifiable`で蔵書をロックしてから呼び出すこと。
+async fn record_book_revision(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    book_id: BookId,
+    revised_by: UserId,
+) -> AppResult<()> {
+    sqlx::query!(
+        r#"
+            INSERT INTO book_revisions
+                (book_id, revision_number, title, author, isbn, description, revised_by)
+            SELECT
+                b.book_id,
+                COALESCE(
+                    (SELECT MAX(revision_number) FROM book_revisions WHERE book_id = b.book_id),
+                    0
+                ) + 1,
+                b.title, b.author, b.isbn, b.description, $2
+            FROM books b
+            WHERE b.book_id = $1
+        "#,
+        book_id as _,
+        revised_by as _
+    )
+    .execute(&mut **tx)
+    .await
+    .map_err(AppError::SpecificOperationError)?;
+
+    Ok(())
+}
+
+/// 同じISBNを持つ蔵書が存在する場合は、重複として扱う。ゴミ箱にある蔵書は重複として扱わない。
+///
+/// 同じISBNの蔵書が同時に登録されないように、トランザクションが終わるまでISBNごとのロックを取得する。
+/// `exclude`には、更新する蔵書自身を除外するために蔵書IDを指定する。
+async fn ensure_isbn_is_unique(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    isbn: &Isbn,
+    exclude: Option<BookId>,
+) -> AppResult<()> {
+    sqlx::query!("SELECT pg_advisory_xact_lock(hashtext($1))", isbn.as_str())
+        .execute(&mut **tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+    let duplicated = sqlx::query_scalar!(
+        r#"
+            SELECT book_id "book_id: BookId"
+            FROM books
+            WHERE
+                isbn = $1
+                AND ($2::UUID IS NULL OR book_id <> $2)
+                AND deleted_at IS NULL
+            ORDER BY created_at
+            LIMIT 1
+        "#,
+        isbn.as_str(),
+        exclude as _
+    )
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(AppError::SpecificOperationError)?;
+
+    match duplicated {
+        Some(book_id) => Err(AppError::Conflict(format!(
+            "the book ({book_id}) with the same ISBN ({isbn}) already exists"
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// バーコードの一意制約に違反した場合は、処理できないリクエストとして扱う。
+fn barcode_error(barcode: &str) -> impl FnOnce(sqlx::Error) -> AppError + '_ {
+    move |e| match e {
+        sqlx::Error::Database(ref db) if db.is_unique_violation() => {
+            AppError::UnprocessableEntity(format!("the barcode ({barcode}) is already used"))
+        }
+        e => AppError::SpecificOperationError(e),
+    }
+}
+
+/// 並べ替えキーに対応する式をSQL文に追加する。
+fn push_sort_key(builder: &mut QueryBuilder<'_, Postgres>, key: BookSortKey, query: Option<&str>) {
+    match (key, query) {
+        (BookSortKey::Relevance, Some(query)) => {
+            builder
+                .push("ts_rank(b.search_vector, websearch_to_tsquery('simple', ")
+                .push_bind(query.to_string())
+                .push("))");
+        }
+        // 検索語が指定されていない場合、関連度はすべての蔵書で等しい。
+        (BookSortKey::Relevance, None) => {
+            builder.push("0::real");
+        }
+        (BookSortKey::Title, _) => {
+            builder.push("b.title");
+        }
+        (BookSortKey::Author, _) => {
+            builder.push("b.author");
+        }
+        (BookSortKey::CreatedAt, _) => {
+            builder.push("b.created_at");
+        }
+        (BookSortKey::UpdatedAt, _) => {
+            builder.push("b.updated_at");
+        }
+        // レビューがない蔵書の評価は0とみなし、カーソルで比較できるようにNULLにしない。
+        (BookSortKey::Rating, _) => {
+            builder.push(
+                "(SELECT COALESCE(AVG(r.rating), 0)::FLOAT8 FROM reviews r \
+                 WHERE r.book_id = b.book_id)",
+            );
+        }
+    }
+}
+
+/// カーソルに格納した並べ替えキーの値を、元の型に戻すための型名を返す。
+fn sort_key_type(key: BookSortKey) -> &'static str {
+    match key {
+        BookSortKey::Relevance => "real",
+        BookSortKey::Title | BookSortKey::Author => "text",
+        BookSortKey::CreatedAt | BookSortKey::UpdatedAt => "timestamptz",
+        BookSortKey::Rating => "float8",
+    }
+}
+
+/// `LIKE`のパターンで特別な意味を持つ`%`、`_`及びエスケープ文字`\`をエスケープする。
+fn escape_like_pattern(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '\\' | '%' | '_') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// 蔵書一覧の絞り込み条件をSQL文に追加する。
+///
+/// 検索語が指定された場合は、全文検索用の列と検索語を照合する。
+/// `simple`設定は日本語を分かち書きできないため、タイトルと著者は部分一致でも照合する。
+fn push_book_filters(
+    builder: &mut QueryBuilder<'_, Postgres>,
+    query: Option<&str>,
+    owner: Option<UserId>,
+    checkout_state: Option<BookCheckoutState>,
+    tags: Option<&TagFilter>,
+) {
+    if let Some(query) = query {
+        // 全文検索で見つからない日本語などは部分一致で照合する。検索語の`%`と`_`は文字として扱う。
+        let pattern = format!("%{}%", escape_like_pattern(query));
+        builder
+            .push(" AND (b.search_vector @@ websearch_to_tsquery('simple', ")
+            .push_bind(query.to_string())
+            .push(") OR b.title ILIKE ")
+            .push_bind(pattern.clone())
+            .push(r" ESCAPE '\' OR b.author ILIKE ")
+            .push_bind(pattern)
+            .push(r" ESCAPE '\'");
+        // 検索語がISBNの場合は、ハイフンの有無やISBN-10とISBN-13の違いにかかわらず照合する。
+        if let Ok(isbn) = query.parse::<Isbn>() {
+            builder.push(" OR b.isbn = ").push_bind(String::from(isbn));
+        }
+        builder.push(")");
+    }
+    if let Some(owner) = owner {
+        builder.push(" AND b.user_id = ").push_bind(owner);
+    }
+    // タグ名は大文字と小文字を区別せずに照合する。すべてのタグを指定した場合は、
+    // 指定したタグのうち蔵書に付いているものの数が、指定したタグの数と等しい蔵書を選ぶ。
+    if let Some(tags) = tags.filter(|t| !t.names.is_empty()) {
+        let mut names = tags
+            .names
+            .iter()
+            .map(|n| n.to_lowercase())
+            .collect::<Vec<_>>();
+        names.sort();
+        names.dedup();
+        let count = names.len() as i64;
+        builder
+            .push(
+                " AND (SELECT COUNT(*) FROM book_tags bt INNER JOIN tags tg ON bt.tag_id = tg.tag_id \
+                 WHERE bt.book_id = b.book_id AND lower(tg.name) = ANY(",
+            )
+            .push_bind(names)
+            .push("))");
+        match tags.match_mode {
+            TagMatch::All => builder.push(" = ").push_bind(count),
+            TagMatch::Any => builder.push(" > 0"),
+        };
+    }
+    // 貸出中でも紛失してもいない複本がある蔵書を貸出可能とし、
+    // 貸出可能な複本がなく、いずれかの複本が貸出中の蔵書を貸出中とする。
+    const AVAILABLE_COPY_EXISTS: &str = r#"
+        EXISTS (
+            SELECT 1 FROM book_copies bc
+            WHERE bc.book_id = b.book_id
+                AND bc.condition <> 'Lost'
+                AND NOT EXISTS (SELECT 1 FROM checkouts c WHERE c.copy_id = bc.copy_id)
+        )
+    "#;
+    match checkout_state {
+        Some(BookCheckoutState::Available) => {
+            builder.push(" AND ").push(AVAILABLE_COPY_EXISTS);
+        }
+        Some(BookCheckoutState::CheckedOut) => {
+            builder
+                .push(" AND NOT ")
+                .push(AVAILABLE_COPY_EXISTS)
+                .push(" AND EXISTS (SELECT 1 FROM checkouts c WHERE c.book_id = b.book_id)");
+        }
+        None => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use sqlx::PgPool;
+
+    use kernel::model::user::event::CreateUser;
+    use kernel::repository::user::UserRepository;
+
+    use kernel::model::book::{BookCopyCondition, BookField, BookFieldChange, BookSort};
+    use kernel::model::id::CheckoutId;
+
+    use kernel::model::role::Role;
+    use kernel::model::tag::event::{AttachTag, CreateTag};
+    use kernel::repository::tag::TagRepository;
+
+    use super::*;
+    use crate::repository::tag::TagRepositoryImpl;
+    use crate::repository::user::UserRepositoryImpl;
+
+    #[sqlx::test]
+    async fn test_register_book(pool: PgPool) -> anyhow::Result<()> {
+        // TODO: ロールを追加（フィクスチャーに変更）
+        sqlx::query!(
+            r#"
+                INSERT INTO roles (name)
+                VALUES
+                    ('Admin'),
+                    ('User')
+            "#,
+        )
+        .execute(&pool)
+        .await?;
+        // TODO: テスト用のユーザーを登録（フィクスチャーに変更）
+        let user_repo = UserRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+        let user = user_repo
+            .create(CreateUser {
+                name: "Test User".into(),
+                email: "test@example.com".into(),
+                password: "test_password".into(),
+                // 監査ログの操作したユーザーには外部キー制約がないため、存在しないユーザーでもよい
+                requested_user: UserId::new(),
+            })
+            .await?;
+        // TODO: テスト用の蔵書を登録（フィクスチャーに変更）
+        let book_repo = BookRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+        let book = CreateBook {
+            title: "Test Title".into(),
+            author: "Test Author".into(),
+            isbn: "978-4-7981-6044-3".parse()?,
+            description: "Test Description".into(),
+            allow_duplicate: false,
+        };
+        book_repo.create(book, user.id).await?;
+
+        // 蔵書リストを取得
+        let options = BookListOptions {
+            limit: 20,
+            offset: 0,
+            sort: BookSortKey::CreatedAt.into(),
+            cursor: None,
+            query: None,
+            owner: None,
+            checkout_state: None,
+            tags: None,
+        };
+        let books = book_repo.find_all(options).await?;
+        assert_eq!(1, books.items.len());
 
         let book_id = books.items[0].id;
         let book = book_repo.find_by_id(book_id).await?;
@@ -288,10 +1567,1028 @@ mod tests {
         assert_eq!(id, book_id);
         assert_eq!(title, "Test Title");
         assert_eq!(author, "Test Author");
-        assert_eq!(isbn, "Test ISBN");
+        // ISBNはハイフンを取り除いて記録される
+        assert_eq!(isbn, "9784798160443");
         assert_eq!(description, "Test Description");
         assert_eq!(owner.name, "Test User");
 
         Ok(())
     }
+
+    fn list_options() -> BookListOptions {
+        BookListOptions {
+            limit: 20,
+            offset: 0,
+            sort: BookSortKey::CreatedAt.into(),
+            cursor: None,
+            query: None,
+            owner: None,
+            checkout_state: None,
+            tags: None,
+        }
+    }
+
+    fn titles(books: &PaginatedList<Book>) -> Vec<&str> {
+        books.items.iter().map(|b| b.title.as_str()).collect()
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_find_all_ordered_by_created_at_desc(pool: PgPool) -> anyhow::Result<()> {
+        let book_repo = BookRepositoryImpl::new(ConnectionPool::new(pool));
+
+        let books = book_repo.find_all(list_options()).await?;
+        assert_eq!(books.total, 4);
+        assert_eq!(
+            titles(&books),
+            vec![
+                "PostgreSQL: Up and Running",
+                "Programming Rust",
+                "The Rust Programming Language",
+                "RustによるWebアプリケーション開発",
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_search_books(pool: PgPool) -> anyhow::Result<()> {
+        let book_repo = BookRepositoryImpl::new(ConnectionPool::new(pool));
+
+        // タイトルに検索語を多く含む蔵書ほど上位に並ぶ
+        let books = book_repo
+            .find_all(BookListOptions {
+                query: Some("rust programming".into()),
+                sort: BookSortKey::Relevance.into(),
+                ..list_options()
+            })
+            .await?;
+        assert_eq!(books.total, 2);
+        assert_eq!(
+            titles(&books),
+            vec!["The Rust Programming Language", "Programming Rust"]
+        );
+
+        // ハイフンの有無やISBN-10とISBN-13の違いにかかわらずISBNで検索できる
+        for isbn in ["978-1-4919-6341-8", "9781491963418", "1-4919-6341-7"] {
+            let books = book_repo
+                .find_all(BookListOptions {
+                    query: Some(isbn.into()),
+                    ..list_options()
+                })
+                .await?;
+            assert_eq!(titles(&books), vec!["PostgreSQL: Up and Running"]);
+        }
+
+        // 日本語は部分一致で検索できる
+        let books = book_repo
+            .find_all(BookListOptions {
+                query: Some("アプリケーション".into()),
+                ..list_options()
+            })
+            .await?;
+        assert_eq!(titles(&books), vec!["RustによるWebアプリケーション開発"]);
+
+        // `%`や`_`はワイルドカードではなく文字として照合する
+        for query in ["%", "_", "\\"] {
+            let books = book_repo
+                .find_all(BookListOptions {
+                    query: Some(query.into()),
+                    ..list_options()
+                })
+                .await?;
+            assert_eq!(books.total, 0, "{query}");
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_filter_books(pool: PgPool) -> anyhow::Result<()> {
+        let book_repo = BookRepositoryImpl::new(ConnectionPool::new(pool));
+        let owner = UserId::from_str("9582f9de-0fd1-4892-b20c-70139a7eb95b")?;
+
+        let books = book_repo
+            .find_all(BookListOptions {
+                owner: Some(owner),
+                ..list_options()
+            })
+            .await?;
+        assert_eq!(
+            titles(&books),
+            vec!["Programming Rust", "The Rust Programming Language"]
+        );
+
+        let books = book_repo
+            .find_all(BookListOptions {
+                checkout_state: Some(BookCheckoutState::CheckedOut),
+                ..list_options()
+            })
+            .await?;
+        assert_eq!(titles(&books), vec!["Programming Rust"]);
+        assert_eq!(books.items[0].checkouts.len(), 1);
+        assert_eq!(books.items[0].available_copies, 0);
+
+        let books = book_repo
+            .find_all(BookListOptions {
+                query: Some("rust".into()),
+                owner: Some(owner),
+                checkout_state: Some(BookCheckoutState::Available),
+                ..list_options()
+            })
+            .await?;
+        assert_eq!(books.total, 1);
+        assert_eq!(titles(&books), vec!["The Rust Programming Language"]);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_filter_books_by_tags(pool: PgPool) -> anyhow::Result<()> {
+        let book_repo = BookRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+        let tag_repo = TagRepositoryImpl::new(ConnectionPool::new(pool));
+        let admin = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+
+        let rust = tag_repo
+            .create(CreateTag::new("Rust".into(), admin))
+            .await?;
+        let web = tag_repo.create(CreateTag::new("Web".into(), admin)).await?;
+        for (book_id, tag_id) in [
+            ("9890736e-a4e4-461a-a77d-eac3517ef11b", rust),
+            ("9890736e-a4e4-461a-a77d-eac3517ef11b", web),
+            ("b8d8a5c3-0f7b-4a2e-9d6c-2e1f3a4b5c6d", rust),
+        ] {
+            let book_id = BookId::from_str(book_id)?;
+            tag_repo
+                .attach(AttachTag::new(book_id, tag_id, admin, Role::Admin))
+                .await?;
+        }
+
+        let filter = |names: &[&str], match_mode| {
+            Some(TagFilter {
+                names: names.iter().map(|n| n.to_string()).collect(),
+                match_mode,
+            })
+        };
+
+        // すべてのタグが付いた蔵書のみを返す。タグ名は大文字と小文字を区別しない
+        let books = book_repo
+            .find_all(BookListOptions {
+                tags: filter(&["rust", "WEB"], TagMatch::All),
+                ..list_options()
+            })
+            .await?;
+        assert_eq!(books.total, 1);
+        assert_eq!(titles(&books), vec!["RustによるWebアプリケーション開発"]);
+        assert_eq!(books.items[0].tags.len(), 2);
+
+        // いずれかのタグが付いた蔵書を返す
+        let books = book_repo
+            .find_all(BookListOptions {
+                tags: filter(&["rust", "web"], TagMatch::Any),
+                ..list_options()
+            })
+            .await?;
+        assert_eq!(
+            titles(&books),
+            vec![
+                "The Rust Programming Language",
+                "RustによるWebアプリケーション開発",
+            ]
+        );
+
+        // 同じタグ名を重複して指定しても結果は変わらない
+        let books = book_repo
+            .find_all(BookListOptions {
+                tags: filter(&["Rust", "rust"], TagMatch::All),
+                ..list_options()
+            })
+            .await?;
+        assert_eq!(books.total, 2);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_sort_books_with_offset(pool: PgPool) -> anyhow::Result<()> {
+        let book_repo = BookRepositoryImpl::new(ConnectionPool::new(pool));
+
+        let books = book_repo
+            .find_all(BookListOptions {
+                limit: 2,
+                offset: 1,
+                sort: BookSortKey::Title.into(),
+                ..list_options()
+            })
+            .await?;
+        assert_eq!(books.total, 4);
+        assert_eq!(books.offset, 1);
+        assert_eq!(
+            titles(&books),
+            vec!["Programming Rust", "RustによるWebアプリケーション開発"]
+        );
+        assert!(books.next_cursor.is_some());
+
+        let books = book_repo
+            .find_all(BookListOptions {
+                sort: BookSort {
+                    key: BookSortKey::Author,
+                    direction: SortDirection::Desc,
+                },
+                ..list_options()
+            })
+            .await?;
+        assert_eq!(
+            titles(&books),
+            vec![
+                "RustによるWebアプリケーション開発",
+                "The Rust Programming Language",
+                "PostgreSQL: Up and Running",
+                "Programming Rust",
+            ]
+        );
+        assert!(books.next_cursor.is_none());
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_paginate_books_with_cursor(pool: PgPool) -> anyhow::Result<()> {
+        let book_repo = BookRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+
+        for sort in [
+            BookSort::from(BookSortKey::CreatedAt),
+            BookSort::from(BookSortKey::Title),
+            BookSort::from(BookSortKey::Rating),
+            BookSort {
+                key: BookSortKey::UpdatedAt,
+                direction: SortDirection::Asc,
+            },
+        ] {
+            let expected = book_repo
+                .find_all(BookListOptions {
+                    sort,
+                    ..list_options()
+                })
+                .await?
+                .items
+                .into_iter()
+                .map(|b| b.id)
+                .collect::<Vec<_>>();
+
+            let mut actual = Vec::new();
+            let mut cursor = None;
+            loop {
+                let books = book_repo
+                    .find_all(BookListOptions {
+                        limit: 3,
+                        sort,
+                        cursor,
+                        ..list_options()
+                    })
+                    .await?;
+                assert_eq!(books.total, 4);
+                actual.extend(books.items.iter().map(|b| b.id));
+                match books.next_cursor {
+                    Some(next) => cursor = Some(BookListCursor::try_from(next)?),
+                    None => break,
+                }
+            }
+            assert_eq!(actual, expected);
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_cursor_is_stable_when_books_are_added(pool: PgPool) -> anyhow::Result<()> {
+        let book_repo = BookRepositoryImpl::new(ConnectionPool::new(pool));
+
+        let first_page = book_repo
+            .find_all(BookListOptions {
+                limit: 2,
+                ..list_options()
+            })
+            .await?;
+        assert_eq!(
+            titles(&first_page),
+            vec!["PostgreSQL: Up and Running", "Programming Rust"]
+        );
+
+        // 1ページ目を取得した後に蔵書が追加されても、2ページ目の内容はずれない
+        book_repo
+            .create(
+                CreateBook {
+                    title: "Zero To Production In Rust".into(),
+                    author: "Luca Palmieri".into(),
+                    isbn: "979-8-8685-7250-0".parse()?,
+                    description: "An introduction to backend development in Rust".into(),
+                    allow_duplicate: false,
+                },
+                UserId::from_str("9582f9de-0fd1-4892-b20c-70139a7eb95b")?,
+            )
+            .await?;
+
+        let cursor = BookListCursor::try_from(first_page.next_cursor.unwrap())?;
+        let second_page = book_repo
+            .find_all(BookListOptions {
+                limit: 2,
+                cursor: Some(cursor),
+                ..list_options()
+            })
+            .await?;
+        assert_eq!(second_page.total, 5);
+        assert_eq!(
+            titles(&second_page),
+            vec![
+                "The Rust Programming Language",
+                "RustによるWebアプリケーション開発"
+            ]
+        );
+        assert!(second_page.next_cursor.is_none());
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_manage_book_copies(pool: PgPool) -> anyhow::Result<()> {
+        let book_repo = BookRepositoryImpl::new(ConnectionPool::new(pool));
+        let book_id = BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b")?;
+        let owner = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+        let other_user = UserId::from_str("9582f9de-0fd1-4892-b20c-70139a7eb95b")?;
+
+        // 所有者と管理者以外は複本を追加できない
+        let result = book_repo
+            .create_copy(CreateBookCopy {
+                book_id,
+                barcode: "BC-0005".into(),
+                condition: BookCopyCondition::Good,
+                location: "3F-A1".into(),
+                requested_user: other_user,
+                requested_role: Role::User,
+            })
+            .await;
+        assert!(matches!(result, Err(AppError::ForbiddenOperation)));
+
+        // 他の複本と同じバーコードは使えない
+        let result = book_repo
+            .create_copy(CreateBookCopy {
+                book_id,
+                barcode: "BC-0002".into(),
+                condition: BookCopyCondition::Good,
+                location: "3F-A1".into(),
+                requested_user: owner,
+                requested_role: Role::Admin,
+            })
+            .await;
+        assert!(matches!(result, Err(AppError::UnprocessableEntity(_))));
+
+        book_repo
+            .create_copy(CreateBookCopy {
+                book_id,
+                barcode: "BC-0005".into(),
+                condition: BookCopyCondition::Fair,
+                location: "3F-A1".into(),
+                requested_user: owner,
+                requested_role: Role::Admin,
+            })
+            .await?;
+
+        let copies = book_repo.find_copies_by_book_id(book_id).await?;
+        assert_eq!(copies.len(), 2);
+        assert_eq!(copies[1].barcode, "BC-0005");
+        assert_eq!(copies[1].condition, BookCopyCondition::Fair);
+        let book = book_repo.find_by_id(book_id).await?.unwrap();
+        assert_eq!(book.total_copies, 2);
+        assert_eq!(book.available_copies, 2);
+
+        // 紛失した複本は蔵書の複本の数に含まれない
+        book_repo
+            .update_copy(UpdateBookCopy {
+                copy_id: copies[1].id,
+                book_id,
+                barcode: "BC-0005".into(),
+                condition: BookCopyCondition::Lost,
+                location: "".into(),
+                requested_user: owner,
+                requested_role: Role::Admin,
+            })
+            .await?;
+        let book = book_repo.find_by_id(book_id).await?.unwrap();
+        assert_eq!(book.total_copies, 1);
+        assert_eq!(book.available_copies, 1);
+
+        book_repo
+            .delete_copy(DeleteBookCopy {
+                copy_id: copies[1].id,
+                book_id,
+                requested_user: owner,
+                requested_role: Role::Admin,
+            })
+            .await?;
+        assert_eq!(book_repo.find_copies_by_book_id(book_id).await?.len(), 1);
+
+        // 貸出中の複本は削除できない
+        let result = book_repo
+            .delete_copy(DeleteBookCopy {
+                copy_id: BookCopyId::from_str("424570b0-8489-4482-95c2-b727d955fb77")?,
+                book_id: BookId::from_str("c1e2d3f4-5a6b-4c7d-8e9f-0a1b2c3d4e5f")?,
+                requested_user: other_user,
+                requested_role: Role::User,
+            })
+            .await;
+        assert!(matches!(result, Err(AppError::UnprocessableEntity(_))));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_detect_duplicate_isbn(pool: PgPool) -> anyhow::Result<()> {
+        let book_repo = BookRepositoryImpl::new(ConnectionPool::new(pool));
+        let user_id = UserId::from_str("9582f9de-0fd1-4892-b20c-70139a7eb95b")?;
+        let create_book = |isbn: &str, allow_duplicate| -> anyhow::Result<CreateBook> {
+            Ok(CreateBook {
+                title: "PostgreSQL: Up and Running".into(),
+                author: "Regina O. Obe and Leo S. Hsu".into(),
+                isbn: isbn.parse()?,
+                description: "".into(),
+                allow_duplicate,
+            })
+        };
+
+        // ISBN-10で指定しても、ISBN-13で登録された蔵書と重複していると判定する
+        let result = book_repo
+            .create(create_book("1-4919-6341-7", false)?, user_id)
+            .await;
+        assert!(matches!(result, Err(AppError::Conflict(_))));
+
+        // 重複を許可した場合は登録できる
+        book_repo
+            .create(create_book("1-4919-6341-7", true)?, user_id)
+            .await?;
+        let books = book_repo
+            .find_all(BookListOptions {
+                query: Some("9781491963418".into()),
+                ..list_options()
+            })
+            .await?;
+        assert_eq!(books.total, 2);
+
+        // 自身のISBNを変更しない更新は重複と判定しない
+        let book_id = BookId::from_str("b8d8a5c3-0f7b-4a2e-9d6c-2e1f3a4b5c6d")?;
+        let update_book = |isbn: &str| -> anyhow::Result<UpdateBook> {
+            Ok(UpdateBook {
+                book_id,
+                title: "The Rust Programming Language".into(),
+                author: "Steve Klabnik and Carol Nichols".into(),
+                isbn: isbn.parse()?,
+                description: "".into(),
+                allow_duplicate: false,
+                requested_user: user_id,
+                requested_role: Role::User,
+                expected_version: None,
+            })
+        };
+        book_repo.update(update_book("978-1-7185-0310-6")?).await?;
+
+        let result = book_repo.update(update_book("978-1-4920-5259-3")?).await;
+        assert!(matches!(result, Err(AppError::Conflict(_))));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_import_books(pool: PgPool) -> anyhow::Result<()> {
+        let book_repo = BookRepositoryImpl::new(ConnectionPool::new(pool));
+        let user_id = UserId::from_str("9582f9de-0fd1-4892-b20c-70139a7eb95b")?;
+        let events = || -> anyhow::Result<Vec<CreateBook>> {
+            [
+                ("Rust for Rustaceans", "978-1-7185-0185-0"),
+                // 登録済みの蔵書と同じISBN
+                ("Programming Rust", "978-1-4920-5259-3"),
+                // データベースに記録できない長さのタイトル
+                (&"x".repeat(256)[..], "978-4-7981-6044-3"),
+            ]
+            .into_iter()
+            .map(|(title, isbn)| {
+                Ok(CreateBook {
+                    title: title.into(),
+                    author: "Test Author".into(),
+                    isbn: isbn.parse()?,
+                    description: "".into(),
+                    allow_duplicate: false,
+                })
+            })
+            .collect()
+        };
+        let total = || async {
+            book_repo
+                .find_all(list_options())
+                .await
+                .map(|books| books.total)
+        };
+
+        // 登録に失敗した蔵書がある場合、いずれの蔵書も登録しない
+        let result = book_repo
+            .import(events()?, user_id, BookImportMode::Atomic)
+            .await;
+        assert!(result.is_err());
+        assert_eq!(total().await?, 4);
+
+        // 登録に失敗した蔵書を除いて登録する
+        let outcomes = book_repo
+            .import(events()?, user_id, BookImportMode::BestEffort)
+            .await?;
+        assert_eq!(outcomes.len(), 3);
+        assert!(matches!(outcomes[0], BookImportOutcome::Created(_)));
+        assert!(matches!(outcomes[1], BookImportOutcome::Skipped(_)));
+        assert!(matches!(outcomes[2], BookImportOutcome::Failed(_)));
+        assert_eq!(total().await?, 5);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_export_books(pool: PgPool) -> anyhow::Result<()> {
+        let book_repo = BookRepositoryImpl::new(ConnectionPool::new(pool));
+
+        let books: Vec<Book> = book_repo.export().try_collect().await?;
+        assert_eq!(books.len(), 4);
+        let mut ids = books.iter().map(|b| b.id.to_string()).collect::<Vec<_>>();
+        ids.sort();
+        assert_eq!(
+            books.iter().map(|b| b.id.to_string()).collect::<Vec<_>>(),
+            ids
+        );
+
+        // 貸出中の蔵書は所有者と貸出を含めて書き出す
+        let book = books
+            .iter()
+            .find(|b| b.title == "Programming Rust")
+            .unwrap();
+        assert_eq!(book.owner.name, "Yuki Toyoda");
+        assert_eq!(book.checkouts.len(), 1);
+        assert_eq!(book.checkouts[0].checked_out_by.name, "Eleazar Fig");
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_authorize_book_mutations(pool: PgPool) -> anyhow::Result<()> {
+        let book_repo = BookRepositoryImpl::new(ConnectionPool::new(pool));
+        let admin = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+        let owner = UserId::from_str("9582f9de-0fd1-4892-b20c-70139a7eb95b")?;
+        let other_user = UserId::new();
+        let admin_book_id = BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b")?;
+        let book_id = BookId::from_str("b8d8a5c3-0f7b-4a2e-9d6c-2e1f3a4b5c6d")?;
+        let checked_out_book_id = BookId::from_str("c1e2d3f4-5a6b-4c7d-8e9f-0a1b2c3d4e5f")?;
+        let update = |book_id, title: &str, requested_user, requested_role| UpdateBook {
+            book_id,
+            title: title.into(),
+            author: "Steve Klabnik and Carol Nichols".into(),
+            isbn: "978-1-7185-0310-6".parse().unwrap(),
+            description: "".into(),
+            allow_duplicate: false,
+            requested_user,
+            requested_role,
+            expected_version: None,
+        };
+        let delete = |book_id, requested_user, requested_role| DeleteBook {
+            book_id,
+            requested_user,
+            requested_role,
+            expected_version: None,
+        };
+
+        // 蔵書の所有者と管理者は蔵書を更新できる
+        book_repo
+            .update(update(book_id, "By Owner", owner, Role::User))
+            .await?;
+        book_repo
+            .update(update(book_id, "By Admin", admin, Role::Admin))
+            .await?;
+        assert_eq!(
+            book_repo.find_by_id(book_id).await?.unwrap().title,
+            "By Admin"
+        );
+
+        // 所有者でも管理者でもないユーザーは、蔵書が存在すれば403、存在しなければ404になる
+        let result = book_repo
+            .update(update(book_id, "By Other", other_user, Role::User))
+            .await;
+        assert!(matches!(result, Err(AppError::ForbiddenOperation)));
+        let result = book_repo
+            .update(update(BookId::new(), "Missing", admin, Role::Admin))
+            .await;
+        assert!(matches!(result, Err(AppError::EntityNotFound(_))));
+        let result = book_repo
+            .delete(delete(admin_book_id, owner, Role::User))
+            .await;
+        assert!(matches!(result, Err(AppError::ForbiddenOperation)));
+        let result = book_repo
+            .delete(delete(BookId::new(), admin, Role::Admin))
+            .await;
+        assert!(matches!(result, Err(AppError::EntityNotFound(_))));
+
+        // 貸出中の蔵書は、所有者も管理者も削除できない
+        for (requested_user, requested_role) in [(owner, Role::User), (admin, Role::Admin)] {
+            let result = book_repo
+                .delete(delete(checked_out_book_id, requested_user, requested_role))
+                .await;
+            assert!(matches!(result, Err(AppError::UnprocessableEntity(_))));
+        }
+        assert!(book_repo.find_by_id(checked_out_book_id).await?.is_some());
+
+        // 管理者は他のユーザーの蔵書も削除できる
+        book_repo
+            .delete(delete(book_id, admin, Role::Admin))
+            .await?;
+        assert!(book_repo.find_by_id(book_id).await?.is_none());
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_patch_book(pool: PgPool) -> anyhow::Result<()> {
+        let book_repo = BookRepositoryImpl::new(ConnectionPool::new(pool));
+        let owner = UserId::from_str("9582f9de-0fd1-4892-b20c-70139a7eb95b")?;
+        let book_id = BookId::from_str("b8d8a5c3-0f7b-4a2e-9d6c-2e1f3a4b5c6d")?;
+        let patch = |requested_user| PatchBook {
+            book_id,
+            title: None,
+            author: None,
+            isbn: None,
+            description: None,
+            allow_duplicate: false,
+            requested_user,
+            requested_role: Role::User,
+            expected_version: None,
+        };
+        let original = book_repo.find_by_id(book_id).await?.unwrap();
+
+        // 指定した項目のみを更新し、その他の項目は変更しない
+        book_repo
+            .patch(PatchBook {
+                description: Some("Updated description".into()),
+                ..patch(owner)
+            })
+            .await?;
+        let book = book_repo.find_by_id(book_id).await?.unwrap();
+        assert_eq!(book.description, "Updated description");
+        assert_eq!(book.title, original.title);
+        assert_eq!(book.author, original.author);
+        assert_eq!(book.isbn, original.isbn);
+        assert_eq!(book.version, original.version + 1);
+
+        // 更新する項目がない場合は、蔵書を変更しない
+        book_repo.patch(patch(owner)).await?;
+        assert_eq!(
+            book_repo.find_by_id(book_id).await?.unwrap().version,
+            book.version
+        );
+
+        // 他の蔵書と同じISBNには、重複を許可しなければ更新できない
+        let duplicate = PatchBook {
+            isbn: Some("9784065369579".parse()?),
+            ..patch(owner)
+        };
+        let result = book_repo.patch(duplicate).await;
+        assert!(matches!(result, Err(AppError::Conflict(_))));
+        book_repo
+            .patch(PatchBook {
+                title: Some("Duplicated".into()),
+                isbn: Some("9784065369579".parse()?),
+                allow_duplicate: true,
+                ..patch(owner)
+            })
+            .await?;
+        let book = book_repo.find_by_id(book_id).await?.unwrap();
+        assert_eq!(book.title, "Duplicated");
+        assert_eq!(book.isbn, "9784065369579");
+        assert_eq!(book.description, "Updated description");
+
+        // 所有者でも管理者でもないユーザーは、更新する項目がなくても更新できない
+        let result = book_repo.patch(patch(UserId::new())).await;
+        assert!(matches!(result, Err(AppError::ForbiddenOperation)));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_book_revisions(pool: PgPool) -> anyhow::Result<()> {
+        let book_repo = BookRepositoryImpl::new(ConnectionPool::new(pool));
+        let admin = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+        let owner = UserId::from_str("9582f9de-0fd1-4892-b20c-70139a7eb95b")?;
+        let book_id = BookId::from_str("b8d8a5c3-0f7b-4a2e-9d6c-2e1f3a4b5c6d")?;
+        let patch = |requested_user, requested_role| PatchBook {
+            book_id,
+            title: None,
+            author: None,
+            isbn: None,
+            description: None,
+            allow_duplicate: false,
+            requested_user,
+            requested_role,
+            expected_version: None,
+        };
+        let revert = |revision_number, requested_user, requested_role| RevertBook {
+            book_id,
+            revision_number,
+            requested_user,
+            requested_role,
+            expected_version: None,
+        };
+        let original = book_repo.find_by_id(book_id).await?.unwrap();
+        assert!(book_repo.find_revisions(book_id).await?.is_empty());
+
+        // 更新するたびに、更新する前の内容と変わった項目が新しい順に記録される
+        book_repo
+            .patch(PatchBook {
+                title: Some("The Rust Book".into()),
+                ..patch(owner, Role::User)
+            })
+            .await?;
+        book_repo
+            .patch(PatchBook {
+                isbn: Some("978-1-7185-0444-8".parse()?),
+                description: Some("2nd edition".into()),
+                ..patch(admin, Role::Admin)
+            })
+            .await?;
+        let revisions = book_repo.find_revisions(book_id).await?;
+        assert_eq!(
+            revisions
+                .iter()
+                .map(|r| r.revision_number)
+                .collect::<Vec<_>>(),
+            vec![2, 1]
+        );
+        assert_eq!(revisions[1].fields.title, original.title);
+        assert_eq!(revisions[1].revised_by.id, owner);
+        assert_eq!(revisions[1].revised_by.name.as_deref(), Some("Yuki Toyoda"));
+        assert_eq!(
+            revisions[1].changes,
+            vec![BookFieldChange {
+                field: BookField::Title,
+                before: original.title.clone(),
+                after: "The Rust Book".into(),
+            }]
+        );
+        assert_eq!(revisions[0].revised_by.id, admin);
+        assert_eq!(
+            revisions[0]
+                .changes
+                .iter()
+                .map(|c| (c.field, c.after.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (BookField::Isbn, "9781718504448"),
+                (BookField::Description, "2nd edition")
+            ]
+        );
+
+        // 所有者でも管理者でもないユーザーは元に戻せず、存在しない更新履歴には戻せない
+        let result = book_repo.revert(revert(1, UserId::new(), Role::User)).await;
+        assert!(matches!(result, Err(AppError::ForbiddenOperation)));
+        let result = book_repo.revert(revert(3, owner, Role::User)).await;
+        assert!(matches!(result, Err(AppError::EntityNotFound(_))));
+
+        // 元に戻すと、蔵書は更新履歴の内容になり、元に戻す操作も更新履歴に記録される
+        book_repo.revert(revert(1, owner, Role::User)).await?;
+        let book = book_repo.find_by_id(book_id).await?.unwrap();
+        assert_eq!(book.title, original.title);
+        assert_eq!(book.isbn, original.isbn);
+        assert_eq!(book.description, original.description);
+        let revisions = book_repo.find_revisions(book_id).await?;
+        assert_eq!(revisions.len(), 3);
+        assert_eq!(revisions[0].fields.title, "The Rust Book");
+        assert_eq!(revisions[0].changes.len(), 3);
+
+        // ゴミ箱にある蔵書の更新履歴は取得できない
+        book_repo
+            .delete(DeleteBook {
+                book_id,
+                requested_user: owner,
+                requested_role: Role::User,
+                expected_version: None,
+            })
+            .await?;
+        let result = book_repo.find_revisions(book_id).await;
+        assert!(matches!(result, Err(AppError::EntityNotFound(_))));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_reject_stale_book_version(pool: PgPool) -> anyhow::Result<()> {
+        let book_repo = BookRepositoryImpl::new(ConnectionPool::new(pool));
+        let owner = UserId::from_str("9582f9de-0fd1-4892-b20c-70139a7eb95b")?;
+        let book_id = BookId::from_str("b8d8a5c3-0f7b-4a2e-9d6c-2e1f3a4b5c6d")?;
+        let update = |title: &str, expected_version| UpdateBook {
+            book_id,
+            title: title.into(),
+            author: "Steve Klabnik and Carol Nichols".into(),
+            isbn: "978-1-7185-0310-6".parse().unwrap(),
+            description: "".into(),
+            allow_duplicate: false,
+            requested_user: owner,
+            requested_role: Role::User,
+            expected_version,
+        };
+
+        // 取得したときのバージョンを指定すれば更新でき、更新するとバージョンが増える
+        let version = book_repo.find_by_id(book_id).await?.unwrap().version;
+        book_repo.update(update("First", Some(version))).await?;
+        let book = book_repo.find_by_id(book_id).await?.unwrap();
+        assert_eq!(book.title, "First");
+        assert_eq!(book.version, version + 1);
+
+        // 古いバージョンを指定した更新や削除は、他のリクエストによる変更を上書きしない
+        let result = book_repo.update(update("Second", Some(version))).await;
+        assert!(matches!(result, Err(AppError::PreconditionFailed(_))));
+        let result = book_repo
+            .delete(DeleteBook {
+                book_id,
+                requested_user: owner,
+                requested_role: Role::User,
+                expected_version: Some(version),
+            })
+            .await;
+        assert!(matches!(result, Err(AppError::PreconditionFailed(_))));
+        assert_eq!(book_repo.find_by_id(book_id).await?.unwrap().title, "First");
+
+        // バージョンを指定しない場合は、無条件に更新する
+        book_repo.update(update("Third", None)).await?;
+        assert_eq!(book_repo.find_by_id(book_id).await?.unwrap().title, "Third");
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_delete_restore_and_purge_books(pool: PgPool) -> anyhow::Result<()> {
+        let book_repo = BookRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+        let admin = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+        let book_id = BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b")?;
+        let other_book_id = BookId::from_str("d4c3b2a1-6f5e-4d7c-9b8a-1f2e3d4c5b6a")?;
+        let delete = |book_id| DeleteBook {
+            book_id,
+            requested_user: admin,
+            requested_role: Role::Admin,
+            expected_version: None,
+        };
+        let trash = || DeletedBookListOptions {
+            limit: 10,
+            offset: 0,
+        };
+
+        // 削除した蔵書はゴミ箱に移り、貸出履歴は削除されない
+        sqlx::query!(
+            r#"
+                INSERT INTO returned_checkouts
+                    (checkout_id, book_id, copy_id, user_id, checked_out_at, due_at, returned_at)
+                VALUES
+                    ($1, $2, 'baab162c-0dc7-440b-9176-a65e2c68ed99',
+                     '9582f9de-0fd1-4892-b20c-70139a7eb95b',
+                     '2024-12-01 09:00:00+09', '2024-12-15 09:00:00+09',
+                     '2024-12-10 09:00:00+09')
+            "#,
+            CheckoutId::new() as _,
+            book_id as _
+        )
+        .execute(&pool)
+        .await?;
+        sqlx::query!(
+            r#"
+                INSERT INTO holds (book_id, user_id, ready_at, expires_at)
+                VALUES ($1, '9582f9de-0fd1-4892-b20c-70139a7eb95b', NOW(), NOW() + INTERVAL '3 days')
+            "#,
+            book_id as _
+        )
+        .execute(&pool)
+        .await?;
+        book_repo.delete(delete(other_book_id)).await?;
+        book_repo.delete(delete(book_id)).await?;
+        assert!(book_repo.find_by_id(book_id).await?.is_none());
+
+        // 削除した蔵書の予約は取り消される
+        let holds = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) "count!" FROM holds WHERE book_id = $1"#,
+            book_id as _
+        )
+        .fetch_one(&pool)
+        .await?;
+        assert_eq!(holds, 0);
+        let history = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) "count!" FROM returned_checkouts WHERE book_id = $1"#,
+            book_id as _
+        )
+        .fetch_one(&pool)
+        .await?;
+        assert_eq!(history, 1);
+
+        // ゴミ箱にある蔵書は一覧及び書き出しの対象にならない
+        let books = book_repo.find_all(list_options()).await?;
+        assert_eq!(books.total, 2);
+        assert!(books
+            .items
+            .iter()
+            .all(|b| b.id != book_id && b.id != other_book_id));
+        let exported: Vec<Book> = book_repo.export().try_collect().await?;
+        assert_eq!(exported.len(), 2);
+
+        let trashed = book_repo.find_deleted_all(trash()).await?;
+        assert_eq!(trashed.total, 2);
+        assert!(trashed.items.iter().any(|b| b.id == book_id));
+        assert!(trashed.items.iter().all(|b| b.owner.id == admin));
+
+        // ゴミ箱にある蔵書は更新も、もう一度削除もできない
+        let result = book_repo
+            .update(UpdateBook {
+                book_id,
+                title: "Title".into(),
+                author: "Author".into(),
+                isbn: Isbn::from_str("9784065369579")?,
+                description: "Description".into(),
+                allow_duplicate: true,
+                requested_user: admin,
+                requested_role: Role::Admin,
+                expected_version: None,
+            })
+            .await;
+        assert!(matches!(result, Err(AppError::EntityNotFound(_))));
+        let result = book_repo.delete(delete(book_id)).await;
+        assert!(matches!(result, Err(AppError::EntityNotFound(_))));
+
+        // ゴミ箱にある間に同じISBNの蔵書が登録された場合は、元に戻せない
+        let set_isbn = |isbn: &'static str| {
+            sqlx::query!(
+                r#"
+                    UPDATE books SET isbn = $1
+                    WHERE book_id = 'b8d8a5c3-0f7b-4a2e-9d6c-2e1f3a4b5c6d'
+                "#,
+                isbn
+            )
+            .execute(&pool)
+        };
+        set_isbn("9784065369579").await?;
+        let result = book_repo
+            .restore(RestoreBook {
+                book_id,
+                requested_user: admin,
+            })
+            .await;
+        assert!(matches!(result, Err(AppError::Conflict(_))));
+        assert!(book_repo.find_by_id(book_id).await?.is_none());
+        set_isbn("9781718503106").await?;
+
+        // ゴミ箱から元に戻した蔵書は、ふたたび取得できる
+        book_repo
+            .restore(RestoreBook {
+                book_id,
+                requested_user: admin,
+            })
+            .await?;
+        assert!(book_repo.find_by_id(book_id).await?.is_some());
+        let result = book_repo
+            .restore(RestoreBook {
+                book_id,
+                requested_user: admin,
+            })
+            .await;
+        assert!(matches!(result, Err(AppError::EntityNotFound(_))));
+
+        // ゴミ箱にない蔵書は完全に削除できない
+        let result = book_repo
+            .purge(PurgeBook {
+                book_id,
+                requested_user: admin,
+            })
+            .await;
+        assert!(matches!(result, Err(AppError::EntityNotFound(_))));
+
+        // 完全に削除すると、貸出履歴も削除される
+        book_repo.delete(delete(book_id)).await?;
+        book_repo
+            .purge(PurgeBook {
+                book_id,
+                requested_user: admin,
+            })
+            .await?;
+        let remaining = sqlx::query_scalar!(
+            r#"
+                SELECT
+                    (SELECT COUNT(*) FROM books WHERE book_id = $1)
+                    + (SELECT COUNT(*) FROM returned_checkouts WHERE book_id = $1) "count!"
+            "#,
+            book_id as _
+        )
+        .fetch_one(&pool)
+        .await?;
+        assert_eq!(remaining, 0);
+        let trashed = book_repo.find_deleted_all(trash()).await?;
+        assert_eq!(trashed.total, 1);
+        assert_eq!(trashed.items[0].id, other_book_id);
+
+        Ok(())
+    }
 }
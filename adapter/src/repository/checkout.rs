@@ -1,18 +1,30 @@
 use async_trait::async_trait;
+use chrono::Duration;
 use derive_new::new;
 
-use kernel::model::checkout::event::{CreateCheckout, UpdateReturned};
-use kernel::model::checkout::Checkout;
-use kernel::model::id::{BookId, CheckoutId, UserId};
+use kernel::model::audit::{AuditAction, AuditEntityType};
+use kernel::model::book::BookCopyCondition;
+use kernel::model::checkout::event::{CreateCheckout, RenewCheckout, UpdateReturned};
+use kernel::model::checkout::{Checkout, CheckoutListOptions, CheckoutOutcome};
+use kernel::model::id::{BookCopyId, BookId, CheckoutId, HoldId, UserId};
+use kernel::model::list::PaginatedList;
+use kernel::model::role::Role;
 use kernel::repository::checkout::CheckoutRepository;
+use shared::config::CheckoutConfig;
 use shared::error::{AppError, AppResult};
 
-use crate::database::checkout::{CheckoutRow, CheckoutStateRow, ReturnedCheckoutRow};
+use crate::database::checkout::{CheckoutListRow, CheckoutRow, CheckoutStateRow};
 use crate::database::ConnectionPool;
+use crate::repository::audit::{
+    checkout_actor, checkout_snapshot, record_audit_event, AuditRecord,
+};
+use crate::repository::hold::refresh_holds;
+use crate::repository::loan_policy::find_loan_policy_by_user_id;
 
 #[derive(new)]
 pub struct CheckoutRepositoryImpl {
     db: ConnectionPool,
+    config: CheckoutConfig,
 }
 
 impl CheckoutRepositoryImpl {
@@ -30,32 +42,6 @@ impl CheckoutRepositoryImpl {
         .map_err(AppError::SpecificOperationError)?;
         Ok(())
     }
-
-    async fn find_returned_by_book_id(&self, book_id: BookId) -> AppResult<Option<Checkout>> {
-        let result = sqlx::query_as!(
-            CheckoutRow,
-            r#"
-                SELECT
-                    c.checkout_id,
-                    c.book_id,
-                    c.user_id,
-                    c.checked_out_at,
-                    b.title,
-                    b.author,
-                    b.isbn
-                FROM checkouts c
-                INNER JOIN books b ON c.book_id = c.book_id
-                WHERE c.book_id = $1
-            "#,
-            book_id as _
-        )
-        .fetch_optional(self.db.inner_ref())
-        .await
-        .map_err(AppError::SpecificOperationError)?
-        .map(Checkout::from);
-
-        Ok(result)
-    }
 }
 
 #[async_trait]
@@ -66,25 +52,54 @@ impl CheckoutRepository for CheckoutRepositoryImpl {
         // トランザクション分離レベルをシリアライザブルに設定
         self.set_transaction_serializable(&mut tx).await?;
 
+        // 取り置き期限を過ぎた予約を取り消してから、蔵書の状態を確認する。
+        refresh_holds(
+            &mut tx,
+            event.book_id,
+            event.checked_out_at,
+            Duration::hours(self.config.hold_pickup_hours),
+        )
+        .await?;
+
         // 貸出する前に次のブロックで以下を確認する。
         // * 指定の蔵書IDを持つ蔵書が存在するか確認する (リピータブルリードを保証しなくてはならない)。
-        // * 存在した場合、その蔵書が貸出中でないか確認する (リピータブルリードを保証しなくてはならない)。
-        // 上記をすべて満たす場合、蔵書を貸出する (ファントムリードが発生してはならない)。
+        //   ゴミ箱にある蔵書は存在しないものとして扱う。
+        // * 存在した場合、借りるユーザーがその蔵書の複本を借りていないか確認する。
+        // * 貸出中でも紛失してもいない複本があるか確認する (リピータブルリードを保証しなくてはならない)。
+        // * 借りるユーザーのために取り置かれていない場合、他のユーザーのために取り置かれていない
+        //   複本があるか確認する。
+        // 上記をすべて満たす場合、複本を貸出する (ファントムリードが発生してはならない)。
         // 他のトランザクションが上記の状態を変更しないように、トランザクション分離レベルをシリアライザブル
         // にする必要がある。
         {
-            let result = sqlx::query_as!(
-                CheckoutStateRow,
+            let result = sqlx::query!(
                 r#"
                     SELECT
-                        b.book_id,
-                        c.checkout_id "checkout_id?: CheckoutId",
-                        NULL "user_id?: UserId"
+                        EXISTS (
+                            SELECT 1 FROM checkouts c
+                            WHERE c.book_id = b.book_id AND c.user_id = $2
+                        ) "already_borrowed!",
+                        (
+                            SELECT COUNT(*) FROM book_copies bc
+                            WHERE bc.book_id = b.book_id
+                                AND bc.condition <> 'Lost'
+                                AND NOT EXISTS (
+                                    SELECT 1 FROM checkouts c WHERE c.copy_id = bc.copy_id
+                                )
+                        ) "available_copies!",
+                        (
+                            SELECT COUNT(*) FROM holds h
+                            WHERE h.book_id = b.book_id AND h.ready_at IS NOT NULL
+                        ) "ready_holds!",
+                        EXISTS (
+                            SELECT 1 FROM holds h
+                            WHERE h.book_id = b.book_id AND h.user_id = $2 AND h.ready_at IS NOT NULL
+                        ) "has_ready_hold!"
                     FROM books b
-                    LEFT OUTER JOIN checkouts c ON b.book_id = c.book_id
-                    WHERE b.book_id = $1
+                    WHERE b.book_id = $1 AND b.deleted_at IS NULL
                 "#,
-                event.book_id as _
+                event.book_id as _,
+                event.checked_out_by as _
             )
             .fetch_optional(&mut *tx)
             .await
@@ -96,12 +111,21 @@ impl CheckoutRepository for CheckoutRepositoryImpl {
                         event.book_id
                     )))
                 }
-                Some(CheckoutStateRow {
-                    checkout_id: Some(_),
-                    ..
-                }) => {
+                Some(r) if r.already_borrowed => {
+                    return Err(AppError::UnprocessableEntity(format!(
+                        "The user ({}) has already borrowed the book ({})",
+                        event.checked_out_by, event.book_id
+                    )))
+                }
+                Some(r) if r.available_copies == 0 => {
+                    return Err(AppError::UnprocessableEntity(format!(
+                        "All copies of the book ({}) were already borrowed",
+                        event.book_id
+                    )))
+                }
+                Some(r) if !r.has_ready_hold && r.available_copies <= r.ready_holds => {
                     return Err(AppError::UnprocessableEntity(format!(
-                        "The book ({}) was already borrowed",
+                        "The book ({}) is on hold for other users",
                         event.book_id
                     )))
                 }
@@ -109,20 +133,66 @@ impl CheckoutRepository for CheckoutRepositoryImpl {
             }
         }
 
-        // 蔵書を貸出
+        // 借りるユーザーのロールの貸出ポリシーで、同時に借りられる蔵書の数の上限を確認する。
+        // 同じユーザーが同時に別の蔵書を借りても上限を超えないように、この確認もシリアライザブルな
+        // トランザクションの中で行う。
+        let policy =
+            find_loan_policy_by_user_id(&mut tx, event.checked_out_by, &self.config).await?;
+        if let Some(max_checkouts) = policy.max_checkouts {
+            let checkout_count = sqlx::query_scalar!(
+                r#"
+                    SELECT COUNT(*) "count!"
+                    FROM checkouts
+                    WHERE user_id = $1
+                "#,
+                event.checked_out_by as _
+            )
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(AppError::SpecificOperationError)?;
+            if checkout_count >= i64::from(max_checkouts) {
+                return Err(AppError::UnprocessableEntity(format!(
+                    "The user ({}) has already borrowed {} books, which is the limit for the role ({})",
+                    event.checked_out_by,
+                    checkout_count,
+                    policy.role.as_ref()
+                )));
+            }
+        }
+
+        // 貸出中でも紛失してもいない複本を、バーコードの順に選んで貸出
+        // 返却期限は、貸出日時に貸出ポリシーの貸出期間を加えた日時とする。
+        let copy_id = sqlx::query_scalar!(
+            r#"
+                SELECT bc.copy_id "copy_id: BookCopyId"
+                FROM book_copies bc
+                WHERE bc.book_id = $1
+                    AND bc.condition <> 'Lost'
+                    AND NOT EXISTS (SELECT 1 FROM checkouts c WHERE c.copy_id = bc.copy_id)
+                ORDER BY bc.barcode
+                LIMIT 1
+            "#,
+            event.book_id as _
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
         let checkout_id = CheckoutId::new();
+        let due_at = event.checked_out_at + Duration::days(i64::from(policy.loan_period_days));
         let result = sqlx::query!(
             r#"
                 INSERT INTO checkouts (
-                    checkout_id, book_id, user_id, checked_out_at
+                    checkout_id, book_id, copy_id, user_id, checked_out_at, due_at
                 ) VALUES (
-                    $1, $2, $3, $4
+                    $1, $2, $3, $4, $5, $6
                 )
             "#,
             checkout_id as _,
             event.book_id as _,
+            copy_id as _,
             event.checked_out_by as _,
             event.checked_out_at,
+            due_at,
         )
         .execute(&mut *tx)
         .await
@@ -134,20 +204,173 @@ impl CheckoutRepository for CheckoutRepositoryImpl {
             ));
         }
 
+        let actor = checkout_actor(&mut tx, checkout_id, event.checked_out_by).await?;
+
+        // 取り置かれていた蔵書を予約したユーザーが借りた場合は、その予約を完了する。
+        let holds = sqlx::query!(
+            r#"
+                DELETE FROM holds h
+                WHERE h.book_id = $1 AND h.user_id = $2
+                RETURNING h.hold_id "hold_id: HoldId", to_jsonb(h) "snapshot!"
+            "#,
+            event.book_id as _,
+            event.checked_out_by as _
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+        for hold in holds {
+            record_audit_event(
+                &mut tx,
+                AuditRecord::new(
+                    actor,
+                    AuditAction::DeleteHold,
+                    AuditEntityType::Hold,
+                    hold.hold_id.to_string(),
+                    Some(hold.snapshot),
+                    None,
+                ),
+            )
+            .await?;
+        }
+
+        let after = checkout_snapshot(&mut tx, checkout_id).await?;
+        record_audit_event(
+            &mut tx,
+            AuditRecord::new(
+                actor,
+                AuditAction::CreateCheckout,
+                AuditEntityType::Checkout,
+                checkout_id.to_string(),
+                None,
+                after,
+            ),
+        )
+        .await?;
+
         // トランザクションをコミット
         tx.commit().await.map_err(AppError::TransactionError)?;
 
         Ok(())
     }
 
+    async fn renew(&self, event: RenewCheckout) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        self.set_transaction_serializable(&mut tx).await?;
+
+        let policy = find_loan_policy_by_user_id(&mut tx, event.renewed_by, &self.config).await?;
+
+        // 延長する前に次のブロックで以下を確認する。
+        // * 指定の貸出IDを持つ貸出が、指定の蔵書IDを持つ蔵書の未返却の貸出として存在するか
+        // * 存在する場合、その蔵書を借りたユーザーが延長するユーザーと同じか
+        // * 延長回数が延長するユーザーのロールの貸出ポリシーの上限に達していないか
+        // * 複本の返却を待っている他のユーザーの予約がないか
+        let due_at = {
+            let result = sqlx::query!(
+                r#"
+                    SELECT
+                        c.user_id "user_id: UserId",
+                        c.due_at,
+                        c.renewal_count,
+                        EXISTS (
+                            SELECT 1 FROM holds h
+                            WHERE h.book_id = c.book_id AND h.ready_at IS NULL
+                        ) "has_holds!"
+                    FROM checkouts c
+                    WHERE c.checkout_id = $1 AND c.book_id = $2
+                "#,
+                event.checkout_id as _,
+                event.book_id as _
+            )
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(AppError::SpecificOperationError)?;
+
+            match result {
+                None => {
+                    return Err(AppError::EntityNotFound(format!(
+                        "the checkout ({}) of the book ({}) doesn't exist",
+                        event.checkout_id, event.book_id
+                    )))
+                }
+                Some(r) if r.user_id != event.renewed_by => {
+                    return Err(AppError::UnprocessableEntity(format!(
+                        "the user ({}) can not renew the checkout ({})",
+                        event.renewed_by, event.checkout_id
+                    )));
+                }
+                Some(r) if r.renewal_count >= policy.max_renewals => {
+                    return Err(AppError::UnprocessableEntity(format!(
+                        "the checkout ({}) has already been renewed {} times",
+                        event.checkout_id, r.renewal_count
+                    )));
+                }
+                Some(r) if r.has_holds => {
+                    return Err(AppError::UnprocessableEntity(format!(
+                        "the book ({}) of the checkout ({}) is on hold for another user",
+                        event.book_id, event.checkout_id
+                    )));
+                }
+                Some(r) => r.due_at,
+            }
+        };
+
+        // 延滞している場合は延長した日時から、延滞していない場合は現在の返却期限から貸出期間を延長する。
+        let new_due_at =
+            due_at.max(event.renewed_at) + Duration::days(i64::from(policy.loan_period_days));
+        let before = checkout_snapshot(&mut tx, event.checkout_id).await?;
+        let result = sqlx::query!(
+            r#"
+                UPDATE checkouts
+                SET
+                    due_at = $2,
+                    renewal_count = renewal_count + 1
+                WHERE checkout_id = $1
+            "#,
+            event.checkout_id as _,
+            new_due_at,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        if result.rows_affected() < 1 {
+            return Err(AppError::NoRowsAffectedError(
+                "no checkout record has been renewed".into(),
+            ));
+        }
+
+        let after = checkout_snapshot(&mut tx, event.checkout_id).await?;
+        let actor = checkout_actor(&mut tx, event.checkout_id, event.renewed_by).await?;
+        record_audit_event(
+            &mut tx,
+            AuditRecord::new(
+                actor,
+                AuditAction::RenewCheckout,
+                AuditEntityType::Checkout,
+                event.checkout_id.to_string(),
+                before,
+                after,
+            ),
+        )
+        .await?;
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(())
+    }
+
     async fn update_returned(&self, event: UpdateReturned) -> AppResult<()> {
         let mut tx = self.db.begin().await?;
 
         self.set_transaction_serializable(&mut tx).await?;
 
         // 返却する前に次のブロックで以下を確認する。
-        // * 指定の蔵書IDを持つ蔵書が存在するか
-        // * 存在中の場合、その蔵書は貸出中であり、借りたユーザーが返却したユーザーと同じか
+        // * 指定の蔵書IDを持つ蔵書が存在するか (貸出中に削除された蔵書も返却できるように、
+        //   ゴミ箱にある蔵書も対象とする)
+        // * 存在する場合、その蔵書は指定の貸出IDの貸出で貸出中か
+        // * 借りたユーザーが返却を記録するユーザーと同じか (管理者は他のユーザーの貸出も返却できる)
         {
             let result = sqlx::query_as!(
                 CheckoutStateRow,
@@ -157,10 +380,12 @@ impl CheckoutRepository for CheckoutRepositoryImpl {
                         c.checkout_id "checkout_id?: CheckoutId",
                         c.user_id "user_id?: UserId"
                     FROM books b
-                    LEFT OUTER JOIN checkouts c ON b.book_id = c.book_id
+                    LEFT OUTER JOIN checkouts c
+                        ON b.book_id = c.book_id AND c.checkout_id = $2
                     WHERE b.book_id = $1
                 "#,
-                event.book_id as _
+                event.book_id as _,
+                event.checkout_id as _
             )
             .fetch_optional(&mut *tx)
             .await
@@ -173,11 +398,15 @@ impl CheckoutRepository for CheckoutRepositoryImpl {
                         event.book_id
                     )))
                 }
+                Some(CheckoutStateRow { checkout_id: c, .. }) if c != Some(event.checkout_id) => {
+                    return Err(AppError::EntityNotFound(format!(
+                        "the checkout ({}) of the book ({}) doesn't exist",
+                        event.checkout_id, event.book_id
+                    )))
+                }
                 Some(CheckoutStateRow {
-                    checkout_id: Some(c),
-                    user_id: Some(u),
-                    ..
-                }) if (c, u) != (event.checkout_id, event.returned_by) => {
+                    user_id: Some(u), ..
+                }) if u != event.returned_by && event.requested_role != Role::Admin => {
                     return Err(AppError::UnprocessableEntity(format!(
                         "the user ({}) can not return the book ({}) of the checkout ({})",
                         event.returned_by, event.book_id, event.checkout_id
@@ -187,20 +416,45 @@ impl CheckoutRepository for CheckoutRepositoryImpl {
             }
         }
 
-        // checkoutsテーブルにある当該貸出IDのレコードにreturned_atを設定して、returned_checkouts
-        // テーブルに行を登録
+        let before = checkout_snapshot(&mut tx, event.checkout_id).await?;
+
+        // 紛失または破損した場合は、貸出した複本の状態に記録する。
+        if let Some(condition) = match event.outcome {
+            CheckoutOutcome::Returned => None,
+            CheckoutOutcome::Lost => Some(BookCopyCondition::Lost),
+            CheckoutOutcome::Damaged => Some(BookCopyCondition::Damaged),
+        } {
+            sqlx::query!(
+                r#"
+                    UPDATE book_copies
+                    SET condition = $2
+                    WHERE copy_id = (SELECT copy_id FROM checkouts WHERE checkout_id = $1)
+                "#,
+                event.checkout_id as _,
+                condition.as_ref()
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::SpecificOperationError)?;
+        }
+
+        // checkoutsテーブルにある当該貸出IDのレコードにreturned_atと貸出の終わり方を設定して、
+        // returned_checkoutsテーブルに行を登録
         let result = sqlx::query!(
             r#"
                 INSERT INTO returned_checkouts (
-                    checkout_id, book_id, user_id, checked_out_at, returned_at
+                    checkout_id, book_id, copy_id, user_id, checked_out_at, due_at, renewal_count,
+                    returned_at, outcome
                 )
                 SELECT
-                    checkout_id, book_id, user_id, checked_out_at, $2
+                    checkout_id, book_id, copy_id, user_id, checked_out_at, due_at, renewal_count,
+                    $2, $3
                 FROM checkouts
                 WHERE checkout_id = $1
             "#,
             event.checkout_id as _,
-            event.returned_at
+            event.returned_at,
+            event.outcome.as_ref()
         )
         .execute(&mut *tx)
         .await
@@ -212,6 +466,20 @@ impl CheckoutRepository for CheckoutRepositoryImpl {
             ));
         }
 
+        // 借りたユーザーがレビューを書けるように、レビューを書けることだけを記録する。
+        // この記録はレビューを書くと削除し、貸出の履歴を残さない設定にしたときにも削除する。
+        sqlx::query!(
+            r#"
+                INSERT INTO reviewable_books (user_id, book_id)
+                SELECT user_id, book_id FROM checkouts WHERE checkout_id = $1
+                ON CONFLICT DO NOTHING
+            "#,
+            event.checkout_id as _
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
         // checkoutsテーブルから当該貸出IDのレコードを削除
         let result = sqlx::query!(
             r#"
@@ -230,94 +498,709 @@ impl CheckoutRepository for CheckoutRepositoryImpl {
             ));
         }
 
+        let after = checkout_snapshot(&mut tx, event.checkout_id).await?;
+        let actor = checkout_actor(&mut tx, event.checkout_id, event.returned_by).await?;
+        record_audit_event(
+            &mut tx,
+            AuditRecord::new(
+                actor,
+                AuditAction::ReturnCheckout,
+                AuditEntityType::Checkout,
+                event.checkout_id.to_string(),
+                before,
+                after,
+            ),
+        )
+        .await?;
+
+        // 借りたユーザーが履歴を残さない設定の場合は、返却した貸出を履歴から削除する。
+        sqlx::query!(
+            r#"
+                DELETE FROM returned_checkouts rc
+                USING users u
+                WHERE rc.checkout_id = $1
+                    AND rc.user_id = u.user_id
+                    AND NOT u.retain_checkout_history
+            "#,
+            event.checkout_id as _
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        // 予約がある場合は、待ち行列の先頭の予約のために蔵書を取り置く。
+        refresh_holds(
+            &mut tx,
+            event.book_id,
+            event.returned_at,
+            Duration::hours(self.config.hold_pickup_hours),
+        )
+        .await?;
+
         tx.commit().await.map_err(AppError::TransactionError)?;
 
         Ok(())
     }
 
-    async fn find_unreturned_all(&self) -> AppResult<Vec<Checkout>> {
-        sqlx::query_as!(
-            CheckoutRow,
+    async fn find_all(&self, options: CheckoutListOptions) -> AppResult<PaginatedList<Checkout>> {
+        let CheckoutListOptions {
+            limit,
+            offset,
+            borrower,
+            book_id,
+            state,
+            since,
+            until,
+        } = options;
+
+        // 未返却の貸出はcheckoutsテーブルに、返却済みの貸出はreturned_checkoutsテーブルにあるため、
+        // 両方を合わせてから絞り込む。
+        let rows = sqlx::query_as!(
+            CheckoutListRow,
             r#"
+                WITH all_checkouts AS (
+                    SELECT
+                        checkout_id, book_id, copy_id, user_id, checked_out_at, due_at,
+                        renewal_count, NULL::TIMESTAMPTZ returned_at, NULL::VARCHAR outcome
+                    FROM checkouts
+                    WHERE $3::TEXT IS NULL OR $3 = 'Unreturned'
+                    UNION ALL
+                    SELECT
+                        checkout_id, book_id, copy_id, user_id, checked_out_at, due_at,
+                        renewal_count, returned_at, outcome
+                    FROM returned_checkouts
+                    WHERE $3::TEXT IS NULL OR $3 = 'Returned'
+                )
                 SELECT
-                    c.checkout_id,
-                    c.book_id,
-                    c.user_id,
-                    c.checked_out_at,
+                    COUNT(*) OVER() "total!",
+                    ac.checkout_id "checkout_id!: CheckoutId",
+                    ac.book_id "book_id!: BookId",
+                    ac.copy_id "copy_id: BookCopyId",
+                    ac.user_id "user_id!: UserId",
+                    ac.checked_out_at "checked_out_at!",
+                    ac.due_at "due_at!",
+                    ac.renewal_count "renewal_count!",
+                    ac.returned_at,
+                    ac.outcome,
                     b.title,
                     b.author,
                     b.isbn
-                FROM checkouts c
-                INNER JOIN books b ON c.book_id = b.book_id
-                ORDER BY c.checked_out_at
+                FROM all_checkouts ac
+                INNER JOIN books b ON ac.book_id = b.book_id
+                WHERE ($1::UUID IS NULL OR ac.user_id = $1)
+                    AND ($2::UUID IS NULL OR ac.book_id = $2)
+                    AND ($4::TIMESTAMPTZ IS NULL OR ac.checked_out_at >= $4)
+                    AND ($5::TIMESTAMPTZ IS NULL OR ac.checked_out_at < $5)
+                ORDER BY ac.checked_out_at DESC, ac.checkout_id
+                LIMIT $6
+                OFFSET $7
             "#,
+            borrower as _,
+            book_id as _,
+            state.as_ref().map(AsRef::<str>::as_ref),
+            since,
+            until,
+            limit,
+            offset
         )
         .fetch_all(self.db.inner_ref())
         .await
-        .map(|rows| rows.into_iter().map(Checkout::from).collect())
-        .map_err(AppError::SpecificOperationError)
+        .map_err(AppError::SpecificOperationError)?;
+
+        let total = rows.first().map(|r| r.total).unwrap_or_default();
+        let items = rows
+            .into_iter()
+            .map(Checkout::try_from)
+            .collect::<AppResult<Vec<_>>>()?;
+
+        Ok(PaginatedList {
+            total,
+            limit,
+            offset,
+            next_cursor: None,
+            items,
+        })
     }
 
-    async fn find_unreturned_by_user_id(&self, user_id: UserId) -> AppResult<Vec<Checkout>> {
+    async fn find_history_by_user_id(
+        &self,
+        user_id: UserId,
+        options: CheckoutListOptions,
+    ) -> AppResult<PaginatedList<Checkout>> {
+        self.find_all(CheckoutListOptions {
+            borrower: Some(user_id),
+            ..options
+        })
+        .await
+    }
+
+    async fn find_overdue_all(&self) -> AppResult<Vec<Checkout>> {
         sqlx::query_as!(
             CheckoutRow,
             r#"
                 SELECT
                     c.checkout_id,
                     c.book_id,
+                    c.copy_id,
                     c.user_id,
                     c.checked_out_at,
+                    c.due_at,
+                    c.renewal_count,
                     b.title,
                     b.author,
                     b.isbn
                 FROM checkouts c
                 INNER JOIN books b ON c.book_id = b.book_id
-                WHERE c.user_id = $1
-                ORDER BY c.checked_out_at
+                WHERE c.due_at < CURRENT_TIMESTAMP
+                ORDER BY c.due_at
             "#,
-            user_id as _
         )
         .fetch_all(self.db.inner_ref())
         .await
         .map(|rows| rows.into_iter().map(Checkout::from).collect())
         .map_err(AppError::SpecificOperationError)
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use chrono::{SubsecRound, Utc};
+    use kernel::model::checkout::CheckoutState;
+    use kernel::model::loan_policy::event::UpdateLoanPolicy;
+    use kernel::model::user::event::UpdateUserPrivacy;
+    use kernel::repository::loan_policy::LoanPolicyRepository;
+    use kernel::repository::user::UserRepository;
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::repository::loan_policy::LoanPolicyRepositoryImpl;
+    use crate::repository::user::UserRepositoryImpl;
+
+    fn checkout_config() -> CheckoutConfig {
+        CheckoutConfig {
+            loan_period_days: 14,
+            max_renewals: 2,
+            hold_pickup_hours: 72,
+        }
+    }
+
+    fn checkout_repository(pool: PgPool) -> CheckoutRepositoryImpl {
+        CheckoutRepositoryImpl::new(ConnectionPool::new(pool), checkout_config())
+    }
+
+    fn list_options(
+        borrower: Option<UserId>,
+        book_id: Option<BookId>,
+        state: Option<CheckoutState>,
+    ) -> CheckoutListOptions {
+        CheckoutListOptions {
+            limit: 20,
+            offset: 0,
+            borrower,
+            book_id,
+            state,
+            since: None,
+            until: None,
+        }
+    }
+
+    async fn find_unreturned(
+        checkout_repo: &CheckoutRepositoryImpl,
+        user_id: UserId,
+    ) -> AppResult<Vec<Checkout>> {
+        checkout_repo
+            .find_all(list_options(
+                Some(user_id),
+                None,
+                Some(CheckoutState::Unreturned),
+            ))
+            .await
+            .map(PaginatedList::into_inner)
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_checkout_has_due_date(pool: PgPool) -> anyhow::Result<()> {
+        let checkout_repo = checkout_repository(pool);
+        let book_id = BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b")?;
+        let user_id = UserId::from_str("9582f9de-0fd1-4892-b20c-70139a7eb95b")?;
+        // データベースはミリ秒単位で日時を記録するため、比較できるようにミリ秒未満を切り捨てる。
+        let checked_out_at = Utc::now().trunc_subsecs(3);
+
+        checkout_repo
+            .create(CreateCheckout::new(book_id, user_id, checked_out_at))
+            .await?;
+
+        let checkouts = find_unreturned(&checkout_repo, user_id).await?;
+        assert_eq!(checkouts.len(), 1);
+        let checkout = &checkouts[0];
+        assert_eq!(checkout.due_at, checked_out_at + Duration::days(14));
+        assert!(!checkout.is_overdue(Utc::now()));
+        assert!(checkout.is_overdue(checked_out_at + Duration::days(15)));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_find_overdue_all(pool: PgPool) -> anyhow::Result<()> {
+        let checkout_repo = checkout_repository(pool);
+        let book_id = BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b")?;
+        let user_id = UserId::from_str("9582f9de-0fd1-4892-b20c-70139a7eb95b")?;
+
+        // 返却期限内の貸出は延滞に含まれない
+        checkout_repo
+            .create(CreateCheckout::new(book_id, user_id, Utc::now()))
+            .await?;
+
+        let overdue = checkout_repo.find_overdue_all().await?;
+        assert_eq!(overdue.len(), 1);
+        assert_eq!(
+            overdue[0].id,
+            CheckoutId::from_str("e7f6a5b4-c3d2-4e1f-8a9b-0c1d2e3f4a5b")?
+        );
+        assert!(overdue[0].is_overdue(Utc::now()));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_renew_checkout(pool: PgPool) -> anyhow::Result<()> {
+        let checkout_repo = checkout_repository(pool);
+        let book_id = BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b")?;
+        let user_id = UserId::from_str("9582f9de-0fd1-4892-b20c-70139a7eb95b")?;
+        let other_user_id = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+        // データベースはミリ秒単位で日時を記録するため、比較できるようにミリ秒未満を切り捨てる。
+        let checked_out_at = Utc::now().trunc_subsecs(3);
+
+        checkout_repo
+            .create(CreateCheckout::new(book_id, user_id, checked_out_at))
+            .await?;
+        let checkout_id = find_unreturned(&checkout_repo, user_id).await?[0].id;
+
+        // 借りたユーザー以外は延長できない
+        let result = checkout_repo
+            .renew(RenewCheckout::new(
+                checkout_id,
+                book_id,
+                other_user_id,
+                Utc::now(),
+            ))
+            .await;
+        assert!(matches!(result, Err(AppError::UnprocessableEntity(_))));
+
+        // 上限回数まで、現在の返却期限から貸出期間ずつ延長できる
+        for renewal_count in 1..=2 {
+            checkout_repo
+                .renew(RenewCheckout::new(
+                    checkout_id,
+                    book_id,
+                    user_id,
+                    Utc::now(),
+                ))
+                .await?;
+            let checkout = &find_unreturned(&checkout_repo, user_id).await?[0];
+            assert_eq!(checkout.renewal_count, renewal_count);
+            assert_eq!(
+                checkout.due_at,
+                checked_out_at + Duration::days(14 * (renewal_count as i64 + 1))
+            );
+        }
+
+        // 上限回数を超えて延長できない
+        let result = checkout_repo
+            .renew(RenewCheckout::new(
+                checkout_id,
+                book_id,
+                user_id,
+                Utc::now(),
+            ))
+            .await;
+        assert!(matches!(result, Err(AppError::UnprocessableEntity(_))));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_renew_overdue_checkout(pool: PgPool) -> anyhow::Result<()> {
+        let checkout_repo = checkout_repository(pool);
+        let book_id = BookId::from_str("c1e2d3f4-5a6b-4c7d-8e9f-0a1b2c3d4e5f")?;
+        let checkout_id = CheckoutId::from_str("e7f6a5b4-c3d2-4e1f-8a9b-0c1d2e3f4a5b")?;
+        let user_id = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+        let renewed_at = Utc::now().trunc_subsecs(3);
+
+        // 延滞している貸出は、延長した日時から貸出期間を延長する
+        checkout_repo
+            .renew(RenewCheckout::new(
+                checkout_id,
+                book_id,
+                user_id,
+                renewed_at,
+            ))
+            .await?;
+        let checkout = &find_unreturned(&checkout_repo, user_id).await?[0];
+        assert_eq!(checkout.due_at, renewed_at + Duration::days(14));
+        assert!(!checkout.is_overdue(Utc::now()));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_checkout_follows_loan_policy(pool: PgPool) -> anyhow::Result<()> {
+        let loan_policy_repo =
+            LoanPolicyRepositoryImpl::new(ConnectionPool::new(pool.clone()), checkout_config());
+        let checkout_repo = checkout_repository(pool);
+        let admin_id = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+        let user_id = UserId::from_str("9582f9de-0fd1-4892-b20c-70139a7eb95b")?;
+        let first_book_id = BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b")?;
+        let second_book_id = BookId::from_str("b8d8a5c3-0f7b-4a2e-9d6c-2e1f3a4b5c6d")?;
+        // データベースはミリ秒単位で日時を記録するため、比較できるようにミリ秒未満を切り捨てる。
+        let checked_out_at = Utc::now().trunc_subsecs(3);
+
+        loan_policy_repo
+            .update(UpdateLoanPolicy::new(Role::User, Some(1), 7, 0, admin_id))
+            .await?;
+
+        // 返却期限は貸出ポリシーの貸出期間から決まる
+        checkout_repo
+            .create(CreateCheckout::new(first_book_id, user_id, checked_out_at))
+            .await?;
+        let checkout = &find_unreturned(&checkout_repo, user_id).await?[0];
+        assert_eq!(checkout.due_at, checked_out_at + Duration::days(7));
+
+        // 同時に借りられる数の上限を超えて借りられない
+        let result = checkout_repo
+            .create(CreateCheckout::new(second_book_id, user_id, Utc::now()))
+            .await;
+        assert!(matches!(result, Err(AppError::UnprocessableEntity(_))));
 
-    async fn find_history_by_book_id(&self, book_id: BookId) -> AppResult<Vec<Checkout>> {
-        // 蔵書について未返却の貸出を取得
-        let checkout = self.find_returned_by_book_id(book_id).await?;
+        // 延長回数の上限も貸出ポリシーから決まる
+        let result = checkout_repo
+            .renew(RenewCheckout::new(
+                checkout.id,
+                first_book_id,
+                user_id,
+                Utc::now(),
+            ))
+            .await;
+        assert!(matches!(result, Err(AppError::UnprocessableEntity(_))));
 
-        // 返却済みの貸出を取得
-        let mut checkout_histories: Vec<Checkout> = sqlx::query_as!(
-            ReturnedCheckoutRow,
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_return_on_behalf_of_borrower(pool: PgPool) -> anyhow::Result<()> {
+        let checkout_repo = checkout_repository(pool);
+        let book_id = BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b")?;
+        let user_id = UserId::from_str("9582f9de-0fd1-4892-b20c-70139a7eb95b")?;
+        let admin_id = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+
+        checkout_repo
+            .create(CreateCheckout::new(book_id, user_id, Utc::now()))
+            .await?;
+        let checkout_id = find_unreturned(&checkout_repo, user_id).await?[0].id;
+
+        // 存在しない貸出は返却できない
+        let result = checkout_repo
+            .update_returned(UpdateReturned::new(
+                CheckoutId::new(),
+                book_id,
+                user_id,
+                Role::User,
+                CheckoutOutcome::Returned,
+                Utc::now(),
+            ))
+            .await;
+        assert!(matches!(result, Err(AppError::EntityNotFound(_))));
+
+        // 借りたユーザー以外の一般ユーザーは返却できない
+        let result = checkout_repo
+            .update_returned(UpdateReturned::new(
+                checkout_id,
+                book_id,
+                admin_id,
+                Role::User,
+                CheckoutOutcome::Returned,
+                Utc::now(),
+            ))
+            .await;
+        assert!(matches!(result, Err(AppError::UnprocessableEntity(_))));
+
+        // 管理者は借りたユーザーに代わって、紛失として貸出を終えられる
+        checkout_repo
+            .update_returned(UpdateReturned::new(
+                checkout_id,
+                book_id,
+                admin_id,
+                Role::Admin,
+                CheckoutOutcome::Lost,
+                Utc::now(),
+            ))
+            .await?;
+        assert!(checkout_repo
+            .find_all(list_options(
+                Some(user_id),
+                None,
+                Some(CheckoutState::Unreturned)
+            ))
+            .await?
+            .items
+            .is_empty());
+
+        let history = checkout_repo
+            .find_all(list_options(None, Some(book_id), None))
+            .await?
+            .items;
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].checked_out_by, user_id);
+        assert_eq!(history[0].outcome, Some(CheckoutOutcome::Lost));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_checkout_multiple_copies(pool: PgPool) -> anyhow::Result<()> {
+        let checkout_repo = checkout_repository(pool.clone());
+        let book_id = BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b")?;
+        let user_id = UserId::from_str("9582f9de-0fd1-4892-b20c-70139a7eb95b")?;
+        let admin_id = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+
+        sqlx::query!(
             r#"
-                SELECT
-                    rc.checkout_id,
-                    rc.book_id,
-                    rc.user_id,
-                    rc.checked_out_at,
-                    rc.returned_at,
-                    b.title,
-                    b.author,
-                    b.isbn
-                FROM returned_checkouts rc
-                INNER JOIN books b ON rc.book_id = b.book_id
-                WHERE rc.book_id = $1
-                ORDER BY rc.checked_out_at DESC
+                INSERT INTO book_copies (book_id, barcode)
+                VALUES ($1, 'BC-0005')
             "#,
             book_id as _
         )
-        .fetch_all(self.db.inner_ref())
-        .await
-        .map_err(AppError::SpecificOperationError)?
-        .into_iter()
-        .map(Checkout::from)
-        .collect();
+        .execute(&pool)
+        .await?;
+
+        // 複本の数だけ、異なるユーザーが同時に借りられる
+        checkout_repo
+            .create(CreateCheckout::new(book_id, user_id, Utc::now()))
+            .await?;
+        checkout_repo
+            .create(CreateCheckout::new(book_id, admin_id, Utc::now()))
+            .await?;
+        let user_checkout = &find_unreturned(&checkout_repo, user_id).await?[0];
+        let admin_checkout = &find_unreturned(&checkout_repo, admin_id).await?[0];
+        assert_ne!(user_checkout.copy_id, admin_checkout.copy_id);
+
+        // 同じユーザーは同じ蔵書の複本を重ねて借りられない
+        let result = checkout_repo
+            .create(CreateCheckout::new(book_id, user_id, Utc::now()))
+            .await;
+        assert!(matches!(result, Err(AppError::UnprocessableEntity(_))));
+
+        // 紛失として貸出を終えた複本は、以降貸し出されない
+        checkout_repo
+            .update_returned(UpdateReturned::new(
+                user_checkout.id,
+                book_id,
+                user_id,
+                Role::User,
+                CheckoutOutcome::Lost,
+                Utc::now(),
+            ))
+            .await?;
+        let condition = sqlx::query_scalar!(
+            r#"
+                SELECT condition FROM book_copies WHERE copy_id = $1
+            "#,
+            user_checkout.copy_id as _
+        )
+        .fetch_one(&pool)
+        .await?;
+        assert_eq!(condition, BookCopyCondition::Lost.as_ref());
+
+        let result = checkout_repo
+            .create(CreateCheckout::new(book_id, user_id, Utc::now()))
+            .await;
+        assert!(matches!(result, Err(AppError::UnprocessableEntity(_))));
+
+        Ok(())
+    }
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_find_all_with_filters(pool: PgPool) -> anyhow::Result<()> {
+        let checkout_repo = checkout_repository(pool);
+        let book_id = BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b")?;
+        let other_book_id = BookId::from_str("b8d8a5c3-0f7b-4a2e-9d6c-2e1f3a4b5c6d")?;
+        let user_id = UserId::from_str("9582f9de-0fd1-4892-b20c-70139a7eb95b")?;
+        let admin_id = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+        let checked_out_at = Utc::now().trunc_subsecs(3) - Duration::days(3);
+
+        // 一般ユーザーが借りて返却した後に、もう一冊を借りる
+        checkout_repo
+            .create(CreateCheckout::new(book_id, user_id, checked_out_at))
+            .await?;
+        let checkout_id = find_unreturned(&checkout_repo, user_id).await?[0].id;
+        checkout_repo
+            .update_returned(UpdateReturned::new(
+                checkout_id,
+                book_id,
+                user_id,
+                Role::User,
+                CheckoutOutcome::Returned,
+                checked_out_at + Duration::days(1),
+            ))
+            .await?;
+        checkout_repo
+            .create(CreateCheckout::new(
+                other_book_id,
+                user_id,
+                checked_out_at + Duration::days(2),
+            ))
+            .await?;
+
+        // 返却状態で絞り込める
+        let returned = checkout_repo
+            .find_all(list_options(None, None, Some(CheckoutState::Returned)))
+            .await?;
+        assert_eq!(returned.total, 1);
+        assert_eq!(returned.items[0].id, checkout_id);
+        assert!(returned.items[0].returned_at.is_some());
 
-        if let Some(checkout) = checkout {
-            checkout_histories.insert(0, checkout);
+        // 絞り込まない場合は、貸出日時の新しい順に返却済みの貸出も含めて返す
+        let all = checkout_repo
+            .find_all(list_options(Some(user_id), None, None))
+            .await?;
+        assert_eq!(all.total, 2);
+        assert_eq!(all.items[0].book.book_id, other_book_id);
+        assert_eq!(all.items[1].id, checkout_id);
+        // 管理者はフィクスチャーで別の蔵書を借りている
+        let admin_checkouts = checkout_repo
+            .find_all(list_options(Some(admin_id), None, None))
+            .await?;
+        assert_eq!(admin_checkouts.total, 1);
+        assert_eq!(admin_checkouts.items[0].checked_out_by, admin_id);
+
+        // 件数は絞り込んだ全体の件数で、取得する範囲はlimitとoffsetで指定する
+        let page = checkout_repo
+            .find_all(CheckoutListOptions {
+                limit: 1,
+                offset: 1,
+                ..list_options(Some(user_id), None, None)
+            })
+            .await?;
+        assert_eq!(page.total, 2);
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].id, checkout_id);
+
+        // 貸出日時の範囲で絞り込める
+        let ranged = checkout_repo
+            .find_all(CheckoutListOptions {
+                since: Some(checked_out_at + Duration::days(1)),
+                ..list_options(Some(user_id), None, None)
+            })
+            .await?;
+        assert_eq!(ranged.total, 1);
+        assert_eq!(ranged.items[0].book.book_id, other_book_id);
+        let ranged = checkout_repo
+            .find_all(CheckoutListOptions {
+                until: Some(checked_out_at + Duration::days(1)),
+                ..list_options(None, Some(book_id), None)
+            })
+            .await?;
+        assert_eq!(ranged.total, 1);
+        assert_eq!(ranged.items[0].id, checkout_id);
+
+        Ok(())
+    }
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_opt_out_of_checkout_history(pool: PgPool) -> anyhow::Result<()> {
+        let checkout_repo = checkout_repository(pool.clone());
+        let user_repo = UserRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+        let book_id = BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b")?;
+        let user_id = UserId::from_str("9582f9de-0fd1-4892-b20c-70139a7eb95b")?;
+        let borrow_and_return = || async {
+            checkout_repo
+                .create(CreateCheckout::new(book_id, user_id, Utc::now()))
+                .await?;
+            let checkout_id = find_unreturned(&checkout_repo, user_id).await?[0].id;
+            checkout_repo
+                .update_returned(UpdateReturned::new(
+                    checkout_id,
+                    book_id,
+                    user_id,
+                    Role::User,
+                    CheckoutOutcome::Returned,
+                    Utc::now(),
+                ))
+                .await
+        };
+        let history = || async {
+            checkout_repo
+                .find_history_by_user_id(user_id, list_options(None, None, None))
+                .await
+        };
+
+        // 既定では返却した貸出も履歴に残る
+        borrow_and_return().await?;
+        assert_eq!(history().await?.total, 1);
+
+        // 履歴を残さない設定にすると、それまでの履歴も削除する
+        user_repo
+            .update_privacy(UpdateUserPrivacy {
+                user_id,
+                retain_checkout_history: false,
+                expected_version: None,
+            })
+            .await?;
+        assert!(
+            !user_repo
+                .find_current_user(user_id)
+                .await?
+                .unwrap()
+                .retain_checkout_history
+        );
+        assert_eq!(history().await?.total, 0);
+        let reviewable = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) "count!" FROM reviewable_books WHERE user_id = $1"#,
+            user_id as _
+        )
+        .fetch_one(&pool)
+        .await?;
+        assert_eq!(reviewable, 0);
+
+        // 未返却の貸出は履歴に含まれ、返却すると履歴に残らない
+        checkout_repo
+            .create(CreateCheckout::new(book_id, user_id, Utc::now()))
+            .await?;
+        assert_eq!(history().await?.total, 1);
+        let checkout_id = find_unreturned(&checkout_repo, user_id).await?[0].id;
+        checkout_repo
+            .update_returned(UpdateReturned::new(
+                checkout_id,
+                book_id,
+                user_id,
+                Role::User,
+                CheckoutOutcome::Returned,
+                Utc::now(),
+            ))
+            .await?;
+        assert_eq!(history().await?.total, 0);
+
+        // 監査ログにも、借りたユーザーを記録しない
+        let events = sqlx::query!(
+            r#"
+                SELECT actor_id "actor_id: UserId", before, after
+                FROM audit_events
+                WHERE entity_type = $1 AND entity_id = $2
+            "#,
+            AuditEntityType::Checkout.as_ref(),
+            checkout_id.to_string()
+        )
+        .fetch_all(&pool)
+        .await?;
+        assert_eq!(events.len(), 2);
+        for event in events {
+            assert_eq!(event.actor_id, None);
+            for snapshot in [event.before, event.after].into_iter().flatten() {
+                assert_eq!(snapshot["book_id"], "9890736e-a4e4-461a-a77d-eac3517ef11b");
+                assert!(snapshot.get("user_id").is_none());
+            }
         }
 
-        Ok(checkout_histories)
+        Ok(())
     }
 }
@@ -0,0 +1,358 @@
+use std::io::Cursor;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use derive_new::new;
+use image::{ImageError, ImageFormat, ImageOutputFormat};
+use sha2::{Digest, Sha256};
+
+use kernel::model::audit::{AuditAction, AuditEntityType};
+use kernel::model::book::event::UpdateBookCover;
+use kernel::model::book::{BookCover, CoverImageFormat, CoverSize};
+use kernel::model::id::{BookId, UserId};
+use kernel::model::role::Role;
+use kernel::repository::blob::BlobStore;
+use kernel::repository::cover::BookCoverRepository;
+use shared::error::{AppError, AppResult};
+
+use crate::database::model::book::BookCoverRow;
+use crate::database::ConnectionPool;
+use crate::repository::audit::{book_cover_snapshot, record_audit_event, AuditRecord};
+
+#[derive(new)]
+pub struct BookCoverRepositoryImpl {
+    db: ConnectionPool,
+    blob_store: Arc<dyn BlobStore>,
+}
+
+/// 蔵書の表紙画像を保存するキーの接頭辞
+fn blob_prefix(book_id: BookId) -> String {
+    format!("covers/{book_id}")
+}
+
+/// 表紙画像を保存するキー
+///
+/// 画像の内容が変わるとキーも変わるため、読み出し中の画像が置き換わることはない。
+fn blob_key(book_id: BookId, etag: &str, size: CoverSize) -> String {
+    match size {
+        CoverSize::Original => format!("{}/{etag}", blob_prefix(book_id)),
+        CoverSize::Thumbnail => format!("{}/{etag}-thumbnail", blob_prefix(book_id)),
+    }
+}
+
+/// 縮小画像の長辺の上限 (ピクセル)
+const THUMBNAIL_MAX_DIMENSION: u32 = 200;
+
+/// 復号できる画像の画素数の上限
+///
+/// 圧縮率の高い巨大な画像を復号してメモリを使い果たさないように、復号する前に大きさを確認する。
+const MAX_DECODED_PIXELS: u64 = 4096 * 4096;
+
+/// 表紙画像から、長辺が`THUMBNAIL_MAX_DIMENSION`以下の縮小画像 (PNG) を作成する。
+fn create_thumbnail(format: CoverImageFormat, data: &[u8]) -> AppResult<Vec<u8>> {
+    let broken =
+        |e: ImageError| AppError::UnprocessableEntity(format!("the cover image is broken: {e}"));
+    let format = match format {
+        CoverImageFormat::Jpeg => ImageFormat::Jpeg,
+        CoverImageFormat::Png => ImageFormat::Png,
+    };
+
+    let (width, height) = image::io::Reader::with_format(Cursor::new(data), format)
+        .into_dimensions()
+        .map_err(broken)?;
+    if width == 0 || height == 0 || width as u64 * height as u64 > MAX_DECODED_PIXELS {
+        return Err(AppError::UnprocessableEntity(format!(
+            "the cover image dimensions ({width}x{height}) are not supported"
+        )));
+    }
+
+    let mut image = image::load_from_memory_with_format(data, format).map_err(broken)?;
+    // 上限より小さい画像は拡大しない。
+    if width > THUMBNAIL_MAX_DIMENSION || height > THUMBNAIL_MAX_DIMENSION {
+        image = image.thumbnail(THUMBNAIL_MAX_DIMENSION, THUMBNAIL_MAX_DIMENSION);
+    }
+    let mut thumbnail = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut thumbnail), ImageOutputFormat::Png)
+        .map_err(|e| AppError::ConversionEntityError(e.to_string()))?;
+    Ok(thumbnail)
+}
+
+#[async_trait]
+impl BookCoverRepository for BookCoverRepositoryImpl {
+    async fn update(&self, event: UpdateBookCover) -> AppResult<()> {
+        let UpdateBookCover {
+            book_id,
+            format,
+            data,
+            requested_user,
+            requested_role,
+        } = event;
+
+        // 蔵書の表紙画像を変更できるのは、蔵書の所有者と管理者のみである。
+        let book = sqlx::query!(
+            r#"
+                SELECT b.user_id "owned_by: UserId", bcv.etag "etag?"
+                FROM books b
+                LEFT OUTER JOIN book_covers bcv ON b.book_id = bcv.book_id
+                WHERE b.book_id = $1 AND b.deleted_at IS NULL
+            "#,
+            book_id as _
+        )
+        .fetch_optional(self.db.inner_ref())
+        .await
+        .map_err(AppError::SpecificOperationError)?
+        .ok_or_else(|| AppError::EntityNotFound(format!("the book ({book_id}) doesn't exist")))?;
+        if book.owned_by != requested_user && requested_role != Role::Admin {
+            return Err(AppError::ForbiddenOperation);
+        }
+
+        if CoverImageFormat::detect(&data) != Some(format) {
+            return Err(AppError::UnprocessableEntity(format!(
+                "the cover image is not {}",
+                format.as_ref()
+            )));
+        }
+
+        // 同じ画像が登録済みの場合は何もしない。
+        let etag = format!("{:x}", Sha256::digest(&data));
+        if book.etag.as_deref() == Some(etag.as_str()) {
+            return Ok(());
+        }
+
+        // 画像の復号と縮小は時間がかかるため、非同期ランタイムのスレッドを塞がないようにする。
+        let (data, thumbnail) = tokio::task::spawn_blocking(move || {
+            create_thumbnail(format, &data).map(|thumbnail| (data, thumbnail))
+        })
+        .await
+        .unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()))?;
+        let thumbnail_format = CoverImageFormat::Png;
+
+        self.blob_store
+            .put(&blob_key(book_id, &etag, CoverSize::Original), data)
+            .await?;
+        self.blob_store
+            .put(&blob_key(book_id, &etag, CoverSize::Thumbnail), thumbnail)
+            .await?;
+
+        let mut tx = self.db.begin().await?;
+        let before = book_cover_snapshot(&mut tx, book_id).await?;
+        sqlx::query!(
+            r#"
+                INSERT INTO book_covers (book_id, content_type, thumbnail_content_type, etag)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (book_id) DO UPDATE SET
+                    content_type = EXCLUDED.content_type,
+                    thumbnail_content_type = EXCLUDED.thumbnail_content_type,
+                    etag = EXCLUDED.etag
+            "#,
+            book_id as _,
+            format.as_ref(),
+            thumbnail_format.as_ref(),
+            etag
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+        let after = book_cover_snapshot(&mut tx, book_id).await?;
+        record_audit_event(
+            &mut tx,
+            AuditRecord::new(
+                Some(requested_user),
+                AuditAction::UpdateBookCover,
+                AuditEntityType::Book,
+                book_id.to_string(),
+                before,
+                after,
+            ),
+        )
+        .await?;
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        // 置き換えた画像を削除する。削除に失敗しても表紙画像の登録は成功しているため、記録だけする。
+        if let Some(previous) = book.etag {
+            for size in [CoverSize::Original, CoverSize::Thumbnail] {
+                let key = blob_key(book_id, &previous, size);
+                if let Err(e) = self.blob_store.delete(&key).await {
+                    tracing::warn!(error = %e, key, "failed to delete the replaced cover image");
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn find(&self, book_id: BookId, size: CoverSize) -> AppResult<Option<BookCover>> {
+        let row = sqlx::query_as!(
+            BookCoverRow,
+            r#"
+                SELECT
+                    bcv.content_type,
+                    bcv.thumbnail_content_type,
+                    bcv.etag,
+                    bcv.updated_at
+                FROM book_covers bcv
+                INNER JOIN books b ON bcv.book_id = b.book_id
+                WHERE bcv.book_id = $1 AND b.deleted_at IS NULL
+            "#,
+            book_id as _
+        )
+        .fetch_optional(self.db.inner_ref())
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let content_type = match size {
+            CoverSize::Original => &row.content_type,
+            CoverSize::Thumbnail => &row.thumbnail_content_type,
+        };
+        let format = CoverImageFormat::from_str(content_type)
+            .map_err(|e| AppError::ConversionEntityError(e.to_string()))?;
+        let data = self
+            .blob_store
+            .get(&blob_key(book_id, &row.etag, size))
+            .await?;
+
+        Ok(data.map(|data| BookCover {
+            format,
+            data,
+            etag: row.etag,
+            updated_at: row.updated_at,
+        }))
+    }
+
+    async fn delete_all(&self, book_id: BookId) -> AppResult<()> {
+        self.blob_store.delete_prefix(&blob_prefix(book_id)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+    use uuid::Uuid;
+
+    use kernel::repository::book::BookRepository;
+    use shared::config::BlobStoreConfig;
+
+    use super::*;
+    use crate::blob::LocalBlobStore;
+    use crate::repository::book::BookRepositoryImpl;
+
+    /// 指定した大きさの、すべての画素が`value`のRGBのPNG画像を作成する。
+    fn png(width: u32, height: u32, value: u8) -> Vec<u8> {
+        let mut png = Vec::new();
+        image::RgbImage::from_pixel(width, height, image::Rgb([value; 3]))
+            .write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)
+            .unwrap();
+        png
+    }
+
+    fn png_width(png: &[u8]) -> u32 {
+        image::load_from_memory_with_format(png, ImageFormat::Png)
+            .unwrap()
+            .width()
+    }
+
+    #[test]
+    fn test_create_thumbnail() {
+        // 長辺を上限に合わせ、縦横比を保つ
+        let thumbnail = create_thumbnail(CoverImageFormat::Png, &png(400, 600, 0)).unwrap();
+        let image = image::load_from_memory(&thumbnail).unwrap();
+        assert_eq!((image.width(), image.height()), (133, 200));
+
+        // 上限より小さい画像は拡大しない
+        let thumbnail = create_thumbnail(CoverImageFormat::Png, &png(120, 80, 0)).unwrap();
+        assert_eq!(png_width(&thumbnail), 120);
+
+        // 壊れた画像は復号しない
+        let broken = png(640, 480, 0);
+        let res = create_thumbnail(CoverImageFormat::Png, &broken[..broken.len() / 2]);
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_update_book_cover(pool: PgPool) -> anyhow::Result<()> {
+        let root = std::env::temp_dir().join(format!("book-covers-{}", Uuid::new_v4()));
+        let blob_store = Arc::new(LocalBlobStore::new(&BlobStoreConfig {
+            local_root: root.to_string_lossy().into_owned(),
+        }));
+        let repo = BookCoverRepositoryImpl::new(ConnectionPool::new(pool.clone()), blob_store);
+        let book_repo = BookRepositoryImpl::new(ConnectionPool::new(pool));
+        let book_id = BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b")?;
+        let owner = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+        let other = UserId::from_str("9582f9de-0fd1-4892-b20c-70139a7eb95b")?;
+        let update = |data: Vec<u8>, format, user, role| UpdateBookCover {
+            book_id,
+            format,
+            data,
+            requested_user: user,
+            requested_role: role,
+        };
+
+        assert!(repo.find(book_id, CoverSize::Original).await?.is_none());
+
+        // 所有者以外の一般ユーザーは表紙画像を登録できない
+        let res = repo
+            .update(update(
+                png(8, 8, 0),
+                CoverImageFormat::Png,
+                other,
+                Role::User,
+            ))
+            .await;
+        assert!(matches!(res, Err(AppError::ForbiddenOperation)));
+
+        // 画像の内容が形式と一致しない場合は登録できない
+        let res = repo
+            .update(update(
+                png(8, 8, 0),
+                CoverImageFormat::Jpeg,
+                owner,
+                Role::User,
+            ))
+            .await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+
+        let original = png(600, 300, 200);
+        repo.update(update(
+            original.clone(),
+            CoverImageFormat::Png,
+            owner,
+            Role::User,
+        ))
+        .await?;
+        let cover = repo.find(book_id, CoverSize::Original).await?.unwrap();
+        assert_eq!(cover.format, CoverImageFormat::Png);
+        assert_eq!(cover.data, original);
+        let thumbnail = repo.find(book_id, CoverSize::Thumbnail).await?.unwrap();
+        assert_eq!(thumbnail.format, CoverImageFormat::Png);
+        assert_eq!(png_width(&thumbnail.data), 200);
+
+        let book = book_repo.find_by_id(book_id).await?.unwrap();
+        assert_eq!(book.cover_etag, Some(cover.etag.clone()));
+
+        // 管理者は他のユーザーの蔵書の表紙画像を置き換えられる。置き換えた画像は削除される
+        repo.update(update(
+            png(100, 50, 10),
+            CoverImageFormat::Png,
+            other,
+            Role::Admin,
+        ))
+        .await?;
+        let replaced = repo.find(book_id, CoverSize::Thumbnail).await?.unwrap();
+        assert_ne!(replaced.etag, cover.etag);
+        assert_eq!(png_width(&replaced.data), 100);
+        let mut files = tokio::fs::read_dir(root.join("covers").join(book_id.to_string())).await?;
+        let mut count = 0;
+        while files.next_entry().await?.is_some() {
+            count += 1;
+        }
+        assert_eq!(count, 2);
+
+        tokio::fs::remove_dir_all(&root).await?;
+        Ok(())
+    }
+}
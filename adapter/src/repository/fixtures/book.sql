@@ -0,0 +1,56 @@
+INSERT INTO books (book_id, title, author, isbn, description, user_id, created_at)
+VALUES
+    (
+        '9890736e-a4e4-461a-a77d-eac3517ef11b',
+        'RustによるWebアプリケーション開発',
+        '豊田優貴／松本健太郎／吉川哲史',
+        '9784065369579',
+        'Rustによるアプリケーション開発のベストプラクティス',
+        '5b4c96ac-316a-4bee-8e69-cac5eb84ff4c',
+        '2024-12-01 09:00:00+09'
+    ),
+    (
+        'b8d8a5c3-0f7b-4a2e-9d6c-2e1f3a4b5c6d',
+        'The Rust Programming Language',
+        'Steve Klabnik and Carol Nichols',
+        '9781718503106',
+        'The official book on the Rust programming language',
+        '9582f9de-0fd1-4892-b20c-70139a7eb95b',
+        '2024-12-02 09:00:00+09'
+    ),
+    (
+        'c1e2d3f4-5a6b-4c7d-8e9f-0a1b2c3d4e5f',
+        'Programming Rust',
+        'Jim Blandy, Jason Orendorff and Leonora F. S. Tindall',
+        '9781492052593',
+        'Fast, safe systems development',
+        '9582f9de-0fd1-4892-b20c-70139a7eb95b',
+        '2024-12-03 09:00:00+09'
+    ),
+    (
+        'd4c3b2a1-6f5e-4d7c-9b8a-1f2e3d4c5b6a',
+        'PostgreSQL: Up and Running',
+        'Regina O. Obe and Leo S. Hsu',
+        '9781491963418',
+        'A practical guide to the advanced open source database',
+        '5b4c96ac-316a-4bee-8e69-cac5eb84ff4c',
+        '2024-12-04 09:00:00+09'
+    );
+
+INSERT INTO book_copies (copy_id, book_id, barcode, location)
+VALUES
+    ('baab162c-0dc7-440b-9176-a65e2c68ed99', '9890736e-a4e4-461a-a77d-eac3517ef11b', 'BC-0001', '3F-A1'),
+    ('99cc5cc5-564c-460b-aeb1-c869a3255a76', 'b8d8a5c3-0f7b-4a2e-9d6c-2e1f3a4b5c6d', 'BC-0002', '3F-A1'),
+    ('424570b0-8489-4482-95c2-b727d955fb77', 'c1e2d3f4-5a6b-4c7d-8e9f-0a1b2c3d4e5f', 'BC-0003', '3F-A2'),
+    ('3fa03f75-ffc9-4556-9435-d7153de7eac2', 'd4c3b2a1-6f5e-4d7c-9b8a-1f2e3d4c5b6a', 'BC-0004', '3F-B1');
+
+INSERT INTO checkouts (checkout_id, book_id, copy_id, user_id, checked_out_at, due_at)
+VALUES
+    (
+        'e7f6a5b4-c3d2-4e1f-8a9b-0c1d2e3f4a5b',
+        'c1e2d3f4-5a6b-4c7d-8e9f-0a1b2c3d4e5f',
+        '424570b0-8489-4482-95c2-b727d955fb77',
+        '5b4c96ac-316a-4bee-8e69-cac5eb84ff4c',
+        '2024-12-05 09:00:00+09',
+        '2024-12-19 09:00:00+09'
+    );
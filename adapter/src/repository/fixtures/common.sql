@@ -0,0 +1,25 @@
+INSERT INTO roles (name)
+VALUES
+    ('Admin'),
+    ('User')
+ON CONFLICT DO NOTHING;
+
+INSERT INTO users (user_id, name, email, password_hash, role_id)
+SELECT
+    '5b4c96ac-316a-4bee-8e69-cac5eb84ff4c',
+    'Eleazar Fig',
+    'eleazar.fig@example.com',
+    '$2b$12$B4fCusqV6Ke8Eip1C3/ZMOg3YwLBO.AQkd3zxU62N.iv43QEUbJ22',
+    role_id
+FROM roles
+WHERE name = 'Admin';
+
+INSERT INTO users (user_id, name, email, password_hash, role_id)
+SELECT
+    '9582f9de-0fd1-4892-b20c-70139a7eb95b',
+    'Yuki Toyoda',
+    'yuki.toyoda@example.com',
+    '$2b$12$B4fCusqV6Ke8Eip1C3/ZMOg3YwLBO.AQkd3zxU62N.iv43QEUbJ22',
+    role_id
+FROM roles
+WHERE name = 'User';
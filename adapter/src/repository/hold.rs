@@ -0,0 +1,595 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use derive_new::new;
+
+use kernel::model::audit::{AuditAction, AuditEntityType};
+use kernel::model::hold::event::{CreateHold, DeleteHold};
+use kernel::model::hold::Hold;
+use kernel::model::id::{BookId, HoldId, UserId};
+use kernel::model::role::Role;
+use kernel::repository::hold::HoldRepository;
+use shared::config::CheckoutConfig;
+use shared::error::{AppError, AppResult};
+
+use crate::database::model::hold::HoldRow;
+use crate::database::ConnectionPool;
+use crate::repository::audit::{hold_snapshot, record_audit_event, AuditRecord};
+
+#[derive(new)]
+pub struct HoldRepositoryImpl {
+    db: ConnectionPool,
+    config: CheckoutConfig,
+}
+
+impl HoldRepositoryImpl {
+    async fn set_transaction_serializable(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    ) -> AppResult<()> {
+        sqlx::query!(
+            r#"
+                SET TRANSACTION ISOLATION LEVEL SERIALIZABLE
+            "#
+        )
+        .execute(&mut **tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl HoldRepository for HoldRepositoryImpl {
+    async fn create(&self, event: CreateHold) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        self.set_transaction_serializable(&mut tx).await?;
+
+        // 取り置き期限を過ぎた予約を取り消してから、予約の待ち行列の状態を確認する。
+        refresh_holds(
+            &mut tx,
+            event.book_id,
+            event.held_at,
+            Duration::hours(self.config.hold_pickup_hours),
+        )
+        .await?;
+
+        // 予約する前に次のブロックで以下を確認する。
+        // * 指定の蔵書IDを持つ蔵書が存在するか (ゴミ箱にある蔵書は予約できない)
+        // * 予約するユーザーがその蔵書の複本を借りていないか
+        // * 予約するユーザーがその蔵書をすでに予約していないか
+        // * 他のユーザーのために取り置かれていない、貸出できる複本がないか
+        {
+            let result = sqlx::query!(
+                r#"
+                    SELECT
+                        EXISTS (
+                            SELECT 1 FROM checkouts c
+                            WHERE c.book_id = b.book_id AND c.user_id = $2
+                        ) "already_borrowed!",
+                        EXISTS (
+                            SELECT 1 FROM holds h WHERE h.book_id = b.book_id AND h.user_id = $2
+                        ) "already_held!",
+                        (
+                            SELECT COUNT(*) FROM book_copies bc
+                            WHERE bc.book_id = b.book_id
+                                AND bc.condition <> 'Lost'
+                                AND NOT EXISTS (
+                                    SELECT 1 FROM checkouts c WHERE c.copy_id = bc.copy_id
+                                )
+                        ) "available_copies!",
+                        (
+                            SELECT COUNT(*) FROM holds h
+                            WHERE h.book_id = b.book_id AND h.ready_at IS NOT NULL
+                        ) "ready_holds!"
+                    FROM books b
+                    WHERE b.book_id = $1 AND b.deleted_at IS NULL
+                "#,
+                event.book_id as _,
+                event.held_by as _
+            )
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(AppError::SpecificOperationError)?;
+
+            match result {
+                None => {
+                    return Err(AppError::EntityNotFound(format!(
+                        "the book ({}) doesn't exist",
+                        event.book_id
+                    )))
+                }
+                Some(r) if r.already_borrowed => {
+                    return Err(AppError::UnprocessableEntity(format!(
+                        "the user ({}) has already borrowed the book ({})",
+                        event.held_by, event.book_id
+                    )))
+                }
+                Some(r) if r.already_held => {
+                    return Err(AppError::UnprocessableEntity(format!(
+                        "the user ({}) has already placed a hold on the book ({})",
+                        event.held_by, event.book_id
+                    )))
+                }
+                Some(r) if r.available_copies > r.ready_holds => {
+                    return Err(AppError::UnprocessableEntity(format!(
+                        "the book ({}) is available for checkout",
+                        event.book_id
+                    )))
+                }
+                _ => {}
+            }
+        }
+
+        let hold_id = HoldId::new();
+        let result = sqlx::query!(
+            r#"
+                INSERT INTO holds (hold_id, book_id, user_id, created_at)
+                VALUES ($1, $2, $3, $4)
+            "#,
+            hold_id as _,
+            event.book_id as _,
+            event.held_by as _,
+            event.held_at,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        if result.rows_affected() < 1 {
+            return Err(AppError::NoRowsAffectedError(
+                "no hold record has been created".into(),
+            ));
+        }
+
+        let after = hold_snapshot(&mut tx, hold_id).await?;
+        record_audit_event(
+            &mut tx,
+            AuditRecord::new(
+                Some(event.held_by),
+                AuditAction::CreateHold,
+                AuditEntityType::Hold,
+                hold_id.to_string(),
+                None,
+                after,
+            ),
+        )
+        .await?;
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(())
+    }
+
+    async fn find_by_book_id(&self, book_id: BookId) -> AppResult<Vec<Hold>> {
+        // 取り置き期限を過ぎた予約は、次に蔵書の貸出や予約が操作されたときに取り消されるため、
+        // ここでは待ち行列に含めない。
+        sqlx::query_as!(
+            HoldRow,
+            r#"
+                SELECT
+                    h.hold_id,
+                    h.book_id,
+                    h.user_id,
+                    u.name user_name,
+                    ROW_NUMBER() OVER (ORDER BY h.created_at, h.hold_id) "position!",
+                    h.created_at,
+                    h.ready_at,
+                    h.expires_at
+                FROM holds h
+                INNER JOIN users u ON h.user_id = u.user_id
+                WHERE h.book_id = $1
+                    AND (h.expires_at IS NULL OR h.expires_at > CURRENT_TIMESTAMP)
+                ORDER BY h.created_at, h.hold_id
+            "#,
+            book_id as _
+        )
+        .fetch_all(self.db.inner_ref())
+        .await
+        .map(|rows| rows.into_iter().map(Hold::from).collect())
+        .map_err(AppError::SpecificOperationError)
+    }
+
+    async fn delete(&self, event: DeleteHold) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        self.set_transaction_serializable(&mut tx).await?;
+
+        // 予約を取り消せるのは、予約したユーザーまたは管理者のみ
+        {
+            let result = sqlx::query!(
+                r#"
+                    SELECT user_id "user_id: UserId"
+                    FROM holds
+                    WHERE hold_id = $1 AND book_id = $2
+                "#,
+                event.hold_id as _,
+                event.book_id as _
+            )
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(AppError::SpecificOperationError)?;
+
+            match result {
+                None => {
+                    return Err(AppError::EntityNotFound(format!(
+                        "the hold ({}) of the book ({}) doesn't exist",
+                        event.hold_id, event.book_id
+                    )))
+                }
+                Some(r)
+                    if r.user_id != event.requested_user && event.requested_role != Role::Admin =>
+                {
+                    return Err(AppError::ForbiddenOperation)
+                }
+                _ => {}
+            }
+        }
+
+        let before = hold_snapshot(&mut tx, event.hold_id).await?;
+        let result = sqlx::query!(
+            r#"
+                DELETE FROM holds
+                WHERE hold_id = $1
+            "#,
+            event.hold_id as _
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        if result.rows_affected() < 1 {
+            return Err(AppError::NoRowsAffectedError(
+                "no hold record has been deleted".into(),
+            ));
+        }
+
+        record_audit_event(
+            &mut tx,
+            AuditRecord::new(
+                Some(event.requested_user),
+                AuditAction::DeleteHold,
+                AuditEntityType::Hold,
+                event.hold_id.to_string(),
+                before,
+                None,
+            ),
+        )
+        .await?;
+
+        // 取り置き中の予約を取り消した場合は、待ち行列の次の予約のために蔵書を取り置く。
+        refresh_holds(
+            &mut tx,
+            event.book_id,
+            event.canceled_at,
+            Duration::hours(self.config.hold_pickup_hours),
+        )
+        .await?;
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(())
+    }
+}
+
+/// 蔵書の予約の待ち行列を`now`の時点の状態に更新する。
+///
+/// 取り置き期限を過ぎた予約を取り消し、貸出中でも紛失してもいない複本が取り置き中の予約の数より
+/// 多い場合は、その差の数だけ待ち行列の先頭から予約を選び、`pickup_window`の間だけ複本を取り置く。
+/// 蔵書の貸出、返却及び予約を操作するトランザクションの中で呼び出す。
+pub(crate) async fn refresh_holds(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    book_id: BookId,
+    now: DateTime<Utc>,
+    pickup_window: Duration,
+) -> AppResult<()> {
+    // 期限切れによる取り消しはユーザーの操作ではないため、操作者を記録しない。
+    let expired = sqlx::query!(
+        r#"
+            DELETE FROM holds h
+            WHERE h.book_id = $1 AND h.expires_at <= $2
+            RETURNING h.hold_id "hold_id: HoldId", to_jsonb(h) "snapshot!"
+        "#,
+        book_id as _,
+        now
+    )
+    .fetch_all(&mut **tx)
+    .await
+    .map_err(AppError::SpecificOperationError)?;
+    for hold in expired {
+        record_audit_event(
+            tx,
+            AuditRecord::new(
+                None,
+                AuditAction::DeleteHold,
+                AuditEntityType::Hold,
+                hold.hold_id.to_string(),
+                Some(hold.snapshot),
+                None,
+            ),
+        )
+        .await?;
+    }
+
+    sqlx::query!(
+        r#"
+            UPDATE holds
+            SET
+                ready_at = $2,
+                expires_at = $3
+            WHERE hold_id IN (
+                SELECT h.hold_id
+                FROM holds h
+                WHERE h.book_id = $1 AND h.ready_at IS NULL
+                ORDER BY h.created_at, h.hold_id
+                LIMIT GREATEST(
+                    0,
+                    (
+                        SELECT COUNT(*) FROM book_copies bc
+                        WHERE bc.book_id = $1
+                            AND bc.condition <> 'Lost'
+                            AND NOT EXISTS (
+                                SELECT 1 FROM checkouts c WHERE c.copy_id = bc.copy_id
+                            )
+                    ) - (
+                        SELECT COUNT(*) FROM holds r
+                        WHERE r.book_id = $1 AND r.ready_at IS NOT NULL
+                    )
+                )
+            )
+        "#,
+        book_id as _,
+        now,
+        now + pickup_window
+    )
+    .execute(&mut **tx)
+    .await
+    .map_err(AppError::SpecificOperationError)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use chrono::SubsecRound;
+    use kernel::model::checkout::event::{CreateCheckout, RenewCheckout, UpdateReturned};
+    use kernel::model::checkout::CheckoutOutcome;
+    use kernel::model::id::CheckoutId;
+    use kernel::repository::checkout::CheckoutRepository;
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::repository::checkout::CheckoutRepositoryImpl;
+
+    fn checkout_config() -> CheckoutConfig {
+        CheckoutConfig {
+            loan_period_days: 14,
+            max_renewals: 2,
+            hold_pickup_hours: 72,
+        }
+    }
+
+    fn repositories(pool: PgPool) -> (HoldRepositoryImpl, CheckoutRepositoryImpl) {
+        let db = ConnectionPool::new(pool);
+        (
+            HoldRepositoryImpl::new(db.clone(), checkout_config()),
+            CheckoutRepositoryImpl::new(db, checkout_config()),
+        )
+    }
+
+    struct DeletedHoldEvent {
+        actor_id: Option<UserId>,
+        before: serde_json::Value,
+    }
+
+    async fn deleted_hold_events(pool: &PgPool) -> anyhow::Result<Vec<DeletedHoldEvent>> {
+        let events = sqlx::query_as!(
+            DeletedHoldEvent,
+            r#"
+                SELECT actor_id "actor_id: UserId", before "before!"
+                FROM audit_events
+                WHERE action = $1 AND entity_type = $2
+            "#,
+            AuditAction::DeleteHold.as_ref(),
+            AuditEntityType::Hold.as_ref()
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(events)
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_hold_queue(pool: PgPool) -> anyhow::Result<()> {
+        let (hold_repo, checkout_repo) = repositories(pool.clone());
+        // 管理者が借りている蔵書
+        let book_id = BookId::from_str("c1e2d3f4-5a6b-4c7d-8e9f-0a1b2c3d4e5f")?;
+        let checkout_id = CheckoutId::from_str("e7f6a5b4-c3d2-4e1f-8a9b-0c1d2e3f4a5b")?;
+        let admin_id = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+        let user_id = UserId::from_str("9582f9de-0fd1-4892-b20c-70139a7eb95b")?;
+
+        // 借りられる蔵書は予約できない
+        let available_book_id = BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b")?;
+        let result = hold_repo
+            .create(CreateHold::new(available_book_id, user_id, Utc::now()))
+            .await;
+        assert!(matches!(result, Err(AppError::UnprocessableEntity(_))));
+
+        // 借りているユーザーは予約できない
+        let result = hold_repo
+            .create(CreateHold::new(book_id, admin_id, Utc::now()))
+            .await;
+        assert!(matches!(result, Err(AppError::UnprocessableEntity(_))));
+
+        // 貸出中の蔵書は予約でき、同じユーザーが重ねて予約することはできない
+        hold_repo
+            .create(CreateHold::new(book_id, user_id, Utc::now()))
+            .await?;
+        let result = hold_repo
+            .create(CreateHold::new(book_id, user_id, Utc::now()))
+            .await;
+        assert!(matches!(result, Err(AppError::UnprocessableEntity(_))));
+
+        let holds = hold_repo.find_by_book_id(book_id).await?;
+        assert_eq!(holds.len(), 1);
+        assert_eq!(holds[0].held_by.id, user_id);
+        assert_eq!(holds[0].position, 1);
+        assert!(!holds[0].is_ready(Utc::now()));
+
+        // 予約されている蔵書の貸出は延長できない
+        let result = checkout_repo
+            .renew(RenewCheckout::new(
+                checkout_id,
+                book_id,
+                admin_id,
+                Utc::now(),
+            ))
+            .await;
+        assert!(matches!(result, Err(AppError::UnprocessableEntity(_))));
+
+        // 返却されると、待ち行列の先頭の予約のために取り置かれる
+        // データベースはミリ秒単位で日時を記録するため、比較できるようにミリ秒未満を切り捨てる。
+        let returned_at = Utc::now().trunc_subsecs(3);
+        checkout_repo
+            .update_returned(UpdateReturned::new(
+                checkout_id,
+                book_id,
+                admin_id,
+                Role::Admin,
+                CheckoutOutcome::Returned,
+                returned_at,
+            ))
+            .await?;
+        let holds = hold_repo.find_by_book_id(book_id).await?;
+        assert!(holds[0].is_ready(Utc::now()));
+        assert_eq!(holds[0].ready_at, Some(returned_at));
+        assert_eq!(holds[0].expires_at, Some(returned_at + Duration::hours(72)));
+
+        // 取り置き期限までは、予約したユーザー以外は借りられない
+        let result = checkout_repo
+            .create(CreateCheckout::new(book_id, admin_id, Utc::now()))
+            .await;
+        assert!(matches!(result, Err(AppError::UnprocessableEntity(_))));
+
+        // 予約したユーザーが借りると、予約は完了する
+        checkout_repo
+            .create(CreateCheckout::new(book_id, user_id, Utc::now()))
+            .await?;
+        assert!(hold_repo.find_by_book_id(book_id).await?.is_empty());
+        let events = deleted_hold_events(&pool).await?;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].actor_id, Some(user_id));
+        assert_eq!(events[0].before["user_id"], user_id.raw().to_string());
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_hold_expires_after_pickup_window(pool: PgPool) -> anyhow::Result<()> {
+        let (hold_repo, checkout_repo) = repositories(pool.clone());
+        let book_id = BookId::from_str("c1e2d3f4-5a6b-4c7d-8e9f-0a1b2c3d4e5f")?;
+        let checkout_id = CheckoutId::from_str("e7f6a5b4-c3d2-4e1f-8a9b-0c1d2e3f4a5b")?;
+        let admin_id = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+        let user_id = UserId::from_str("9582f9de-0fd1-4892-b20c-70139a7eb95b")?;
+
+        hold_repo
+            .create(CreateHold::new(
+                book_id,
+                user_id,
+                Utc::now() - Duration::hours(200),
+            ))
+            .await?;
+        checkout_repo
+            .update_returned(UpdateReturned::new(
+                checkout_id,
+                book_id,
+                admin_id,
+                Role::Admin,
+                CheckoutOutcome::Returned,
+                Utc::now() - Duration::hours(100),
+            ))
+            .await?;
+
+        // 取り置き期限を過ぎた予約は待ち行列に含まれず、他のユーザーが借りられる
+        assert!(hold_repo.find_by_book_id(book_id).await?.is_empty());
+        checkout_repo
+            .create(CreateCheckout::new(book_id, admin_id, Utc::now()))
+            .await?;
+
+        // 期限切れで取り消した予約は、操作者なしで監査ログに記録する
+        let events = deleted_hold_events(&pool).await?;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].actor_id, None);
+        assert_eq!(events[0].before["user_id"], user_id.raw().to_string());
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_cancel_hold(pool: PgPool) -> anyhow::Result<()> {
+        let (hold_repo, checkout_repo) = repositories(pool);
+        let book_id = BookId::from_str("c1e2d3f4-5a6b-4c7d-8e9f-0a1b2c3d4e5f")?;
+        let checkout_id = CheckoutId::from_str("e7f6a5b4-c3d2-4e1f-8a9b-0c1d2e3f4a5b")?;
+        let admin_id = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+        let user_id = UserId::from_str("9582f9de-0fd1-4892-b20c-70139a7eb95b")?;
+
+        hold_repo
+            .create(CreateHold::new(book_id, user_id, Utc::now()))
+            .await?;
+        let hold_id = hold_repo.find_by_book_id(book_id).await?[0].id;
+
+        // 存在しない予約は取り消せない
+        let result = hold_repo
+            .delete(DeleteHold::new(
+                HoldId::new(),
+                book_id,
+                user_id,
+                Role::User,
+                Utc::now(),
+            ))
+            .await;
+        assert!(matches!(result, Err(AppError::EntityNotFound(_))));
+
+        // 予約したユーザー以外の一般ユーザーは取り消せない
+        let result = hold_repo
+            .delete(DeleteHold::new(
+                hold_id,
+                book_id,
+                admin_id,
+                Role::User,
+                Utc::now(),
+            ))
+            .await;
+        assert!(matches!(result, Err(AppError::ForbiddenOperation)));
+
+        // 管理者は取り消せる
+        hold_repo
+            .delete(DeleteHold::new(
+                hold_id,
+                book_id,
+                admin_id,
+                Role::Admin,
+                Utc::now(),
+            ))
+            .await?;
+        assert!(hold_repo.find_by_book_id(book_id).await?.is_empty());
+
+        // 予約がなくなったため、返却された蔵書は取り置かれず、誰でも借りられる
+        checkout_repo
+            .update_returned(UpdateReturned::new(
+                checkout_id,
+                book_id,
+                admin_id,
+                Role::Admin,
+                CheckoutOutcome::Returned,
+                Utc::now(),
+            ))
+            .await?;
+        checkout_repo
+            .create(CreateCheckout::new(book_id, admin_id, Utc::now()))
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,189 @@
+use async_trait::async_trait;
+use derive_new::new;
+
+use kernel::model::audit::{AuditAction, AuditEntityType};
+use kernel::model::id::UserId;
+use kernel::model::loan_policy::event::UpdateLoanPolicy;
+use kernel::model::loan_policy::LoanPolicy;
+use kernel::repository::loan_policy::LoanPolicyRepository;
+use shared::config::CheckoutConfig;
+use shared::error::{AppError, AppResult};
+
+use crate::database::model::loan_policy::LoanPolicyRow;
+use crate::database::ConnectionPool;
+use crate::repository::audit::{loan_policy_snapshot, record_audit_event, AuditRecord};
+
+#[derive(new)]
+pub struct LoanPolicyRepositoryImpl {
+    db: ConnectionPool,
+    config: CheckoutConfig,
+}
+
+#[async_trait]
+impl LoanPolicyRepository for LoanPolicyRepositoryImpl {
+    async fn find_all(&self) -> AppResult<Vec<LoanPolicy>> {
+        sqlx::query_as!(
+            LoanPolicyRow,
+            r#"
+                SELECT
+                    r.name role_name,
+                    lp.max_checkouts "max_checkouts?",
+                    lp.loan_period_days "loan_period_days?",
+                    lp.max_renewals "max_renewals?"
+                FROM roles r
+                LEFT OUTER JOIN loan_policies lp ON r.role_id = lp.role_id
+                ORDER BY r.name
+            "#
+        )
+        .fetch_all(self.db.inner_ref())
+        .await
+        .map_err(AppError::SpecificOperationError)?
+        .into_iter()
+        .map(|row| row.into_loan_policy(&self.config))
+        .collect()
+    }
+
+    async fn update(&self, event: UpdateLoanPolicy) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        let before = loan_policy_snapshot(&mut tx, event.role).await?;
+        let result = sqlx::query!(
+            r#"
+                INSERT INTO loan_policies (role_id, max_checkouts, loan_period_days, max_renewals)
+                SELECT role_id, $2, $3, $4 FROM roles WHERE name = $1
+                ON CONFLICT (role_id) DO UPDATE SET
+                    max_checkouts = EXCLUDED.max_checkouts,
+                    loan_period_days = EXCLUDED.loan_period_days,
+                    max_renewals = EXCLUDED.max_renewals
+            "#,
+            event.role.as_ref(),
+            event.max_checkouts,
+            event.loan_period_days,
+            event.max_renewals,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        if result.rows_affected() < 1 {
+            return Err(AppError::EntityNotFound(format!(
+                "the role ({}) doesn't exist",
+                event.role.as_ref()
+            )));
+        }
+
+        let after = loan_policy_snapshot(&mut tx, event.role).await?;
+        record_audit_event(
+            &mut tx,
+            AuditRecord::new(
+                Some(event.requested_user),
+                AuditAction::UpdateLoanPolicy,
+                AuditEntityType::LoanPolicy,
+                event.role.as_ref().to_string(),
+                before,
+                after,
+            ),
+        )
+        .await?;
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(())
+    }
+}
+
+/// ユーザーのロールの貸出ポリシーを返す。
+///
+/// 蔵書を貸出または延長するトランザクションの中で呼び出す。
+pub(crate) async fn find_loan_policy_by_user_id(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    user_id: UserId,
+    config: &CheckoutConfig,
+) -> AppResult<LoanPolicy> {
+    sqlx::query_as!(
+        LoanPolicyRow,
+        r#"
+            SELECT
+                r.name role_name,
+                lp.max_checkouts "max_checkouts?",
+                lp.loan_period_days "loan_period_days?",
+                lp.max_renewals "max_renewals?"
+            FROM users u
+            INNER JOIN roles r ON u.role_id = r.role_id
+            LEFT OUTER JOIN loan_policies lp ON r.role_id = lp.role_id
+            WHERE u.user_id = $1
+        "#,
+        user_id as _
+    )
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(AppError::SpecificOperationError)?
+    .ok_or_else(|| AppError::EntityNotFound(format!("the user ({}) doesn't exist", user_id)))?
+    .into_loan_policy(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use kernel::model::role::Role;
+    use sqlx::PgPool;
+
+    use super::*;
+
+    fn loan_policy_repository(pool: PgPool) -> LoanPolicyRepositoryImpl {
+        LoanPolicyRepositoryImpl::new(
+            ConnectionPool::new(pool),
+            CheckoutConfig {
+                loan_period_days: 14,
+                max_renewals: 2,
+                hold_pickup_hours: 72,
+            },
+        )
+    }
+
+    #[sqlx::test(fixtures("common"))]
+    async fn test_update_loan_policy(pool: PgPool) -> anyhow::Result<()> {
+        let repo = loan_policy_repository(pool);
+        let admin_id = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+
+        // 貸出ポリシーが登録されていないロールは設定値を使用し、同時に借りられる数を制限しない
+        let policies = repo.find_all().await?;
+        assert_eq!(
+            policies,
+            vec![
+                LoanPolicy {
+                    role: Role::Admin,
+                    max_checkouts: None,
+                    loan_period_days: 14,
+                    max_renewals: 2,
+                },
+                LoanPolicy {
+                    role: Role::User,
+                    max_checkouts: None,
+                    loan_period_days: 14,
+                    max_renewals: 2,
+                },
+            ]
+        );
+
+        // 登録と更新のいずれもできる
+        repo.update(UpdateLoanPolicy::new(Role::User, Some(3), 7, 1, admin_id))
+            .await?;
+        repo.update(UpdateLoanPolicy::new(Role::User, Some(5), 21, 0, admin_id))
+            .await?;
+        let policies = repo.find_all().await?;
+        assert_eq!(
+            policies[1],
+            LoanPolicy {
+                role: Role::User,
+                max_checkouts: Some(5),
+                loan_period_days: 21,
+                max_renewals: 0,
+            }
+        );
+        assert_eq!(policies[0].max_checkouts, None);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,253 @@
+use async_trait::async_trait;
+use derive_new::new;
+
+use kernel::model::id::{BookId, UserId};
+use kernel::model::recommendation::RecommendationCandidate;
+use kernel::repository::recommendation::RecommendationRepository;
+use shared::error::{AppError, AppResult};
+
+use crate::database::model::recommendation::{BorrowedTogetherRow, SimilarBookRow};
+use crate::database::ConnectionPool;
+
+#[derive(new)]
+pub struct RecommendationRepositoryImpl {
+    db: ConnectionPool,
+}
+
+#[async_trait]
+impl RecommendationRepository for RecommendationRepositoryImpl {
+    async fn find_borrowed_together(
+        &self,
+        book_ids: Vec<BookId>,
+    ) -> AppResult<Vec<RecommendationCandidate>> {
+        // 貸出中の貸出と返却済みの貸出を合わせて、指定した蔵書を借りたユーザーを求め、
+        // そのユーザーが借りたほかの蔵書を、借りたユーザーの数とともに返す。
+        let rows = sqlx::query_as!(
+            BorrowedTogetherRow,
+            r#"
+                WITH all_checkouts AS (
+                    SELECT book_id, user_id FROM checkouts
+                    UNION
+                    SELECT book_id, user_id FROM returned_checkouts
+                ),
+                co_borrowers AS (
+                    SELECT DISTINCT user_id FROM all_checkouts
+                    WHERE book_id = ANY($1)
+                )
+                SELECT
+                    b.book_id "book_id: BookId",
+                    b.title,
+                    b.author,
+                    b.isbn,
+                    COUNT(DISTINCT ac.user_id) "borrower_count!"
+                FROM all_checkouts ac
+                INNER JOIN co_borrowers cb ON ac.user_id = cb.user_id
+                INNER JOIN books b ON ac.book_id = b.book_id
+                WHERE b.deleted_at IS NULL
+                    AND NOT (b.book_id = ANY($1))
+                GROUP BY b.book_id
+            "#,
+            &book_ids as _
+        )
+        .fetch_all(self.db.inner_ref())
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        Ok(rows
+            .into_iter()
+            .map(RecommendationCandidate::from)
+            .collect())
+    }
+
+    async fn find_similar(&self, book_ids: Vec<BookId>) -> AppResult<Vec<RecommendationCandidate>> {
+        // 著者は大文字と小文字、前後の空白を区別せずに比較する。
+        let rows = sqlx::query_as!(
+            SimilarBookRow,
+            r#"
+                WITH similar_books AS (
+                    SELECT
+                        b.book_id,
+                        b.title,
+                        b.author,
+                        b.isbn,
+                        EXISTS (
+                            SELECT 1 FROM books s
+                            WHERE s.book_id = ANY($1)
+                                AND lower(trim(s.author)) = lower(trim(b.author))
+                        ) AS same_author,
+                        (
+                            SELECT COUNT(*) FROM book_tags bt
+                            WHERE bt.book_id = b.book_id
+                                AND bt.tag_id IN (
+                                    SELECT tag_id FROM book_tags WHERE book_id = ANY($1)
+                                )
+                        ) AS shared_tag_count
+                    FROM books b
+                    WHERE b.deleted_at IS NULL
+                        AND NOT (b.book_id = ANY($1))
+                )
+                SELECT
+                    book_id "book_id!: BookId",
+                    title "title!",
+                    author "author!",
+                    isbn "isbn!",
+                    same_author "same_author!",
+                    shared_tag_count "shared_tag_count!"
+                FROM similar_books
+                WHERE same_author OR shared_tag_count > 0
+            "#,
+            &book_ids as _
+        )
+        .fetch_all(self.db.inner_ref())
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        Ok(rows
+            .into_iter()
+            .flat_map(SimilarBookRow::into_candidates)
+            .collect())
+    }
+
+    async fn find_borrowed_book_ids(&self, user_id: UserId) -> AppResult<Vec<BookId>> {
+        sqlx::query_scalar!(
+            r#"
+                SELECT book_id "book_id!: BookId" FROM checkouts WHERE user_id = $1
+                UNION
+                SELECT book_id FROM returned_checkouts WHERE user_id = $1
+            "#,
+            user_id as _
+        )
+        .fetch_all(self.db.inner_ref())
+        .await
+        .map_err(AppError::SpecificOperationError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::str::FromStr;
+
+    use kernel::model::recommendation::RecommendationReason;
+    use sqlx::PgPool;
+
+    use super::*;
+
+    fn strengths(
+        candidates: Vec<RecommendationCandidate>,
+    ) -> HashMap<(BookId, RecommendationReason), i64> {
+        candidates
+            .into_iter()
+            .map(|c| ((c.book_id, c.reason), c.strength))
+            .collect()
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_find_recommendation_candidates(pool: PgPool) -> anyhow::Result<()> {
+        let repo = RecommendationRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+        let web_book_id = BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b")?;
+        let rust_book_id = BookId::from_str("b8d8a5c3-0f7b-4a2e-9d6c-2e1f3a4b5c6d")?;
+        // 管理者が借りている蔵書
+        let programming_rust_id = BookId::from_str("c1e2d3f4-5a6b-4c7d-8e9f-0a1b2c3d4e5f")?;
+        let postgres_book_id = BookId::from_str("d4c3b2a1-6f5e-4d7c-9b8a-1f2e3d4c5b6a")?;
+        let admin_id = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+        let user_id = UserId::from_str("9582f9de-0fd1-4892-b20c-70139a7eb95b")?;
+
+        // 管理者は「RustによるWebアプリケーション開発」を、一般ユーザーは
+        // 「Programming Rust」と「The Rust Programming Language」を借りて返却した
+        for (book_id, user_id) in [
+            (web_book_id, admin_id),
+            (programming_rust_id, user_id),
+            (rust_book_id, user_id),
+        ] {
+            sqlx::query!(
+                r#"
+                    INSERT INTO returned_checkouts (
+                        checkout_id, book_id, user_id, checked_out_at, due_at, returned_at
+                    ) VALUES (
+                        gen_random_uuid(), $1, $2, '2024-11-01 09:00:00+09',
+                        '2024-11-15 09:00:00+09', '2024-11-08 09:00:00+09'
+                    )
+                "#,
+                book_id as _,
+                user_id as _
+            )
+            .execute(&pool)
+            .await?;
+        }
+        let mut borrowed = repo.find_borrowed_book_ids(admin_id).await?;
+        borrowed.sort_by_key(|id| id.raw());
+        let mut expected = vec![web_book_id, programming_rust_id];
+        expected.sort_by_key(|id| id.raw());
+        assert_eq!(borrowed, expected);
+
+        // 「Programming Rust」を借りた2人は、ほかにそれぞれ1冊ずつ借りている
+        let together = strengths(
+            repo.find_borrowed_together(vec![programming_rust_id])
+                .await?,
+        );
+        assert_eq!(
+            together,
+            HashMap::from([
+                ((web_book_id, RecommendationReason::BorrowedTogether), 1),
+                ((rust_book_id, RecommendationReason::BorrowedTogether), 1),
+            ])
+        );
+
+        // 著者は大文字と小文字、前後の空白を区別せずに比較する
+        sqlx::query!(
+            r#"
+                UPDATE books
+                SET author = ' jim blandy, jason orendorff and leonora f. s. tindall '
+                WHERE book_id = $1
+            "#,
+            postgres_book_id as _
+        )
+        .execute(&pool)
+        .await?;
+        // 「Programming Rust」と共通するタグを、「The Rust Programming Language」には2つ、
+        // 「RustによるWebアプリケーション開発」には1つ付ける
+        sqlx::query!(
+            r#"
+                WITH new_tags AS (
+                    INSERT INTO tags (name) VALUES ('rust'), ('programming')
+                    RETURNING tag_id, name
+                )
+                INSERT INTO book_tags (book_id, tag_id)
+                SELECT b.book_id, t.tag_id
+                FROM new_tags t
+                CROSS JOIN UNNEST($1::uuid[]) AS b (book_id)
+                WHERE t.name = 'rust' OR b.book_id <> $2
+            "#,
+            &[programming_rust_id, rust_book_id, web_book_id] as _,
+            web_book_id as _
+        )
+        .execute(&pool)
+        .await?;
+        let similar = strengths(repo.find_similar(vec![programming_rust_id]).await?);
+        assert_eq!(
+            similar,
+            HashMap::from([
+                ((postgres_book_id, RecommendationReason::SameAuthor), 1),
+                ((rust_book_id, RecommendationReason::SharedTags), 2),
+                ((web_book_id, RecommendationReason::SharedTags), 1),
+            ])
+        );
+
+        // 削除された蔵書は候補にしない
+        sqlx::query!(
+            "UPDATE books SET deleted_at = CURRENT_TIMESTAMP(3) WHERE book_id = $1",
+            web_book_id as _
+        )
+        .execute(&pool)
+        .await?;
+        let together = repo
+            .find_borrowed_together(vec![programming_rust_id])
+            .await?;
+        assert!(together.iter().all(|c| c.book_id != web_book_id));
+        let similar = repo.find_similar(vec![programming_rust_id]).await?;
+        assert!(similar.iter().all(|c| c.book_id != web_book_id));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,290 @@
+use async_trait::async_trait;
+use derive_new::new;
+
+use kernel::model::id::{BookId, UserId};
+use kernel::model::report::{
+    BookCirculation, BorrowerActivity, LoanDuration, MonthlyCheckouts, ReportPeriod, Utilisation,
+};
+use kernel::repository::report::ReportRepository;
+use shared::error::{AppError, AppResult};
+
+use crate::database::model::report::{
+    BookCirculationRow, BorrowerActivityRow, MonthlyCheckoutsRow,
+};
+use crate::database::ConnectionPool;
+
+#[derive(new)]
+pub struct ReportRepositoryImpl {
+    db: ConnectionPool,
+}
+
+// 未返却の貸出はcheckoutsテーブルに、返却済みの貸出はreturned_checkoutsテーブルにあるため、
+// 貸出回数を数える集計は両方を合わせてから、貸出日時が期間に含まれる貸出を数える。
+#[async_trait]
+impl ReportRepository for ReportRepositoryImpl {
+    async fn most_borrowed_books(
+        &self,
+        period: ReportPeriod,
+        limit: i64,
+    ) -> AppResult<Vec<BookCirculation>> {
+        let rows = sqlx::query_as!(
+            BookCirculationRow,
+            r#"
+                WITH all_checkouts AS (
+                    SELECT book_id, checked_out_at FROM checkouts
+                    UNION ALL
+                    SELECT book_id, checked_out_at FROM returned_checkouts
+                )
+                SELECT
+                    b.book_id "book_id: BookId",
+                    b.title,
+                    b.author,
+                    b.isbn,
+                    COUNT(*) "checkout_count!"
+                FROM all_checkouts ac
+                INNER JOIN books b ON ac.book_id = b.book_id
+                WHERE ($1::TIMESTAMPTZ IS NULL OR ac.checked_out_at >= $1)
+                    AND ($2::TIMESTAMPTZ IS NULL OR ac.checked_out_at < $2)
+                GROUP BY b.book_id
+                ORDER BY COUNT(*) DESC, b.title, b.book_id
+                LIMIT $3
+            "#,
+            period.since,
+            period.until,
+            limit
+        )
+        .fetch_all(self.db.inner_ref())
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        Ok(rows.into_iter().map(BookCirculation::from).collect())
+    }
+
+    async fn most_active_borrowers(
+        &self,
+        period: ReportPeriod,
+        limit: i64,
+    ) -> AppResult<Vec<BorrowerActivity>> {
+        let rows = sqlx::query_as!(
+            BorrowerActivityRow,
+            r#"
+                WITH all_checkouts AS (
+                    SELECT user_id, checked_out_at FROM checkouts
+                    UNION ALL
+                    SELECT user_id, checked_out_at FROM returned_checkouts
+                )
+                SELECT
+                    u.user_id "user_id: UserId",
+                    u.name,
+                    COUNT(*) "checkout_count!"
+                FROM all_checkouts ac
+                INNER JOIN users u ON ac.user_id = u.user_id
+                WHERE ($1::TIMESTAMPTZ IS NULL OR ac.checked_out_at >= $1)
+                    AND ($2::TIMESTAMPTZ IS NULL OR ac.checked_out_at < $2)
+                GROUP BY u.user_id
+                ORDER BY COUNT(*) DESC, u.name, u.user_id
+                LIMIT $3
+            "#,
+            period.since,
+            period.until,
+            limit
+        )
+        .fetch_all(self.db.inner_ref())
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        Ok(rows.into_iter().map(BorrowerActivity::from).collect())
+    }
+
+    async fn average_loan_duration(&self, period: ReportPeriod) -> AppResult<LoanDuration> {
+        let row = sqlx::query!(
+            r#"
+                SELECT
+                    COUNT(*) "returned_count!",
+                    AVG(EXTRACT(EPOCH FROM returned_at - checked_out_at) / 86400)::FLOAT8
+                        "average_days"
+                FROM returned_checkouts
+                WHERE ($1::TIMESTAMPTZ IS NULL OR checked_out_at >= $1)
+                    AND ($2::TIMESTAMPTZ IS NULL OR checked_out_at < $2)
+            "#,
+            period.since,
+            period.until
+        )
+        .fetch_one(self.db.inner_ref())
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        Ok(LoanDuration {
+            returned_count: row.returned_count,
+            average_days: row.average_days,
+        })
+    }
+
+    async fn utilisation(&self) -> AppResult<Utilisation> {
+        // ゴミ箱にある蔵書の複本と、紛失した複本は数えない。
+        let row = sqlx::query!(
+            r#"
+                SELECT
+                    COUNT(*) "total_copies!",
+                    COUNT(*) FILTER (
+                        WHERE EXISTS (SELECT 1 FROM checkouts c WHERE c.copy_id = bc.copy_id)
+                    ) "checked_out_copies!"
+                FROM book_copies bc
+                INNER JOIN books b ON bc.book_id = b.book_id
+                WHERE b.deleted_at IS NULL AND bc.condition <> 'Lost'
+            "#
+        )
+        .fetch_one(self.db.inner_ref())
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        Ok(Utilisation {
+            total_copies: row.total_copies,
+            checked_out_copies: row.checked_out_copies,
+        })
+    }
+
+    async fn checkouts_per_month(&self, period: ReportPeriod) -> AppResult<Vec<MonthlyCheckouts>> {
+        let rows = sqlx::query_as!(
+            MonthlyCheckoutsRow,
+            r#"
+                WITH all_checkouts AS (
+                    SELECT checked_out_at FROM checkouts
+                    UNION ALL
+                    SELECT checked_out_at FROM returned_checkouts
+                )
+                SELECT
+                    to_char(checked_out_at AT TIME ZONE 'UTC', 'YYYY-MM') "month!",
+                    COUNT(*) "checkout_count!"
+                FROM all_checkouts
+                WHERE ($1::TIMESTAMPTZ IS NULL OR checked_out_at >= $1)
+                    AND ($2::TIMESTAMPTZ IS NULL OR checked_out_at < $2)
+                GROUP BY 1
+                ORDER BY 1
+            "#,
+            period.since,
+            period.until
+        )
+        .fetch_all(self.db.inner_ref())
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        Ok(rows.into_iter().map(MonthlyCheckouts::from).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use chrono::{DateTime, Utc};
+    use sqlx::PgPool;
+
+    use super::*;
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_reports(pool: PgPool) -> anyhow::Result<()> {
+        let repo = ReportRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+        let book_id = BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b")?;
+        let other_book_id = BookId::from_str("b8d8a5c3-0f7b-4a2e-9d6c-2e1f3a4b5c6d")?;
+        // 管理者が借りている蔵書
+        let checked_out_book_id = BookId::from_str("c1e2d3f4-5a6b-4c7d-8e9f-0a1b2c3d4e5f")?;
+        let admin_id = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+        let user_id = UserId::from_str("9582f9de-0fd1-4892-b20c-70139a7eb95b")?;
+
+        // 一般ユーザーが11月に2回、12月に1回借りて、それぞれ2日、4日、3日で返却した
+        for (book_id, checked_out_at, returned_at) in [
+            (book_id, "2024-11-01T00:00:00Z", "2024-11-03T00:00:00Z"),
+            (book_id, "2024-11-10T00:00:00Z", "2024-11-14T00:00:00Z"),
+            (
+                other_book_id,
+                "2024-12-10T00:00:00Z",
+                "2024-12-13T00:00:00Z",
+            ),
+        ] {
+            let checked_out_at = DateTime::<Utc>::from_str(checked_out_at)?;
+            sqlx::query!(
+                r#"
+                    INSERT INTO returned_checkouts (
+                        checkout_id, book_id, user_id, checked_out_at, due_at, returned_at
+                    ) VALUES (
+                        gen_random_uuid(), $1, $2, $3, $3::TIMESTAMPTZ + INTERVAL '14 days', $4
+                    )
+                "#,
+                book_id as _,
+                user_id as _,
+                checked_out_at,
+                DateTime::<Utc>::from_str(returned_at)?
+            )
+            .execute(&pool)
+            .await?;
+        }
+
+        // 貸出回数が同じ場合はタイトルの順に並ぶ
+        let books = repo.most_borrowed_books(ReportPeriod::default(), 2).await?;
+        assert_eq!(
+            books
+                .iter()
+                .map(|b| (b.book_id, b.checkout_count))
+                .collect::<Vec<_>>(),
+            vec![(book_id, 2), (checked_out_book_id, 1)]
+        );
+
+        let borrowers = repo
+            .most_active_borrowers(ReportPeriod::default(), 10)
+            .await?;
+        assert_eq!(
+            borrowers
+                .iter()
+                .map(|b| (b.user_id, b.checkout_count))
+                .collect::<Vec<_>>(),
+            vec![(user_id, 3), (admin_id, 1)]
+        );
+
+        let duration = repo.average_loan_duration(ReportPeriod::default()).await?;
+        assert_eq!(duration.returned_count, 3);
+        assert!((duration.average_days.unwrap() - 3.0).abs() < 1e-9);
+
+        let utilisation = repo.utilisation().await?;
+        assert_eq!(utilisation.total_copies, 4);
+        assert_eq!(utilisation.checked_out_copies, 1);
+        assert_eq!(utilisation.available_copies(), 3);
+
+        let months = repo.checkouts_per_month(ReportPeriod::default()).await?;
+        assert_eq!(
+            months
+                .iter()
+                .map(|m| (m.month.as_str(), m.checkout_count))
+                .collect::<Vec<_>>(),
+            vec![("2024-11", 2), ("2024-12", 2)]
+        );
+
+        // 期間を指定すると、貸出日時が期間に含まれる貸出のみを集計する
+        let december = ReportPeriod {
+            since: Some(DateTime::<Utc>::from_str("2024-12-01T00:00:00Z")?),
+            until: None,
+        };
+        let books = repo.most_borrowed_books(december, 10).await?;
+        assert_eq!(books.len(), 2);
+        assert!(books.iter().all(|b| b.book_id != book_id));
+        let duration = repo.average_loan_duration(december).await?;
+        assert_eq!(duration.returned_count, 1);
+        let november = ReportPeriod {
+            since: None,
+            until: december.since,
+        };
+        let months = repo.checkouts_per_month(november).await?;
+        assert_eq!(months.len(), 1);
+        assert_eq!(months[0].month, "2024-11");
+        let duration = repo
+            .average_loan_duration(ReportPeriod {
+                since: Some(DateTime::<Utc>::from_str("2025-01-01T00:00:00Z")?),
+                until: None,
+            })
+            .await?;
+        assert_eq!(duration.returned_count, 0);
+        assert!(duration.average_days.is_none());
+
+        Ok(())
+    }
+}
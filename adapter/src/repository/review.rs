@@ -0,0 +1,491 @@
+use async_trait::async_trait;
+use derive_new::new;
+
+use kernel::model::audit::{AuditAction, AuditEntityType};
+use kernel::model::id::{BookId, ReviewId, UserId};
+use kernel::model::review::event::{CreateReview, DeleteReview, UpdateReview};
+use kernel::model::review::Review;
+use kernel::model::role::Role;
+use kernel::repository::review::ReviewRepository;
+use shared::error::{AppError, AppResult};
+
+use crate::database::model::review::ReviewRow;
+use crate::database::ConnectionPool;
+use crate::repository::audit::{record_audit_event, review_snapshot, AuditRecord};
+
+#[derive(new)]
+pub struct ReviewRepositoryImpl {
+    db: ConnectionPool,
+}
+
+impl ReviewRepositoryImpl {
+    /// 蔵書のレビューを書いたユーザーを返す。
+    async fn find_reviewer(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        review_id: ReviewId,
+        book_id: BookId,
+    ) -> AppResult<UserId> {
+        sqlx::query_scalar!(
+            r#"
+                SELECT user_id "user_id: UserId"
+                FROM reviews
+                WHERE review_id = $1 AND book_id = $2
+                FOR UPDATE
+            "#,
+            review_id as _,
+            book_id as _
+        )
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?
+        .ok_or_else(|| {
+            AppError::EntityNotFound(format!(
+                "the review ({review_id}) of the book ({book_id}) doesn't exist"
+            ))
+        })
+    }
+}
+
+#[async_trait]
+impl ReviewRepository for ReviewRepositoryImpl {
+    async fn create(&self, event: CreateReview) -> AppResult<ReviewId> {
+        let mut tx = self.db.begin().await?;
+
+        // レビューを書く前に次のブロックで以下を確認する。
+        // * 指定の蔵書IDを持つ蔵書が存在するか (ゴミ箱にある蔵書にはレビューを書けない)
+        // * レビューを書くユーザーがその蔵書のレビューをすでに書いていないか
+        // * レビューを書くユーザーがその蔵書を借りて返却したことがあるか
+        {
+            let result = sqlx::query!(
+                r#"
+                    SELECT
+                        EXISTS (
+                            SELECT 1 FROM reviewable_books rb
+                            WHERE rb.book_id = b.book_id AND rb.user_id = $2
+                        ) "returned!",
+                        EXISTS (
+                            SELECT 1 FROM reviews r
+                            WHERE r.book_id = b.book_id AND r.user_id = $2
+                        ) "already_reviewed!"
+                    FROM books b
+                    WHERE b.book_id = $1 AND b.deleted_at IS NULL
+                "#,
+                event.book_id as _,
+                event.reviewed_by as _
+            )
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(AppError::SpecificOperationError)?;
+
+            match result {
+                None => {
+                    return Err(AppError::EntityNotFound(format!(
+                        "the book ({}) doesn't exist",
+                        event.book_id
+                    )))
+                }
+                // レビューを書くとレビューを書けることの記録を削除するため、先に確認する。
+                Some(r) if r.already_reviewed => return Err(review_conflict(&event)),
+                Some(r) if !r.returned => return Err(AppError::ForbiddenOperation),
+                _ => {}
+            }
+        }
+
+        let review_id = ReviewId::new();
+        sqlx::query!(
+            r#"
+                INSERT INTO reviews (review_id, book_id, user_id, rating, comment)
+                VALUES ($1, $2, $3, $4, $5)
+            "#,
+            review_id as _,
+            event.book_id as _,
+            event.reviewed_by as _,
+            event.rating,
+            event.comment,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| match e {
+            // 同時に同じ蔵書のレビューを書いた場合
+            sqlx::Error::Database(ref db) if db.is_unique_violation() => review_conflict(&event),
+            e => AppError::SpecificOperationError(e),
+        })?;
+
+        // レビューを書いたら、レビューを書けることの記録は不要になるため削除する。
+        sqlx::query!(
+            r#"
+                DELETE FROM reviewable_books
+                WHERE user_id = $1 AND book_id = $2
+            "#,
+            event.reviewed_by as _,
+            event.book_id as _
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        let after = review_snapshot(&mut tx, review_id).await?;
+        record_audit_event(
+            &mut tx,
+            AuditRecord::new(
+                Some(event.reviewed_by),
+                AuditAction::CreateReview,
+                AuditEntityType::Review,
+                review_id.to_string(),
+                None,
+                after,
+            ),
+        )
+        .await?;
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(review_id)
+    }
+
+    async fn find_by_book_id(&self, book_id: BookId) -> AppResult<Vec<Review>> {
+        sqlx::query_as!(
+            ReviewRow,
+            r#"
+                SELECT
+                    r.review_id,
+                    r.book_id,
+                    r.user_id,
+                    u.name user_name,
+                    r.rating,
+                    r.comment,
+                    r.created_at,
+                    r.updated_at
+                FROM reviews r
+                INNER JOIN users u ON r.user_id = u.user_id
+                WHERE r.book_id = $1
+                ORDER BY r.created_at DESC, r.review_id
+            "#,
+            book_id as _
+        )
+        .fetch_all(self.db.inner_ref())
+        .await
+        .map(|rows| rows.into_iter().map(Review::from).collect())
+        .map_err(AppError::SpecificOperationError)
+    }
+
+    async fn update(&self, event: UpdateReview) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        // レビューを編集できるのは、レビューを書いたユーザーのみ
+        let reviewer = self
+            .find_reviewer(&mut tx, event.review_id, event.book_id)
+            .await?;
+        if reviewer != event.requested_user {
+            return Err(AppError::ForbiddenOperation);
+        }
+
+        let before = review_snapshot(&mut tx, event.review_id).await?;
+        let result = sqlx::query!(
+            r#"
+                UPDATE reviews
+                SET rating = $2, comment = $3
+                WHERE review_id = $1
+            "#,
+            event.review_id as _,
+            event.rating,
+            event.comment,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        if result.rows_affected() < 1 {
+            return Err(AppError::NoRowsAffectedError(
+                "no review record has been updated".into(),
+            ));
+        }
+
+        let after = review_snapshot(&mut tx, event.review_id).await?;
+        record_audit_event(
+            &mut tx,
+            AuditRecord::new(
+                Some(event.requested_user),
+                AuditAction::UpdateReview,
+                AuditEntityType::Review,
+                event.review_id.to_string(),
+                before,
+                after,
+            ),
+        )
+        .await?;
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(())
+    }
+
+    async fn delete(&self, event: DeleteReview) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        // レビューを削除できるのは、レビューを書いたユーザーまたは管理者のみ
+        let reviewer = self
+            .find_reviewer(&mut tx, event.review_id, event.book_id)
+            .await?;
+        if reviewer != event.requested_user && event.requested_role != Role::Admin {
+            return Err(AppError::ForbiddenOperation);
+        }
+
+        let before = review_snapshot(&mut tx, event.review_id).await?;
+        let result = sqlx::query!(
+            r#"
+                DELETE FROM reviews
+                WHERE review_id = $1
+            "#,
+            event.review_id as _
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        if result.rows_affected() < 1 {
+            return Err(AppError::NoRowsAffectedError(
+                "no review record has been deleted".into(),
+            ));
+        }
+
+        record_audit_event(
+            &mut tx,
+            AuditRecord::new(
+                Some(event.requested_user),
+                AuditAction::DeleteReview,
+                AuditEntityType::Review,
+                event.review_id.to_string(),
+                before,
+                None,
+            ),
+        )
+        .await?;
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(())
+    }
+}
+
+fn review_conflict(event: &CreateReview) -> AppError {
+    AppError::Conflict(format!(
+        "the user ({}) has already reviewed the book ({})",
+        event.reviewed_by, event.book_id
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use chrono::Utc;
+    use kernel::model::book::{BookListOptions, BookSortKey};
+    use kernel::model::checkout::event::{CreateCheckout, UpdateReturned};
+    use kernel::model::checkout::{CheckoutListOptions, CheckoutOutcome};
+    use kernel::model::id::CheckoutId;
+    use kernel::model::user::event::UpdateUserPrivacy;
+    use kernel::repository::book::BookRepository;
+    use kernel::repository::checkout::CheckoutRepository;
+    use kernel::repository::user::UserRepository;
+    use shared::config::CheckoutConfig;
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::repository::book::BookRepositoryImpl;
+    use crate::repository::checkout::CheckoutRepositoryImpl;
+    use crate::repository::user::UserRepositoryImpl;
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_review_lifecycle(pool: PgPool) -> anyhow::Result<()> {
+        let db = ConnectionPool::new(pool.clone());
+        let review_repo = ReviewRepositoryImpl::new(db.clone());
+        let book_repo = BookRepositoryImpl::new(db.clone());
+        let checkout_repo = CheckoutRepositoryImpl::new(
+            db,
+            CheckoutConfig {
+                loan_period_days: 14,
+                max_renewals: 2,
+                hold_pickup_hours: 72,
+            },
+        );
+        // 管理者が借りている蔵書
+        let book_id = BookId::from_str("c1e2d3f4-5a6b-4c7d-8e9f-0a1b2c3d4e5f")?;
+        let checkout_id = CheckoutId::from_str("e7f6a5b4-c3d2-4e1f-8a9b-0c1d2e3f4a5b")?;
+        let admin_id = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+        let user_id = UserId::from_str("9582f9de-0fd1-4892-b20c-70139a7eb95b")?;
+
+        // 返却するまではレビューを書けない
+        let review = || CreateReview::new(book_id, admin_id, 4, Some("Very thorough".into()));
+        let result = review_repo.create(review()).await;
+        assert!(matches!(result, Err(AppError::ForbiddenOperation)));
+
+        checkout_repo
+            .update_returned(UpdateReturned::new(
+                checkout_id,
+                book_id,
+                admin_id,
+                Role::Admin,
+                CheckoutOutcome::Returned,
+                Utc::now(),
+            ))
+            .await?;
+
+        // 返却するとレビューを書けるが、同じ蔵書に2件は書けない
+        let review_id = review_repo.create(review()).await?;
+        let result = review_repo.create(review()).await;
+        assert!(matches!(result, Err(AppError::Conflict(_))));
+
+        // 借りたことのないユーザーや、存在しない蔵書にはレビューを書けない
+        let result = review_repo
+            .create(CreateReview::new(book_id, user_id, 5, None))
+            .await;
+        assert!(matches!(result, Err(AppError::ForbiddenOperation)));
+        let result = review_repo
+            .create(CreateReview::new(BookId::new(), admin_id, 5, None))
+            .await;
+        assert!(matches!(result, Err(AppError::EntityNotFound(_))));
+
+        let reviews = review_repo.find_by_book_id(book_id).await?;
+        assert_eq!(reviews.len(), 1);
+        assert_eq!(reviews[0].id, review_id);
+        assert_eq!(reviews[0].reviewed_by.id, admin_id);
+        assert_eq!(reviews[0].rating, 4);
+        assert_eq!(reviews[0].comment.as_deref(), Some("Very thorough"));
+
+        // 評価の平均とレビューの数は蔵書に含まれ、評価の高い蔵書から並べられる
+        let book = book_repo.find_by_id(book_id).await?.unwrap();
+        assert_eq!(book.average_rating, Some(4.0));
+        assert_eq!(book.review_count, 1);
+        let books = book_repo
+            .find_all(BookListOptions {
+                limit: 20,
+                offset: 0,
+                sort: BookSortKey::Rating.into(),
+                cursor: None,
+                query: None,
+                owner: None,
+                checkout_state: None,
+                tags: None,
+            })
+            .await?;
+        assert_eq!(books.items[0].id, book_id);
+        assert!(books.items[1..]
+            .iter()
+            .all(|b| b.average_rating.is_none() && b.review_count == 0));
+
+        // レビューを編集できるのはレビューを書いたユーザーのみ
+        let update =
+            |requested_user| UpdateReview::new(review_id, book_id, requested_user, 2, None);
+        let result = review_repo.update(update(user_id)).await;
+        assert!(matches!(result, Err(AppError::ForbiddenOperation)));
+        review_repo.update(update(admin_id)).await?;
+        let reviews = review_repo.find_by_book_id(book_id).await?;
+        assert_eq!(reviews[0].rating, 2);
+        assert_eq!(reviews[0].comment, None);
+
+        // レビューを削除できるのは、レビューを書いたユーザーまたは管理者のみ
+        let result = review_repo
+            .delete(DeleteReview::new(review_id, book_id, user_id, Role::User))
+            .await;
+        assert!(matches!(result, Err(AppError::ForbiddenOperation)));
+        review_repo
+            .delete(DeleteReview::new(review_id, book_id, admin_id, Role::Admin))
+            .await?;
+        assert!(review_repo.find_by_book_id(book_id).await?.is_empty());
+        let result = review_repo
+            .delete(DeleteReview::new(review_id, book_id, admin_id, Role::Admin))
+            .await;
+        assert!(matches!(result, Err(AppError::EntityNotFound(_))));
+
+        let audit_count = sqlx::query_scalar!(
+            r#"
+                SELECT COUNT(*) "count!" FROM audit_events
+                WHERE entity_type = 'review' AND entity_id = $1
+            "#,
+            review_id.to_string()
+        )
+        .fetch_one(&pool)
+        .await?;
+        assert_eq!(audit_count, 3);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_opted_out_borrower_can_review(pool: PgPool) -> anyhow::Result<()> {
+        let db = ConnectionPool::new(pool.clone());
+        let review_repo = ReviewRepositoryImpl::new(db.clone());
+        let user_repo = UserRepositoryImpl::new(db.clone());
+        let checkout_repo = CheckoutRepositoryImpl::new(
+            db,
+            CheckoutConfig {
+                loan_period_days: 14,
+                max_renewals: 2,
+                hold_pickup_hours: 72,
+            },
+        );
+        let book_id = BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b")?;
+        let user_id = UserId::from_str("9582f9de-0fd1-4892-b20c-70139a7eb95b")?;
+        let history = || async {
+            checkout_repo
+                .find_history_by_user_id(
+                    user_id,
+                    CheckoutListOptions {
+                        limit: 20,
+                        offset: 0,
+                        borrower: None,
+                        book_id: None,
+                        state: None,
+                        since: None,
+                        until: None,
+                    },
+                )
+                .await
+        };
+
+        // 貸出の履歴を残さない設定にしてから借りて返却する
+        user_repo
+            .update_privacy(UpdateUserPrivacy {
+                user_id,
+                retain_checkout_history: false,
+                expected_version: None,
+            })
+            .await?;
+        checkout_repo
+            .create(CreateCheckout::new(book_id, user_id, Utc::now()))
+            .await?;
+        let checkout_id = history().await?.items[0].id;
+        checkout_repo
+            .update_returned(UpdateReturned::new(
+                checkout_id,
+                book_id,
+                user_id,
+                Role::User,
+                CheckoutOutcome::Returned,
+                Utc::now(),
+            ))
+            .await?;
+        assert_eq!(history().await?.total, 0);
+
+        // 履歴が残っていなくても、返却した蔵書のレビューを書ける
+        review_repo
+            .create(CreateReview::new(book_id, user_id, 5, None))
+            .await?;
+        let reviews = review_repo.find_by_book_id(book_id).await?;
+        assert_eq!(reviews.len(), 1);
+        assert_eq!(reviews[0].reviewed_by.id, user_id);
+
+        // レビューを書いた後は、レビューを書けることの記録も残らない
+        let reviewable = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) "count!" FROM reviewable_books WHERE user_id = $1"#,
+            user_id as _
+        )
+        .fetch_one(&pool)
+        .await?;
+        assert_eq!(reviewable, 0);
+
+        Ok(())
+    }
+}
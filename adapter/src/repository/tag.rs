@@ -0,0 +1,394 @@
+use async_trait::async_trait;
+use derive_new::new;
+
+use kernel::model::audit::{AuditAction, AuditEntityType};
+use kernel::model::id::{BookId, TagId, UserId};
+use kernel::model::role::Role;
+use kernel::model::tag::event::{AttachTag, CreateTag, DeleteTag, DetachTag, UpdateTag};
+use kernel::model::tag::Tag;
+use kernel::repository::tag::TagRepository;
+use shared::error::{AppError, AppResult};
+
+use crate::database::model::tag::TagRow;
+use crate::database::ConnectionPool;
+use crate::repository::audit::{record_audit_event, tag_snapshot, AuditRecord};
+
+#[derive(new)]
+pub struct TagRepositoryImpl {
+    db: ConnectionPool,
+}
+
+impl TagRepositoryImpl {
+    /// 蔵書とタグが存在し、リクエストしたユーザーが蔵書のタグを変更できるか確認する。
+    ///
+    /// 蔵書のタグを変更できるのは、蔵書の所有者と管理者のみである。
+    async fn ensure_taggable(
+        &self,
+        book_id: BookId,
+        tag_id: TagId,
+        requested_user: UserId,
+        requested_role: Role,
+    ) -> AppResult<()> {
+        let result = sqlx::query!(
+            r#"
+                SELECT
+                    b.user_id "owned_by: UserId",
+                    EXISTS (SELECT 1 FROM tags t WHERE t.tag_id = $2) "tag_exists!"
+                FROM books b
+                WHERE b.book_id = $1 AND b.deleted_at IS NULL
+            "#,
+            book_id as _,
+            tag_id as _
+        )
+        .fetch_optional(self.db.inner_ref())
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        match result {
+            None => Err(AppError::EntityNotFound(format!(
+                "the book ({book_id}) doesn't exist"
+            ))),
+            Some(r) if r.owned_by != requested_user && requested_role != Role::Admin => {
+                Err(AppError::ForbiddenOperation)
+            }
+            Some(r) if !r.tag_exists => Err(AppError::EntityNotFound(format!(
+                "the tag ({tag_id}) doesn't exist"
+            ))),
+            Some(_) => Ok(()),
+        }
+    }
+}
+
+#[async_trait]
+impl TagRepository for TagRepositoryImpl {
+    async fn find_all(&self) -> AppResult<Vec<Tag>> {
+        let rows = sqlx::query_as!(
+            TagRow,
+            r#"
+                SELECT tag_id, name
+                FROM tags
+                ORDER BY lower(name)
+            "#
+        )
+        .fetch_all(self.db.inner_ref())
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        Ok(rows.into_iter().map(Tag::from).collect())
+    }
+
+    async fn create(&self, event: CreateTag) -> AppResult<TagId> {
+        let mut tx = self.db.begin().await?;
+
+        let tag_id = TagId::new();
+        sqlx::query!(
+            r#"
+                INSERT INTO tags (tag_id, name)
+                VALUES ($1, $2)
+            "#,
+            tag_id as _,
+            event.name
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(tag_name_error(&event.name))?;
+
+        let after = tag_snapshot(&mut tx, tag_id).await?;
+        record_audit_event(
+            &mut tx,
+            AuditRecord::new(
+                Some(event.requested_user),
+                AuditAction::CreateTag,
+                AuditEntityType::Tag,
+                tag_id.to_string(),
+                None,
+                after,
+            ),
+        )
+        .await?;
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(tag_id)
+    }
+
+    async fn update(&self, event: UpdateTag) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        let before = tag_snapshot(&mut tx, event.tag_id).await?;
+        let result = sqlx::query!(
+            r#"
+                UPDATE tags
+                SET name = $2
+                WHERE tag_id = $1
+            "#,
+            event.tag_id as _,
+            event.name
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(tag_name_error(&event.name))?;
+
+        if result.rows_affected() < 1 {
+            return Err(AppError::EntityNotFound("specified tag not found".into()));
+        }
+
+        let after = tag_snapshot(&mut tx, event.tag_id).await?;
+        record_audit_event(
+            &mut tx,
+            AuditRecord::new(
+                Some(event.requested_user),
+                AuditAction::UpdateTag,
+                AuditEntityType::Tag,
+                event.tag_id.to_string(),
+                before,
+                after,
+            ),
+        )
+        .await?;
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(())
+    }
+
+    async fn delete(&self, event: DeleteTag) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        let before = tag_snapshot(&mut tx, event.tag_id).await?;
+        let result = sqlx::query!(
+            r#"
+                DELETE FROM tags
+                WHERE tag_id = $1
+            "#,
+            event.tag_id as _
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        if result.rows_affected() < 1 {
+            return Err(AppError::EntityNotFound("specified tag not found".into()));
+        }
+
+        record_audit_event(
+            &mut tx,
+            AuditRecord::new(
+                Some(event.requested_user),
+                AuditAction::DeleteTag,
+                AuditEntityType::Tag,
+                event.tag_id.to_string(),
+                before,
+                None,
+            ),
+        )
+        .await?;
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(())
+    }
+
+    async fn attach(&self, event: AttachTag) -> AppResult<()> {
+        self.ensure_taggable(
+            event.book_id,
+            event.tag_id,
+            event.requested_user,
+            event.requested_role,
+        )
+        .await?;
+
+        let mut tx = self.db.begin().await?;
+
+        let result = sqlx::query!(
+            r#"
+                INSERT INTO book_tags (book_id, tag_id)
+                VALUES ($1, $2)
+                ON CONFLICT DO NOTHING
+            "#,
+            event.book_id as _,
+            event.tag_id as _
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        // すでにタグが付いていた場合は、何も変更していないため記録しない。
+        if result.rows_affected() > 0 {
+            let after = tag_snapshot(&mut tx, event.tag_id).await?;
+            record_audit_event(
+                &mut tx,
+                AuditRecord::new(
+                    Some(event.requested_user),
+                    AuditAction::AttachTag,
+                    AuditEntityType::Book,
+                    event.book_id.to_string(),
+                    None,
+                    after,
+                ),
+            )
+            .await?;
+        }
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(())
+    }
+
+    async fn detach(&self, event: DetachTag) -> AppResult<()> {
+        self.ensure_taggable(
+            event.book_id,
+            event.tag_id,
+            event.requested_user,
+            event.requested_role,
+        )
+        .await?;
+
+        let mut tx = self.db.begin().await?;
+
+        let result = sqlx::query!(
+            r#"
+                DELETE FROM book_tags
+                WHERE book_id = $1 AND tag_id = $2
+            "#,
+            event.book_id as _,
+            event.tag_id as _
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        if result.rows_affected() < 1 {
+            return Err(AppError::EntityNotFound(format!(
+                "the tag ({}) is not attached to the book ({})",
+                event.tag_id, event.book_id
+            )));
+        }
+
+        let before = tag_snapshot(&mut tx, event.tag_id).await?;
+        record_audit_event(
+            &mut tx,
+            AuditRecord::new(
+                Some(event.requested_user),
+                AuditAction::DetachTag,
+                AuditEntityType::Book,
+                event.book_id.to_string(),
+                before,
+                None,
+            ),
+        )
+        .await?;
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(())
+    }
+}
+
+/// タグ名の一意制約に違反した場合は、重複として扱う。
+fn tag_name_error(name: &str) -> impl FnOnce(sqlx::Error) -> AppError + '_ {
+    move |e| match e {
+        sqlx::Error::Database(ref db) if db.is_unique_violation() => {
+            AppError::Conflict(format!("the tag ({name}) already exists"))
+        }
+        e => AppError::SpecificOperationError(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use sqlx::PgPool;
+
+    use kernel::repository::book::BookRepository;
+
+    use super::*;
+    use crate::repository::book::BookRepositoryImpl;
+
+    const ADMIN: &str = "5b4c96ac-316a-4bee-8e69-cac5eb84ff4c";
+    const USER: &str = "9582f9de-0fd1-4892-b20c-70139a7eb95b";
+    const ADMIN_BOOK: &str = "9890736e-a4e4-461a-a77d-eac3517ef11b";
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_manage_tags(pool: PgPool) -> anyhow::Result<()> {
+        let repo = TagRepositoryImpl::new(ConnectionPool::new(pool));
+        let admin = UserId::from_str(ADMIN)?;
+
+        let rust = repo.create(CreateTag::new("Rust".into(), admin)).await?;
+        let web = repo.create(CreateTag::new("web".into(), admin)).await?;
+
+        // タグ名は大文字と小文字を区別せずに重複を検出する
+        let res = repo.create(CreateTag::new("RUST".into(), admin)).await;
+        assert!(matches!(res, Err(AppError::Conflict(_))));
+        let res = repo.update(UpdateTag::new(web, "rust".into(), admin)).await;
+        assert!(matches!(res, Err(AppError::Conflict(_))));
+
+        repo.update(UpdateTag::new(web, "Web".into(), admin))
+            .await?;
+        let tags = repo.find_all().await?;
+        let names: Vec<_> = tags.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["Rust", "Web"]);
+
+        repo.delete(DeleteTag::new(rust, admin)).await?;
+        let res = repo.delete(DeleteTag::new(rust, admin)).await;
+        assert!(matches!(res, Err(AppError::EntityNotFound(_))));
+        let res = repo
+            .update(UpdateTag::new(rust, "Rust".into(), admin))
+            .await;
+        assert!(matches!(res, Err(AppError::EntityNotFound(_))));
+        assert_eq!(repo.find_all().await?.len(), 1);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_attach_and_detach_tag(pool: PgPool) -> anyhow::Result<()> {
+        let repo = TagRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+        let book_repo = BookRepositoryImpl::new(ConnectionPool::new(pool));
+        let admin = UserId::from_str(ADMIN)?;
+        let user = UserId::from_str(USER)?;
+        let book_id = BookId::from_str(ADMIN_BOOK)?;
+
+        let tag_id = repo.create(CreateTag::new("Rust".into(), admin)).await?;
+
+        // 所有者以外の一般ユーザーはタグを付けられない
+        let res = repo
+            .attach(AttachTag::new(book_id, tag_id, user, Role::User))
+            .await;
+        assert!(matches!(res, Err(AppError::ForbiddenOperation)));
+
+        // 存在しないタグは付けられない
+        let res = repo
+            .attach(AttachTag::new(book_id, TagId::new(), admin, Role::Admin))
+            .await;
+        assert!(matches!(res, Err(AppError::EntityNotFound(_))));
+
+        // 同じタグを2回付けても1つだけ付く
+        for _ in 0..2 {
+            repo.attach(AttachTag::new(book_id, tag_id, admin, Role::Admin))
+                .await?;
+        }
+        let book = book_repo.find_by_id(book_id).await?.unwrap();
+        let names: Vec<_> = book.tags.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["Rust"]);
+
+        // 管理者は他のユーザーが所有する蔵書のタグも外せる
+        let other_book = BookId::from_str("b8d8a5c3-0f7b-4a2e-9d6c-2e1f3a4b5c6d")?;
+        repo.attach(AttachTag::new(other_book, tag_id, admin, Role::Admin))
+            .await?;
+        repo.detach(DetachTag::new(other_book, tag_id, admin, Role::Admin))
+            .await?;
+
+        repo.detach(DetachTag::new(book_id, tag_id, admin, Role::User))
+            .await?;
+        let res = repo
+            .detach(DetachTag::new(book_id, tag_id, admin, Role::User))
+            .await;
+        assert!(matches!(res, Err(AppError::EntityNotFound(_))));
+        let book = book_repo.find_by_id(book_id).await?.unwrap();
+        assert!(book.tags.is_empty());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,423 @@
+use async_trait::async_trait;
+use derive_new::new;
+use sqlx::Postgres;
+
+use kernel::model::audit::{AuditAction, AuditEntityType};
+use kernel::model::id::{BookId, BookTransferId, UserId};
+use kernel::model::role::Role;
+use kernel::model::transfer::event::{
+    AcceptBookTransfer, CreateBookTransfer, DeleteBookTransfer, ForceBookTransfer,
+};
+use kernel::model::transfer::BookTransfer;
+use kernel::repository::transfer::BookTransferRepository;
+use shared::error::{AppError, AppResult};
+
+use crate::database::model::transfer::BookTransferRow;
+use crate::database::ConnectionPool;
+use crate::repository::audit::{
+    book_snapshot, book_transfer_snapshot, record_audit_event, AuditRecord,
+};
+
+#[derive(new)]
+pub struct BookTransferRepositoryImpl {
+    db: ConnectionPool,
+}
+
+#[async_trait]
+impl BookTransferRepository for BookTransferRepositoryImpl {
+    async fn find_by_user_id(&self, user_id: UserId) -> AppResult<Vec<BookTransfer>> {
+        let rows: Vec<BookTransferRow> = sqlx::query_as!(
+            BookTransferRow,
+            r#"
+                SELECT
+                    bt.book_transfer_id, bt.book_id, b.title,
+                    bt.from_user_id, fu.name from_user_name,
+                    bt.to_user_id, tu.name to_user_name,
+                    bt.created_at
+                FROM book_transfers bt
+                INNER JOIN books b ON bt.book_id = b.book_id
+                INNER JOIN users fu ON bt.from_user_id = fu.user_id
+                INNER JOIN users tu ON bt.to_user_id = tu.user_id
+                WHERE (bt.from_user_id = $1 OR bt.to_user_id = $1) AND b.deleted_at IS NULL
+                ORDER BY bt.created_at, bt.book_transfer_id
+            "#,
+            user_id as _
+        )
+        .fetch_all(self.db.inner_ref())
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        Ok(rows.into_iter().map(BookTransfer::from).collect())
+    }
+
+    async fn create(&self, event: CreateBookTransfer) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        // 譲渡を申し込めるのは蔵書の所有者のみである。
+        let owned_by = lock_book_owner(&mut tx, event.book_id).await?;
+        if owned_by != event.requested_user {
+            return Err(AppError::ForbiddenOperation);
+        }
+        ensure_transfer_recipient(&mut tx, owned_by, event.to_user).await?;
+
+        let transfer_id = BookTransferId::new();
+        sqlx::query!(
+            r#"
+                INSERT INTO book_transfers (book_transfer_id, book_id, from_user_id, to_user_id)
+                VALUES ($1, $2, $3, $4)
+            "#,
+            transfer_id as _,
+            event.book_id as _,
+            owned_by as _,
+            event.to_user as _,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db) if db.is_unique_violation() => {
+                AppError::Conflict(format!(
+                    "the book ({}) already has a pending transfer",
+                    event.book_id
+                ))
+            }
+            e => AppError::SpecificOperationError(e),
+        })?;
+
+        let after = book_transfer_snapshot(&mut tx, transfer_id).await?;
+        record_audit_event(
+            &mut tx,
+            AuditRecord::new(
+                Some(event.requested_user),
+                AuditAction::CreateBookTransfer,
+                AuditEntityType::BookTransfer,
+                transfer_id.to_string(),
+                None,
+                after,
+            ),
+        )
+        .await?;
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(())
+    }
+
+    async fn force(&self, event: ForceBookTransfer) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        let owned_by = lock_book_owner(&mut tx, event.book_id).await?;
+        ensure_transfer_recipient(&mut tx, owned_by, event.to_user).await?;
+        change_book_owner(&mut tx, event.book_id, event.to_user, event.requested_user).await?;
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(())
+    }
+
+    async fn accept(&self, event: AcceptBookTransfer) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        // 所有者を強制的に変更する処理とデッドロックしないように、譲渡の申し込みより先に
+        // 蔵書の行をロックする。そのため、ここでは譲渡の申し込みをロックせずに取得する。
+        let transfer = sqlx::query!(
+            r#"
+                SELECT book_id "book_id: BookId", to_user_id "to_user_id: UserId"
+                FROM book_transfers
+                WHERE book_transfer_id = $1
+            "#,
+            event.transfer_id as _
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?
+        .ok_or_else(|| transfer_not_found(&event))?;
+
+        // 譲渡を承諾できるのは譲渡先のユーザーのみである。
+        if transfer.to_user_id != event.requested_user {
+            return Err(AppError::ForbiddenOperation);
+        }
+        lock_book_owner(&mut tx, transfer.book_id).await?;
+
+        // 蔵書の行をロックするまでに、譲渡の申し込みが取り消されたり、所有者の変更によって
+        // 削除されたりしていないか確認する。
+        sqlx::query_scalar!(
+            r#"
+                SELECT book_transfer_id
+                FROM book_transfers
+                WHERE book_transfer_id = $1
+                FOR UPDATE
+            "#,
+            event.transfer_id as _
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?
+        .ok_or_else(|| transfer_not_found(&event))?;
+        change_book_owner(
+            &mut tx,
+            transfer.book_id,
+            transfer.to_user_id,
+            event.requested_user,
+        )
+        .await?;
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(())
+    }
+
+    async fn delete(&self, event: DeleteBookTransfer) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        let transfer = sqlx::query!(
+            r#"
+                SELECT from_user_id "from_user_id: UserId", to_user_id "to_user_id: UserId"
+                FROM book_transfers
+                WHERE book_transfer_id = $1
+                FOR UPDATE
+            "#,
+            event.transfer_id as _
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?
+        .ok_or_else(|| {
+            AppError::EntityNotFound(format!(
+                "the book transfer ({}) doesn't exist",
+                event.transfer_id
+            ))
+        })?;
+
+        // 譲渡先のユーザーは辞退でき、申し込んだユーザーと管理者は取り消せる。
+        if event.requested_user != transfer.from_user_id
+            && event.requested_user != transfer.to_user_id
+            && event.requested_role != Role::Admin
+        {
+            return Err(AppError::ForbiddenOperation);
+        }
+
+        let before = book_transfer_snapshot(&mut tx, event.transfer_id).await?;
+        sqlx::query!(
+            r#"
+                DELETE FROM book_transfers WHERE book_transfer_id = $1
+            "#,
+            event.transfer_id as _
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        record_audit_event(
+            &mut tx,
+            AuditRecord::new(
+                Some(event.requested_user),
+                AuditAction::DeleteBookTransfer,
+                AuditEntityType::BookTransfer,
+                event.transfer_id.to_string(),
+                before,
+                None,
+            ),
+        )
+        .await?;
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(())
+    }
+}
+
+fn transfer_not_found(event: &AcceptBookTransfer) -> AppError {
+    AppError::EntityNotFound(format!(
+        "the book transfer ({}) doesn't exist",
+        event.transfer_id
+    ))
+}
+
+/// 蔵書をロックして、蔵書の所有者を返す。
+///
+/// 蔵書が存在しないか、ゴミ箱にある場合は`AppError::EntityNotFound`を返す。
+async fn lock_book_owner(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    book_id: BookId,
+) -> AppResult<UserId> {
+    sqlx::query_scalar!(
+        r#"
+            SELECT user_id "user_id: UserId"
+            FROM books
+            WHERE book_id = $1 AND deleted_at IS NULL
+            FOR UPDATE
+        "#,
+        book_id as _
+    )
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(AppError::SpecificOperationError)?
+    .ok_or_else(|| AppError::EntityNotFound(format!("the book ({book_id}) doesn't exist")))
+}
+
+/// 譲渡先のユーザーが存在し、蔵書の所有者と異なるか確認する。
+pub(crate) async fn ensure_transfer_recipient(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    owned_by: UserId,
+    to_user: UserId,
+) -> AppResult<()> {
+    if owned_by == to_user {
+        return Err(AppError::UnprocessableEntity(format!(
+            "the user ({to_user}) already owns the book"
+        )));
+    }
+
+    let exists = sqlx::query_scalar!(
+        r#"
+            SELECT EXISTS (SELECT 1 FROM users WHERE user_id = $1) "exists!"
+        "#,
+        to_user as _
+    )
+    .fetch_one(&mut **tx)
+    .await
+    .map_err(AppError::SpecificOperationError)?;
+    if !exists {
+        return Err(AppError::EntityNotFound(format!(
+            "the user ({to_user}) doesn't exist"
+        )));
+    }
+
+    Ok(())
+}
+
+/// 蔵書の所有者を変更し、監査ログに記録する。
+///
+/// 以前の所有者が申し込んだ譲渡は無効になるため、蔵書への譲渡の申し込みを削除する。
+pub(crate) async fn change_book_owner(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    book_id: BookId,
+    to_user: UserId,
+    requested_user: UserId,
+) -> AppResult<()> {
+    let before = book_snapshot(tx, book_id).await?;
+    sqlx::query!(
+        r#"
+            UPDATE books SET user_id = $2 WHERE book_id = $1
+        "#,
+        book_id as _,
+        to_user as _
+    )
+    .execute(&mut **tx)
+    .await
+    .map_err(AppError::SpecificOperationError)?;
+    sqlx::query!(
+        r#"
+            DELETE FROM book_transfers WHERE book_id = $1
+        "#,
+        book_id as _
+    )
+    .execute(&mut **tx)
+    .await
+    .map_err(AppError::SpecificOperationError)?;
+
+    let after = book_snapshot(tx, book_id).await?;
+    record_audit_event(
+        tx,
+        AuditRecord::new(
+            Some(requested_user),
+            AuditAction::TransferBook,
+            AuditEntityType::Book,
+            book_id.to_string(),
+            before,
+            after,
+        ),
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use kernel::repository::book::BookRepository;
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::repository::book::BookRepositoryImpl;
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_book_transfer_lifecycle(pool: PgPool) -> anyhow::Result<()> {
+        let db = ConnectionPool::new(pool);
+        let repo = BookTransferRepositoryImpl::new(db.clone());
+        let book_repo = BookRepositoryImpl::new(db);
+        // ユーザーが所有する蔵書
+        let book_id = BookId::from_str("b8d8a5c3-0f7b-4a2e-9d6c-2e1f3a4b5c6d")?;
+        let admin_id = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+        let user_id = UserId::from_str("9582f9de-0fd1-4892-b20c-70139a7eb95b")?;
+        let create = |requested_user, to_user| CreateBookTransfer {
+            book_id,
+            to_user,
+            requested_user,
+        };
+
+        // 所有者以外は申し込めず、所有者自身や存在しないユーザーには譲渡できない
+        let result = repo.create(create(admin_id, admin_id)).await;
+        assert!(matches!(result, Err(AppError::ForbiddenOperation)));
+        let result = repo.create(create(user_id, user_id)).await;
+        assert!(matches!(result, Err(AppError::UnprocessableEntity(_))));
+        let result = repo.create(create(user_id, UserId::new())).await;
+        assert!(matches!(result, Err(AppError::EntityNotFound(_))));
+
+        // 申し込んだ譲渡は、申し込んだユーザーと譲渡先のユーザーの両方に表示される
+        repo.create(create(user_id, admin_id)).await?;
+        let result = repo.create(create(user_id, admin_id)).await;
+        assert!(matches!(result, Err(AppError::Conflict(_))));
+        let transfers = repo.find_by_user_id(admin_id).await?;
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(transfers[0].from.id, user_id);
+        assert_eq!(transfers[0].book_title, "The Rust Programming Language");
+        assert_eq!(repo.find_by_user_id(user_id).await?.len(), 1);
+        let transfer_id = transfers[0].id;
+
+        // 譲渡先のユーザー以外は承諾できない
+        let result = repo
+            .accept(AcceptBookTransfer {
+                transfer_id,
+                requested_user: user_id,
+            })
+            .await;
+        assert!(matches!(result, Err(AppError::ForbiddenOperation)));
+
+        // 承諾すると所有者が変わり、譲渡の申し込みはなくなる
+        repo.accept(AcceptBookTransfer {
+            transfer_id,
+            requested_user: admin_id,
+        })
+        .await?;
+        let book = book_repo.find_by_id(book_id).await?.unwrap();
+        assert_eq!(book.owner.id, admin_id);
+        assert!(repo.find_by_user_id(admin_id).await?.is_empty());
+
+        // 辞退された譲渡は、所有者を変えずになくなる
+        repo.create(create(admin_id, user_id)).await?;
+        let transfer_id = repo.find_by_user_id(user_id).await?[0].id;
+        repo.delete(DeleteBookTransfer {
+            transfer_id,
+            requested_user: user_id,
+            requested_role: Role::User,
+        })
+        .await?;
+        assert!(repo.find_by_user_id(user_id).await?.is_empty());
+        let book = book_repo.find_by_id(book_id).await?.unwrap();
+        assert_eq!(book.owner.id, admin_id);
+
+        // 管理者は承諾なしに所有者を変更でき、申し込まれていた譲渡はなくなる
+        repo.create(create(admin_id, user_id)).await?;
+        repo.force(ForceBookTransfer {
+            book_id,
+            to_user: user_id,
+            requested_user: admin_id,
+        })
+        .await?;
+        let book = book_repo.find_by_id(book_id).await?.unwrap();
+        assert_eq!(book.owner.id, user_id);
+        assert!(repo.find_by_user_id(user_id).await?.is_empty());
+
+        Ok(())
+    }
+}
@@ -1,15 +1,21 @@
 use async_trait::async_trait;
 use derive_new::new;
+use sqlx::Postgres;
 
-use kernel::model::id::UserId;
+use kernel::model::audit::{AuditAction, AuditEntityType};
+use kernel::model::id::{BookId, UserId};
 use kernel::model::role::Role;
-use kernel::model::user::event::{CreateUser, DeleteUser, UpdateUserPassword, UpdateUserRole};
+use kernel::model::user::event::{
+    CreateUser, DeleteUser, UpdateUserPassword, UpdateUserPrivacy, UpdateUserRole,
+};
 use kernel::model::user::User;
 use kernel::repository::user::UserRepository;
 use shared::error::{AppError, AppResult};
 
 use crate::database::model::user::UserRow;
 use crate::database::ConnectionPool;
+use crate::repository::audit::{record_audit_event, user_snapshot, AuditRecord};
+use crate::repository::transfer::{change_book_owner, ensure_transfer_recipient};
 
 #[derive(new)]
 pub struct UserRepositoryImpl {
@@ -28,7 +34,9 @@ impl UserRepository for UserRepositoryImpl {
                     u.email,
                     r.name as role_name,
                     u.created_at,
-                    u.updated_at
+                    u.updated_at,
+                    u.retain_checkout_history,
+                    u.version
                 FROM
                     users u
                 INNER JOIN roles r ON u.role_id = r.role_id
@@ -56,7 +64,9 @@ impl UserRepository for UserRepositoryImpl {
                     u.email,
                     r.name as role_name,
                     u.created_at,
-                    u.updated_at
+                    u.updated_at,
+                    u.retain_checkout_history,
+                    u.version
                 FROM
                     users u
                 INNER JOIN roles r on u.role_id = r.role_id
@@ -78,6 +88,8 @@ impl UserRepository for UserRepositoryImpl {
         let hashed_password = hash_password(&event.password)?;
         let role = Role::User;
 
+        let mut tx = self.db.begin().await?;
+
         let result = sqlx::query!(
             r#"
                 INSERT INTO users (user_id, name, email, password_hash, role_id)
@@ -89,7 +101,7 @@ impl UserRepository for UserRepositoryImpl {
             hashed_password,
             role.as_ref()
         )
-        .execute(self.db.inner_ref())
+        .execute(&mut *tx)
         .await
         .map_err(AppError::SpecificOperationError)?;
 
@@ -99,11 +111,29 @@ impl UserRepository for UserRepositoryImpl {
             ));
         }
 
+        let after = user_snapshot(&mut tx, user_id).await?;
+        record_audit_event(
+            &mut tx,
+            AuditRecord::new(
+                Some(event.requested_user),
+                AuditAction::CreateUser,
+                AuditEntityType::User,
+                user_id.to_string(),
+                None,
+                after,
+            ),
+        )
+        .await?;
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
         Ok(User {
             id: user_id,
             name: event.name,
             email: event.email,
             role,
+            retain_checkout_history: true,
+            version: 1,
         })
     }
 
@@ -119,6 +149,7 @@ impl UserRepository for UserRepositoryImpl {
                     users
                 WHERE
                     user_id = $1
+                FOR UPDATE
             "#,
             event.user_id as _
         )
@@ -129,8 +160,10 @@ impl UserRepository for UserRepositoryImpl {
 
         // 入力されたパスワードが等しいか確認
         verify_password(&event.current_password, &original_password_hash)?;
+        ensure_user_version(&mut tx, event.user_id, event.expected_version).await?;
 
         // 新しいパスワードをハッシュ化して、データベースに保存
+        let before = user_snapshot(&mut tx, event.user_id).await?;
         let new_password_hash = hash_password(&event.new_password)?;
         sqlx::query!(
             r#"
@@ -145,12 +178,32 @@ impl UserRepository for UserRepositoryImpl {
         .await
         .map_err(AppError::SpecificOperationError)?;
 
+        // パスワードのハッシュ値は記録しないため、変更前後で異なるのは更新日時のみである。
+        let after = user_snapshot(&mut tx, event.user_id).await?;
+        record_audit_event(
+            &mut tx,
+            AuditRecord::new(
+                Some(event.user_id),
+                AuditAction::UpdateUserPassword,
+                AuditEntityType::User,
+                event.user_id.to_string(),
+                before,
+                after,
+            ),
+        )
+        .await?;
+
         tx.commit().await.map_err(AppError::TransactionError)?;
 
         Ok(())
     }
 
     async fn update_role(&self, event: UpdateUserRole) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        ensure_user_version(&mut tx, event.user_id, event.expected_version).await?;
+
+        let before = user_snapshot(&mut tx, event.user_id).await?;
         let result = sqlx::query!(
             r#"
                 UPDATE users
@@ -164,34 +217,202 @@ impl UserRepository for UserRepositoryImpl {
             event.user_id as _,
             event.role.as_ref()
         )
-        .execute(self.db.inner_ref())
+        .execute(&mut *tx)
         .await
         .map_err(AppError::SpecificOperationError)?;
         if result.rows_affected() < 1 {
             return Err(AppError::EntityNotFound("specified user not found".into()));
         }
 
+        let after = user_snapshot(&mut tx, event.user_id).await?;
+        record_audit_event(
+            &mut tx,
+            AuditRecord::new(
+                Some(event.requested_user),
+                AuditAction::UpdateUserRole,
+                AuditEntityType::User,
+                event.user_id.to_string(),
+                before,
+                after,
+            ),
+        )
+        .await?;
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(())
+    }
+
+    async fn update_privacy(&self, event: UpdateUserPrivacy) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        ensure_user_version(&mut tx, event.user_id, event.expected_version).await?;
+
+        let before = user_snapshot(&mut tx, event.user_id).await?;
+        sqlx::query!(
+            r#"
+                UPDATE users SET retain_checkout_history = $2 WHERE user_id = $1
+            "#,
+            event.user_id as _,
+            event.retain_checkout_history
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        // 履歴を残さない場合は、それまでに返却した貸出の履歴と、返却した蔵書のレビューを
+        // 書けることの記録も削除する。
+        // 監査ログは追記のみのため、それまでに記録した貸出の監査ログは削除せずに残す。
+        // 以降の貸出の監査ログには、借りたユーザーを記録しない (`checkout_snapshot`を参照)。
+        if !event.retain_checkout_history {
+            sqlx::query!(
+                r#"
+                    DELETE FROM returned_checkouts WHERE user_id = $1
+                "#,
+                event.user_id as _
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::SpecificOperationError)?;
+            sqlx::query!(
+                r#"
+                    DELETE FROM reviewable_books WHERE user_id = $1
+                "#,
+                event.user_id as _
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::SpecificOperationError)?;
+        }
+
+        let after = user_snapshot(&mut tx, event.user_id).await?;
+        record_audit_event(
+            &mut tx,
+            AuditRecord::new(
+                Some(event.user_id),
+                AuditAction::UpdateUserPrivacy,
+                AuditEntityType::User,
+                event.user_id.to_string(),
+                before,
+                after,
+            ),
+        )
+        .await?;
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
         Ok(())
     }
 
     async fn delete_user(&self, event: DeleteUser) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        ensure_user_version(&mut tx, event.user_id, event.expected_version).await?;
+        reassign_owned_books(&mut tx, &event).await?;
+
+        let before = user_snapshot(&mut tx, event.user_id).await?;
         let result = sqlx::query!(
             r#"
                 DELETE FROM users WHERE user_id = $1
             "#,
             event.user_id as _
         )
-        .execute(self.db.inner_ref())
+        .execute(&mut *tx)
         .await
         .map_err(AppError::SpecificOperationError)?;
         if result.rows_affected() < 1 {
             return Err(AppError::EntityNotFound("specified user not found".into()));
         }
 
+        record_audit_event(
+            &mut tx,
+            AuditRecord::new(
+                Some(event.requested_user),
+                AuditAction::DeleteUser,
+                AuditEntityType::User,
+                event.user_id.to_string(),
+                before,
+                None,
+            ),
+        )
+        .await?;
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
         Ok(())
     }
 }
 
+/// 削除するユーザーが所有する蔵書を、ゴミ箱にある蔵書も含めて`reassign_to`のユーザーに譲渡する。
+///
+/// 蔵書を所有しているユーザーを削除する場合は、譲渡先のユーザーの指定が必要である。
+async fn reassign_owned_books(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    event: &DeleteUser,
+) -> AppResult<()> {
+    let book_ids = sqlx::query_scalar!(
+        r#"
+            SELECT book_id "book_id: BookId" FROM books WHERE user_id = $1 FOR UPDATE
+        "#,
+        event.user_id as _
+    )
+    .fetch_all(&mut **tx)
+    .await
+    .map_err(AppError::SpecificOperationError)?;
+    if book_ids.is_empty() {
+        return Ok(());
+    }
+
+    let Some(reassign_to) = event.reassign_to else {
+        return Err(AppError::UnprocessableEntity(format!(
+            "the user ({}) owns {} book(s); specify a user to reassign them to",
+            event.user_id,
+            book_ids.len()
+        )));
+    };
+    ensure_transfer_recipient(tx, event.user_id, reassign_to)
+        .await
+        .map_err(|e| match e {
+            // 譲渡先のユーザーはリクエストの内容であるため、存在しない場合は422を返す。
+            AppError::EntityNotFound(msg) => AppError::UnprocessableEntity(msg),
+            e => e,
+        })?;
+    for book_id in book_ids {
+        change_book_owner(tx, book_id, reassign_to, event.requested_user).await?;
+    }
+
+    Ok(())
+}
+
+/// `expected_version`を指定した場合、ユーザーのバージョンが異なれば他のリクエストによって
+/// 更新されているため、`AppError::PreconditionFailed`を返す。
+///
+/// ユーザーが存在しない場合は`AppError::EntityNotFound`を返す。確認してから変更するまでの間に
+/// 更新されないように、トランザクションが終わるまでユーザーをロックする。
+async fn ensure_user_version(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    user_id: UserId,
+    expected_version: Option<i64>,
+) -> AppResult<()> {
+    let version = sqlx::query_scalar!(
+        r#"
+            SELECT version FROM users WHERE user_id = $1 FOR UPDATE
+        "#,
+        user_id as _
+    )
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(AppError::SpecificOperationError)?
+    .ok_or_else(|| AppError::EntityNotFound("specified user not found".into()))?;
+
+    match expected_version {
+        Some(expected) if expected != version => Err(AppError::PreconditionFailed(format!(
+            "the user ({user_id}) has been modified by another request"
+        ))),
+        _ => Ok(()),
+    }
+}
+
 fn hash_password(password: &str) -> AppResult<String> {
     bcrypt::hash(password, bcrypt::DEFAULT_COST).map_err(AppError::from)
 }
@@ -203,3 +424,101 @@ fn verify_password(password: &str, hash: &str) -> AppResult<()> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use sqlx::PgPool;
+
+    use super::*;
+
+    #[sqlx::test(fixtures("common"))]
+    async fn test_reject_stale_user_version(pool: PgPool) -> anyhow::Result<()> {
+        let repo = UserRepositoryImpl::new(ConnectionPool::new(pool));
+        let admin = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+        let user_id = UserId::from_str("9582f9de-0fd1-4892-b20c-70139a7eb95b")?;
+        let update_role = |role, expected_version| UpdateUserRole {
+            user_id,
+            role,
+            requested_user: admin,
+            expected_version,
+        };
+
+        // 取得したときのバージョンを指定すれば更新でき、更新するとバージョンが増える
+        let version = repo.find_current_user(user_id).await?.unwrap().version;
+        repo.update_role(update_role(Role::Admin, Some(version)))
+            .await?;
+        let user = repo.find_current_user(user_id).await?.unwrap();
+        assert_eq!(user.role, Role::Admin);
+        assert_eq!(user.version, version + 1);
+
+        // 古いバージョンを指定した更新や削除は、他のリクエストによる変更を上書きしない
+        let result = repo
+            .update_role(update_role(Role::User, Some(version)))
+            .await;
+        assert!(matches!(result, Err(AppError::PreconditionFailed(_))));
+        let result = repo
+            .delete_user(DeleteUser {
+                user_id,
+                requested_user: admin,
+                reassign_to: None,
+                expected_version: Some(version),
+            })
+            .await;
+        assert!(matches!(result, Err(AppError::PreconditionFailed(_))));
+        assert_eq!(
+            repo.find_current_user(user_id).await?.unwrap().role,
+            Role::Admin
+        );
+
+        // 存在しないユーザーは、バージョンにかかわらず404になる
+        let result = repo
+            .delete_user(DeleteUser {
+                user_id: UserId::new(),
+                requested_user: admin,
+                reassign_to: None,
+                expected_version: Some(version),
+            })
+            .await;
+        assert!(matches!(result, Err(AppError::EntityNotFound(_))));
+
+        Ok(())
+    }
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_delete_user_reassigns_owned_books(pool: PgPool) -> anyhow::Result<()> {
+        let repo = UserRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+        let admin_id = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+        let user_id = UserId::from_str("9582f9de-0fd1-4892-b20c-70139a7eb95b")?;
+        let delete_user = |reassign_to| DeleteUser {
+            user_id,
+            requested_user: admin_id,
+            reassign_to,
+            expected_version: None,
+        };
+
+        // 蔵書を所有するユーザーは、譲渡先を指定しなければ削除できない
+        let result = repo.delete_user(delete_user(None)).await;
+        assert!(matches!(result, Err(AppError::UnprocessableEntity(_))));
+        let result = repo.delete_user(delete_user(Some(user_id))).await;
+        assert!(matches!(result, Err(AppError::UnprocessableEntity(_))));
+        let result = repo.delete_user(delete_user(Some(UserId::new()))).await;
+        assert!(matches!(result, Err(AppError::UnprocessableEntity(_))));
+        assert!(repo.find_current_user(user_id).await?.is_some());
+
+        // 譲渡先を指定すると、蔵書の所有者を変えてから削除する
+        repo.delete_user(delete_user(Some(admin_id))).await?;
+        assert!(repo.find_current_user(user_id).await?.is_none());
+        let owned = sqlx::query_scalar!(
+            r#"
+                SELECT COUNT(*) "count!" FROM books WHERE user_id = $1
+            "#,
+            admin_id as _
+        )
+        .fetch_one(&pool)
+        .await?;
+        assert_eq!(owned, 4);
+
+        Ok(())
+    }
+}
@@ -1,8 +1,10 @@
 use axum::extract::FromRequestParts;
+use axum::http::header::IF_MATCH;
 use axum::http::request::Parts;
 use axum::{async_trait, RequestPartsExt};
 use axum_extra::headers::authorization::Bearer;
-use axum_extra::headers::Authorization;
+use axum_extra::headers::{self, Authorization};
+use axum_extra::typed_header::TypedHeaderRejection;
 use axum_extra::TypedHeader;
 
 use kernel::model::auth::AccessToken;
@@ -12,6 +14,8 @@ use kernel::model::user::User;
 use registry::AppRegistry;
 use shared::error::AppError;
 
+use crate::handler::precondition::IfMatch;
+
 pub struct AuthorizedUser {
     pub access_token: AccessToken,
     pub user: User,
@@ -60,3 +64,25 @@ impl FromRequestParts<AppRegistry> for AuthorizedUser {
         Ok(AuthorizedUser { access_token, user })
     }
 }
+
+/// `If-Match`ヘッダー (ヘッダーが指定されていない場合は`None`)
+///
+/// `headers::IfMatch`は空の値を受け付けるため、`Option<TypedHeader<headers::IfMatch>>`ではヘッダーを指定しなくても
+/// `Some`になり、どのエンティティタグとも一致しない条件として扱われてしまう。
+pub struct IfMatchHeader(pub Option<IfMatch>);
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for IfMatchHeader {
+    type Rejection = TypedHeaderRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        if !parts.headers.contains_key(IF_MATCH) {
+            return Ok(Self(None));
+        }
+        // ヘッダーの形式を検証してから、エンティティタグのバージョンを取り出す。
+        let TypedHeader(_) = parts.extract::<TypedHeader<headers::IfMatch>>().await?;
+        Ok(Self(Some(IfMatch::from_header_values(
+            parts.headers.get_all(IF_MATCH),
+        ))))
+    }
+}
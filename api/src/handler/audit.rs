@@ -0,0 +1,59 @@
+use axum::extract::{Query, State};
+use axum::Json;
+use garde::Validate;
+
+use kernel::model::audit::AuditEventListOptions;
+use registry::AppRegistry;
+use shared::error::{AppError, AppResult};
+
+use crate::extractor::AuthorizedUser;
+use crate::model::audit::{AuditEventListQuery, PaginatedAuditEventResponse};
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/audit-events",
+        params(
+            ("limit" = i64, Query, description = "一度に取得する監査ログの数の上限値の指定"),
+            ("offset" = i64, Query, description = "取得対象とする監査ログの開始位置"),
+            ("actorId" = Option<Uuid>, Query, description = "操作したユーザーのユーザーID"),
+            ("entityType" = Option<AuditEntityTypeName>, Query, description = "変更した対象の種類"),
+            ("entityId" = Option<String>, Query, description = "変更した対象のID (貸出ポリシーの場合はロール名)"),
+            ("since" = Option<DateTime<Utc>>, Query, description = "この日時以降に記録した監査ログ"),
+            ("until" = Option<DateTime<Utc>>, Query, description = "この日時より前に記録した監査ログ"),
+        ),
+        responses(
+            (status = 200, description = "監査ログの取得に成功した場合。監査ログは記録した日時の新しい順に並ぶ。", body = PaginatedAuditEventResponse),
+            (status = 400, description = "クエリに指定された上限値、開始位置または検索条件に不備があった場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 403, description = "管理者以外がアクセスした場合。"),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "show audit event list",
+    skip(user, registry),
+    fields(
+        user_id = %user.user.id.to_string(),
+    )
+)]
+pub async fn show_audit_event_list(
+    user: AuthorizedUser,
+    Query(query): Query<AuditEventListQuery>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Json<PaginatedAuditEventResponse>> {
+    // ユーザーが管理者の場合のみ許可
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenOperation);
+    }
+
+    query.validate(&())?;
+
+    registry
+        .audit_event_repository()
+        .find_all(AuditEventListOptions::from(query))
+        .await
+        .map(PaginatedAuditEventResponse::from)
+        .map(Json)
+}
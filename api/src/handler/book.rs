@@ -1,19 +1,36 @@
+use axum::body::{Body, Bytes};
 use axum::extract::{Path, Query, State};
+use axum::http::header::{CACHE_CONTROL, CONTENT_DISPOSITION, CONTENT_TYPE, ETAG, LAST_MODIFIED};
 use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
 use axum::Json;
+use axum_extra::headers::{ContentType, ETag, IfNoneMatch};
+use axum_extra::TypedHeader;
+use futures::future::ready;
+use futures::stream::{once, StreamExt, TryStreamExt};
 use garde::Validate;
 
-use kernel::model::book::event::{DeleteBook, UpdateBook};
-use kernel::model::book::BookListOptions;
-use kernel::model::id::BookId;
+use kernel::model::book::event::{
+    CreateBookCopy, DeleteBook, DeleteBookCopy, PatchBook, PurgeBook, RestoreBook, RevertBook,
+    UpdateBook, UpdateBookCopy, UpdateBookCover,
+};
+use kernel::model::book::{
+    BookImportMode, BookImportOutcome, BookListOptions, CoverSize, DeletedBookListOptions,
+};
+use kernel::model::id::{BookCopyId, BookId};
 use registry::AppRegistry;
 use shared::error::{AppError, AppResult};
 
-use crate::extractor::AuthorizedUser;
+use crate::extractor::{AuthorizedUser, IfMatchHeader};
+use crate::handler::precondition::{json_with_etag, verify_if_match, IfMatch};
 use crate::model::book::{
-    BookListQuery, BookResponse, CreateBookRequest, PaginatedBookResponse, UpdateBookRequest,
-    UpdateBookRequestWithIds,
+    BookCopiesResponse, BookExportQuery, BookImportFormat, BookImportQuery, BookImportResponse,
+    BookListQuery, BookResponse, BookRevisionsResponse, CreateBookCopyRequest,
+    CreateBookCopyRequestWithIds, CreateBookRequest, DeletedBookListQuery, PaginatedBookResponse,
+    PaginatedDeletedBookResponse, PatchBookRequest, PatchBookRequestWithIds, UpdateBookCopyRequest,
+    UpdateBookCopyRequestWithIds, UpdateBookRequest, UpdateBookRequestWithIds,
 };
+use crate::model::cover::{BookCoverQuery, CoverUpload};
 
 #[cfg_attr(
     debug_assertions,
@@ -23,10 +40,18 @@ use crate::model::book::{
         params(
             ("limit" = i64, Query, description = "一度に取得する蔵書数の上限値の指定"),
             ("offset" = i64, Query, description = "取得対象とする蔵書一覧の開始位置"),
+            ("q" = Option<String>, Query, description = "タイトル、著者、ISBN及び説明を対象とした検索語。指定した場合は関連度が高い順に並ぶ"),
+            ("ownerId" = Option<Uuid>, Query, description = "蔵書の所有者のユーザーID"),
+            ("checkoutState" = Option<BookCheckoutStateName>, Query, description = "蔵書の貸出状態"),
+            ("sort" = Option<BookSortKeyName>, Query, description = "並べ替えキー。省略した場合は検索語が指定されていれば関連度順、指定されていなければ登録日時順"),
+            ("order" = Option<SortDirectionName>, Query, description = "並び順。省略した場合はタイトルと著者は昇順、それ以外は降順"),
+            ("cursor" = Option<String>, Query, description = "前のページのレスポンスに含まれる`nextCursor`の値。指定した場合は開始位置と並べ替えの指定を無視する"),
+            ("tags" = Option<String>, Query, description = "カンマ区切りのタグ名。大文字と小文字を区別しない"),
+            ("tagMatch" = Option<TagMatchName>, Query, description = "複数のタグを指定したときの絞り込み方。`all`はすべてのタグ、`any`はいずれかのタグが付いた蔵書。省略した場合は`all`"),
         ),
         responses(
             (status = 200, description = "蔵書一覧の取得に成功した場合。", body = PaginatedBookResponse),
-            (status = 400, description = "クエリに指定された上限値または開始位置に不備があった場合。"),
+            (status = 400, description = "クエリに指定された上限値、開始位置、検索条件またはカーソルに不備があった場合。"),
             (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
         ),
     )
@@ -60,9 +85,12 @@ pub async fn show_book_list(
         path = "/api/v1/books/{book_id}",
         params(
             ("book_id" = Uuid, Path, description = "蔵書ID"),
+            ("If-None-Match" = Option<String>, Header, description = "以前に取得した蔵書の`ETag`"),
         ),
         responses(
-            (status = 200, description = "指定された蔵書の取得に成功した場合。", body = BookResponse),
+            (status = 200, description = "指定された蔵書の取得に成功した場合。", body = BookResponse,
+                headers(("ETag" = String, description = "蔵書のエンティティタグ"))),
+            (status = 304, description = "`If-None-Match`に指定された`ETag`から蔵書が変わっていない場合。"),
             (status = 400, description = "パスで指定された蔵書IDに不備があった場合。"),
             (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
             (status = 404, description = "パスで指定された蔵書IDを持つ蔵書が存在しない場合。"),
@@ -80,18 +108,90 @@ pub async fn show_book(
     _user: AuthorizedUser,
     Path(book_id): Path<BookId>,
     State(registry): State<AppRegistry>,
-) -> AppResult<Json<BookResponse>> {
+    if_none_match: Option<TypedHeader<IfNoneMatch>>,
+) -> AppResult<Response> {
     tracing::info!("ここにINFOログを追加しました。");
-    registry
+    let book = registry
         .book_repository()
         .find_by_id(book_id)
-        .await
-        .and_then(|b| match b {
-            Some(b) => Ok(Json(b.into())),
-            None => Err(AppError::EntityNotFound(
-                "the specified book was not fount".into(),
-            )),
-        })
+        .await?
+        .ok_or_else(|| AppError::EntityNotFound("the specified book was not fount".into()))?;
+    json_with_etag(book.version, BookResponse::from(book), if_none_match)
+}
+
+/// `If-Match`が指定された場合は、現在の蔵書のバージョンと一致するか確認し、
+/// 確認した蔵書のバージョンを返す。
+async fn expected_book_version(
+    registry: &AppRegistry,
+    book_id: BookId,
+    if_match: Option<IfMatch>,
+) -> AppResult<Option<i64>> {
+    let Some(if_match) = if_match else {
+        return Ok(None);
+    };
+    let book = registry
+        .book_repository()
+        .find_by_id(book_id)
+        .await?
+        .ok_or_else(|| AppError::EntityNotFound("the specified book was not fount".into()))?;
+    verify_if_match(&if_match, book.version).map(Some)
+}
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/books/export",
+        params(
+            ("format" = Option<BookExportFormat>, Query, description = "書き出す形式。省略した場合は`csv`"),
+        ),
+        responses(
+            (status = 200, description = "すべての蔵書を所有者と貸出中の複本を含めて書き出した場合。", body = String),
+            (status = 400, description = "クエリに指定された形式に不備があった場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "export books",
+    skip(_user, registry),
+    fields(
+        user_id = %_user.user.id.to_string()
+    )
+)]
+pub async fn export_books(
+    _user: AuthorizedUser,
+    Query(query): Query<BookExportQuery>,
+    State(registry): State<AppRegistry>,
+) -> Response {
+    let format = query.format;
+    // 蔵書をデータベースから読み出しながら、変換したものから順にレスポンスとして送る。
+    let books = registry
+        .book_repository()
+        .export()
+        .and_then(move |book| ready(format.format(book)))
+        .inspect_err(|e| {
+            tracing::error!(
+                error.cause_chain = ?e,
+                error.message = %e,
+                "Failed to export books"
+            )
+        });
+    let body = once(ready(format.header()))
+        .chain(books)
+        .chain(once(ready(Ok(format.footer()))));
+
+    (
+        [
+            (CONTENT_TYPE, format.content_type().to_string()),
+            (
+                CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", format.file_name()),
+            ),
+        ],
+        Body::from_stream(body),
+    )
+        .into_response()
 }
 
 /// The HyperText Transfer Protocol (HTTP) の 422 Unprocessable Entity 応答状態コードは、
@@ -107,6 +207,7 @@ pub async fn show_book(
             (status = 201, description = "蔵書の登録に成功した場合。"),
             (status = 400, description = "リクエストした蔵書に不備があった場合。"),
             (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 409, description = "同じISBNを持つ蔵書がすでに存在し、重複を許可しなかった場合。"),
             (status = 422, description = "リクエストした蔵書の記録に失敗した場合。"),
         )
     )
@@ -127,11 +228,101 @@ pub async fn register_book(
 
     registry
         .book_repository()
-        .create(body.into(), user.id())
+        .create(body.try_into()?, user.id())
         .await
         .map(|_| StatusCode::CREATED)
 }
 
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        post,
+        path = "/api/v1/books/import",
+        params(
+            ("mode" = Option<BookImportModeName>, Query, description = "登録の方法。`atomic`の場合は1冊でも登録できなければいずれの蔵書も登録しない。省略した場合は`atomic`"),
+            ("allowDuplicate" = Option<bool>, Query, description = "同じISBNを持つ蔵書がすでに存在しても登録するか"),
+        ),
+        request_body(
+            content = String,
+            description = "1行目に`title`、`author`、`isbn`及び`description`の列名を持つCSV (`text/csv`)、または1行に1冊の蔵書を記述したJSON Lines (`application/x-ndjson`)",
+        ),
+        responses(
+            (status = 200, description = "蔵書を一括で登録した場合。行ごとの結果を返す。", body = BookImportResponse),
+            (status = 400, description = "リクエストに不備があった場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 422, description = "リクエストの形式に対応していない場合、CSVの構文が誤っている場合、または`atomic`で登録できない行があった場合。`atomic`で登録できない行があった場合は行ごとの結果を返す。", body = BookImportResponse),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "import books",
+    skip(user, registry, body),
+    fields(
+        user_id = %user.user.id.to_string()
+    )
+)]
+pub async fn import_books(
+    user: AuthorizedUser,
+    Query(query): Query<BookImportQuery>,
+    State(registry): State<AppRegistry>,
+    TypedHeader(content_type): TypedHeader<ContentType>,
+    body: String,
+) -> AppResult<(StatusCode, Json<BookImportResponse>)> {
+    let rows = BookImportFormat::from_content_type(&content_type.to_string())?
+        .parse(&body, query.allow_duplicate)?;
+    let mode = BookImportMode::from(query.mode);
+
+    // 読み込みや検証に失敗した行がある場合、`atomic`ではいずれの蔵書も登録しない。
+    if mode == BookImportMode::Atomic && rows.iter().any(|r| r.event.is_err()) {
+        let outcomes = rows
+            .into_iter()
+            .map(|r| {
+                let outcome = match r.event {
+                    Ok(_) => {
+                        BookImportOutcome::Skipped("not imported because other rows failed".into())
+                    }
+                    Err(message) => BookImportOutcome::Failed(message),
+                };
+                (r.row, outcome)
+            })
+            .collect::<Vec<_>>();
+        return Ok((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(BookImportResponse::from(outcomes)),
+        ));
+    }
+
+    let mut events = Vec::with_capacity(rows.len());
+    let mut results = Vec::with_capacity(rows.len());
+    for r in rows {
+        match r.event {
+            Ok(event) => {
+                events.push(event);
+                results.push((r.row, None));
+            }
+            Err(message) => results.push((r.row, Some(BookImportOutcome::Failed(message)))),
+        }
+    }
+
+    // 登録した蔵書の結果を、検証に失敗した行の結果と合わせて行の順に並べる。
+    let mut imported = registry
+        .book_repository()
+        .import(events, user.id(), mode)
+        .await?
+        .into_iter();
+    let outcomes = results
+        .into_iter()
+        .map(|(row, outcome)| {
+            let outcome = outcome.or_else(|| imported.next()).ok_or_else(|| {
+                AppError::ConversionEntityError("missing book import outcome".into())
+            })?;
+            Ok((row, outcome))
+        })
+        .collect::<AppResult<Vec<_>>>()?;
+
+    Ok((StatusCode::OK, Json(BookImportResponse::from(outcomes))))
+}
+
 #[cfg_attr(
     debug_assertions,
     utoipa::path(
@@ -139,13 +330,17 @@ pub async fn register_book(
         path = "/api/v1/books/{book_id}",
         params(
             ("book_id" = Uuid, Path, description = "蔵書ID"),
+            ("If-Match" = Option<String>, Header, description = "蔵書を取得したときの`ETag`。指定した場合、蔵書が更新されていれば412を返す (貸出状況やレビューの変化は対象外)"),
         ),
         request_body = UpdateBookRequest,
         responses(
             (status = 200, description = "蔵書の更新に成功した場合。"),
             (status = 400, description = "パスで指定された蔵書IDまたはリクエストボディに不備があった場合。"),
             (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 403, description = "蔵書の所有者でも管理者でもないユーザーがアクセスした場合。"),
             (status = 404, description = "パスで指定された蔵書IDを持つ蔵書が存在しない場合。"),
+            (status = 409, description = "同じISBNを持つ他の蔵書がすでに存在し、重複を許可しなかった場合。"),
+            (status = 412, description = "`If-Match`に指定された`ETag`を取得した後に、蔵書が変更された場合。"),
             (status = 422, description = "蔵書の記録に失敗した場合。"),
 
         )
@@ -162,15 +357,67 @@ pub async fn update_book(
     user: AuthorizedUser,
     Path(book_id): Path<BookId>,
     State(registry): State<AppRegistry>,
+    IfMatchHeader(if_match): IfMatchHeader,
     Json(body): Json<UpdateBookRequest>,
 ) -> AppResult<StatusCode> {
     body.validate(&())?;
 
-    let update_book = UpdateBookRequestWithIds::new(book_id, user.id(), body);
+    let expected_version = expected_book_version(&registry, book_id, if_match).await?;
+    let update_book =
+        UpdateBookRequestWithIds::new(book_id, user.id(), user.user.role, expected_version, body);
+
+    registry
+        .book_repository()
+        .update(UpdateBook::try_from(update_book)?)
+        .await
+        .map(|_| StatusCode::OK)
+}
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        patch,
+        path = "/api/v1/books/{book_id}",
+        params(
+            ("book_id" = Uuid, Path, description = "蔵書ID"),
+            ("If-Match" = Option<String>, Header, description = "蔵書を取得したときの`ETag`。指定した場合、蔵書が更新されていれば412を返す (貸出状況やレビューの変化は対象外)"),
+        ),
+        request_body = PatchBookRequest,
+        responses(
+            (status = 200, description = "蔵書の指定された項目の更新に成功した場合。"),
+            (status = 400, description = "パスで指定された蔵書IDまたはリクエストボディに不備があった場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 403, description = "蔵書の所有者でも管理者でもないユーザーがアクセスした場合。"),
+            (status = 404, description = "パスで指定された蔵書IDを持つ蔵書が存在しない場合。"),
+            (status = 409, description = "同じISBNを持つ他の蔵書がすでに存在し、重複を許可しなかった場合。"),
+            (status = 412, description = "`If-Match`に指定された`ETag`を取得した後に、蔵書が変更された場合。"),
+            (status = 422, description = "蔵書の記録に失敗した場合。"),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "patch book",
+    skip(user, registry),
+    fields(
+        user_id = %user.user.id.to_string(),
+    )
+)]
+pub async fn patch_book(
+    user: AuthorizedUser,
+    Path(book_id): Path<BookId>,
+    State(registry): State<AppRegistry>,
+    IfMatchHeader(if_match): IfMatchHeader,
+    Json(body): Json<PatchBookRequest>,
+) -> AppResult<StatusCode> {
+    body.validate(&())?;
+
+    let expected_version = expected_book_version(&registry, book_id, if_match).await?;
+    let patch_book =
+        PatchBookRequestWithIds::new(book_id, user.id(), user.user.role, expected_version, body);
 
     registry
         .book_repository()
-        .update(UpdateBook::from(update_book))
+        .patch(PatchBook::try_from(patch_book)?)
         .await
         .map(|_| StatusCode::OK)
 }
@@ -182,12 +429,16 @@ pub async fn update_book(
         path = "/api/v1/books/{book_id}",
         params(
             ("book_id" = Uuid, Path, description = "蔵書ID"),
+            ("If-Match" = Option<String>, Header, description = "蔵書を取得したときの`ETag`。指定した場合、蔵書が更新されていれば412を返す (貸出状況やレビューの変化は対象外)"),
         ),
         responses(
-            (status = 204, description = "蔵書の削除に成功した場合。"),
+            (status = 204, description = "蔵書を削除してゴミ箱に移した場合。蔵書の予約は取り消される。"),
             (status = 400, description = "パスで指定した蔵書IDに不備があった場合。"),
-            (status = 404, description = "パスで指定した蔵書IDを持つ蔵書が存在しない場合。"),
-            (status = 422, description = "蔵書を削除できなかった場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 403, description = "蔵書の所有者でも管理者でもないユーザーがアクセスした場合。"),
+            (status = 404, description = "パスで指定した蔵書IDを持つ蔵書が存在しないか、すでに削除されている場合。"),
+            (status = 412, description = "`If-Match`に指定された`ETag`を取得した後に、蔵書が変更された場合。"),
+            (status = 422, description = "蔵書の複本が貸出中の場合。"),
         ),
     )
 )]
@@ -202,10 +453,14 @@ pub async fn delete_book(
     user: AuthorizedUser,
     Path(book_id): Path<BookId>,
     State(registry): State<AppRegistry>,
+    IfMatchHeader(if_match): IfMatchHeader,
 ) -> AppResult<StatusCode> {
+    let expected_version = expected_book_version(&registry, book_id, if_match).await?;
     let delete_book = DeleteBook {
         book_id,
         requested_user: user.id(),
+        requested_role: user.user.role,
+        expected_version,
     };
 
     registry
@@ -214,3 +469,528 @@ pub async fn delete_book(
         .await
         .map(|_| StatusCode::NO_CONTENT)
 }
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/books/trash",
+        params(
+            ("limit" = i64, Query, description = "一度に取得する蔵書数の上限値の指定"),
+            ("offset" = i64, Query, description = "取得対象とする蔵書一覧の開始位置"),
+        ),
+        responses(
+            (status = 200, description = "ゴミ箱にある蔵書の取得に成功した場合。蔵書は削除した日時の新しい順に並ぶ。", body = PaginatedDeletedBookResponse),
+            (status = 400, description = "クエリに指定された上限値または開始位置に不備があった場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 403, description = "管理者以外がアクセスした場合。"),
+        ),
+    )
+)]
+#[tracing::instrument(
+    name = "show deleted book list",
+    skip(user, registry),
+    fields(
+        user_id = %user.user.id.to_string()
+    )
+)]
+pub async fn show_deleted_book_list(
+    user: AuthorizedUser,
+    Query(query): Query<DeletedBookListQuery>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Json<PaginatedDeletedBookResponse>> {
+    // ユーザーが管理者の場合のみ許可
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenOperation);
+    }
+
+    query.validate(&())?;
+
+    registry
+        .book_repository()
+        .find_deleted_all(DeletedBookListOptions::from(query))
+        .await
+        .map(PaginatedDeletedBookResponse::from)
+        .map(Json)
+}
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        post,
+        path = "/api/v1/books/trash/{book_id}/restore",
+        params(
+            ("book_id" = Uuid, Path, description = "蔵書ID"),
+        ),
+        responses(
+            (status = 200, description = "ゴミ箱にある蔵書を元に戻した場合。"),
+            (status = 400, description = "パスで指定した蔵書IDに不備があった場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 403, description = "管理者以外がアクセスした場合。"),
+            (status = 404, description = "パスで指定した蔵書IDを持つ蔵書がゴミ箱に存在しない場合。"),
+            (status = 409, description = "ゴミ箱にある間に、同じISBNを持つ蔵書が登録された場合。"),
+        ),
+    )
+)]
+#[tracing::instrument(
+    name = "restore book",
+    skip(user, registry),
+    fields(
+        user_id = %user.user.id.to_string()
+    )
+)]
+pub async fn restore_book(
+    user: AuthorizedUser,
+    Path(book_id): Path<BookId>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<StatusCode> {
+    // ユーザーが管理者の場合のみ許可
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenOperation);
+    }
+
+    let restore_book = RestoreBook {
+        book_id,
+        requested_user: user.id(),
+    };
+
+    registry
+        .book_repository()
+        .restore(restore_book)
+        .await
+        .map(|_| StatusCode::OK)
+}
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        delete,
+        path = "/api/v1/books/trash/{book_id}",
+        params(
+            ("book_id" = Uuid, Path, description = "蔵書ID"),
+        ),
+        responses(
+            (status = 204, description = "ゴミ箱にある蔵書を、貸出履歴などとともに完全に削除した場合。"),
+            (status = 400, description = "パスで指定した蔵書IDに不備があった場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 403, description = "管理者以外がアクセスした場合。"),
+            (status = 404, description = "パスで指定した蔵書IDを持つ蔵書がゴミ箱に存在しない場合。"),
+            (status = 422, description = "蔵書の複本が貸出中の場合。"),
+        ),
+    )
+)]
+#[tracing::instrument(
+    name = "purge book",
+    skip(user, registry),
+    fields(
+        user_id = %user.user.id.to_string()
+    )
+)]
+pub async fn purge_book(
+    user: AuthorizedUser,
+    Path(book_id): Path<BookId>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<StatusCode> {
+    // ユーザーが管理者の場合のみ許可
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenOperation);
+    }
+
+    let purge_book = PurgeBook {
+        book_id,
+        requested_user: user.id(),
+    };
+
+    registry.book_repository().purge(purge_book).await?;
+
+    // 蔵書の削除は完了しているため、表紙画像の削除に失敗しても記録だけする。
+    if let Err(e) = registry.book_cover_repository().delete_all(book_id).await {
+        tracing::warn!(error = %e, %book_id, "failed to delete the cover images of the purged book");
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/books/{book_id}/revisions",
+        params(
+            ("book_id" = Uuid, Path, description = "蔵書ID"),
+        ),
+        responses(
+            (status = 200, description = "蔵書の更新履歴を新しい順に取得した場合。", body = BookRevisionsResponse),
+            (status = 400, description = "パスで指定された蔵書IDに不備があった場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 404, description = "パスで指定された蔵書IDを持つ蔵書が存在しない場合。"),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "show book revision list",
+    skip(_user, registry),
+    fields(
+        user_id = %_user.user.id.to_string()
+    )
+)]
+pub async fn show_book_revision_list(
+    _user: AuthorizedUser,
+    Path(book_id): Path<BookId>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Json<BookRevisionsResponse>> {
+    registry
+        .book_repository()
+        .find_revisions(book_id)
+        .await
+        .map(BookRevisionsResponse::from)
+        .map(Json)
+}
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        post,
+        path = "/api/v1/books/{book_id}/revisions/{revision_number}/revert",
+        params(
+            ("book_id" = Uuid, Path, description = "蔵書ID"),
+            ("revision_number" = i32, Path, description = "戻す内容の更新履歴の番号"),
+            ("If-Match" = Option<String>, Header, description = "蔵書を取得したときの`ETag`。指定した場合、蔵書が更新されていれば412を返す (貸出状況やレビューの変化は対象外)"),
+        ),
+        responses(
+            (status = 200, description = "蔵書を指定した更新履歴の内容に戻した場合。"),
+            (status = 400, description = "パスで指定された蔵書IDまたは更新履歴の番号に不備があった場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 403, description = "蔵書の所有者でも管理者でもないユーザーがアクセスした場合。"),
+            (status = 404, description = "パスで指定された蔵書または更新履歴が存在しない場合。"),
+            (status = 409, description = "戻すISBNを持つ他の蔵書がすでに存在する場合。"),
+            (status = 412, description = "`If-Match`に指定された`ETag`を取得した後に、蔵書が変更された場合。"),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "revert book",
+    skip(user, registry),
+    fields(
+        user_id = %user.user.id.to_string()
+    )
+)]
+pub async fn revert_book(
+    user: AuthorizedUser,
+    Path((book_id, revision_number)): Path<(BookId, i32)>,
+    State(registry): State<AppRegistry>,
+    IfMatchHeader(if_match): IfMatchHeader,
+) -> AppResult<StatusCode> {
+    let expected_version = expected_book_version(&registry, book_id, if_match).await?;
+    let revert_book = RevertBook {
+        book_id,
+        revision_number,
+        requested_user: user.id(),
+        requested_role: user.user.role,
+        expected_version,
+    };
+
+    registry
+        .book_repository()
+        .revert(revert_book)
+        .await
+        .map(|_| StatusCode::OK)
+}
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/books/{book_id}/copies",
+        params(
+            ("book_id" = Uuid, Path, description = "蔵書ID"),
+        ),
+        responses(
+            (status = 200, description = "蔵書の複本一覧の取得に成功した場合。", body = BookCopiesResponse),
+            (status = 400, description = "パスで指定された蔵書IDに不備があった場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 404, description = "パスで指定された蔵書IDを持つ蔵書が存在しない場合。"),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "show book copy list",
+    skip(_user, registry),
+    fields(
+        user_id = %_user.user.id.to_string()
+    )
+)]
+pub async fn show_book_copy_list(
+    _user: AuthorizedUser,
+    Path(book_id): Path<BookId>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Json<BookCopiesResponse>> {
+    let book_repository = registry.book_repository();
+    if book_repository.find_by_id(book_id).await?.is_none() {
+        return Err(AppError::EntityNotFound(
+            "the specified book was not found".into(),
+        ));
+    }
+
+    book_repository
+        .find_copies_by_book_id(book_id)
+        .await
+        .map(BookCopiesResponse::from)
+        .map(Json)
+}
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        post,
+        path = "/api/v1/books/{book_id}/copies",
+        params(
+            ("book_id" = Uuid, Path, description = "蔵書ID"),
+        ),
+        request_body = CreateBookCopyRequest,
+        responses(
+            (status = 201, description = "複本の追加に成功した場合。"),
+            (status = 400, description = "パスで指定された蔵書IDまたはリクエストボディに不備があった場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 403, description = "蔵書の所有者でも管理者でもないユーザーがアクセスした場合。"),
+            (status = 404, description = "パスで指定された蔵書IDを持つ蔵書が存在しない場合。"),
+            (status = 422, description = "バーコードがすでに使われている場合。"),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "register book copy",
+    skip(user, registry),
+    fields(
+        user_id = %user.user.id.to_string()
+    )
+)]
+pub async fn register_book_copy(
+    user: AuthorizedUser,
+    Path(book_id): Path<BookId>,
+    State(registry): State<AppRegistry>,
+    Json(body): Json<CreateBookCopyRequest>,
+) -> AppResult<StatusCode> {
+    body.validate(&())?;
+
+    let create_copy = CreateBookCopyRequestWithIds::new(book_id, user.id(), user.user.role, body);
+
+    registry
+        .book_repository()
+        .create_copy(CreateBookCopy::from(create_copy))
+        .await
+        .map(|_| StatusCode::CREATED)
+}
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        put,
+        path = "/api/v1/books/{book_id}/copies/{copy_id}",
+        params(
+            ("book_id" = Uuid, Path, description = "蔵書ID"),
+            ("copy_id" = Uuid, Path, description = "複本ID"),
+        ),
+        request_body = UpdateBookCopyRequest,
+        responses(
+            (status = 200, description = "複本の更新に成功した場合。"),
+            (status = 400, description = "パスで指定されたIDまたはリクエストボディに不備があった場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 403, description = "蔵書の所有者でも管理者でもないユーザーがアクセスした場合。"),
+            (status = 404, description = "パスで指定されたIDを持つ蔵書の複本が存在しない場合。"),
+            (status = 422, description = "バーコードがすでに使われている場合。"),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "update book copy",
+    skip(user, registry),
+    fields(
+        user_id = %user.user.id.to_string()
+    )
+)]
+pub async fn update_book_copy(
+    user: AuthorizedUser,
+    Path((book_id, copy_id)): Path<(BookId, BookCopyId)>,
+    State(registry): State<AppRegistry>,
+    Json(body): Json<UpdateBookCopyRequest>,
+) -> AppResult<StatusCode> {
+    body.validate(&())?;
+
+    let update_copy =
+        UpdateBookCopyRequestWithIds::new(book_id, copy_id, user.id(), user.user.role, body);
+
+    registry
+        .book_repository()
+        .update_copy(UpdateBookCopy::from(update_copy))
+        .await
+        .map(|_| StatusCode::OK)
+}
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        delete,
+        path = "/api/v1/books/{book_id}/copies/{copy_id}",
+        params(
+            ("book_id" = Uuid, Path, description = "蔵書ID"),
+            ("copy_id" = Uuid, Path, description = "複本ID"),
+        ),
+        responses(
+            (status = 204, description = "複本の削除に成功した場合。"),
+            (status = 400, description = "パスで指定されたIDに不備があった場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 403, description = "蔵書の所有者でも管理者でもないユーザーがアクセスした場合。"),
+            (status = 404, description = "パスで指定されたIDを持つ蔵書の複本が存在しない場合。"),
+            (status = 422, description = "複本が貸出中の場合。"),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "delete book copy",
+    skip(user, registry),
+    fields(
+        user_id = %user.user.id.to_string()
+    )
+)]
+pub async fn delete_book_copy(
+    user: AuthorizedUser,
+    Path((book_id, copy_id)): Path<(BookId, BookCopyId)>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<StatusCode> {
+    let delete_copy = DeleteBookCopy {
+        copy_id,
+        book_id,
+        requested_user: user.id(),
+        requested_role: user.user.role,
+    };
+
+    registry
+        .book_repository()
+        .delete_copy(delete_copy)
+        .await
+        .map(|_| StatusCode::NO_CONTENT)
+}
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        put,
+        path = "/api/v1/books/{book_id}/cover",
+        params(
+            ("book_id" = Uuid, Path, description = "蔵書ID"),
+        ),
+        request_body(
+            content = Vec<u8>,
+            content_type = "multipart/form-data",
+            description = "`cover`フィールドにJPEGまたはPNGの画像 (5MiB以下) を指定する。"
+        ),
+        responses(
+            (status = 200, description = "表紙画像の登録に成功した場合。"),
+            (status = 400, description = "パスで指定された蔵書IDに不備がある場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 403, description = "蔵書の所有者または管理者以外がアクセスした場合。"),
+            (status = 404, description = "パスで指定された蔵書が存在しない場合。"),
+            (status = 413, description = "画像が大きすぎる場合。"),
+            (status = 422, description = "リクエストボディの形式が誤っているか、画像がJPEGまたはPNGでない場合。"),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "upload book cover",
+    skip(user, registry, body),
+    fields(
+        user_id = %user.user.id.to_string()
+    )
+)]
+pub async fn upload_book_cover(
+    user: AuthorizedUser,
+    Path(book_id): Path<BookId>,
+    State(registry): State<AppRegistry>,
+    TypedHeader(content_type): TypedHeader<ContentType>,
+    body: Bytes,
+) -> AppResult<StatusCode> {
+    let upload = CoverUpload::from_multipart(&content_type.to_string(), &body)?;
+    let event = UpdateBookCover {
+        book_id,
+        format: upload.format,
+        data: upload.data,
+        requested_user: user.id(),
+        requested_role: user.user.role,
+    };
+
+    registry
+        .book_cover_repository()
+        .update(event)
+        .await
+        .map(|_| StatusCode::OK)
+}
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/books/{book_id}/cover",
+        params(
+            ("book_id" = Uuid, Path, description = "蔵書ID"),
+            ("size" = Option<CoverSizeName>, Query, description = "取得する画像の大きさ。`thumbnail`は長辺が200ピクセル以下の縮小画像。省略した場合は`original`"),
+        ),
+        responses(
+            (status = 200, description = "表紙画像の取得に成功した場合。", content_type = ["image/jpeg", "image/png"]),
+            (status = 304, description = "`If-None-Match`ヘッダーに指定したETagが表紙画像と一致した場合。"),
+            (status = 400, description = "パスで指定された蔵書IDまたはクエリに不備がある場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 404, description = "パスで指定された蔵書が存在しないか、表紙画像が登録されていない場合。"),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "show book cover",
+    skip(_user, registry, if_none_match),
+    fields(
+        user_id = %_user.user.id.to_string()
+    )
+)]
+pub async fn show_book_cover(
+    _user: AuthorizedUser,
+    Path(book_id): Path<BookId>,
+    Query(query): Query<BookCoverQuery>,
+    State(registry): State<AppRegistry>,
+    if_none_match: Option<TypedHeader<IfNoneMatch>>,
+) -> AppResult<Response> {
+    let cover = registry
+        .book_cover_repository()
+        .find(book_id, CoverSize::from(query.size))
+        .await?
+        .ok_or_else(|| {
+            AppError::EntityNotFound(format!("the book ({book_id}) doesn't have a cover image"))
+        })?;
+
+    let etag = format!("\"{}\"", cover.etag);
+    // 表紙画像を置き換えると`coverUrl`も変わるため、ブラウザーに長めにキャッシュさせる。
+    let headers = [
+        (ETAG, etag.clone()),
+        (CACHE_CONTROL, "private, max-age=86400".to_string()),
+        (
+            LAST_MODIFIED,
+            cover
+                .updated_at
+                .format("%a, %d %b %Y %H:%M:%S GMT")
+                .to_string(),
+        ),
+    ];
+    let not_modified = match (if_none_match, etag.parse::<ETag>()) {
+        (Some(TypedHeader(if_none_match)), Ok(etag)) => !if_none_match.precondition_passes(&etag),
+        _ => false,
+    };
+    if not_modified {
+        return Ok((StatusCode::NOT_MODIFIED, headers).into_response());
+    }
+
+    Ok((
+        headers,
+        [(CONTENT_TYPE, cover.format.as_ref().to_string())],
+        cover.data,
+    )
+        .into_response())
+}
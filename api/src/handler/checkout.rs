@@ -1,15 +1,21 @@
-use axum::extract::{Path, State};
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{Path, Query, State};
 use axum::http::StatusCode;
 use axum::Json;
 use chrono::Utc;
+use garde::Validate;
 
-use kernel::model::checkout::event::{CreateCheckout, UpdateReturned};
+use kernel::model::checkout::event::{CreateCheckout, RenewCheckout, UpdateReturned};
+use kernel::model::checkout::{CheckoutListOptions, CheckoutState};
 use kernel::model::id::{BookId, CheckoutId};
 use registry::AppRegistry;
-use shared::error::AppResult;
+use shared::error::{AppError, AppResult};
 
 use crate::extractor::AuthorizedUser;
-use crate::model::checkout::CheckoutsResponse;
+use crate::model::checkout::{
+    CheckoutListQuery, CheckoutStateName, CheckoutsResponse, PaginatedCheckoutResponse,
+    ReturnBookRequest,
+};
 
 #[cfg_attr(
     debug_assertions,
@@ -24,7 +30,7 @@ use crate::model::checkout::CheckoutsResponse;
             (status = 400, description = "パスで指定された蔵書IDに不備があった場合。"),
             (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
             (status = 404, description = "パスで指定された蔵書IDを持つ蔵書が存在しない場合。"),
-            (status = 422, description = "蔵書の貸出を記録できなかった場合。"),
+            (status = 422, description = "蔵書が貸出中か、他のユーザーのために取り置かれている場合や、同時に借りられる蔵書の数が貸出ポリシーの上限に達している場合。"),
         )
     )
 )]
@@ -57,12 +63,16 @@ pub async fn checkout_book(
             ("book_id" = Uuid, Path, description = "貸し出した蔵書ID"),
             ("checkout_id" = Uuid, Path, description = "貸出ID"),
         ),
+        request_body(
+            content = Option<ReturnBookRequest>,
+            description = "貸出の終わり方。省略した場合は返却として記録する。",
+        ),
         responses(
             (status = 200, description = "貸出した蔵書の返却に成功した場合。"),
             (status = 400, description = "パスで指定された蔵書IDまたは貸出IDに不備があった場合。"),
             (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
             (status = 404, description = "パスで指定された蔵書IDを持つ蔵書、または貸出IDを持つ貸出が存在しなかった場合。"),
-            (status = 422, description = "貸出した蔵書の返却の記録に失敗した場合。"),
+            (status = 422, description = "借りたユーザー以外の管理者でないユーザーが返却しようとした場合や、リクエストボディに不備があった場合。"),
         )
     )
 )]
@@ -77,8 +87,22 @@ pub async fn return_book(
     user: AuthorizedUser,
     Path((book_id, checkout_id)): Path<(BookId, CheckoutId)>,
     State(registry): State<AppRegistry>,
+    body: Result<Json<ReturnBookRequest>, JsonRejection>,
 ) -> AppResult<StatusCode> {
-    let event = UpdateReturned::new(checkout_id, book_id, user.id(), Utc::now());
+    let body = match body {
+        Ok(Json(body)) => body,
+        // リクエストボディを省略した場合は返却として記録する
+        Err(JsonRejection::MissingJsonContentType(_)) => ReturnBookRequest::default(),
+        Err(rejection) => return Err(AppError::UnprocessableEntity(rejection.body_text())),
+    };
+    let event = UpdateReturned::new(
+        checkout_id,
+        book_id,
+        user.id(),
+        user.user.role,
+        body.outcome.into(),
+        Utc::now(),
+    );
     registry
         .checkout_repository()
         .update_returned(event)
@@ -86,31 +110,113 @@ pub async fn return_book(
         .map(|_| StatusCode::OK)
 }
 
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        put,
+        path = "/api/v1/books/{book_id}/checkouts/{checkout_id}/renewal",
+        params(
+            ("book_id" = Uuid, Path, description = "貸し出した蔵書ID"),
+            ("checkout_id" = Uuid, Path, description = "貸出ID"),
+        ),
+        responses(
+            (status = 200, description = "貸出の返却期限の延長に成功した場合。"),
+            (status = 400, description = "パスで指定された蔵書IDまたは貸出IDに不備があった場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 404, description = "パスで指定された蔵書IDを持つ蔵書の、貸出IDを持つ未返却の貸出が存在しなかった場合。"),
+            (status = 422, description = "蔵書を借りたユーザー以外が延長しようとした場合や、延長回数が上限に達していた場合、他のユーザーが蔵書を予約している場合。"),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "renew checkout",
+    skip(user, registry),
+    fields(
+        user_id = %user.user.id.to_string(),
+    )
+)]
+pub async fn renew_checkout(
+    user: AuthorizedUser,
+    Path((book_id, checkout_id)): Path<(BookId, CheckoutId)>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<StatusCode> {
+    let event = RenewCheckout::new(checkout_id, book_id, user.id(), Utc::now());
+    registry
+        .checkout_repository()
+        .renew(event)
+        .await
+        .map(|_| StatusCode::OK)
+}
+
 #[cfg_attr(
     debug_assertions,
     utoipa::path(
         get,
         path = "/api/v1/books/checkouts",
+        params(
+            ("limit" = Option<i64>, Query, description = "一度に取得する貸出数の上限値の指定"),
+            ("offset" = Option<i64>, Query, description = "取得対象とする貸出一覧の開始位置"),
+            ("userId" = Option<Uuid>, Query, description = "蔵書を借りたユーザーのユーザーID。管理者以外は自分のユーザーIDのみ指定でき、返却済みの貸出を含む場合は自分の貸出のみを返す"),
+            ("state" = Option<CheckoutStateName>, Query, description = "返却状態。省略した場合は`unreturned`"),
+            ("since" = Option<DateTime<Utc>>, Query, description = "この日時以降に貸し出した貸出"),
+            ("until" = Option<DateTime<Utc>>, Query, description = "この日時より前に貸し出した貸出"),
+        ),
         responses(
-            (status = 200, description = "貸し出した蔵書の一覧の取得に成功した場合。", body = CheckoutsResponse),
+            (status = 200, description = "貸し出した蔵書の一覧の取得に成功した場合。", body = PaginatedCheckoutResponse),
+            (status = 400, description = "指定されたクエリの値に不備があった場合。"),
             (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 403, description = "管理者以外が他のユーザーの貸出を指定した場合。"),
         ),
     )
 )]
 #[tracing::instrument(
     name = "show checked-out list",
+    skip(user, registry),
+    fields(
+        user_id = %user.user.id.to_string(),
+    )
+)]
+pub async fn show_checked_out_list(
+    user: AuthorizedUser,
+    Query(query): Query<CheckoutListQuery>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Json<PaginatedCheckoutResponse>> {
+    query.validate(&())?;
+
+    let options = restrict_to_visible(&user, query.into_options(CheckoutStateName::Unreturned))?;
+    registry
+        .checkout_repository()
+        .find_all(options)
+        .await
+        .map(PaginatedCheckoutResponse::from)
+        .map(Json)
+}
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/books/checkouts/overdue",
+        responses(
+            (status = 200, description = "返却期限を過ぎた蔵書の一覧の取得に成功した場合。", body = CheckoutsResponse),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+        ),
+    )
+)]
+#[tracing::instrument(
+    name = "show overdue list",
     skip(_user, registry),
     fields(
         user_id = %_user.user.id.to_string(),
     )
 )]
-pub async fn show_checked_out_list(
+pub async fn show_overdue_list(
     _user: AuthorizedUser,
     State(registry): State<AppRegistry>,
 ) -> AppResult<Json<CheckoutsResponse>> {
     registry
         .checkout_repository()
-        .find_unreturned_all()
+        .find_overdue_all()
         .await
         .map(CheckoutsResponse::from)
         .map(Json)
@@ -123,31 +229,71 @@ pub async fn show_checked_out_list(
         path = "/api/v1/books/{book_id}/checkout-history",
         params(
             ("book_id" = Uuid, Path, description = "蔵書ID"),
+            ("limit" = Option<i64>, Query, description = "一度に取得する貸出数の上限値の指定"),
+            ("offset" = Option<i64>, Query, description = "取得対象とする貸出履歴の開始位置"),
+            ("userId" = Option<Uuid>, Query, description = "蔵書を借りたユーザーのユーザーID。管理者以外は自分のユーザーIDのみ指定でき、返却済みの貸出を含む場合は自分の貸出のみを返す"),
+            ("state" = Option<CheckoutStateName>, Query, description = "返却状態。省略した場合は`all`"),
+            ("since" = Option<DateTime<Utc>>, Query, description = "この日時以降に貸し出した貸出"),
+            ("until" = Option<DateTime<Utc>>, Query, description = "この日時より前に貸し出した貸出"),
         ),
         responses(
-            (status = 200, description = "指定した蔵書の貸出履歴。", body = CheckoutsResponse),
-            (status = 400, description = "パスで指定した蔵書IDに不備があった場合。"),
+            (status = 200, description = "指定した蔵書の貸出履歴。", body = PaginatedCheckoutResponse),
+            (status = 400, description = "パスで指定した蔵書IDや、クエリの値に不備があった場合。"),
             (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
-            (status = 404, description = "パスで指定した蔵書IDを持つ蔵書が存在しない場合。"),
+            (status = 403, description = "管理者以外が他のユーザーの貸出を指定した場合。"),
         )
     )
 )]
 #[tracing::instrument(
     name = "checkout history",
-    skip(_user, registry),
+    skip(user, registry),
     fields(
-        user_id = %_user.user.id.to_string(),
+        user_id = %user.user.id.to_string(),
     )
 )]
 pub async fn checkout_history(
-    _user: AuthorizedUser,
+    user: AuthorizedUser,
     Path(book_id): Path<BookId>,
+    Query(query): Query<CheckoutListQuery>,
     State(registry): State<AppRegistry>,
-) -> AppResult<Json<CheckoutsResponse>> {
+) -> AppResult<Json<PaginatedCheckoutResponse>> {
+    query.validate(&())?;
+
+    let options = CheckoutListOptions {
+        book_id: Some(book_id),
+        ..query.into_options(CheckoutStateName::All)
+    };
+    let options = restrict_to_visible(&user, options)?;
     registry
         .checkout_repository()
-        .find_history_by_book_id(book_id)
+        .find_all(options)
         .await
-        .map(CheckoutsResponse::from)
+        .map(PaginatedCheckoutResponse::from)
         .map(Json)
 }
+
+/// 管理者以外が取得できる貸出に条件を制限する。
+///
+/// 管理者以外は、他のユーザーの貸出を指定できない。また、返却済みの貸出は借りたユーザーの
+/// 履歴であるため、返却済みの貸出を含む場合は自分の貸出のみを対象とする。
+fn restrict_to_visible(
+    user: &AuthorizedUser,
+    options: CheckoutListOptions,
+) -> AppResult<CheckoutListOptions> {
+    if user.is_admin() {
+        return Ok(options);
+    }
+    if options
+        .borrower
+        .is_some_and(|borrower| borrower != user.id())
+    {
+        return Err(AppError::ForbiddenOperation);
+    }
+    if options.state == Some(CheckoutState::Unreturned) {
+        return Ok(options);
+    }
+    Ok(CheckoutListOptions {
+        borrower: Some(user.id()),
+        ..options
+    })
+}
@@ -0,0 +1,122 @@
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::Json;
+use chrono::Utc;
+
+use kernel::model::hold::event::{CreateHold, DeleteHold};
+use kernel::model::id::{BookId, HoldId};
+use registry::AppRegistry;
+use shared::error::AppResult;
+
+use crate::extractor::AuthorizedUser;
+use crate::model::hold::HoldsResponse;
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        post,
+        path = "/api/v1/books/{book_id}/holds",
+        params(
+            ("book_id" = Uuid, Path, description = "予約する蔵書ID"),
+        ),
+        responses(
+            (status = 201, description = "蔵書の予約に成功した場合。"),
+            (status = 400, description = "パスで指定された蔵書IDに不備があった場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 404, description = "パスで指定された蔵書IDを持つ蔵書が存在しない場合。"),
+            (status = 422, description = "蔵書を借りているか、すでに予約している場合や、蔵書をすぐに借りられる場合。"),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "place hold",
+    skip(user, registry),
+    fields(
+        user_id = %user.user.id.to_string(),
+    )
+)]
+pub async fn place_hold(
+    user: AuthorizedUser,
+    Path(book_id): Path<BookId>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<StatusCode> {
+    let event = CreateHold::new(book_id, user.id(), Utc::now());
+    registry
+        .hold_repository()
+        .create(event)
+        .await
+        .map(|_| StatusCode::CREATED)
+}
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/books/{book_id}/holds",
+        params(
+            ("book_id" = Uuid, Path, description = "蔵書ID"),
+        ),
+        responses(
+            (status = 200, description = "指定した蔵書の予約の待ち行列。", body = HoldsResponse),
+            (status = 400, description = "パスで指定された蔵書IDに不備があった場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "show hold list",
+    skip(_user, registry),
+    fields(
+        user_id = %_user.user.id.to_string(),
+    )
+)]
+pub async fn show_hold_list(
+    _user: AuthorizedUser,
+    Path(book_id): Path<BookId>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Json<HoldsResponse>> {
+    registry
+        .hold_repository()
+        .find_by_book_id(book_id)
+        .await
+        .map(HoldsResponse::from)
+        .map(Json)
+}
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        delete,
+        path = "/api/v1/books/{book_id}/holds/{hold_id}",
+        params(
+            ("book_id" = Uuid, Path, description = "蔵書ID"),
+            ("hold_id" = Uuid, Path, description = "予約ID"),
+        ),
+        responses(
+            (status = 204, description = "予約の取り消しに成功した場合。"),
+            (status = 400, description = "パスで指定された蔵書IDまたは予約IDに不備があった場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 403, description = "予約したユーザー以外の管理者でないユーザーが取り消そうとした場合。"),
+            (status = 404, description = "パスで指定された蔵書IDを持つ蔵書の、予約IDを持つ予約が存在しない場合。"),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "cancel hold",
+    skip(user, registry),
+    fields(
+        user_id = %user.user.id.to_string(),
+    )
+)]
+pub async fn cancel_hold(
+    user: AuthorizedUser,
+    Path((book_id, hold_id)): Path<(BookId, HoldId)>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<StatusCode> {
+    let event = DeleteHold::new(hold_id, book_id, user.id(), user.user.role, Utc::now());
+    registry
+        .hold_repository()
+        .delete(event)
+        .await
+        .map(|_| StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,90 @@
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::Json;
+use garde::Validate;
+
+use kernel::model::loan_policy::event::UpdateLoanPolicy;
+use registry::AppRegistry;
+use shared::error::{AppError, AppResult};
+
+use crate::extractor::AuthorizedUser;
+use crate::model::loan_policy::{
+    LoanPoliciesResponse, UpdateLoanPolicyRequest, UpdateLoanPolicyRequestWithRole,
+};
+use crate::model::user::RoleName;
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/loan-policies",
+        responses(
+            (status = 200, description = "ロールごとの貸出ポリシーの取得に成功した場合。", body = LoanPoliciesResponse),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "show loan policy list",
+    skip(_user, registry),
+    fields(
+        user_id = %_user.user.id.to_string(),
+    )
+)]
+pub async fn show_loan_policy_list(
+    _user: AuthorizedUser,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Json<LoanPoliciesResponse>> {
+    registry
+        .loan_policy_repository()
+        .find_all()
+        .await
+        .map(LoanPoliciesResponse::from)
+        .map(Json)
+}
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        put,
+        path = "/api/v1/loan-policies/{role}",
+        params(
+            ("role" = RoleName, Path, description = "貸出ポリシーを変更するロール"),
+        ),
+        request_body = UpdateLoanPolicyRequest,
+        responses(
+            (status = 200, description = "貸出ポリシーの変更に成功した場合。"),
+            (status = 400, description = "パスで指定されたロールまたはリクエストボディの内容に不備がある場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 403, description = "管理者以外がアクセスした場合。"),
+            (status = 404, description = "パスで指定されたロールが存在しない場合。"),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "update loan policy",
+    skip(user, registry, body),
+    fields(
+        user_id = %user.user.id.to_string(),
+    )
+)]
+pub async fn update_loan_policy(
+    user: AuthorizedUser,
+    Path(role): Path<RoleName>,
+    State(registry): State<AppRegistry>,
+    Json(body): Json<UpdateLoanPolicyRequest>,
+) -> AppResult<StatusCode> {
+    // ユーザーが管理者の場合のみ許可
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenOperation);
+    }
+
+    body.validate(&())?;
+
+    let request = UpdateLoanPolicyRequestWithRole::new(role, user.id(), body);
+    registry
+        .loan_policy_repository()
+        .update(UpdateLoanPolicy::from(request))
+        .await
+        .map(|_| StatusCode::OK)
+}
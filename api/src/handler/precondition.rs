@@ -0,0 +1,103 @@
+//! 条件付きリクエストの処理
+//!
+//! 蔵書やユーザーを取得するときに`ETag`ヘッダーを返し、`If-None-Match`ヘッダーが一致する場合は
+//! 304を返す。更新や削除では、`If-Match`ヘッダーのバージョンが現在のバージョンと一致しない場合に
+//! 412を返して、他のリクエストによる変更を上書きしないようにする。
+use axum::http::{HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use axum_extra::headers::{ETag, IfNoneMatch};
+use axum_extra::TypedHeader;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use shared::error::{AppError, AppResult};
+
+/// バージョンとレスポンスの内容から、`W/"<バージョン>-<内容のハッシュ値>"`の形式の
+/// 弱いエンティティタグを作成する。
+///
+/// 蔵書の貸出状況のように、バージョンを変えずに内容が変わる場合があるため、`If-None-Match`で
+/// 比較できるように内容も含めて計算する。`If-Match`ではバージョンだけを比較する。
+pub fn entity_tag<T: Serialize>(version: i64, representation: &T) -> AppResult<ETag> {
+    let bytes = serde_json::to_vec(representation)
+        .map_err(|e| AppError::ConversionEntityError(e.to_string()))?;
+    format!("W/\"{version}-{:x}\"", Sha256::digest(bytes))
+        .parse::<ETag>()
+        .map_err(|e| AppError::ConversionEntityError(e.to_string()))
+}
+
+/// レスポンスの内容をJSONで返し、`ETag`ヘッダーを付ける。
+///
+/// `If-None-Match`ヘッダーがエンティティタグと一致する場合は、内容を返さずに304を返す。
+pub fn json_with_etag<T: Serialize>(
+    version: i64,
+    representation: T,
+    if_none_match: Option<TypedHeader<IfNoneMatch>>,
+) -> AppResult<Response> {
+    let etag = entity_tag(version, &representation)?;
+    if let Some(TypedHeader(if_none_match)) = if_none_match {
+        if !if_none_match.precondition_passes(&etag) {
+            return Ok((StatusCode::NOT_MODIFIED, TypedHeader(etag)).into_response());
+        }
+    }
+    Ok((TypedHeader(etag), Json(representation)).into_response())
+}
+
+/// `If-Match`ヘッダーに指定された条件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IfMatch {
+    /// `*`が指定された場合 (どのバージョンとも一致する)
+    Any,
+    /// エンティティタグから取り出したバージョン
+    ///
+    /// バージョンを取り出せないエンティティタグは、どのバージョンとも一致しない。
+    Versions(Vec<i64>),
+}
+
+impl IfMatch {
+    /// `If-Match`ヘッダーの値から、エンティティタグのバージョンを取り出す。
+    ///
+    /// `entity_tag`で作成したエンティティタグのほか、`"<バージョン>"`の形式も受け付ける。
+    pub fn from_header_values<'a>(values: impl IntoIterator<Item = &'a HeaderValue>) -> Self {
+        let mut versions = Vec::new();
+        for tag in values
+            .into_iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+        {
+            if tag == "*" {
+                return Self::Any;
+            }
+            let version = tag
+                .strip_prefix("W/")
+                .unwrap_or(tag)
+                .strip_prefix('"')
+                .and_then(|tag| tag.strip_suffix('"'))
+                .map(|tag| tag.split_once('-').map_or(tag, |(version, _)| version))
+                .and_then(|version| version.parse::<i64>().ok());
+            versions.extend(version);
+        }
+        Self::Versions(versions)
+    }
+
+    fn matches(&self, version: i64) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Versions(versions) => versions.contains(&version),
+        }
+    }
+}
+
+/// `If-Match`ヘッダーのバージョンが現在のバージョンと一致するか確認し、確認したバージョンを返す。
+///
+/// 返したバージョンをリポジトリに渡すことで、確認してから更新するまでの間に
+/// 他のリクエストによって更新された場合も、リポジトリが412を返す。
+pub fn verify_if_match(if_match: &IfMatch, version: i64) -> AppResult<i64> {
+    if !if_match.matches(version) {
+        return Err(AppError::PreconditionFailed(
+            "the entity tag doesn't match the current version".into(),
+        ));
+    }
+    Ok(version)
+}
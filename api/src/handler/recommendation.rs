@@ -0,0 +1,93 @@
+use axum::extract::{Path, Query, State};
+use axum::Json;
+use garde::Validate;
+
+use kernel::model::id::BookId;
+use registry::AppRegistry;
+use shared::error::{AppError, AppResult};
+
+use crate::extractor::AuthorizedUser;
+use crate::model::recommendation::{RecommendationQuery, RecommendationsResponse};
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/books/{book_id}/recommendations",
+        params(
+            ("book_id" = Uuid, Path, description = "蔵書ID"),
+            ("limit" = Option<usize>, Query, description = "推薦する蔵書の数 (1から50、既定値は10)"),
+        ),
+        responses(
+            (status = 200, description = "指定した蔵書と一緒に借りられた蔵書や、著者またはタグが共通する蔵書。自分が借りたことのある蔵書は含まず、推薦の強い順に並ぶ。", body = RecommendationsResponse),
+            (status = 400, description = "パスで指定した蔵書IDや、クエリの値に不備があった場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 404, description = "指定した蔵書が存在しない場合。"),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "show book recommendations",
+    skip(user, registry),
+    fields(
+        user_id = %user.user.id.to_string(),
+    )
+)]
+pub async fn show_book_recommendations(
+    user: AuthorizedUser,
+    Path(book_id): Path<BookId>,
+    Query(query): Query<RecommendationQuery>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Json<RecommendationsResponse>> {
+    query.validate(&())?;
+
+    registry
+        .book_repository()
+        .find_by_id(book_id)
+        .await?
+        .ok_or_else(|| AppError::EntityNotFound("the specified book was not found".into()))?;
+
+    registry
+        .recommendation_service()
+        .recommend_for_book(book_id, user.id(), query.limit)
+        .await
+        .map(RecommendationsResponse::from)
+        .map(Json)
+}
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/users/me/recommendations",
+        params(
+            ("limit" = Option<usize>, Query, description = "推薦する蔵書の数 (1から50、既定値は10)"),
+        ),
+        responses(
+            (status = 200, description = "自分が借りた蔵書と一緒に借りられた蔵書や、著者またはタグが共通する蔵書。自分が借りたことのある蔵書は含まず、推薦の強い順に並ぶ。", body = RecommendationsResponse),
+            (status = 400, description = "クエリの値に不備があった場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "get recommendations",
+    skip(user, registry),
+    fields(
+        user_id = %user.user.id.to_string(),
+    )
+)]
+pub async fn get_recommendations(
+    user: AuthorizedUser,
+    Query(query): Query<RecommendationQuery>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Json<RecommendationsResponse>> {
+    query.validate(&())?;
+
+    registry
+        .recommendation_service()
+        .recommend_for_user(user.id(), query.limit)
+        .await
+        .map(RecommendationsResponse::from)
+        .map(Json)
+}
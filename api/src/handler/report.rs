@@ -0,0 +1,235 @@
+use axum::extract::{Query, State};
+use axum::response::Response;
+use garde::Validate;
+
+use registry::AppRegistry;
+use shared::error::{AppError, AppResult};
+
+use crate::extractor::AuthorizedUser;
+use crate::model::report::{
+    CheckoutsPerMonthResponse, LoanDurationResponse, MostActiveBorrowersResponse,
+    MostBorrowedBooksResponse, ReportFormatQuery, ReportQuery, UtilisationResponse,
+};
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/reports/most-borrowed-books",
+        params(
+            ("limit" = i64, Query, description = "返す蔵書の数 (1から100、既定値は10)"),
+            ("since" = Option<DateTime<Utc>>, Query, description = "この日時以降に貸出した貸出のみ集計"),
+            ("until" = Option<DateTime<Utc>>, Query, description = "この日時より前に貸出した貸出のみ集計"),
+            ("format" = Option<ReportFormat>, Query, description = "レスポンスの形式 (既定値はjson)"),
+        ),
+        responses(
+            (status = 200, description = "集計に成功した場合。蔵書は貸出回数の多い順に並ぶ。", body = MostBorrowedBooksResponse),
+            (status = 400, description = "クエリに指定された値に不備があった場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 403, description = "管理者以外がアクセスした場合。"),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "show most borrowed books",
+    skip(user, registry),
+    fields(
+        user_id = %user.user.id.to_string(),
+    )
+)]
+pub async fn show_most_borrowed_books(
+    user: AuthorizedUser,
+    Query(query): Query<ReportQuery>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Response> {
+    // ユーザーが管理者の場合のみ許可
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenOperation);
+    }
+
+    query.validate(&())?;
+
+    let report = registry
+        .report_repository()
+        .most_borrowed_books(query.period(), query.limit)
+        .await
+        .map(MostBorrowedBooksResponse::from)?;
+    query.format.respond("most-borrowed-books", report)
+}
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/reports/most-active-borrowers",
+        params(
+            ("limit" = i64, Query, description = "返すユーザーの数 (1から100、既定値は10)"),
+            ("since" = Option<DateTime<Utc>>, Query, description = "この日時以降に貸出した貸出のみ集計"),
+            ("until" = Option<DateTime<Utc>>, Query, description = "この日時より前に貸出した貸出のみ集計"),
+            ("format" = Option<ReportFormat>, Query, description = "レスポンスの形式 (既定値はjson)"),
+        ),
+        responses(
+            (status = 200, description = "集計に成功した場合。ユーザーは貸出回数の多い順に並ぶ。", body = MostActiveBorrowersResponse),
+            (status = 400, description = "クエリに指定された値に不備があった場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 403, description = "管理者以外がアクセスした場合。"),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "show most active borrowers",
+    skip(user, registry),
+    fields(
+        user_id = %user.user.id.to_string(),
+    )
+)]
+pub async fn show_most_active_borrowers(
+    user: AuthorizedUser,
+    Query(query): Query<ReportQuery>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Response> {
+    // ユーザーが管理者の場合のみ許可
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenOperation);
+    }
+
+    query.validate(&())?;
+
+    let report = registry
+        .report_repository()
+        .most_active_borrowers(query.period(), query.limit)
+        .await
+        .map(MostActiveBorrowersResponse::from)?;
+    query.format.respond("most-active-borrowers", report)
+}
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/reports/loan-duration",
+        params(
+            ("since" = Option<DateTime<Utc>>, Query, description = "この日時以降に貸出した貸出のみ集計"),
+            ("until" = Option<DateTime<Utc>>, Query, description = "この日時より前に貸出した貸出のみ集計"),
+            ("format" = Option<ReportFormat>, Query, description = "レスポンスの形式 (既定値はjson)"),
+        ),
+        responses(
+            (status = 200, description = "返却済みの貸出の平均貸出期間の集計に成功した場合。", body = LoanDurationResponse),
+            (status = 400, description = "クエリに指定された値に不備があった場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 403, description = "管理者以外がアクセスした場合。"),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "show loan duration",
+    skip(user, registry),
+    fields(
+        user_id = %user.user.id.to_string(),
+    )
+)]
+pub async fn show_loan_duration(
+    user: AuthorizedUser,
+    Query(query): Query<ReportQuery>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Response> {
+    // ユーザーが管理者の場合のみ許可
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenOperation);
+    }
+
+    query.validate(&())?;
+
+    let report = registry
+        .report_repository()
+        .average_loan_duration(query.period())
+        .await
+        .map(LoanDurationResponse::from)?;
+    query.format.respond("loan-duration", report)
+}
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/reports/utilisation",
+        params(
+            ("format" = Option<ReportFormat>, Query, description = "レスポンスの形式 (既定値はjson)"),
+        ),
+        responses(
+            (status = 200, description = "現在の複本の利用状況の集計に成功した場合。", body = UtilisationResponse),
+            (status = 400, description = "クエリに指定された値に不備があった場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 403, description = "管理者以外がアクセスした場合。"),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "show utilisation",
+    skip(user, registry),
+    fields(
+        user_id = %user.user.id.to_string(),
+    )
+)]
+pub async fn show_utilisation(
+    user: AuthorizedUser,
+    Query(query): Query<ReportFormatQuery>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Response> {
+    // ユーザーが管理者の場合のみ許可
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenOperation);
+    }
+
+    let report = registry
+        .report_repository()
+        .utilisation()
+        .await
+        .map(UtilisationResponse::from)?;
+    query.format.respond("utilisation", report)
+}
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/reports/checkouts-per-month",
+        params(
+            ("since" = Option<DateTime<Utc>>, Query, description = "この日時以降に貸出した貸出のみ集計"),
+            ("until" = Option<DateTime<Utc>>, Query, description = "この日時より前に貸出した貸出のみ集計"),
+            ("format" = Option<ReportFormat>, Query, description = "レスポンスの形式 (既定値はjson)"),
+        ),
+        responses(
+            (status = 200, description = "集計に成功した場合。月は古い順に並び、貸出のない月は含まない。", body = CheckoutsPerMonthResponse),
+            (status = 400, description = "クエリに指定された値に不備があった場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 403, description = "管理者以外がアクセスした場合。"),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "show checkouts per month",
+    skip(user, registry),
+    fields(
+        user_id = %user.user.id.to_string(),
+    )
+)]
+pub async fn show_checkouts_per_month(
+    user: AuthorizedUser,
+    Query(query): Query<ReportQuery>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Response> {
+    // ユーザーが管理者の場合のみ許可
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenOperation);
+    }
+
+    query.validate(&())?;
+
+    let report = registry
+        .report_repository()
+        .checkouts_per_month(query.period())
+        .await
+        .map(CheckoutsPerMonthResponse::from)?;
+    query.format.respond("checkouts-per-month", report)
+}
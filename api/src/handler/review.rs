@@ -0,0 +1,172 @@
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::Json;
+use garde::Validate;
+
+use kernel::model::id::{BookId, ReviewId};
+use kernel::model::review::event::{CreateReview, DeleteReview, UpdateReview};
+use registry::AppRegistry;
+use shared::error::AppResult;
+
+use crate::extractor::AuthorizedUser;
+use crate::model::review::{
+    CreateReviewRequest, CreateReviewRequestWithIds, CreateReviewResponse, ReviewsResponse,
+    UpdateReviewRequest, UpdateReviewRequestWithIds,
+};
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        post,
+        path = "/api/v1/books/{book_id}/reviews",
+        params(
+            ("book_id" = Uuid, Path, description = "レビューを書く蔵書ID"),
+        ),
+        request_body = CreateReviewRequest,
+        responses(
+            (status = 201, description = "レビューの作成に成功した場合。", body = CreateReviewResponse),
+            (status = 400, description = "パスで指定された蔵書IDまたはリクエストボディの内容に不備がある場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 403, description = "蔵書を借りて返却したことがない場合。"),
+            (status = 404, description = "パスで指定された蔵書IDを持つ蔵書が存在しない場合。"),
+            (status = 409, description = "蔵書のレビューをすでに書いている場合。"),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "create review",
+    skip(user, registry, body),
+    fields(
+        user_id = %user.user.id.to_string(),
+    )
+)]
+pub async fn create_review(
+    user: AuthorizedUser,
+    Path(book_id): Path<BookId>,
+    State(registry): State<AppRegistry>,
+    Json(body): Json<CreateReviewRequest>,
+) -> AppResult<(StatusCode, Json<CreateReviewResponse>)> {
+    body.validate(&())?;
+
+    let request = CreateReviewRequestWithIds::new(book_id, user.id(), body);
+    registry
+        .review_repository()
+        .create(CreateReview::from(request))
+        .await
+        .map(|id| (StatusCode::CREATED, Json(CreateReviewResponse { id })))
+}
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/books/{book_id}/reviews",
+        params(
+            ("book_id" = Uuid, Path, description = "蔵書ID"),
+        ),
+        responses(
+            (status = 200, description = "指定した蔵書のレビュー。レビューは書いた日時の新しい順に並ぶ。", body = ReviewsResponse),
+            (status = 400, description = "パスで指定された蔵書IDに不備があった場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "show review list",
+    skip(_user, registry),
+    fields(
+        user_id = %_user.user.id.to_string(),
+    )
+)]
+pub async fn show_review_list(
+    _user: AuthorizedUser,
+    Path(book_id): Path<BookId>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Json<ReviewsResponse>> {
+    registry
+        .review_repository()
+        .find_by_book_id(book_id)
+        .await
+        .map(ReviewsResponse::from)
+        .map(Json)
+}
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        put,
+        path = "/api/v1/books/{book_id}/reviews/{review_id}",
+        params(
+            ("book_id" = Uuid, Path, description = "蔵書ID"),
+            ("review_id" = Uuid, Path, description = "レビューID"),
+        ),
+        request_body = UpdateReviewRequest,
+        responses(
+            (status = 200, description = "レビューの編集に成功した場合。"),
+            (status = 400, description = "パスで指定された蔵書IDまたはレビューID、リクエストボディの内容に不備がある場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 403, description = "レビューを書いたユーザー以外が編集しようとした場合。"),
+            (status = 404, description = "パスで指定された蔵書IDを持つ蔵書の、レビューIDを持つレビューが存在しない場合。"),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "update review",
+    skip(user, registry, body),
+    fields(
+        user_id = %user.user.id.to_string(),
+    )
+)]
+pub async fn update_review(
+    user: AuthorizedUser,
+    Path((book_id, review_id)): Path<(BookId, ReviewId)>,
+    State(registry): State<AppRegistry>,
+    Json(body): Json<UpdateReviewRequest>,
+) -> AppResult<StatusCode> {
+    body.validate(&())?;
+
+    let request = UpdateReviewRequestWithIds::new(book_id, review_id, user.id(), body);
+    registry
+        .review_repository()
+        .update(UpdateReview::from(request))
+        .await
+        .map(|_| StatusCode::OK)
+}
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        delete,
+        path = "/api/v1/books/{book_id}/reviews/{review_id}",
+        params(
+            ("book_id" = Uuid, Path, description = "蔵書ID"),
+            ("review_id" = Uuid, Path, description = "レビューID"),
+        ),
+        responses(
+            (status = 204, description = "レビューの削除に成功した場合。"),
+            (status = 400, description = "パスで指定された蔵書IDまたはレビューIDに不備があった場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 403, description = "レビューを書いたユーザー以外の管理者でないユーザーが削除しようとした場合。"),
+            (status = 404, description = "パスで指定された蔵書IDを持つ蔵書の、レビューIDを持つレビューが存在しない場合。"),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "delete review",
+    skip(user, registry),
+    fields(
+        user_id = %user.user.id.to_string(),
+    )
+)]
+pub async fn delete_review(
+    user: AuthorizedUser,
+    Path((book_id, review_id)): Path<(BookId, ReviewId)>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<StatusCode> {
+    let event = DeleteReview::new(review_id, book_id, user.id(), user.user.role);
+    registry
+        .review_repository()
+        .delete(event)
+        .await
+        .map(|_| StatusCode::NO_CONTENT)
+}
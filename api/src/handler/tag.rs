@@ -0,0 +1,251 @@
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::Json;
+use garde::Validate;
+
+use kernel::model::id::{BookId, TagId};
+use kernel::model::tag::event::{AttachTag, CreateTag, DeleteTag, DetachTag, UpdateTag};
+use registry::AppRegistry;
+use shared::error::{AppError, AppResult};
+
+use crate::extractor::AuthorizedUser;
+use crate::model::tag::{
+    CreateTagRequest, CreateTagRequestWithUserId, CreateTagResponse, TagsResponse,
+    UpdateTagRequest, UpdateTagRequestWithIds,
+};
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/tags",
+        responses(
+            (status = 200, description = "タグ一覧の取得に成功した場合。", body = TagsResponse),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "show tag list",
+    skip(_user, registry),
+    fields(
+        user_id = %_user.user.id.to_string(),
+    )
+)]
+pub async fn show_tag_list(
+    _user: AuthorizedUser,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Json<TagsResponse>> {
+    registry
+        .tag_repository()
+        .find_all()
+        .await
+        .map(TagsResponse::from)
+        .map(Json)
+}
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        post,
+        path = "/api/v1/tags",
+        request_body = CreateTagRequest,
+        responses(
+            (status = 201, description = "タグの作成に成功した場合。", body = CreateTagResponse),
+            (status = 400, description = "リクエストボディの内容に不備がある場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 403, description = "管理者以外がアクセスした場合。"),
+            (status = 409, description = "同じ名前のタグがすでに存在する場合。"),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "register tag",
+    skip(user, registry, body),
+    fields(
+        user_id = %user.user.id.to_string(),
+    )
+)]
+pub async fn register_tag(
+    user: AuthorizedUser,
+    State(registry): State<AppRegistry>,
+    Json(body): Json<CreateTagRequest>,
+) -> AppResult<(StatusCode, Json<CreateTagResponse>)> {
+    // ユーザーが管理者の場合のみ許可
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenOperation);
+    }
+
+    body.validate(&())?;
+
+    let request = CreateTagRequestWithUserId::new(user.id(), body);
+    registry
+        .tag_repository()
+        .create(CreateTag::from(request))
+        .await
+        .map(|id| (StatusCode::CREATED, Json(CreateTagResponse { id })))
+}
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        put,
+        path = "/api/v1/tags/{tag_id}",
+        params(
+            ("tag_id" = Uuid, Path, description = "タグID"),
+        ),
+        request_body = UpdateTagRequest,
+        responses(
+            (status = 200, description = "タグ名の変更に成功した場合。"),
+            (status = 400, description = "パスで指定されたタグIDまたはリクエストボディの内容に不備がある場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 403, description = "管理者以外がアクセスした場合。"),
+            (status = 404, description = "パスで指定されたタグが存在しない場合。"),
+            (status = 409, description = "同じ名前のタグがすでに存在する場合。"),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "update tag",
+    skip(user, registry, body),
+    fields(
+        user_id = %user.user.id.to_string(),
+    )
+)]
+pub async fn update_tag(
+    user: AuthorizedUser,
+    Path(tag_id): Path<TagId>,
+    State(registry): State<AppRegistry>,
+    Json(body): Json<UpdateTagRequest>,
+) -> AppResult<StatusCode> {
+    // ユーザーが管理者の場合のみ許可
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenOperation);
+    }
+
+    body.validate(&())?;
+
+    let request = UpdateTagRequestWithIds::new(tag_id, user.id(), body);
+    registry
+        .tag_repository()
+        .update(UpdateTag::from(request))
+        .await
+        .map(|_| StatusCode::OK)
+}
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        delete,
+        path = "/api/v1/tags/{tag_id}",
+        params(
+            ("tag_id" = Uuid, Path, description = "タグID"),
+        ),
+        responses(
+            (status = 204, description = "タグの削除に成功した場合。"),
+            (status = 400, description = "パスで指定されたタグIDに不備がある場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 403, description = "管理者以外がアクセスした場合。"),
+            (status = 404, description = "パスで指定されたタグが存在しない場合。"),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "delete tag",
+    skip(user, registry),
+    fields(
+        user_id = %user.user.id.to_string(),
+    )
+)]
+pub async fn delete_tag(
+    user: AuthorizedUser,
+    Path(tag_id): Path<TagId>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<StatusCode> {
+    // ユーザーが管理者の場合のみ許可
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenOperation);
+    }
+
+    registry
+        .tag_repository()
+        .delete(DeleteTag::new(tag_id, user.id()))
+        .await
+        .map(|_| StatusCode::NO_CONTENT)
+}
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        put,
+        path = "/api/v1/books/{book_id}/tags/{tag_id}",
+        params(
+            ("book_id" = Uuid, Path, description = "蔵書ID"),
+            ("tag_id" = Uuid, Path, description = "タグID"),
+        ),
+        responses(
+            (status = 200, description = "蔵書にタグを付けた場合。すでに付いていた場合も含む。"),
+            (status = 400, description = "パスで指定された蔵書IDまたはタグIDに不備がある場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 403, description = "蔵書の所有者または管理者以外がアクセスした場合。"),
+            (status = 404, description = "パスで指定された蔵書またはタグが存在しない場合。"),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "attach tag",
+    skip(user, registry),
+    fields(
+        user_id = %user.user.id.to_string(),
+    )
+)]
+pub async fn attach_tag(
+    user: AuthorizedUser,
+    Path((book_id, tag_id)): Path<(BookId, TagId)>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<StatusCode> {
+    let event = AttachTag::new(book_id, tag_id, user.id(), user.user.role);
+    registry
+        .tag_repository()
+        .attach(event)
+        .await
+        .map(|_| StatusCode::OK)
+}
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        delete,
+        path = "/api/v1/books/{book_id}/tags/{tag_id}",
+        params(
+            ("book_id" = Uuid, Path, description = "蔵書ID"),
+            ("tag_id" = Uuid, Path, description = "タグID"),
+        ),
+        responses(
+            (status = 204, description = "蔵書からタグを外した場合。"),
+            (status = 400, description = "パスで指定された蔵書IDまたはタグIDに不備がある場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 403, description = "蔵書の所有者または管理者以外がアクセスした場合。"),
+            (status = 404, description = "パスで指定された蔵書またはタグが存在しないか、蔵書にタグが付いていない場合。"),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "detach tag",
+    skip(user, registry),
+    fields(
+        user_id = %user.user.id.to_string(),
+    )
+)]
+pub async fn detach_tag(
+    user: AuthorizedUser,
+    Path((book_id, tag_id)): Path<(BookId, TagId)>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<StatusCode> {
+    let event = DetachTag::new(book_id, tag_id, user.id(), user.user.role);
+    registry
+        .tag_repository()
+        .detach(event)
+        .await
+        .map(|_| StatusCode::NO_CONTENT)
+}
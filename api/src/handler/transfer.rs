@@ -0,0 +1,184 @@
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::Json;
+
+use kernel::model::id::{BookId, BookTransferId};
+use kernel::model::transfer::event::{
+    AcceptBookTransfer, CreateBookTransfer, DeleteBookTransfer, ForceBookTransfer,
+};
+use registry::AppRegistry;
+use shared::error::{AppError, AppResult};
+
+use crate::extractor::AuthorizedUser;
+use crate::model::transfer::{BookTransfersResponse, CreateBookTransferRequest};
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        post,
+        path = "/api/v1/books/{book_id}/transfers",
+        params(
+            ("book_id" = Uuid, Path, description = "譲渡する蔵書ID"),
+        ),
+        request_body = CreateBookTransferRequest,
+        responses(
+            (status = 201, description = "蔵書の譲渡の申し込みに成功した場合。"),
+            (status = 204, description = "管理者が`force`を指定して、蔵書の所有者の変更に成功した場合。"),
+            (status = 400, description = "パスで指定された蔵書IDに不備があった場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 403, description = "蔵書の所有者以外が申し込んだ場合や、管理者以外が`force`を指定した場合。"),
+            (status = 404, description = "指定された蔵書または譲渡先のユーザーが存在しない場合。"),
+            (status = 409, description = "蔵書の譲渡をすでに申し込んでいる場合。"),
+            (status = 422, description = "譲渡先のユーザーが蔵書の所有者の場合。"),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "create book transfer",
+    skip(user, registry),
+    fields(
+        user_id = %user.user.id.to_string(),
+    )
+)]
+pub async fn create_book_transfer(
+    user: AuthorizedUser,
+    Path(book_id): Path<BookId>,
+    State(registry): State<AppRegistry>,
+    Json(req): Json<CreateBookTransferRequest>,
+) -> AppResult<StatusCode> {
+    if req.force {
+        // ユーザーが管理者の場合のみ許可
+        if !user.is_admin() {
+            return Err(AppError::ForbiddenOperation);
+        }
+
+        return registry
+            .book_transfer_repository()
+            .force(ForceBookTransfer {
+                book_id,
+                to_user: req.to_user_id,
+                requested_user: user.id(),
+            })
+            .await
+            .map(|_| StatusCode::NO_CONTENT);
+    }
+
+    registry
+        .book_transfer_repository()
+        .create(CreateBookTransfer {
+            book_id,
+            to_user: req.to_user_id,
+            requested_user: user.id(),
+        })
+        .await
+        .map(|_| StatusCode::CREATED)
+}
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/books/transfers",
+        responses(
+            (status = 200, description = "ユーザーが申し込んだ譲渡と、ユーザーへの譲渡の一覧。", body = BookTransfersResponse),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "show book transfer list",
+    skip(user, registry),
+    fields(
+        user_id = %user.user.id.to_string(),
+    )
+)]
+pub async fn show_book_transfer_list(
+    user: AuthorizedUser,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Json<BookTransfersResponse>> {
+    registry
+        .book_transfer_repository()
+        .find_by_user_id(user.id())
+        .await
+        .map(BookTransfersResponse::from)
+        .map(Json)
+}
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        post,
+        path = "/api/v1/books/transfers/{transfer_id}/accept",
+        params(
+            ("transfer_id" = Uuid, Path, description = "譲渡ID"),
+        ),
+        responses(
+            (status = 204, description = "譲渡を承諾して、蔵書の所有者になった場合。"),
+            (status = 400, description = "パスで指定された譲渡IDに不備があった場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 403, description = "譲渡先のユーザー以外が承諾しようとした場合。"),
+            (status = 404, description = "パスで指定された譲渡IDを持つ譲渡が存在しない場合。"),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "accept book transfer",
+    skip(user, registry),
+    fields(
+        user_id = %user.user.id.to_string(),
+    )
+)]
+pub async fn accept_book_transfer(
+    user: AuthorizedUser,
+    Path(transfer_id): Path<BookTransferId>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<StatusCode> {
+    registry
+        .book_transfer_repository()
+        .accept(AcceptBookTransfer {
+            transfer_id,
+            requested_user: user.id(),
+        })
+        .await
+        .map(|_| StatusCode::NO_CONTENT)
+}
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        delete,
+        path = "/api/v1/books/transfers/{transfer_id}",
+        params(
+            ("transfer_id" = Uuid, Path, description = "譲渡ID"),
+        ),
+        responses(
+            (status = 204, description = "譲渡の辞退または取り消しに成功した場合。"),
+            (status = 400, description = "パスで指定された譲渡IDに不備があった場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 403, description = "譲渡に関係しない管理者でないユーザーが取り消そうとした場合。"),
+            (status = 404, description = "パスで指定された譲渡IDを持つ譲渡が存在しない場合。"),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "delete book transfer",
+    skip(user, registry),
+    fields(
+        user_id = %user.user.id.to_string(),
+    )
+)]
+pub async fn delete_book_transfer(
+    user: AuthorizedUser,
+    Path(transfer_id): Path<BookTransferId>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<StatusCode> {
+    registry
+        .book_transfer_repository()
+        .delete(DeleteBookTransfer {
+            transfer_id,
+            requested_user: user.id(),
+            requested_role: user.user.role,
+        })
+        .await
+        .map(|_| StatusCode::NO_CONTENT)
+}
@@ -1,18 +1,28 @@
-use axum::extract::{Path, State};
+use axum::extract::{Path, Query, State};
 use axum::http::StatusCode;
+use axum::response::Response;
 use axum::Json;
+use axum_extra::headers::IfNoneMatch;
+use axum_extra::TypedHeader;
 use garde::Validate;
 
+use kernel::model::checkout::CheckoutListOptions;
 use kernel::model::id::UserId;
-use kernel::model::user::event::{CreateUser, DeleteUser, UpdateUserPassword, UpdateUserRole};
+use kernel::model::user::event::{
+    CreateUser, DeleteUser, UpdateUserPassword, UpdateUserPrivacy, UpdateUserRole,
+};
+use kernel::model::user::User;
 use registry::AppRegistry;
 use shared::error::{AppError, AppResult};
 
-use crate::extractor::AuthorizedUser;
-use crate::model::checkout::CheckoutsResponse;
+use crate::extractor::{AuthorizedUser, IfMatchHeader};
+use crate::handler::precondition::{json_with_etag, verify_if_match, IfMatch};
+use crate::model::checkout::{CheckoutListQuery, CheckoutStateName, PaginatedCheckoutResponse};
 use crate::model::user::{
-    CreateUserRequest, UpdateUserPasswordRequest, UpdateUserPasswordRequestWithUserId,
-    UpdateUserRoleRequest, UpdateUserRoleRequestWithUserId, UserResponse, UsersResponse,
+    CreateUserRequest, CreateUserRequestWithUserId, DeleteUserQuery, UpdateUserPasswordRequest,
+    UpdateUserPasswordRequestWithUserId, UpdateUserPrivacyRequest,
+    UpdateUserPrivacyRequestWithUserId, UpdateUserRoleRequest, UpdateUserRoleRequestWithUserIds,
+    UserResponse, UsersResponse,
 };
 
 #[cfg_attr(
@@ -20,8 +30,13 @@ use crate::model::user::{
     utoipa::path(
         get,
         path = "/api/v1/users/me",
+        params(
+            ("If-None-Match" = Option<String>, Header, description = "以前に取得したユーザーの`ETag`"),
+        ),
         responses(
-            (status = 200, description = "ログインしているユーザーの情報を取得できた場合。", body = UserResponse),
+            (status = 200, description = "ログインしているユーザーの情報を取得できた場合。", body = UserResponse,
+                headers(("ETag" = String, description = "ユーザーのエンティティタグ"))),
+            (status = 304, description = "`If-None-Match`に指定された`ETag`からユーザーが変わっていない場合。"),
             (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
         )
     )
@@ -33,8 +48,73 @@ use crate::model::user::{
         user_id = %user.user.id.to_string(),
     )
 )]
-pub async fn get_current_user(user: AuthorizedUser) -> Json<UserResponse> {
-    Json(UserResponse::from(user.user))
+pub async fn get_current_user(
+    user: AuthorizedUser,
+    if_none_match: Option<TypedHeader<IfNoneMatch>>,
+) -> AppResult<Response> {
+    json_with_etag(
+        user.user.version,
+        UserResponse::from(user.user),
+        if_none_match,
+    )
+}
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/users/{user_id}",
+        params(
+            ("user_id" = Uuid, Path, description = "取得するユーザーのユーザーID。"),
+            ("If-None-Match" = Option<String>, Header, description = "以前に取得したユーザーの`ETag`"),
+        ),
+        responses(
+            (status = 200, description = "指定されたユーザーの取得に成功した場合。", body = UserResponse,
+                headers(("ETag" = String, description = "ユーザーのエンティティタグ"))),
+            (status = 304, description = "`If-None-Match`に指定された`ETag`からユーザーが変わっていない場合。"),
+            (status = 400, description = "パスで指定されたユーザーIDに不備がある場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 404, description = "パスで指定されたユーザーIDを持つユーザーが存在しない場合。"),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "show user",
+    skip(_user, registry),
+    fields(
+        user_id = %_user.user.id.to_string(),
+    )
+)]
+pub async fn show_user(
+    _user: AuthorizedUser,
+    Path(user_id): Path<UserId>,
+    State(registry): State<AppRegistry>,
+    if_none_match: Option<TypedHeader<IfNoneMatch>>,
+) -> AppResult<Response> {
+    let user = find_user(&registry, user_id).await?;
+    json_with_etag(user.version, UserResponse::from(user), if_none_match)
+}
+
+async fn find_user(registry: &AppRegistry, user_id: UserId) -> AppResult<User> {
+    registry
+        .user_repository()
+        .find_current_user(user_id)
+        .await?
+        .ok_or_else(|| AppError::EntityNotFound("specified user not found".into()))
+}
+
+/// `If-Match`が指定された場合は、現在のユーザーのバージョンと一致するか確認し、
+/// 確認したユーザーのバージョンを返す。
+async fn expected_user_version(
+    registry: &AppRegistry,
+    user_id: UserId,
+    if_match: Option<IfMatch>,
+) -> AppResult<Option<i64>> {
+    let Some(if_match) = if_match else {
+        return Ok(None);
+    };
+    let user = find_user(registry, user_id).await?;
+    verify_if_match(&if_match, user.version).map(Some)
 }
 
 #[cfg_attr(
@@ -106,7 +186,10 @@ pub async fn register_user(
 
     let registered_user = registry
         .user_repository()
-        .create(CreateUser::from(body))
+        .create(CreateUser::from(CreateUserRequestWithUserId::new(
+            user.id(),
+            body,
+        )))
         .await?;
 
     Ok(Json(UserResponse::from(registered_user)))
@@ -117,11 +200,15 @@ pub async fn register_user(
     utoipa::path(
         put,
         path = "/api/v1/users/me/password",
+        params(
+            ("If-Match" = Option<String>, Header, description = "ユーザーを取得したときの`ETag`。指定した場合、ユーザーが更新されていれば412を返す"),
+        ),
         request_body = UpdateUserPasswordRequest,
         responses(
             (status = 200, description = "ユーザーのパスワードの変更に成功した場合。"),
             (status = 400, description = "リクエストボディに不備があった場合。"),
             (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 412, description = "`If-Match`に指定された`ETag`を取得した後に、ユーザーが変更された場合。"),
             (status = 422, description = "ユーザーのパスワードの記録に失敗した場合。"),
         )
     )
@@ -136,11 +223,18 @@ pub async fn register_user(
 pub async fn change_password(
     user: AuthorizedUser,
     State(registry): State<AppRegistry>,
+    IfMatchHeader(if_match): IfMatchHeader,
     Json(body): Json<UpdateUserPasswordRequest>,
 ) -> AppResult<StatusCode> {
     body.validate(&())?;
 
-    let request = UpdateUserPasswordRequestWithUserId::new(user.id(), body);
+    // ログインしているユーザーは認証時に取得しているため、改めて取得せずに確認する。
+    let user_id = user.id();
+    let expected_version = match if_match {
+        Some(if_match) => Some(verify_if_match(&if_match, user.user.version)?),
+        None => None,
+    };
+    let request = UpdateUserPasswordRequestWithUserId::new(user_id, expected_version, body);
 
     registry
         .user_repository()
@@ -149,6 +243,50 @@ pub async fn change_password(
     Ok(StatusCode::OK)
 }
 
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        put,
+        path = "/api/v1/users/me/privacy",
+        params(
+            ("If-Match" = Option<String>, Header, description = "ユーザーを取得したときの`ETag`。指定した場合、ユーザーが更新されていれば412を返す"),
+        ),
+        request_body = UpdateUserPrivacyRequest,
+        responses(
+            (status = 200, description = "プライバシー設定の変更に成功した場合。履歴を残さない設定にしても、それまでに記録した貸出の監査ログは残る。"),
+            (status = 400, description = "リクエストボディに不備があった場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 412, description = "`If-Match`に指定された`ETag`を取得した後に、ユーザーが変更された場合。"),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "change user privacy",
+    skip(user, registry, body),
+    fields(
+        user_id = %user.user.id.to_string(),
+    )
+)]
+pub async fn change_privacy(
+    user: AuthorizedUser,
+    State(registry): State<AppRegistry>,
+    IfMatchHeader(if_match): IfMatchHeader,
+    Json(body): Json<UpdateUserPrivacyRequest>,
+) -> AppResult<StatusCode> {
+    let user_id = user.id();
+    let expected_version = match if_match {
+        Some(if_match) => Some(verify_if_match(&if_match, user.user.version)?),
+        None => None,
+    };
+    let request = UpdateUserPrivacyRequestWithUserId::new(user_id, expected_version, body);
+
+    registry
+        .user_repository()
+        .update_privacy(UpdateUserPrivacy::from(request))
+        .await?;
+    Ok(StatusCode::OK)
+}
+
 #[cfg_attr(
     debug_assertions,
     utoipa::path(
@@ -156,6 +294,7 @@ pub async fn change_password(
         path = "/api/v1/users/{user_id}/role",
         params(
             ("user_id" = Uuid, Path, description = "ロールを変更するユーザーのユーザーID。"),
+            ("If-Match" = Option<String>, Header, description = "ユーザーを取得したときの`ETag`。指定した場合、ユーザーが更新されていれば412を返す"),
         ),
         request_body = UpdateUserRoleRequest,
         responses(
@@ -164,6 +303,7 @@ pub async fn change_password(
             (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
             (status = 403, description = "管理者以外がアクセスした場合。"),
             (status = 404, description = "パスで指定されたユーザーIDを持つユーザーが存在しない場合。"),
+            (status = 412, description = "`If-Match`に指定された`ETag`を取得した後に、ユーザーが変更された場合。"),
             (status = 422, description = "ユーザーのロールの記録に失敗した場合。"),
         )
 
@@ -171,7 +311,9 @@ pub async fn change_password(
 )]
 pub async fn change_role(
     user: AuthorizedUser,
+    Path(user_id): Path<UserId>,
     State(registry): State<AppRegistry>,
+    IfMatchHeader(if_match): IfMatchHeader,
     Json(body): Json<UpdateUserRoleRequest>,
 ) -> AppResult<StatusCode> {
     // ユーザーが管理者の場合のみ許可
@@ -179,7 +321,8 @@ pub async fn change_role(
         return Err(AppError::ForbiddenOperation);
     }
 
-    let request = UpdateUserRoleRequestWithUserId::new(user.id(), body);
+    let expected_version = expected_user_version(&registry, user_id, if_match).await?;
+    let request = UpdateUserRoleRequestWithUserIds::new(user_id, user.id(), expected_version, body);
 
     registry
         .user_repository()
@@ -196,6 +339,8 @@ pub async fn change_role(
         path = "/api/v1/users/{user_id}",
         params(
             ("user_id" = Uuid, Path, description = "削除するユーザーのユーザーID。"),
+            ("reassignTo" = Option<Uuid>, Query, description = "削除するユーザーが所有する蔵書の譲渡先のユーザーID。蔵書を所有している場合は必須"),
+            ("If-Match" = Option<String>, Header, description = "ユーザーを取得したときの`ETag`。指定した場合、ユーザーが更新されていれば412を返す"),
         ),
         responses(
             (status = 204, description = "ユーザーの削除に成功した場合。"),
@@ -203,7 +348,8 @@ pub async fn change_role(
             (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
             (status = 403, description = "管理者以外がアクセスした場合。"),
             (status = 404, description = "パスで指定されたユーザーIDを持つユーザーが存在しない場合。"),
-            (status = 422, description = "ユーザーの削除に失敗した場合。"),
+            (status = 412, description = "`If-Match`に指定された`ETag`を取得した後に、ユーザーが変更された場合。"),
+            (status = 422, description = "蔵書を所有するユーザーの譲渡先が指定されていないか、譲渡先のユーザーが存在しない場合など、ユーザーの削除に失敗した場合。"),
         )
     )
 )]
@@ -217,16 +363,24 @@ pub async fn change_role(
 pub async fn delete_user(
     user: AuthorizedUser,
     Path(user_id): Path<UserId>,
+    Query(query): Query<DeleteUserQuery>,
     State(registry): State<AppRegistry>,
+    IfMatchHeader(if_match): IfMatchHeader,
 ) -> AppResult<StatusCode> {
     // ユーザーが管理者の場合のみ場一項可能
     if !user.is_admin() {
         return Err(AppError::ForbiddenOperation);
     }
 
+    let expected_version = expected_user_version(&registry, user_id, if_match).await?;
     registry
         .user_repository()
-        .delete_user(DeleteUser { user_id })
+        .delete_user(DeleteUser {
+            user_id,
+            requested_user: user.id(),
+            reassign_to: query.reassign_to,
+            expected_version,
+        })
         .await?;
 
     Ok(StatusCode::NO_CONTENT)
@@ -237,8 +391,16 @@ pub async fn delete_user(
     utoipa::path(
         get,
         path = "/api/v1/users/me/checkouts",
+        params(
+            ("limit" = Option<i64>, Query, description = "一度に取得する貸出数の上限値の指定"),
+            ("offset" = Option<i64>, Query, description = "取得対象とする貸出一覧の開始位置"),
+            ("state" = Option<CheckoutStateName>, Query, description = "返却状態。省略した場合は`unreturned`"),
+            ("since" = Option<DateTime<Utc>>, Query, description = "この日時以降に貸し出した貸出"),
+            ("until" = Option<DateTime<Utc>>, Query, description = "この日時より前に貸し出した貸出"),
+        ),
         responses(
-            (status = 200, description = "ユーザーが借りている蔵書の一覧の取得に成功した場合。", body = CheckoutsResponse),
+            (status = 200, description = "ユーザーが借りている蔵書の一覧の取得に成功した場合。", body = PaginatedCheckoutResponse),
+            (status = 400, description = "指定されたクエリの値に不備があった場合。"),
             (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
         )
     )
@@ -252,12 +414,112 @@ pub async fn delete_user(
 )]
 pub async fn get_checkouts(
     user: AuthorizedUser,
+    Query(query): Query<CheckoutListQuery>,
     State(registry): State<AppRegistry>,
-) -> AppResult<Json<CheckoutsResponse>> {
+) -> AppResult<Json<PaginatedCheckoutResponse>> {
+    query.validate(&())?;
+
+    // 借りたユーザーは、指定されていてもリクエストしたユーザーとする。
+    let options = CheckoutListOptions {
+        borrower: Some(user.id()),
+        ..query.into_options(CheckoutStateName::Unreturned)
+    };
+    registry
+        .checkout_repository()
+        .find_all(options)
+        .await
+        .map(PaginatedCheckoutResponse::from)
+        .map(Json)
+}
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/users/me/checkout-history",
+        params(
+            ("limit" = Option<i64>, Query, description = "一度に取得する貸出数の上限値の指定"),
+            ("offset" = Option<i64>, Query, description = "取得対象とする貸出履歴の開始位置"),
+            ("state" = Option<CheckoutStateName>, Query, description = "返却状態。省略した場合は`all`"),
+            ("since" = Option<DateTime<Utc>>, Query, description = "この日時以降に貸し出した貸出"),
+            ("until" = Option<DateTime<Utc>>, Query, description = "この日時より前に貸し出した貸出"),
+        ),
+        responses(
+            (status = 200, description = "ユーザーの返却済みを含む貸出履歴。", body = PaginatedCheckoutResponse),
+            (status = 400, description = "指定されたクエリの値に不備があった場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "get checkout history by users",
+    skip(user, registry),
+    fields(
+        user_id = %user.user.id.to_string(),
+    )
+)]
+pub async fn get_checkout_history(
+    user: AuthorizedUser,
+    Query(query): Query<CheckoutListQuery>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Json<PaginatedCheckoutResponse>> {
+    query.validate(&())?;
+
+    registry
+        .checkout_repository()
+        .find_history_by_user_id(user.id(), query.into_options(CheckoutStateName::All))
+        .await
+        .map(PaginatedCheckoutResponse::from)
+        .map(Json)
+}
+
+#[cfg_attr(
+    debug_assertions,
+    utoipa::path(
+        get,
+        path = "/api/v1/users/{user_id}/checkout-history",
+        params(
+            ("user_id" = Uuid, Path, description = "ユーザーID"),
+            ("limit" = Option<i64>, Query, description = "一度に取得する貸出数の上限値の指定"),
+            ("offset" = Option<i64>, Query, description = "取得対象とする貸出履歴の開始位置"),
+            ("state" = Option<CheckoutStateName>, Query, description = "返却状態。省略した場合は`all`"),
+            ("since" = Option<DateTime<Utc>>, Query, description = "この日時以降に貸し出した貸出"),
+            ("until" = Option<DateTime<Utc>>, Query, description = "この日時より前に貸し出した貸出"),
+        ),
+        responses(
+            (status = 200, description = "指定したユーザーの返却済みを含む貸出履歴。", body = PaginatedCheckoutResponse),
+            (status = 400, description = "パスで指定したユーザーIDや、クエリの値に不備があった場合。"),
+            (status = 401, description = "認証されていないユーザーがアクセスした場合。"),
+            (status = 403, description = "管理者以外がアクセスした場合。"),
+            (status = 404, description = "パスで指定したユーザーIDを持つユーザーが存在しない場合。"),
+        )
+    )
+)]
+#[tracing::instrument(
+    name = "show user checkout history",
+    skip(user, registry),
+    fields(
+        user_id = %user.user.id.to_string(),
+    )
+)]
+pub async fn show_user_checkout_history(
+    user: AuthorizedUser,
+    Path(user_id): Path<UserId>,
+    Query(query): Query<CheckoutListQuery>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Json<PaginatedCheckoutResponse>> {
+    // ユーザーが管理者の場合のみ許可
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenOperation);
+    }
+
+    query.validate(&())?;
+
+    let user = find_user(&registry, user_id).await?;
     registry
         .checkout_repository()
-        .find_unreturned_by_user_id(user.id())
+        .find_history_by_user_id(user.id, query.into_options(CheckoutStateName::All))
         .await
-        .map(CheckoutsResponse::from)
+        .map(PaginatedCheckoutResponse::from)
         .map(Json)
 }
@@ -0,0 +1,179 @@
+use chrono::{DateTime, Utc};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+#[cfg(debug_assertions)]
+use utoipa::ToSchema;
+
+use kernel::model::audit::{AuditEntityType, AuditEvent, AuditEventListOptions};
+use kernel::model::id::{AuditEventId, UserId};
+use kernel::model::list::PaginatedList;
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEventListQuery {
+    #[garde(range(min = 0))]
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+    #[garde(range(min = 0))]
+    #[serde(default)]
+    pub offset: i64,
+    #[garde(skip)]
+    pub actor_id: Option<UserId>,
+    #[garde(skip)]
+    pub entity_type: Option<AuditEntityTypeName>,
+    #[garde(length(min = 1, max = 255))]
+    pub entity_id: Option<String>,
+    #[garde(skip)]
+    pub since: Option<DateTime<Utc>>,
+    #[garde(skip)]
+    pub until: Option<DateTime<Utc>>,
+}
+
+const DEFAULT_LIMIT: i64 = 50;
+const fn default_limit() -> i64 {
+    DEFAULT_LIMIT
+}
+
+impl From<AuditEventListQuery> for AuditEventListOptions {
+    fn from(value: AuditEventListQuery) -> Self {
+        let AuditEventListQuery {
+            limit,
+            offset,
+            actor_id,
+            entity_type,
+            entity_id,
+            since,
+            until,
+        } = value;
+        Self {
+            limit,
+            offset,
+            actor: actor_id,
+            entity_type: entity_type.map(AuditEntityType::from),
+            entity_id,
+            since,
+            until,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum AuditEntityTypeName {
+    Book,
+    BookCopy,
+    Tag,
+    User,
+    Checkout,
+    Hold,
+    LoanPolicy,
+    BookTransfer,
+    Review,
+}
+
+impl From<AuditEntityTypeName> for AuditEntityType {
+    fn from(value: AuditEntityTypeName) -> Self {
+        match value {
+            AuditEntityTypeName::Book => AuditEntityType::Book,
+            AuditEntityTypeName::BookCopy => AuditEntityType::BookCopy,
+            AuditEntityTypeName::Tag => AuditEntityType::Tag,
+            AuditEntityTypeName::User => AuditEntityType::User,
+            AuditEntityTypeName::Checkout => AuditEntityType::Checkout,
+            AuditEntityTypeName::Hold => AuditEntityType::Hold,
+            AuditEntityTypeName::LoanPolicy => AuditEntityType::LoanPolicy,
+            AuditEntityTypeName::BookTransfer => AuditEntityType::BookTransfer,
+            AuditEntityTypeName::Review => AuditEntityType::Review,
+        }
+    }
+}
+
+impl From<AuditEntityType> for AuditEntityTypeName {
+    fn from(value: AuditEntityType) -> Self {
+        match value {
+            AuditEntityType::Book => AuditEntityTypeName::Book,
+            AuditEntityType::BookCopy => AuditEntityTypeName::BookCopy,
+            AuditEntityType::Tag => AuditEntityTypeName::Tag,
+            AuditEntityType::User => AuditEntityTypeName::User,
+            AuditEntityType::Checkout => AuditEntityTypeName::Checkout,
+            AuditEntityType::Hold => AuditEntityTypeName::Hold,
+            AuditEntityType::LoanPolicy => AuditEntityTypeName::LoanPolicy,
+            AuditEntityType::BookTransfer => AuditEntityTypeName::BookTransfer,
+            AuditEntityType::Review => AuditEntityTypeName::Review,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEventResponse {
+    pub id: AuditEventId,
+    /// 操作したユーザーのユーザーID (ログインの失敗など、認証されていない操作の場合は`null`)
+    pub actor_id: Option<UserId>,
+    /// 操作 (`create_book`、`update_user_role`、`login`など)
+    pub action: String,
+    pub entity_type: AuditEntityTypeName,
+    /// 変更した対象のID (貸出ポリシーの場合はロール名)
+    pub entity_id: String,
+    /// 変更前の対象 (作成した場合は`null`)
+    #[cfg_attr(debug_assertions, schema(value_type = Option<Object>))]
+    pub before: Option<serde_json::Value>,
+    /// 変更後の対象 (削除した場合は`null`)
+    #[cfg_attr(debug_assertions, schema(value_type = Option<Object>))]
+    pub after: Option<serde_json::Value>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+impl From<AuditEvent> for AuditEventResponse {
+    fn from(value: AuditEvent) -> Self {
+        let AuditEvent {
+            id,
+            actor,
+            action,
+            entity_type,
+            entity_id,
+            before,
+            after,
+            occurred_at,
+        } = value;
+        Self {
+            id,
+            actor_id: actor,
+            action: action.as_ref().to_string(),
+            entity_type: AuditEntityTypeName::from(entity_type),
+            entity_id,
+            before,
+            after,
+            occurred_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct PaginatedAuditEventResponse {
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+    pub items: Vec<AuditEventResponse>,
+}
+
+impl From<PaginatedList<AuditEvent>> for PaginatedAuditEventResponse {
+    fn from(value: PaginatedList<AuditEvent>) -> Self {
+        let PaginatedList {
+            total,
+            limit,
+            offset,
+            items,
+            ..
+        } = value;
+        Self {
+            total,
+            limit,
+            offset,
+            items: items.into_iter().map(AuditEventResponse::from).collect(),
+        }
+    }
+}
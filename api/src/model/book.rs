@@ -1,3 +1,5 @@
+use std::str::FromStr;
+
 use chrono::{DateTime, Utc};
 use derive_new::new;
 use garde::Validate;
@@ -5,12 +7,23 @@ use serde::{Deserialize, Serialize};
 #[cfg(debug_assertions)]
 use utoipa::ToSchema;
 
-use kernel::model::book::event::{CreateBook, UpdateBook};
-use kernel::model::book::{Book, BookListOptions, Checkout};
-use kernel::model::id::{BookId, CheckoutId, UserId};
-use kernel::model::list::PaginatedList;
+use kernel::model::book::event::{
+    CreateBook, CreateBookCopy, PatchBook, UpdateBook, UpdateBookCopy,
+};
+use kernel::model::book::{
+    Book, BookCheckoutState, BookCopy, BookCopyCondition, BookField, BookFieldChange, BookFields,
+    BookImportMode, BookImportOutcome, BookListCursor, BookListOptions, BookRevision, BookSort,
+    BookSortKey, Checkout, DeletedBook, DeletedBookListOptions, Isbn,
+};
+use kernel::model::id::{BookCopyId, BookId, CheckoutId, UserId};
+use kernel::model::list::{PaginatedList, SortDirection};
+use kernel::model::role::Role;
+use kernel::model::tag::{TagFilter, TagMatch};
+use shared::error::{AppError, AppResult};
 
-use crate::model::user::{BookOwner, CheckoutUser};
+use crate::model::cover::cover_url;
+use crate::model::tag::TagResponse;
+use crate::model::user::{BookOwner, BookReviser, CheckoutUser};
 
 #[derive(Debug, Deserialize, Validate)]
 #[cfg_attr(debug_assertions, derive(ToSchema))]
@@ -20,20 +33,28 @@ pub struct CreateBookRequest {
     pub title: String,
     #[garde(length(min = 1))]
     pub author: String,
-    #[garde(length(min = 1))]
+    /// ISBN-10またはISBN-13 (ハイフンは省略できる)
+    #[garde(custom(validate_isbn))]
     pub isbn: String,
     #[garde(skip)]
     pub description: String,
+    /// 同じISBNを持つ蔵書がすでに存在しても登録するか
+    #[garde(skip)]
+    #[serde(default)]
+    pub allow_duplicate: bool,
 }
 
-impl From<CreateBookRequest> for CreateBook {
-    fn from(value: CreateBookRequest) -> Self {
-        Self {
+impl TryFrom<CreateBookRequest> for CreateBook {
+    type Error = AppError;
+
+    fn try_from(value: CreateBookRequest) -> Result<Self, Self::Error> {
+        Ok(Self {
             title: value.title,
             author: value.author,
-            isbn: value.isbn,
+            isbn: value.isbn.parse()?,
             description: value.description,
-        }
+            allow_duplicate: value.allow_duplicate,
+        })
     }
 }
 
@@ -45,39 +66,123 @@ pub struct UpdateBookRequest {
     pub title: String,
     #[garde(length(min = 1))]
     pub author: String,
-    #[garde(length(min = 1))]
+    /// ISBN-10またはISBN-13 (ハイフンは省略できる)
+    #[garde(custom(validate_isbn))]
     pub isbn: String,
     #[garde(skip)]
     pub description: String,
+    /// 同じISBNを持つ他の蔵書がすでに存在しても更新するか
+    #[garde(skip)]
+    #[serde(default)]
+    pub allow_duplicate: bool,
 }
 
 #[derive(new)]
-pub struct UpdateBookRequestWithIds(BookId, UserId, UpdateBookRequest);
+pub struct UpdateBookRequestWithIds(BookId, UserId, Role, Option<i64>, UpdateBookRequest);
 
-impl From<UpdateBookRequestWithIds> for UpdateBook {
-    fn from(value: UpdateBookRequestWithIds) -> Self {
+impl TryFrom<UpdateBookRequestWithIds> for UpdateBook {
+    type Error = AppError;
+
+    fn try_from(value: UpdateBookRequestWithIds) -> Result<Self, Self::Error> {
         let UpdateBookRequestWithIds(
             book_id,
             user_id,
+            role,
+            expected_version,
             UpdateBookRequest {
                 title,
                 author,
                 isbn,
                 description,
+                allow_duplicate,
             },
         ) = value;
-        Self {
+        Ok(Self {
             book_id,
             title,
             author,
-            isbn,
+            isbn: isbn.parse()?,
             description,
+            allow_duplicate,
             requested_user: user_id,
-        }
+            requested_role: role,
+            expected_version,
+        })
     }
 }
 
+/// 蔵書の一部の項目を更新するときにハンドラーで受け取るデータの型
+///
+/// 省略した項目や`null`を指定した項目は更新しない。
+#[derive(Debug, Deserialize, Validate)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct PatchBookRequest {
+    #[garde(length(min = 1))]
+    pub title: Option<String>,
+    #[garde(length(min = 1))]
+    pub author: Option<String>,
+    /// ISBN-10またはISBN-13 (ハイフンは省略できる)
+    #[garde(custom(validate_optional_isbn))]
+    pub isbn: Option<String>,
+    #[garde(skip)]
+    pub description: Option<String>,
+    /// 同じISBNを持つ他の蔵書がすでに存在しても更新するか (ISBNを指定した場合のみ使用)
+    #[garde(skip)]
+    #[serde(default)]
+    pub allow_duplicate: bool,
+}
+
+#[derive(new)]
+pub struct PatchBookRequestWithIds(BookId, UserId, Role, Option<i64>, PatchBookRequest);
+
+impl TryFrom<PatchBookRequestWithIds> for PatchBook {
+    type Error = AppError;
+
+    fn try_from(value: PatchBookRequestWithIds) -> Result<Self, Self::Error> {
+        let PatchBookRequestWithIds(
+            book_id,
+            user_id,
+            role,
+            expected_version,
+            PatchBookRequest {
+                title,
+                author,
+                isbn,
+                description,
+                allow_duplicate,
+            },
+        ) = value;
+        Ok(Self {
+            book_id,
+            title,
+            author,
+            isbn: isbn.map(|isbn| isbn.parse()).transpose()?,
+            description,
+            allow_duplicate,
+            requested_user: user_id,
+            requested_role: role,
+            expected_version,
+        })
+    }
+}
+
+/// ISBN-10またはISBN-13として正しいチェックディジットを持つか検証する。
+fn validate_isbn(value: &str, _context: &()) -> garde::Result {
+    Isbn::from_str(value)
+        .map(|_| ())
+        .map_err(|e| garde::Error::new(e.to_string()))
+}
+
+/// ISBNが指定された場合のみ、`validate_isbn`と同様に検証する。
+fn validate_optional_isbn(value: &Option<String>, context: &()) -> garde::Result {
+    value
+        .as_deref()
+        .map_or(Ok(()), |isbn| validate_isbn(isbn, context))
+}
+
 #[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
 pub struct BookListQuery {
     #[garde(range(min = 0))]
     #[serde(default = "default_limit")] // default_limit関数を参照
@@ -85,6 +190,23 @@ pub struct BookListQuery {
     #[garde(range(min = 0))]
     #[serde(default)] // デフォルトは0
     pub offset: i64,
+    #[garde(length(min = 1, max = 255))]
+    pub q: Option<String>,
+    #[garde(skip)]
+    pub owner_id: Option<UserId>,
+    #[garde(skip)]
+    pub checkout_state: Option<BookCheckoutStateName>,
+    #[garde(skip)]
+    pub sort: Option<BookSortKeyName>,
+    #[garde(skip)]
+    pub order: Option<SortDirectionName>,
+    #[garde(skip)]
+    pub cursor: Option<BookListCursor>,
+    /// カンマ区切りのタグ名
+    #[garde(length(min = 1, max = 1024))]
+    pub tags: Option<String>,
+    #[garde(skip)]
+    pub tag_match: Option<TagMatchName>,
 }
 
 const DEFAULT_LIMIT: i64 = 20;
@@ -94,9 +216,131 @@ const fn default_limit() -> i64 {
 
 impl From<BookListQuery> for BookListOptions {
     fn from(value: BookListQuery) -> Self {
+        let BookListQuery {
+            limit,
+            offset,
+            q,
+            owner_id,
+            checkout_state,
+            sort,
+            order,
+            cursor,
+            tags,
+            tag_match,
+        } = value;
+        // カーソルが指定された場合は、カーソルを発行したときの並び順に従う。
+        // 並べ替えキーが指定されなかった場合は、検索語が指定されていれば関連度順、
+        // 指定されていなければ登録日時の降順に並べる。
+        let sort = match &cursor {
+            Some(cursor) => cursor.sort,
+            None => {
+                let key = match (sort, &q) {
+                    (Some(key), _) => BookSortKey::from(key),
+                    (None, Some(_)) => BookSortKey::Relevance,
+                    (None, None) => BookSortKey::CreatedAt,
+                };
+                BookSort {
+                    key,
+                    direction: order
+                        .map(SortDirection::from)
+                        .unwrap_or(key.default_direction()),
+                }
+            }
+        };
         Self {
-            limit: value.limit,
-            offset: value.offset,
+            limit,
+            offset,
+            sort,
+            cursor,
+            query: q,
+            owner: owner_id,
+            checkout_state: checkout_state.map(BookCheckoutState::from),
+            tags: tags.and_then(|tags| {
+                let names: Vec<String> = tags
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|name| !name.is_empty())
+                    .map(String::from)
+                    .collect();
+                (!names.is_empty()).then(|| TagFilter {
+                    names,
+                    match_mode: tag_match.map(TagMatch::from).unwrap_or_default(),
+                })
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum TagMatchName {
+    All,
+    Any,
+}
+
+impl From<TagMatchName> for TagMatch {
+    fn from(value: TagMatchName) -> Self {
+        match value {
+            TagMatchName::All => TagMatch::All,
+            TagMatchName::Any => TagMatch::Any,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum BookCheckoutStateName {
+    Available,
+    CheckedOut,
+}
+
+impl From<BookCheckoutStateName> for BookCheckoutState {
+    fn from(value: BookCheckoutStateName) -> Self {
+        match value {
+            BookCheckoutStateName::Available => BookCheckoutState::Available,
+            BookCheckoutStateName::CheckedOut => BookCheckoutState::CheckedOut,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum BookSortKeyName {
+    Title,
+    Author,
+    CreatedAt,
+    UpdatedAt,
+    Rating,
+}
+
+impl From<BookSortKeyName> for BookSortKey {
+    fn from(value: BookSortKeyName) -> Self {
+        match value {
+            BookSortKeyName::Title => BookSortKey::Title,
+            BookSortKeyName::Author => BookSortKey::Author,
+            BookSortKeyName::CreatedAt => BookSortKey::CreatedAt,
+            BookSortKeyName::UpdatedAt => BookSortKey::UpdatedAt,
+            BookSortKeyName::Rating => BookSortKey::Rating,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum SortDirectionName {
+    Asc,
+    Desc,
+}
+
+impl From<SortDirectionName> for SortDirection {
+    fn from(value: SortDirectionName) -> Self {
+        match value {
+            SortDirectionName::Asc => SortDirection::Asc,
+            SortDirectionName::Desc => SortDirection::Desc,
         }
     }
 }
@@ -111,7 +355,17 @@ pub struct BookResponse {
     pub isbn: String,
     pub description: String,
     pub owner: BookOwner,
-    pub checkout: Option<BookCheckoutResponse>,
+    /// 紛失していない複本の数
+    pub total_copies: i64,
+    /// 貸出できる複本の数
+    pub available_copies: i64,
+    pub checkouts: Vec<BookCheckoutResponse>,
+    pub tags: Vec<TagResponse>,
+    /// 表紙画像のURL (表紙画像が登録されていない場合は`null`)
+    pub cover_url: Option<String>,
+    /// レビューの評価の平均 (レビューがない場合は`null`)
+    pub average_rating: Option<f64>,
+    pub review_count: i64,
 }
 
 impl From<Book> for BookResponse {
@@ -123,7 +377,17 @@ impl From<Book> for BookResponse {
             isbn: value.isbn,
             description: value.description,
             owner: BookOwner::from(value.owner),
-            checkout: value.checkout.map(BookCheckoutResponse::from),
+            total_copies: value.total_copies,
+            available_copies: value.available_copies,
+            checkouts: value
+                .checkouts
+                .into_iter()
+                .map(BookCheckoutResponse::from)
+                .collect(),
+            tags: value.tags.into_iter().map(TagResponse::from).collect(),
+            cover_url: value.cover_etag.map(|etag| cover_url(value.id, &etag)),
+            average_rating: value.average_rating,
+            review_count: value.review_count,
         }
     }
 }
@@ -135,6 +399,8 @@ pub struct PaginatedBookResponse {
     pub total: i64,
     pub limit: i64,
     pub offset: i64,
+    /// 次のページを取得するときに`cursor`クエリに指定する値
+    pub next_cursor: Option<String>,
     pub items: Vec<BookResponse>,
 }
 
@@ -144,37 +410,850 @@ impl From<PaginatedList<Book>> for PaginatedBookResponse {
             total,
             limit,
             offset,
+            next_cursor,
             items,
         } = value;
         Self {
             total,
             limit,
             offset,
+            next_cursor,
             items: items.into_iter().map(BookResponse::from).collect(),
         }
     }
 }
 
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct DeletedBookListQuery {
+    #[garde(range(min = 0))]
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+    #[garde(range(min = 0))]
+    #[serde(default)]
+    pub offset: i64,
+}
+
+impl From<DeletedBookListQuery> for DeletedBookListOptions {
+    fn from(value: DeletedBookListQuery) -> Self {
+        let DeletedBookListQuery { limit, offset } = value;
+        Self { limit, offset }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct DeletedBookResponse {
+    pub id: BookId,
+    pub title: String,
+    pub author: String,
+    pub isbn: String,
+    pub description: String,
+    pub owner: BookOwner,
+    /// 蔵書を削除した日時
+    pub deleted_at: DateTime<Utc>,
+}
+
+impl From<DeletedBook> for DeletedBookResponse {
+    fn from(value: DeletedBook) -> Self {
+        let DeletedBook {
+            id,
+            title,
+            author,
+            isbn,
+            description,
+            owner,
+            deleted_at,
+        } = value;
+        Self {
+            id,
+            title,
+            author,
+            isbn,
+            description,
+            owner: BookOwner::from(owner),
+            deleted_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct PaginatedDeletedBookResponse {
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+    pub items: Vec<DeletedBookResponse>,
+}
+
+impl From<PaginatedList<DeletedBook>> for PaginatedDeletedBookResponse {
+    fn from(value: PaginatedList<DeletedBook>) -> Self {
+        let PaginatedList {
+            total,
+            limit,
+            offset,
+            items,
+            ..
+        } = value;
+        Self {
+            total,
+            limit,
+            offset,
+            items: items.into_iter().map(DeletedBookResponse::from).collect(),
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 #[cfg_attr(debug_assertions, derive(ToSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct BookCheckoutResponse {
     pub id: CheckoutId,
+    pub copy_id: BookCopyId,
     pub checked_out_by: CheckoutUser,
     pub checked_out_at: DateTime<Utc>,
+    pub due_at: DateTime<Utc>,
+    /// 返却期限を過ぎているか
+    pub overdue: bool,
+    pub renewal_count: i32,
 }
 
 impl From<Checkout> for BookCheckoutResponse {
     fn from(value: Checkout) -> Self {
+        let overdue = value.is_overdue(Utc::now());
         let Checkout {
             checkout_id,
+            copy_id,
             checked_out_by,
             checked_out_at,
+            due_at,
+            renewal_count,
         } = value;
         Self {
             id: checkout_id,
+            copy_id,
             checked_out_by: checked_out_by.into(),
             checked_out_at,
+            due_at,
+            overdue,
+            renewal_count,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum BookCopyConditionName {
+    #[default]
+    Good,
+    Fair,
+    Damaged,
+    Lost,
+}
+
+impl From<BookCopyCondition> for BookCopyConditionName {
+    fn from(value: BookCopyCondition) -> Self {
+        match value {
+            BookCopyCondition::Good => Self::Good,
+            BookCopyCondition::Fair => Self::Fair,
+            BookCopyCondition::Damaged => Self::Damaged,
+            BookCopyCondition::Lost => Self::Lost,
+        }
+    }
+}
+
+impl From<BookCopyConditionName> for BookCopyCondition {
+    fn from(value: BookCopyConditionName) -> Self {
+        match value {
+            BookCopyConditionName::Good => Self::Good,
+            BookCopyConditionName::Fair => Self::Fair,
+            BookCopyConditionName::Damaged => Self::Damaged,
+            BookCopyConditionName::Lost => Self::Lost,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct BookCopyResponse {
+    pub id: BookCopyId,
+    pub barcode: String,
+    pub condition: BookCopyConditionName,
+    /// 配架場所
+    pub location: String,
+    /// 貸出中か
+    pub checked_out: bool,
+}
+
+impl From<BookCopy> for BookCopyResponse {
+    fn from(value: BookCopy) -> Self {
+        let BookCopy {
+            id,
+            barcode,
+            condition,
+            location,
+            checked_out,
+            ..
+        } = value;
+        Self {
+            id,
+            barcode,
+            condition: BookCopyConditionName::from(condition),
+            location,
+            checked_out,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct BookCopiesResponse {
+    pub items: Vec<BookCopyResponse>,
+}
+
+impl From<Vec<BookCopy>> for BookCopiesResponse {
+    fn from(value: Vec<BookCopy>) -> Self {
+        let items = value.into_iter().map(BookCopyResponse::from).collect();
+        Self { items }
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct CreateBookCopyRequest {
+    #[garde(length(min = 1))]
+    pub barcode: String,
+    #[garde(skip)]
+    #[serde(default)]
+    pub condition: BookCopyConditionName,
+    #[garde(skip)]
+    #[serde(default)]
+    pub location: String,
+}
+
+#[derive(new)]
+pub struct CreateBookCopyRequestWithIds(BookId, UserId, Role, CreateBookCopyRequest);
+
+impl From<CreateBookCopyRequestWithIds> for CreateBookCopy {
+    fn from(value: CreateBookCopyRequestWithIds) -> Self {
+        let CreateBookCopyRequestWithIds(
+            book_id,
+            user_id,
+            role,
+            CreateBookCopyRequest {
+                barcode,
+                condition,
+                location,
+            },
+        ) = value;
+        Self {
+            book_id,
+            barcode,
+            condition: condition.into(),
+            location,
+            requested_user: user_id,
+            requested_role: role,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateBookCopyRequest {
+    #[garde(length(min = 1))]
+    pub barcode: String,
+    #[garde(skip)]
+    pub condition: BookCopyConditionName,
+    #[garde(skip)]
+    pub location: String,
+}
+
+#[derive(new)]
+pub struct UpdateBookCopyRequestWithIds(BookId, BookCopyId, UserId, Role, UpdateBookCopyRequest);
+
+impl From<UpdateBookCopyRequestWithIds> for UpdateBookCopy {
+    fn from(value: UpdateBookCopyRequestWithIds) -> Self {
+        let UpdateBookCopyRequestWithIds(
+            book_id,
+            copy_id,
+            user_id,
+            role,
+            UpdateBookCopyRequest {
+                barcode,
+                condition,
+                location,
+            },
+        ) = value;
+        Self {
+            copy_id,
+            book_id,
+            barcode,
+            condition: condition.into(),
+            location,
+            requested_user: user_id,
+            requested_role: role,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookImportQuery {
+    #[serde(default)]
+    pub mode: BookImportModeName,
+    /// 同じISBNを持つ蔵書がすでに存在しても登録するか
+    ///
+    /// JSON Linesの場合は、各行の`allowDuplicate`の指定よりも優先する。
+    #[serde(default)]
+    pub allow_duplicate: bool,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum BookImportModeName {
+    #[default]
+    Atomic,
+    BestEffort,
+}
+
+impl From<BookImportModeName> for BookImportMode {
+    fn from(value: BookImportModeName) -> Self {
+        match value {
+            BookImportModeName::Atomic => BookImportMode::Atomic,
+            BookImportModeName::BestEffort => BookImportMode::BestEffort,
+        }
+    }
+}
+
+/// 一括登録する蔵書の形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookImportFormat {
+    /// 1行目に`title`、`author`、`isbn`及び`description`の列名を持つCSV
+    Csv,
+    /// 1行に`CreateBookRequest`を1つ記述したJSON Lines
+    JsonLines,
+}
+
+impl BookImportFormat {
+    /// `Content-Type`ヘッダーの値から形式を判定する。
+    pub fn from_content_type(content_type: &str) -> AppResult<Self> {
+        let mime = content_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+        match mime.as_str() {
+            "text/csv" => Ok(Self::Csv),
+            "application/jsonl" | "application/x-ndjson" | "application/x-jsonlines" => {
+                Ok(Self::JsonLines)
+            }
+            _ => Err(AppError::UnprocessableEntity(format!(
+                "unsupported content type: {content_type}"
+            ))),
+        }
+    }
+
+    /// 一括登録する蔵書を読み込み、行ごとに`CreateBookRequest`と同じ規則で検証する。
+    ///
+    /// CSVの構文が誤っている場合は、いずれの行も読み込まずにエラーを返す。
+    pub fn parse(self, body: &str, allow_duplicate: bool) -> AppResult<Vec<BookImportRow>> {
+        let requests = match self {
+            Self::Csv => parse_csv(body)?,
+            Self::JsonLines => body
+                .lines()
+                .enumerate()
+                .filter(|(_, line)| !line.trim().is_empty())
+                .map(|(i, line)| {
+                    let request =
+                        serde_json::from_str::<CreateBookRequest>(line).map_err(|e| e.to_string());
+                    (i + 1, request)
+                })
+                .collect(),
+        };
+        Ok(requests
+            .into_iter()
+            .map(|(row, request)| {
+                let event = request.and_then(|mut request| {
+                    request.allow_duplicate |= allow_duplicate;
+                    request.validate(&()).map_err(|e| e.to_string())?;
+                    CreateBook::try_from(request).map_err(|e| e.to_string())
+                });
+                BookImportRow { row, event }
+            })
+            .collect())
+    }
+}
+
+/// 一括登録する蔵書の1行
+#[derive(Debug)]
+pub struct BookImportRow {
+    /// 行番号 (CSVの場合は列名の行を除いたレコードの番号)
+    pub row: usize,
+    /// 登録する蔵書、または読み込みや検証に失敗した理由
+    pub event: Result<CreateBook, String>,
+}
+
+/// CSVを読み込み、レコードごとに列名と値を対応付けて`CreateBookRequest`に変換する。
+///
+/// 列名は大文字と小文字を区別しない。`description`の列は省略でき、省略した場合は空文字列とする。
+fn parse_csv(body: &str) -> AppResult<Vec<(usize, Result<CreateBookRequest, String>)>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .flexible(true)
+        .from_reader(body.as_bytes());
+    let mut header: csv::StringRecord = reader
+        .headers()
+        .map_err(csv_syntax_error)?
+        .iter()
+        .map(|h| h.trim().to_lowercase())
+        .collect();
+    let has_description = header.iter().any(|h| h == "description");
+    let fields = header.len();
+    if !has_description {
+        header.push_field("description");
+    }
+
+    reader
+        .records()
+        .enumerate()
+        .map(|(i, record)| {
+            let mut record = record.map_err(csv_syntax_error)?;
+            if record.len() != fields {
+                let message = format!("expected {fields} fields, but found {}", record.len());
+                return Ok((i + 1, Err(message)));
+            }
+            if !has_description {
+                record.push_field("");
+            }
+            let request = record
+                .deserialize::<CreateBookRequest>(Some(&header))
+                .map_err(|e| e.to_string());
+            Ok((i + 1, request))
+        })
+        .collect()
+}
+
+fn csv_syntax_error(e: csv::Error) -> AppError {
+    AppError::UnprocessableEntity(format!("the CSV can not be read: {e}"))
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum BookImportRowStatus {
+    Created,
+    Skipped,
+    Failed,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct BookImportRowResponse {
+    pub row: usize,
+    pub status: BookImportRowStatus,
+    /// 登録した蔵書のID
+    pub book_id: Option<BookId>,
+    /// 読み飛ばした理由、または登録に失敗した理由
+    pub message: Option<String>,
+}
+
+impl BookImportRowResponse {
+    fn new(row: usize, outcome: BookImportOutcome) -> Self {
+        let (status, book_id, message) = match outcome {
+            BookImportOutcome::Created(book_id) => {
+                (BookImportRowStatus::Created, Some(book_id), None)
+            }
+            BookImportOutcome::Skipped(message) => {
+                (BookImportRowStatus::Skipped, None, Some(message))
+            }
+            BookImportOutcome::Failed(message) => {
+                (BookImportRowStatus::Failed, None, Some(message))
+            }
+        };
+        Self {
+            row,
+            status,
+            book_id,
+            message,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct BookImportResponse {
+    pub created: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    pub rows: Vec<BookImportRowResponse>,
+}
+
+impl From<Vec<(usize, BookImportOutcome)>> for BookImportResponse {
+    fn from(value: Vec<(usize, BookImportOutcome)>) -> Self {
+        let rows: Vec<_> = value
+            .into_iter()
+            .map(|(row, outcome)| BookImportRowResponse::new(row, outcome))
+            .collect();
+        let count = |status: fn(&BookImportRowStatus) -> bool| {
+            rows.iter().filter(|r| status(&r.status)).count()
+        };
+        Self {
+            created: count(|s| matches!(s, BookImportRowStatus::Created)),
+            skipped: count(|s| matches!(s, BookImportRowStatus::Skipped)),
+            failed: count(|s| matches!(s, BookImportRowStatus::Failed)),
+            rows,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BookExportQuery {
+    #[serde(default)]
+    pub format: BookExportFormat,
+}
+
+/// 蔵書を書き出す形式
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum BookExportFormat {
+    #[default]
+    Csv,
+    Jsonl,
+    /// MARC 21形式のXML (MARCXML) に準じた形式
+    Marcxml,
+}
+
+impl BookExportFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Jsonl => "application/x-ndjson",
+            Self::Marcxml => "application/marcxml+xml",
         }
     }
+
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Self::Csv => "books.csv",
+            Self::Jsonl => "books.jsonl",
+            Self::Marcxml => "books.xml",
+        }
+    }
+
+    /// 蔵書より前に書き出す内容を返す。
+    pub fn header(self) -> AppResult<String> {
+        match self {
+            Self::Csv => csv_record([
+                "id",
+                "title",
+                "author",
+                "isbn",
+                "description",
+                "owner_id",
+                "owner_name",
+                "total_copies",
+                "available_copies",
+                "checked_out_by",
+                "due_at",
+            ]),
+            Self::Jsonl => Ok(String::new()),
+            Self::Marcxml => Ok(concat!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+                "\n",
+                r#"<collection xmlns="http://www.loc.gov/MARC21/slim">"#,
+                "\n"
+            )
+            .into()),
+        }
+    }
+
+    /// 蔵書の後に書き出す内容を返す。
+    pub fn footer(self) -> String {
+        match self {
+            Self::Csv | Self::Jsonl => String::new(),
+            Self::Marcxml => "</collection>\n".into(),
+        }
+    }
+
+    /// 1冊の蔵書を書き出す形式に変換する。
+    pub fn format(self, book: Book) -> AppResult<String> {
+        match self {
+            Self::Csv => {
+                let (checked_out_by, due_at): (Vec<_>, Vec<_>) = book
+                    .checkouts
+                    .iter()
+                    .map(|c| (c.checked_out_by.id.to_string(), c.due_at.to_rfc3339()))
+                    .unzip();
+                csv_record([
+                    &book.id.to_string(),
+                    &book.title,
+                    &book.author,
+                    &book.isbn,
+                    &book.description,
+                    &book.owner.id.to_string(),
+                    &book.owner.name,
+                    &book.total_copies.to_string(),
+                    &book.available_copies.to_string(),
+                    &checked_out_by.join(";"),
+                    &due_at.join(";"),
+                ])
+            }
+            Self::Jsonl => {
+                let mut line = serde_json::to_string(&BookResponse::from(book))
+                    .map_err(|e| AppError::ConversionEntityError(e.to_string()))?;
+                line.push('\n');
+                Ok(line)
+            }
+            Self::Marcxml => Ok(marcxml_record(&book)),
+        }
+    }
+}
+
+/// フィールドを必要に応じて引用符で囲み、CSVの1レコードを作成する。
+fn csv_record<I, T>(fields: I) -> AppResult<String>
+where
+    I: IntoIterator<Item = T>,
+    T: AsRef<[u8]>,
+{
+    let mut writer = csv::WriterBuilder::new()
+        .terminator(csv::Terminator::CRLF)
+        .from_writer(Vec::new());
+    writer
+        .write_record(fields)
+        .map_err(|e| AppError::ConversionEntityError(e.to_string()))?;
+    let record = writer
+        .into_inner()
+        .map_err(|e| AppError::ConversionEntityError(e.to_string()))?;
+    String::from_utf8(record).map_err(|e| AppError::ConversionEntityError(e.to_string()))
+}
+
+/// 蔵書をMARCXMLのレコードに変換する。
+///
+/// 蔵書IDを001、ISBNを020、著者を100、タイトルを245、説明を520、所有者を852、
+/// 貸出中の複本を876の各フィールドに記録する。
+fn marcxml_record(book: &Book) -> String {
+    let subfield = |code: char, value: &str| {
+        format!(
+            r#"    <subfield code="{code}">{}</subfield>"#,
+            escape_xml(value)
+        )
+    };
+    let datafield = |tag: &str, subfields: Vec<String>| {
+        format!(
+            "  <datafield tag=\"{tag}\" ind1=\" \" ind2=\" \">\n{}\n  </datafield>\n",
+            subfields.join("\n")
+        )
+    };
+
+    let mut record = String::from("<record>\n  <leader>00000nam a2200000 a 4500</leader>\n");
+    record.push_str(&format!(
+        "  <controlfield tag=\"001\">{}</controlfield>\n",
+        book.id
+    ));
+    record.push_str(&datafield("020", vec![subfield('a', &book.isbn)]));
+    record.push_str(&datafield("100", vec![subfield('a', &book.author)]));
+    record.push_str(&datafield("245", vec![subfield('a', &book.title)]));
+    if !book.description.is_empty() {
+        record.push_str(&datafield("520", vec![subfield('a', &book.description)]));
+    }
+    record.push_str(&datafield(
+        "852",
+        vec![
+            subfield('a', &book.owner.name),
+            subfield('x', &book.owner.id.to_string()),
+        ],
+    ));
+    for checkout in &book.checkouts {
+        record.push_str(&datafield(
+            "876",
+            vec![
+                subfield('a', &checkout.copy_id.to_string()),
+                subfield('j', "checked out"),
+                subfield('n', &checkout.due_at.to_rfc3339()),
+                subfield('x', &checkout.checked_out_by.id.to_string()),
+            ],
+        ));
+    }
+    record.push_str("</record>\n");
+    record
+}
+
+fn escape_xml(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum BookFieldName {
+    Title,
+    Author,
+    Isbn,
+    Description,
+}
+
+impl From<BookField> for BookFieldName {
+    fn from(value: BookField) -> Self {
+        match value {
+            BookField::Title => Self::Title,
+            BookField::Author => Self::Author,
+            BookField::Isbn => Self::Isbn,
+            BookField::Description => Self::Description,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct BookFieldChangeResponse {
+    pub field: BookFieldName,
+    pub before: String,
+    pub after: String,
+}
+
+impl From<BookFieldChange> for BookFieldChangeResponse {
+    fn from(value: BookFieldChange) -> Self {
+        let BookFieldChange {
+            field,
+            before,
+            after,
+        } = value;
+        Self {
+            field: BookFieldName::from(field),
+            before,
+            after,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct BookRevisionResponse {
+    /// 蔵書ごとに1から始まる連番
+    pub revision_number: i32,
+    /// 更新する前のタイトル
+    pub title: String,
+    /// 更新する前の著者
+    pub author: String,
+    /// 更新する前のISBN
+    pub isbn: String,
+    /// 更新する前の説明
+    pub description: String,
+    /// この更新で変わった項目
+    pub changes: Vec<BookFieldChangeResponse>,
+    pub revised_by: BookReviser,
+    pub revised_at: DateTime<Utc>,
+}
+
+impl From<BookRevision> for BookRevisionResponse {
+    fn from(value: BookRevision) -> Self {
+        let BookRevision {
+            revision_number,
+            fields:
+                BookFields {
+                    title,
+                    author,
+                    isbn,
+                    description,
+                },
+            changes,
+            revised_by,
+            revised_at,
+        } = value;
+        Self {
+            revision_number,
+            title,
+            author,
+            isbn,
+            description,
+            changes: changes
+                .into_iter()
+                .map(BookFieldChangeResponse::from)
+                .collect(),
+            revised_by: BookReviser::from(revised_by),
+            revised_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct BookRevisionsResponse {
+    pub items: Vec<BookRevisionResponse>,
+}
+
+impl From<Vec<BookRevision>> for BookRevisionsResponse {
+    fn from(value: Vec<BookRevision>) -> Self {
+        let items = value.into_iter().map(BookRevisionResponse::from).collect();
+        Self { items }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_book_import_rows() {
+        let body = "\u{feff}Title,Author,ISBN\r\n\"Programming Rust, 2nd\",Jim Blandy,978-1-4920-5259-3\n,Nobody,978-1-4920-5259-3\n\nToo Few Fields\n";
+        let rows = BookImportFormat::Csv.parse(body, true).unwrap();
+        assert_eq!(rows.len(), 3);
+        let event = rows[0].event.as_ref().unwrap();
+        assert_eq!(event.title, "Programming Rust, 2nd");
+        assert_eq!(event.isbn.as_str(), "9781492052593");
+        assert_eq!(event.description, "");
+        assert!(event.allow_duplicate);
+        // タイトルが空の行と列が足りない行は検証に失敗する
+        assert!(rows[1].event.is_err());
+        assert!(rows[2].event.is_err());
+
+        let body = "title,author,isbn,description\nA,B,9781492052593,\"say \"\"hi\"\"\"\n";
+        let rows = BookImportFormat::Csv.parse(body, false).unwrap();
+        let event = rows[0].event.as_ref().unwrap();
+        assert_eq!(event.description, "say \"hi\"");
+        assert!(!event.allow_duplicate);
+
+        let body = "{\"title\":\"A\",\"author\":\"B\",\"isbn\":\"4-06-536957-X\",\"description\":\"\"}\n\n{\"title\":\"A\"}\n";
+        let rows = BookImportFormat::JsonLines.parse(body, false).unwrap();
+        assert_eq!(rows.iter().map(|r| r.row).collect::<Vec<_>>(), vec![1, 3]);
+        assert!(rows.iter().all(|r| r.event.is_err()));
+    }
+
+    #[test]
+    fn test_escape_exported_fields() {
+        assert_eq!(
+            csv_record(["a", "b,c", "say \"hi\"", "line\nbreak"]).unwrap(),
+            "a,\"b,c\",\"say \"\"hi\"\"\",\"line\nbreak\"\r\n"
+        );
+        assert_eq!(
+            escape_xml(r#"<Tom & "Jerry's">"#),
+            "&lt;Tom &amp; &quot;Jerry&apos;s&quot;&gt;"
+        );
+    }
 }
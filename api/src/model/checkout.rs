@@ -1,12 +1,16 @@
 use chrono::{DateTime, Utc};
-use serde::Serialize;
+use garde::Validate;
+use serde::{Deserialize, Serialize};
 #[cfg(debug_assertions)]
 use utoipa::ToSchema;
 
-use kernel::model::checkout::{Checkout, CheckoutBook};
-use kernel::model::id::{BookId, CheckoutId, UserId};
+use kernel::model::checkout::{
+    Checkout, CheckoutBook, CheckoutListOptions, CheckoutOutcome, CheckoutState,
+};
+use kernel::model::id::{BookCopyId, BookId, CheckoutId, UserId};
+use kernel::model::list::PaginatedList;
 
-#[derive(Serialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[cfg_attr(debug_assertions, derive(ToSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct CheckoutBookResponse {
@@ -33,31 +37,49 @@ impl From<CheckoutBook> for CheckoutBookResponse {
     }
 }
 
-#[derive(Serialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[cfg_attr(debug_assertions, derive(ToSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct CheckoutResponse {
     pub id: CheckoutId,
+    /// 貸し出した複本のID (複本が削除された場合は`null`)
+    pub copy_id: Option<BookCopyId>,
     pub checked_out_by: UserId,
     pub checked_out_at: DateTime<Utc>,
+    pub due_at: DateTime<Utc>,
+    /// 返却期限を過ぎているか、返却済みの場合は返却期限を過ぎて返却されたか
+    pub overdue: bool,
+    pub renewal_count: i32,
     pub returned_at: Option<DateTime<Utc>>,
+    /// 貸出の終わり方 (未返却の場合は`null`)
+    pub outcome: Option<CheckoutOutcomeName>,
     pub book: CheckoutBookResponse,
 }
 
 impl From<Checkout> for CheckoutResponse {
     fn from(value: Checkout) -> Self {
+        let overdue = value.is_overdue(Utc::now());
         let Checkout {
             id,
             book,
+            copy_id,
             checked_out_by,
             checked_out_at,
+            due_at,
+            renewal_count,
             returned_at,
+            outcome,
         } = value;
         Self {
             id,
+            copy_id,
             checked_out_by,
             checked_out_at,
+            due_at,
+            overdue,
+            renewal_count,
             returned_at,
+            outcome: outcome.map(CheckoutOutcomeName::from),
             book: CheckoutBookResponse::from(book),
         }
     }
@@ -76,3 +98,138 @@ impl From<Vec<Checkout>> for CheckoutsResponse {
         Self { items }
     }
 }
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct PaginatedCheckoutResponse {
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+    pub items: Vec<CheckoutResponse>,
+}
+
+impl From<PaginatedList<Checkout>> for PaginatedCheckoutResponse {
+    fn from(value: PaginatedList<Checkout>) -> Self {
+        let PaginatedList {
+            total,
+            limit,
+            offset,
+            items,
+            ..
+        } = value;
+        Self {
+            total,
+            limit,
+            offset,
+            items: items.into_iter().map(CheckoutResponse::from).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckoutListQuery {
+    #[garde(range(min = 0))]
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+    #[garde(range(min = 0))]
+    #[serde(default)]
+    pub offset: i64,
+    /// 借りたユーザー
+    #[garde(skip)]
+    pub user_id: Option<UserId>,
+    /// 返却状態 (省略した場合の値はエンドポイントによって異なる)
+    #[garde(skip)]
+    pub state: Option<CheckoutStateName>,
+    #[garde(skip)]
+    pub since: Option<DateTime<Utc>>,
+    #[garde(skip)]
+    pub until: Option<DateTime<Utc>>,
+}
+
+const DEFAULT_LIMIT: i64 = 20;
+const fn default_limit() -> i64 {
+    DEFAULT_LIMIT
+}
+
+impl CheckoutListQuery {
+    /// `state`が省略された場合は、`default_state`の返却状態の貸出を対象とする。
+    pub fn into_options(self, default_state: CheckoutStateName) -> CheckoutListOptions {
+        let CheckoutListQuery {
+            limit,
+            offset,
+            user_id,
+            state,
+            since,
+            until,
+        } = self;
+        CheckoutListOptions {
+            limit,
+            offset,
+            borrower: user_id,
+            book_id: None,
+            state: state.unwrap_or(default_state).into(),
+            since,
+            until,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum CheckoutStateName {
+    /// 返却済みと未返却の両方
+    All,
+    Unreturned,
+    Returned,
+}
+
+impl From<CheckoutStateName> for Option<CheckoutState> {
+    fn from(value: CheckoutStateName) -> Self {
+        match value {
+            CheckoutStateName::All => None,
+            CheckoutStateName::Unreturned => Some(CheckoutState::Unreturned),
+            CheckoutStateName::Returned => Some(CheckoutState::Returned),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum CheckoutOutcomeName {
+    #[default]
+    Returned,
+    Lost,
+    Damaged,
+}
+
+impl From<CheckoutOutcome> for CheckoutOutcomeName {
+    fn from(value: CheckoutOutcome) -> Self {
+        match value {
+            CheckoutOutcome::Returned => Self::Returned,
+            CheckoutOutcome::Lost => Self::Lost,
+            CheckoutOutcome::Damaged => Self::Damaged,
+        }
+    }
+}
+
+impl From<CheckoutOutcomeName> for CheckoutOutcome {
+    fn from(value: CheckoutOutcomeName) -> Self {
+        match value {
+            CheckoutOutcomeName::Returned => Self::Returned,
+            CheckoutOutcomeName::Lost => Self::Lost,
+            CheckoutOutcomeName::Damaged => Self::Damaged,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ReturnBookRequest {
+    #[serde(default)]
+    pub outcome: CheckoutOutcomeName,
+}
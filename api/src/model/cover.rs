@@ -0,0 +1,238 @@
+use serde::{Deserialize, Serialize};
+#[cfg(debug_assertions)]
+use utoipa::ToSchema;
+
+use kernel::model::book::{CoverImageFormat, CoverSize, MAX_COVER_BYTES};
+use kernel::model::id::BookId;
+use shared::error::{AppError, AppResult};
+
+/// 表紙画像を登録するときの、multipart/form-dataのフィールド名
+pub const COVER_FIELD_NAME: &str = "cover";
+
+/// 表紙画像を登録するリクエストボディの上限 (バイト)
+///
+/// multipart/form-dataの境界やヘッダーの分だけ、表紙画像の上限より大きくする。
+pub const COVER_UPLOAD_BODY_LIMIT: usize = MAX_COVER_BYTES + 64 * 1024;
+
+/// 蔵書の表紙画像を取得するURL
+///
+/// 表紙画像を置き換えるとURLも変わるように、ETagをクエリに含める。
+pub fn cover_url(book_id: BookId, etag: &str) -> String {
+    format!("/api/v1/books/{book_id}/cover?v={etag}")
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookCoverQuery {
+    #[serde(default)]
+    pub size: CoverSizeName,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum CoverSizeName {
+    #[default]
+    Original,
+    Thumbnail,
+}
+
+impl From<CoverSizeName> for CoverSize {
+    fn from(value: CoverSizeName) -> Self {
+        match value {
+            CoverSizeName::Original => CoverSize::Original,
+            CoverSizeName::Thumbnail => CoverSize::Thumbnail,
+        }
+    }
+}
+
+/// multipart/form-dataで送信された表紙画像
+#[derive(Debug)]
+pub struct CoverUpload {
+    pub format: CoverImageFormat,
+    pub data: Vec<u8>,
+}
+
+impl CoverUpload {
+    /// multipart/form-dataのリクエストボディから`cover`フィールドの画像を取り出し、形式と大きさを検証する。
+    pub fn from_multipart(content_type: &str, body: &[u8]) -> AppResult<Self> {
+        let unprocessable = |message: &str| AppError::UnprocessableEntity(message.into());
+
+        let mut params = content_type.split(';').map(str::trim);
+        if !params
+            .next()
+            .unwrap_or_default()
+            .eq_ignore_ascii_case("multipart/form-data")
+        {
+            return Err(AppError::UnprocessableEntity(format!(
+                "unsupported content type: {content_type}"
+            )));
+        }
+        let boundary = params
+            .find_map(|p| parameter(p, "boundary"))
+            .ok_or_else(|| unprocessable("missing multipart boundary"))?;
+
+        let (headers, data) = multipart_fields(body, boundary)?
+            .into_iter()
+            .find(|(headers, _)| {
+                header(headers, "content-disposition")
+                    .and_then(|v| v.split(';').find_map(|p| parameter(p.trim(), "name")))
+                    == Some(COVER_FIELD_NAME)
+            })
+            .ok_or_else(|| unprocessable("missing `cover` field"))?;
+
+        let format = header(headers, "content-type")
+            .and_then(|v| v.parse::<CoverImageFormat>().ok())
+            .ok_or_else(|| unprocessable("the cover image must be image/jpeg or image/png"))?;
+        if data.is_empty() {
+            return Err(unprocessable("the cover image is empty"));
+        }
+        if data.len() > MAX_COVER_BYTES {
+            return Err(AppError::PayloadTooLarge(format!(
+                "the cover image must be {MAX_COVER_BYTES} bytes or less"
+            )));
+        }
+
+        Ok(Self {
+            format,
+            data: data.to_vec(),
+        })
+    }
+}
+
+/// `key=value`または`key="value"`の形式のパラメーターから値を取り出す。
+fn parameter<'a>(param: &'a str, key: &str) -> Option<&'a str> {
+    let (k, v) = param.split_once('=')?;
+    k.trim()
+        .eq_ignore_ascii_case(key)
+        .then(|| v.trim().trim_matches('"'))
+}
+
+fn header<'a>(headers: &'a str, name: &str) -> Option<&'a str> {
+    headers.split("\r\n").find_map(|line| {
+        let (k, v) = line.split_once(':')?;
+        k.trim().eq_ignore_ascii_case(name).then(|| v.trim())
+    })
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+/// multipart/form-dataのリクエストボディを、フィールドごとのヘッダーと内容に分ける。
+fn multipart_fields<'a>(body: &'a [u8], boundary: &str) -> AppResult<Vec<(&'a str, &'a [u8])>> {
+    let malformed = || AppError::UnprocessableEntity("malformed multipart body".into());
+    let delimiter = format!("\r\n--{boundary}");
+
+    // 最初の境界の前の改行は省略できる。
+    let start = find(body, &delimiter.as_bytes()[2..]).ok_or_else(malformed)?;
+    let mut rest = &body[start + delimiter.len() - 2..];
+    let mut fields = Vec::new();
+    loop {
+        if rest.starts_with(b"--") {
+            return Ok(fields);
+        }
+        rest = rest.strip_prefix(b"\r\n").ok_or_else(malformed)?;
+        let end = find(rest, delimiter.as_bytes()).ok_or_else(malformed)?;
+        let part = &rest[..end];
+        rest = &rest[end + delimiter.len()..];
+
+        let (headers, content) = match part.strip_prefix(b"\r\n") {
+            Some(content) => (&[][..], content),
+            None => {
+                let at = find(part, b"\r\n\r\n").ok_or_else(malformed)?;
+                (&part[..at], &part[at + 4..])
+            }
+        };
+        let headers = std::str::from_utf8(headers).map_err(|_| malformed())?;
+        fields.push((headers, content));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+
+    fn multipart(fields: &[(&str, &str, &[u8])]) -> Vec<u8> {
+        let mut body = Vec::new();
+        for (name, content_type, data) in fields {
+            body.extend_from_slice(b"--XyZ\r\n");
+            body.extend_from_slice(
+                format!(
+                    "Content-Disposition: form-data; name=\"{name}\"; filename=\"cover\"\r\n\
+                     Content-Type: {content_type}\r\n\r\n"
+                )
+                .as_bytes(),
+            );
+            body.extend_from_slice(data);
+            body.extend_from_slice(b"\r\n");
+        }
+        body.extend_from_slice(b"--XyZ--\r\n");
+        body
+    }
+
+    #[test]
+    fn test_parse_cover_upload() {
+        let content_type = "multipart/form-data; boundary=XyZ";
+
+        // 他のフィールドを無視し、`cover`フィールドの画像を取り出す
+        let body = multipart(&[
+            ("note", "text/plain", b"hello"),
+            ("cover", "image/png", PNG),
+        ]);
+        let upload = CoverUpload::from_multipart(content_type, &body).unwrap();
+        assert_eq!(upload.format, CoverImageFormat::Png);
+        assert_eq!(upload.data, PNG);
+
+        // 境界は引用符で囲んでもよい
+        let upload =
+            CoverUpload::from_multipart("multipart/form-data; boundary=\"XyZ\"", &body).unwrap();
+        assert_eq!(upload.data, PNG);
+
+        // 画像の内容に改行や境界に似た文字列を含んでもよい
+        let data = b"\xFF\xD8\xFF\r\n--Xy\r\n\r\n";
+        let body = multipart(&[("cover", "image/jpeg", data)]);
+        let upload = CoverUpload::from_multipart(content_type, &body).unwrap();
+        assert_eq!(upload.format, CoverImageFormat::Jpeg);
+        assert_eq!(upload.data, data);
+    }
+
+    #[test]
+    fn test_reject_invalid_cover_upload() {
+        let content_type = "multipart/form-data; boundary=XyZ";
+        let unprocessable = |content_type: &str, body: &[u8]| {
+            matches!(
+                CoverUpload::from_multipart(content_type, body),
+                Err(AppError::UnprocessableEntity(_))
+            )
+        };
+
+        let body = multipart(&[("cover", "image/png", PNG)]);
+        assert!(unprocessable("image/png", &body));
+        assert!(unprocessable("multipart/form-data", &body));
+        assert!(unprocessable(content_type, &body[..body.len() - 12]));
+        assert!(unprocessable(
+            content_type,
+            &multipart(&[("image", "image/png", PNG)])
+        ));
+        assert!(unprocessable(
+            content_type,
+            &multipart(&[("cover", "image/gif", b"GIF89a")])
+        ));
+        assert!(unprocessable(
+            content_type,
+            &multipart(&[("cover", "image/png", b"")])
+        ));
+
+        let large = vec![0; MAX_COVER_BYTES + 1];
+        assert!(matches!(
+            CoverUpload::from_multipart(
+                content_type,
+                &multipart(&[("cover", "image/png", &large)])
+            ),
+            Err(AppError::PayloadTooLarge(_))
+        ));
+    }
+}
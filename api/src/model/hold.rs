@@ -0,0 +1,64 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+#[cfg(debug_assertions)]
+use utoipa::ToSchema;
+
+use kernel::model::hold::Hold;
+use kernel::model::id::{BookId, HoldId};
+
+use super::user::HoldUser;
+
+#[derive(Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct HoldResponse {
+    pub id: HoldId,
+    pub book_id: BookId,
+    pub held_by: HoldUser,
+    /// 予約の待ち行列における順番 (先頭が1)
+    pub position: i64,
+    pub held_at: DateTime<Utc>,
+    /// 予約したユーザーのために蔵書が取り置かれているか
+    pub ready: bool,
+    pub ready_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl From<Hold> for HoldResponse {
+    fn from(value: Hold) -> Self {
+        let ready = value.is_ready(Utc::now());
+        let Hold {
+            id,
+            book_id,
+            held_by,
+            position,
+            held_at,
+            ready_at,
+            expires_at,
+        } = value;
+        Self {
+            id,
+            book_id,
+            held_by: held_by.into(),
+            position,
+            held_at,
+            ready,
+            ready_at,
+            expires_at,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct HoldsResponse {
+    pub items: Vec<HoldResponse>,
+}
+
+impl From<Vec<Hold>> for HoldsResponse {
+    fn from(value: Vec<Hold>) -> Self {
+        let items = value.into_iter().map(HoldResponse::from).collect();
+        Self { items }
+    }
+}
@@ -0,0 +1,90 @@
+use derive_new::new;
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+#[cfg(debug_assertions)]
+use utoipa::ToSchema;
+
+use kernel::model::id::UserId;
+use kernel::model::loan_policy::event::UpdateLoanPolicy;
+use kernel::model::loan_policy::LoanPolicy;
+use kernel::model::role::Role;
+
+use super::user::RoleName;
+
+#[derive(Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct LoanPolicyResponse {
+    pub role: RoleName,
+    /// 同時に借りられる蔵書の数の上限 (`null`の場合は制限しない)
+    pub max_checkouts: Option<i32>,
+    pub loan_period_days: i32,
+    pub max_renewals: i32,
+}
+
+impl From<LoanPolicy> for LoanPolicyResponse {
+    fn from(value: LoanPolicy) -> Self {
+        let LoanPolicy {
+            role,
+            max_checkouts,
+            loan_period_days,
+            max_renewals,
+        } = value;
+        Self {
+            role: RoleName::from(role),
+            max_checkouts,
+            loan_period_days,
+            max_renewals,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct LoanPoliciesResponse {
+    pub items: Vec<LoanPolicyResponse>,
+}
+
+impl From<Vec<LoanPolicy>> for LoanPoliciesResponse {
+    fn from(value: Vec<LoanPolicy>) -> Self {
+        let items = value.into_iter().map(LoanPolicyResponse::from).collect();
+        Self { items }
+    }
+}
+
+#[derive(Deserialize, Validate)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateLoanPolicyRequest {
+    #[garde(range(min = 0))]
+    max_checkouts: Option<i32>,
+    #[garde(range(min = 1))]
+    loan_period_days: i32,
+    #[garde(range(min = 0))]
+    max_renewals: i32,
+}
+
+#[derive(new)]
+pub struct UpdateLoanPolicyRequestWithRole(RoleName, UserId, UpdateLoanPolicyRequest);
+
+impl From<UpdateLoanPolicyRequestWithRole> for UpdateLoanPolicy {
+    fn from(value: UpdateLoanPolicyRequestWithRole) -> Self {
+        let UpdateLoanPolicyRequestWithRole(
+            role,
+            requested_user,
+            UpdateLoanPolicyRequest {
+                max_checkouts,
+                loan_period_days,
+                max_renewals,
+            },
+        ) = value;
+        UpdateLoanPolicy::new(
+            Role::from(role),
+            max_checkouts,
+            loan_period_days,
+            max_renewals,
+            requested_user,
+        )
+    }
+}
@@ -1,4 +1,13 @@
+pub mod audit;
 pub mod auth;
 pub mod book;
 pub mod checkout;
+pub mod cover;
+pub mod hold;
+pub mod loan_policy;
+pub mod recommendation;
+pub mod report;
+pub mod review;
+pub mod tag;
+pub mod transfer;
 pub mod user;
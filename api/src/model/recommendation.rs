@@ -0,0 +1,92 @@
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+#[cfg(debug_assertions)]
+use utoipa::ToSchema;
+
+use kernel::model::id::BookId;
+use kernel::model::recommendation::{Recommendation, RecommendationReason};
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct RecommendationQuery {
+    #[garde(range(min = 1, max = 50))]
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+}
+
+const DEFAULT_LIMIT: usize = 10;
+const fn default_limit() -> usize {
+    DEFAULT_LIMIT
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum RecommendationReasonName {
+    BorrowedTogether,
+    SameAuthor,
+    SharedTags,
+}
+
+impl From<RecommendationReason> for RecommendationReasonName {
+    fn from(value: RecommendationReason) -> Self {
+        match value {
+            RecommendationReason::BorrowedTogether => Self::BorrowedTogether,
+            RecommendationReason::SameAuthor => Self::SameAuthor,
+            RecommendationReason::SharedTags => Self::SharedTags,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct RecommendationResponse {
+    pub book_id: BookId,
+    pub title: String,
+    pub author: String,
+    pub isbn: String,
+    /// 推薦の強さ。大きいほど強く推薦する。
+    pub score: i64,
+    pub reasons: Vec<RecommendationReasonName>,
+}
+
+impl From<Recommendation> for RecommendationResponse {
+    fn from(value: Recommendation) -> Self {
+        let Recommendation {
+            book_id,
+            title,
+            author,
+            isbn,
+            score,
+            reasons,
+        } = value;
+        Self {
+            book_id,
+            title,
+            author,
+            isbn,
+            score,
+            reasons: reasons
+                .into_iter()
+                .map(RecommendationReasonName::from)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct RecommendationsResponse {
+    pub items: Vec<RecommendationResponse>,
+}
+
+impl From<Vec<Recommendation>> for RecommendationsResponse {
+    fn from(value: Vec<Recommendation>) -> Self {
+        let items = value
+            .into_iter()
+            .map(RecommendationResponse::from)
+            .collect();
+        Self { items }
+    }
+}
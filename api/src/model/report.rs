@@ -0,0 +1,354 @@
+use axum::http::header::{CONTENT_DISPOSITION, CONTENT_TYPE};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use chrono::{DateTime, Utc};
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+#[cfg(debug_assertions)]
+use utoipa::ToSchema;
+
+use kernel::model::id::{BookId, UserId};
+use kernel::model::report::{
+    BookCirculation, BorrowerActivity, LoanDuration, MonthlyCheckouts, ReportPeriod, Utilisation,
+};
+use shared::error::{AppError, AppResult};
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportQuery {
+    /// 順位を付ける集計で返す件数
+    #[garde(range(min = 1, max = 100))]
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+    #[garde(skip)]
+    pub since: Option<DateTime<Utc>>,
+    #[garde(skip)]
+    pub until: Option<DateTime<Utc>>,
+    #[garde(skip)]
+    #[serde(default)]
+    pub format: ReportFormat,
+}
+
+const DEFAULT_LIMIT: i64 = 10;
+const fn default_limit() -> i64 {
+    DEFAULT_LIMIT
+}
+
+impl ReportQuery {
+    pub fn period(&self) -> ReportPeriod {
+        ReportPeriod {
+            since: self.since,
+            until: self.until,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReportFormatQuery {
+    #[serde(default)]
+    pub format: ReportFormat,
+}
+
+/// 集計結果を返す形式
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "kebab-case")]
+pub enum ReportFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+/// CSVで返す集計結果
+pub trait CsvReport {
+    /// CSVのヘッダー行に書き出す列名
+    const COLUMNS: &'static [&'static str];
+
+    fn csv_rows(&self) -> Vec<Vec<String>>;
+}
+
+impl ReportFormat {
+    /// 集計結果を指定された形式のレスポンスに変換する。
+    ///
+    /// CSVの場合は、`name`にもとづくファイル名で添付ファイルとして返す。
+    pub fn respond<T: Serialize + CsvReport>(self, name: &str, report: T) -> AppResult<Response> {
+        match self {
+            Self::Json => Ok(Json(report).into_response()),
+            Self::Csv => {
+                let conversion_error =
+                    |e: csv::Error| AppError::ConversionEntityError(e.to_string());
+                let mut writer = csv::WriterBuilder::new()
+                    .terminator(csv::Terminator::CRLF)
+                    .from_writer(Vec::new());
+                writer.write_record(T::COLUMNS).map_err(conversion_error)?;
+                for row in report.csv_rows() {
+                    writer.write_record(&row).map_err(conversion_error)?;
+                }
+                let body = writer
+                    .into_inner()
+                    .map_err(|e| AppError::ConversionEntityError(e.to_string()))?;
+                Ok((
+                    [
+                        (CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+                        (
+                            CONTENT_DISPOSITION,
+                            format!("attachment; filename=\"{name}.csv\""),
+                        ),
+                    ],
+                    body,
+                )
+                    .into_response())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct BookCirculationResponse {
+    pub book_id: BookId,
+    pub title: String,
+    pub author: String,
+    pub isbn: String,
+    pub checkout_count: i64,
+}
+
+impl From<BookCirculation> for BookCirculationResponse {
+    fn from(value: BookCirculation) -> Self {
+        let BookCirculation {
+            book_id,
+            title,
+            author,
+            isbn,
+            checkout_count,
+        } = value;
+        Self {
+            book_id,
+            title,
+            author,
+            isbn,
+            checkout_count,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct MostBorrowedBooksResponse {
+    pub items: Vec<BookCirculationResponse>,
+}
+
+impl From<Vec<BookCirculation>> for MostBorrowedBooksResponse {
+    fn from(value: Vec<BookCirculation>) -> Self {
+        let items = value
+            .into_iter()
+            .map(BookCirculationResponse::from)
+            .collect();
+        Self { items }
+    }
+}
+
+impl CsvReport for MostBorrowedBooksResponse {
+    const COLUMNS: &'static [&'static str] =
+        &["book_id", "title", "author", "isbn", "checkout_count"];
+
+    fn csv_rows(&self) -> Vec<Vec<String>> {
+        self.items
+            .iter()
+            .map(|i| {
+                vec![
+                    i.book_id.to_string(),
+                    i.title.clone(),
+                    i.author.clone(),
+                    i.isbn.clone(),
+                    i.checkout_count.to_string(),
+                ]
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct BorrowerActivityResponse {
+    pub user_id: UserId,
+    pub name: String,
+    pub checkout_count: i64,
+}
+
+impl From<BorrowerActivity> for BorrowerActivityResponse {
+    fn from(value: BorrowerActivity) -> Self {
+        let BorrowerActivity {
+            user_id,
+            name,
+            checkout_count,
+        } = value;
+        Self {
+            user_id,
+            name,
+            checkout_count,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct MostActiveBorrowersResponse {
+    pub items: Vec<BorrowerActivityResponse>,
+}
+
+impl From<Vec<BorrowerActivity>> for MostActiveBorrowersResponse {
+    fn from(value: Vec<BorrowerActivity>) -> Self {
+        let items = value
+            .into_iter()
+            .map(BorrowerActivityResponse::from)
+            .collect();
+        Self { items }
+    }
+}
+
+impl CsvReport for MostActiveBorrowersResponse {
+    const COLUMNS: &'static [&'static str] = &["user_id", "name", "checkout_count"];
+
+    fn csv_rows(&self) -> Vec<Vec<String>> {
+        self.items
+            .iter()
+            .map(|i| {
+                vec![
+                    i.user_id.to_string(),
+                    i.name.clone(),
+                    i.checkout_count.to_string(),
+                ]
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct LoanDurationResponse {
+    /// 集計した返却済みの貸出の数
+    pub returned_count: i64,
+    /// 貸出から返却までの平均日数 (返却済みの貸出がない場合は`null`)
+    pub average_days: Option<f64>,
+}
+
+impl From<LoanDuration> for LoanDurationResponse {
+    fn from(value: LoanDuration) -> Self {
+        let LoanDuration {
+            returned_count,
+            average_days,
+        } = value;
+        Self {
+            returned_count,
+            average_days,
+        }
+    }
+}
+
+impl CsvReport for LoanDurationResponse {
+    const COLUMNS: &'static [&'static str] = &["returned_count", "average_days"];
+
+    fn csv_rows(&self) -> Vec<Vec<String>> {
+        vec![vec![
+            self.returned_count.to_string(),
+            self.average_days.map(|d| d.to_string()).unwrap_or_default(),
+        ]]
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct UtilisationResponse {
+    /// 紛失した複本を除く複本の数
+    pub total_copies: i64,
+    pub checked_out_copies: i64,
+    pub available_copies: i64,
+    /// 貸出中の複本の割合 (0から1)
+    pub utilisation_rate: f64,
+}
+
+impl From<Utilisation> for UtilisationResponse {
+    fn from(value: Utilisation) -> Self {
+        Self {
+            total_copies: value.total_copies,
+            checked_out_copies: value.checked_out_copies,
+            available_copies: value.available_copies(),
+            utilisation_rate: value.rate(),
+        }
+    }
+}
+
+impl CsvReport for UtilisationResponse {
+    const COLUMNS: &'static [&'static str] = &[
+        "total_copies",
+        "checked_out_copies",
+        "available_copies",
+        "utilisation_rate",
+    ];
+
+    fn csv_rows(&self) -> Vec<Vec<String>> {
+        vec![vec![
+            self.total_copies.to_string(),
+            self.checked_out_copies.to_string(),
+            self.available_copies.to_string(),
+            self.utilisation_rate.to_string(),
+        ]]
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct MonthlyCheckoutsResponse {
+    /// `YYYY-MM`形式の年月 (UTC)
+    pub month: String,
+    pub checkout_count: i64,
+}
+
+impl From<MonthlyCheckouts> for MonthlyCheckoutsResponse {
+    fn from(value: MonthlyCheckouts) -> Self {
+        let MonthlyCheckouts {
+            month,
+            checkout_count,
+        } = value;
+        Self {
+            month,
+            checkout_count,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct CheckoutsPerMonthResponse {
+    pub items: Vec<MonthlyCheckoutsResponse>,
+}
+
+impl From<Vec<MonthlyCheckouts>> for CheckoutsPerMonthResponse {
+    fn from(value: Vec<MonthlyCheckouts>) -> Self {
+        let items = value
+            .into_iter()
+            .map(MonthlyCheckoutsResponse::from)
+            .collect();
+        Self { items }
+    }
+}
+
+impl CsvReport for CheckoutsPerMonthResponse {
+    const COLUMNS: &'static [&'static str] = &["month", "checkout_count"];
+
+    fn csv_rows(&self) -> Vec<Vec<String>> {
+        self.items
+            .iter()
+            .map(|i| vec![i.month.clone(), i.checkout_count.to_string()])
+            .collect()
+    }
+}
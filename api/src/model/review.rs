@@ -0,0 +1,131 @@
+use chrono::{DateTime, Utc};
+use derive_new::new;
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+#[cfg(debug_assertions)]
+use utoipa::ToSchema;
+
+use kernel::model::id::{BookId, ReviewId, UserId};
+use kernel::model::review::event::{CreateReview, UpdateReview};
+use kernel::model::review::{Review, MAX_RATING, MIN_RATING};
+
+use super::user::ReviewUser;
+
+/// 空白のみの本文は、本文を省略したものとみなす。
+fn normalize_comment(comment: Option<String>) -> Option<String> {
+    comment
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty())
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct CreateReviewRequest {
+    /// 1から5の星の数
+    #[garde(range(min = MIN_RATING, max = MAX_RATING))]
+    pub rating: i32,
+    #[garde(inner(length(max = 2000)))]
+    pub comment: Option<String>,
+}
+
+#[derive(new)]
+pub struct CreateReviewRequestWithIds(BookId, UserId, CreateReviewRequest);
+
+impl From<CreateReviewRequestWithIds> for CreateReview {
+    fn from(value: CreateReviewRequestWithIds) -> Self {
+        let CreateReviewRequestWithIds(book_id, user_id, CreateReviewRequest { rating, comment }) =
+            value;
+        CreateReview::new(book_id, user_id, rating, normalize_comment(comment))
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateReviewRequest {
+    /// 1から5の星の数
+    #[garde(range(min = MIN_RATING, max = MAX_RATING))]
+    pub rating: i32,
+    #[garde(inner(length(max = 2000)))]
+    pub comment: Option<String>,
+}
+
+#[derive(new)]
+pub struct UpdateReviewRequestWithIds(BookId, ReviewId, UserId, UpdateReviewRequest);
+
+impl From<UpdateReviewRequestWithIds> for UpdateReview {
+    fn from(value: UpdateReviewRequestWithIds) -> Self {
+        let UpdateReviewRequestWithIds(
+            book_id,
+            review_id,
+            user_id,
+            UpdateReviewRequest { rating, comment },
+        ) = value;
+        UpdateReview::new(
+            review_id,
+            book_id,
+            user_id,
+            rating,
+            normalize_comment(comment),
+        )
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct CreateReviewResponse {
+    pub id: ReviewId,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewResponse {
+    pub id: ReviewId,
+    pub book_id: BookId,
+    pub reviewed_by: ReviewUser,
+    /// 1から5の星の数
+    pub rating: i32,
+    pub comment: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<Review> for ReviewResponse {
+    fn from(value: Review) -> Self {
+        let Review {
+            id,
+            book_id,
+            reviewed_by,
+            rating,
+            comment,
+            created_at,
+            updated_at,
+        } = value;
+        Self {
+            id,
+            book_id,
+            reviewed_by: reviewed_by.into(),
+            rating,
+            comment,
+            created_at,
+            updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewsResponse {
+    pub items: Vec<ReviewResponse>,
+}
+
+impl From<Vec<Review>> for ReviewsResponse {
+    fn from(value: Vec<Review>) -> Self {
+        let items = value.into_iter().map(ReviewResponse::from).collect();
+        Self { items }
+    }
+}
@@ -0,0 +1,91 @@
+use derive_new::new;
+use garde::Validate;
+use serde::{Deserialize, Serialize};
+#[cfg(debug_assertions)]
+use utoipa::ToSchema;
+
+use kernel::model::id::{TagId, UserId};
+use kernel::model::tag::event::{CreateTag, UpdateTag};
+use kernel::model::tag::Tag;
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct TagResponse {
+    pub id: TagId,
+    pub name: String,
+}
+
+impl From<Tag> for TagResponse {
+    fn from(value: Tag) -> Self {
+        let Tag { id, name } = value;
+        Self { id, name }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct TagsResponse {
+    pub items: Vec<TagResponse>,
+}
+
+impl From<Vec<Tag>> for TagsResponse {
+    fn from(value: Vec<Tag>) -> Self {
+        let items = value.into_iter().map(TagResponse::from).collect();
+        Self { items }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct CreateTagResponse {
+    pub id: TagId,
+}
+
+/// タグ名にはカンマを含められない。蔵書一覧をタグで絞り込むときに、カンマでタグ名を区切るためである。
+fn validate_tag_name(value: &str, _context: &()) -> garde::Result {
+    if value.trim().is_empty() || value.contains(',') {
+        return Err(garde::Error::new(
+            "tag name must not be blank or contain commas",
+        ));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct CreateTagRequest {
+    #[garde(length(min = 1, max = 64), custom(validate_tag_name))]
+    pub name: String,
+}
+
+#[derive(new)]
+pub struct CreateTagRequestWithUserId(UserId, CreateTagRequest);
+
+impl From<CreateTagRequestWithUserId> for CreateTag {
+    fn from(value: CreateTagRequestWithUserId) -> Self {
+        let CreateTagRequestWithUserId(user_id, CreateTagRequest { name }) = value;
+        CreateTag::new(name.trim().to_string(), user_id)
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateTagRequest {
+    #[garde(length(min = 1, max = 64), custom(validate_tag_name))]
+    pub name: String,
+}
+
+#[derive(new)]
+pub struct UpdateTagRequestWithIds(TagId, UserId, UpdateTagRequest);
+
+impl From<UpdateTagRequestWithIds> for UpdateTag {
+    fn from(value: UpdateTagRequestWithIds) -> Self {
+        let UpdateTagRequestWithIds(tag_id, user_id, UpdateTagRequest { name }) = value;
+        UpdateTag::new(tag_id, name.trim().to_string(), user_id)
+    }
+}
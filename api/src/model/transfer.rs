@@ -0,0 +1,67 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+#[cfg(debug_assertions)]
+use utoipa::ToSchema;
+
+use kernel::model::id::{BookId, BookTransferId, UserId};
+use kernel::model::transfer::BookTransfer;
+
+use super::user::TransferUser;
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct CreateBookTransferRequest {
+    /// 譲渡先のユーザーID
+    pub to_user_id: UserId,
+    /// 管理者が指定した場合、譲渡先のユーザーの承諾なしに所有者を変更する
+    #[serde(default)]
+    pub force: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct BookTransferResponse {
+    pub id: BookTransferId,
+    pub book_id: BookId,
+    pub book_title: String,
+    pub from: TransferUser,
+    pub to: TransferUser,
+    pub requested_at: DateTime<Utc>,
+}
+
+impl From<BookTransfer> for BookTransferResponse {
+    fn from(value: BookTransfer) -> Self {
+        let BookTransfer {
+            id,
+            book_id,
+            book_title,
+            from,
+            to,
+            requested_at,
+        } = value;
+        Self {
+            id,
+            book_id,
+            book_title,
+            from: from.into(),
+            to: to.into(),
+            requested_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct BookTransfersResponse {
+    pub items: Vec<BookTransferResponse>,
+}
+
+impl From<Vec<BookTransfer>> for BookTransfersResponse {
+    fn from(value: Vec<BookTransfer>) -> Self {
+        let items = value.into_iter().map(BookTransferResponse::from).collect();
+        Self { items }
+    }
+}
@@ -7,10 +7,12 @@ use utoipa::ToSchema;
 
 use kernel::model::id::UserId;
 use kernel::model::role::Role;
-use kernel::model::user::event::{CreateUser, UpdateUserPassword, UpdateUserRole};
+use kernel::model::user::event::{
+    CreateUser, UpdateUserPassword, UpdateUserPrivacy, UpdateUserRole,
+};
 use kernel::model::user::User;
 
-#[derive(Deserialize, Serialize, VariantNames)]
+#[derive(Debug, Deserialize, Serialize, VariantNames)]
 #[strum(serialize_all = "kebab-case")]
 #[cfg_attr(debug_assertions, derive(ToSchema))]
 pub enum RoleName {
@@ -53,6 +55,24 @@ impl From<kernel::model::user::BookOwner> for BookOwner {
     }
 }
 
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct BookReviser {
+    pub id: UserId,
+    /// ユーザーの名前 (ユーザーが削除された場合は`null`)
+    pub name: Option<String>,
+}
+
+impl From<kernel::model::user::BookReviser> for BookReviser {
+    fn from(value: kernel::model::user::BookReviser) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+        }
+    }
+}
+
 #[derive(Serialize)]
 #[cfg_attr(debug_assertions, derive(ToSchema))]
 #[serde(rename_all = "camelCase")]
@@ -68,6 +88,8 @@ pub struct UserResponse {
     pub name: String,
     pub email: String,
     pub role: RoleName,
+    /// 返却した貸出の履歴を残すか
+    pub retain_checkout_history: bool,
 }
 
 impl From<User> for UserResponse {
@@ -77,6 +99,7 @@ impl From<User> for UserResponse {
             name: value.name,
             email: value.email,
             role: RoleName::from(value.role),
+            retain_checkout_history: value.retain_checkout_history,
         }
     }
 }
@@ -93,14 +116,15 @@ pub struct UpdateUserPasswordRequest {
 }
 
 #[derive(new)]
-pub struct UpdateUserPasswordRequestWithUserId(UserId, UpdateUserPasswordRequest);
+pub struct UpdateUserPasswordRequestWithUserId(UserId, Option<i64>, UpdateUserPasswordRequest);
 
 impl From<UpdateUserPasswordRequestWithUserId> for UpdateUserPassword {
     fn from(value: UpdateUserPasswordRequestWithUserId) -> Self {
         Self {
             user_id: value.0,
-            current_password: value.1.current_password,
-            new_password: value.1.new_password,
+            current_password: value.2.current_password,
+            new_password: value.2.new_password,
+            expected_version: value.1,
         }
     }
 }
@@ -117,12 +141,24 @@ pub struct CreateUserRequest {
     password: String,
 }
 
-impl From<CreateUserRequest> for CreateUser {
-    fn from(value: CreateUserRequest) -> Self {
+#[derive(new)]
+pub struct CreateUserRequestWithUserId(UserId, CreateUserRequest);
+
+impl From<CreateUserRequestWithUserId> for CreateUser {
+    fn from(value: CreateUserRequestWithUserId) -> Self {
+        let CreateUserRequestWithUserId(
+            requested_user,
+            CreateUserRequest {
+                name,
+                email,
+                password,
+            },
+        ) = value;
         Self {
-            name: value.name,
-            email: value.email,
-            password: value.password,
+            name,
+            email,
+            password,
+            requested_user,
         }
     }
 }
@@ -135,17 +171,64 @@ pub struct UpdateUserRoleRequest {
 }
 
 #[derive(new)]
-pub struct UpdateUserRoleRequestWithUserId(UserId, UpdateUserRoleRequest);
+pub struct UpdateUserRoleRequestWithUserIds(UserId, UserId, Option<i64>, UpdateUserRoleRequest);
 
-impl From<UpdateUserRoleRequestWithUserId> for UpdateUserRole {
-    fn from(value: UpdateUserRoleRequestWithUserId) -> Self {
+impl From<UpdateUserRoleRequestWithUserIds> for UpdateUserRole {
+    fn from(value: UpdateUserRoleRequestWithUserIds) -> Self {
+        let UpdateUserRoleRequestWithUserIds(
+            user_id,
+            requested_user,
+            expected_version,
+            UpdateUserRoleRequest { role },
+        ) = value;
         Self {
-            user_id: value.0,
-            role: Role::from(value.1.role),
+            user_id,
+            role: Role::from(role),
+            requested_user,
+            expected_version,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateUserPrivacyRequest {
+    /// 返却した貸出の履歴を残すか
+    ///
+    /// `false`にすると、それまでの履歴と、返却した蔵書のレビューを書けることの記録も削除し、
+    /// 以降の貸出の監査ログには借りたユーザーを記録しない。ただし、監査ログは追記のみのため、
+    /// それまでに記録した貸出の監査ログは削除されずに残る。
+    retain_checkout_history: bool,
+}
+
+#[derive(new)]
+pub struct UpdateUserPrivacyRequestWithUserId(UserId, Option<i64>, UpdateUserPrivacyRequest);
+
+impl From<UpdateUserPrivacyRequestWithUserId> for UpdateUserPrivacy {
+    fn from(value: UpdateUserPrivacyRequestWithUserId) -> Self {
+        let UpdateUserPrivacyRequestWithUserId(
+            user_id,
+            expected_version,
+            UpdateUserPrivacyRequest {
+                retain_checkout_history,
+            },
+        ) = value;
+        Self {
+            user_id,
+            retain_checkout_history,
+            expected_version,
         }
     }
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteUserQuery {
+    /// 削除するユーザーが所有する蔵書の譲渡先のユーザーID
+    pub reassign_to: Option<UserId>,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 #[cfg_attr(debug_assertions, derive(ToSchema))]
 pub struct CheckoutUser {
@@ -161,3 +244,54 @@ impl From<kernel::model::user::CheckoutUser> for CheckoutUser {
         }
     }
 }
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct HoldUser {
+    pub id: UserId,
+    pub name: String,
+}
+
+impl From<kernel::model::user::HoldUser> for HoldUser {
+    fn from(value: kernel::model::user::HoldUser) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct TransferUser {
+    pub id: UserId,
+    pub name: String,
+}
+
+impl From<kernel::model::user::TransferUser> for TransferUser {
+    fn from(value: kernel::model::user::TransferUser) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(debug_assertions, derive(ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewUser {
+    pub id: UserId,
+    pub name: String,
+}
+
+impl From<kernel::model::user::ReviewUser> for ReviewUser {
+    fn from(value: kernel::model::user::ReviewUser) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+        }
+    }
+}
@@ -22,43 +22,159 @@ Rust でしたいと思った際に、どのようなクレートや実装を利
         handler::book::show_book_list,
         handler::book::show_book,
         handler::book::register_book,
+        handler::book::import_books,
+        handler::book::export_books,
         handler::book::update_book,
+        handler::book::patch_book,
+        handler::book::show_book_revision_list,
+        handler::book::revert_book,
         handler::book::delete_book,
+        handler::book::show_deleted_book_list,
+        handler::book::restore_book,
+        handler::book::purge_book,
+        handler::book::upload_book_cover,
+        handler::book::show_book_cover,
+        handler::book::show_book_copy_list,
+        handler::book::register_book_copy,
+        handler::book::update_book_copy,
+        handler::book::delete_book_copy,
         handler::checkout::checkout_book,
         handler::checkout::return_book,
+        handler::checkout::renew_checkout,
+        handler::checkout::show_overdue_list,
         handler::checkout::checkout_history,
+        handler::hold::place_hold,
+        handler::hold::show_hold_list,
+        handler::hold::cancel_hold,
+        handler::review::create_review,
+        handler::review::show_review_list,
+        handler::review::update_review,
+        handler::review::delete_review,
+        handler::transfer::create_book_transfer,
+        handler::transfer::show_book_transfer_list,
+        handler::transfer::accept_book_transfer,
+        handler::transfer::delete_book_transfer,
+        handler::tag::show_tag_list,
+        handler::tag::register_tag,
+        handler::tag::update_tag,
+        handler::tag::delete_tag,
+        handler::tag::attach_tag,
+        handler::tag::detach_tag,
+        handler::loan_policy::show_loan_policy_list,
+        handler::loan_policy::update_loan_policy,
         handler::user::get_current_user,
         handler::user::list_users,
+        handler::user::show_user,
         handler::user::register_user,
         handler::user::change_password,
+        handler::user::change_privacy,
         handler::user::change_role,
         handler::user::delete_user,
         handler::user::get_checkouts,
+        handler::user::get_checkout_history,
+        handler::user::show_user_checkout_history,
+        handler::recommendation::show_book_recommendations,
+        handler::recommendation::get_recommendations,
+        handler::audit::show_audit_event_list,
+        handler::report::show_most_borrowed_books,
+        handler::report::show_most_active_borrowers,
+        handler::report::show_loan_duration,
+        handler::report::show_utilisation,
+        handler::report::show_checkouts_per_month,
         handler::auth::login,
         handler::auth::logout,
     ),
     components(schemas(
         model::book::CreateBookRequest,
         model::book::UpdateBookRequest,
+        model::book::PatchBookRequest,
+        model::book::BookRevisionsResponse,
+        model::book::BookRevisionResponse,
+        model::book::BookFieldChangeResponse,
+        model::book::BookFieldName,
         model::book::BookResponse,
         model::book::PaginatedBookResponse,
+        model::book::DeletedBookResponse,
+        model::book::PaginatedDeletedBookResponse,
         model::book::BookCheckoutResponse,
+        model::book::BookCheckoutStateName,
+        model::book::BookSortKeyName,
+        model::book::SortDirectionName,
+        model::book::BookCopiesResponse,
+        model::book::BookCopyResponse,
+        model::book::BookCopyConditionName,
+        model::book::CreateBookCopyRequest,
+        model::book::UpdateBookCopyRequest,
+        model::book::BookImportModeName,
+        model::book::BookImportRowStatus,
+        model::book::BookImportRowResponse,
+        model::book::BookImportResponse,
+        model::book::BookExportFormat,
+        model::book::TagMatchName,
+        model::cover::CoverSizeName,
+        model::tag::TagResponse,
+        model::tag::TagsResponse,
+        model::tag::CreateTagRequest,
+        model::tag::CreateTagResponse,
+        model::tag::UpdateTagRequest,
         model::checkout::CheckoutsResponse,
+        model::checkout::PaginatedCheckoutResponse,
+        model::checkout::CheckoutStateName,
         model::checkout::CheckoutResponse,
         model::checkout::CheckoutBookResponse,
+        model::checkout::CheckoutOutcomeName,
+        model::checkout::ReturnBookRequest,
+        model::hold::HoldsResponse,
+        model::hold::HoldResponse,
+        model::review::CreateReviewRequest,
+        model::review::UpdateReviewRequest,
+        model::review::CreateReviewResponse,
+        model::review::ReviewResponse,
+        model::review::ReviewsResponse,
+        model::transfer::CreateBookTransferRequest,
+        model::transfer::BookTransfersResponse,
+        model::transfer::BookTransferResponse,
+        model::loan_policy::LoanPoliciesResponse,
+        model::loan_policy::LoanPolicyResponse,
+        model::loan_policy::UpdateLoanPolicyRequest,
         model::user::UserResponse,
         model::user::UsersResponse,
         model::user::CreateUserRequest,
         model::user::UpdateUserPasswordRequest,
         model::user::UpdateUserRoleRequest,
+        model::user::UpdateUserPrivacyRequest,
         model::user::RoleName,
         model::user::BookOwner,
+        model::user::BookReviser,
         model::user::CheckoutUser,
+        model::user::HoldUser,
+        model::user::TransferUser,
+        model::user::ReviewUser,
+        model::audit::AuditEventResponse,
+        model::audit::PaginatedAuditEventResponse,
+        model::audit::AuditEntityTypeName,
+        model::recommendation::RecommendationReasonName,
+        model::recommendation::RecommendationResponse,
+        model::recommendation::RecommendationsResponse,
+        model::report::ReportFormat,
+        model::report::BookCirculationResponse,
+        model::report::MostBorrowedBooksResponse,
+        model::report::BorrowerActivityResponse,
+        model::report::MostActiveBorrowersResponse,
+        model::report::LoanDurationResponse,
+        model::report::UtilisationResponse,
+        model::report::MonthlyCheckoutsResponse,
+        model::report::CheckoutsPerMonthResponse,
         model::auth::LoginRequest,
         model::auth::AccessTokenResponse,
         kernel::model::id::BookId,
+        kernel::model::id::BookCopyId,
         kernel::model::id::UserId,
         kernel::model::id::CheckoutId,
+        kernel::model::id::HoldId,
+        kernel::model::id::TagId,
+        kernel::model::id::ReviewId,
+        kernel::model::id::AuditEventId,
     ))
 )]
 pub struct ApiDoc;
@@ -0,0 +1,10 @@
+use axum::routing;
+use axum::Router;
+
+use registry::AppRegistry;
+
+use crate::handler::audit::show_audit_event_list;
+
+pub fn build_audit_routers() -> Router<AppRegistry> {
+    Router::new().route("/audit-events", routing::get(show_audit_event_list))
+}
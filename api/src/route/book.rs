@@ -1,26 +1,111 @@
+use axum::extract::DefaultBodyLimit;
 use axum::{routing, Router};
 
 use registry::AppRegistry;
 
-use crate::handler::book::{delete_book, register_book, show_book, show_book_list, update_book};
+use crate::handler::book::{
+    delete_book, delete_book_copy, export_books, import_books, patch_book, purge_book,
+    register_book, register_book_copy, restore_book, revert_book, show_book, show_book_copy_list,
+    show_book_cover, show_book_list, show_book_revision_list, show_deleted_book_list, update_book,
+    update_book_copy, upload_book_cover,
+};
 use crate::handler::checkout::{
-    checkout_book, checkout_history, return_book, show_checked_out_list,
+    checkout_book, checkout_history, renew_checkout, return_book, show_checked_out_list,
+    show_overdue_list,
+};
+use crate::handler::hold::{cancel_hold, place_hold, show_hold_list};
+use crate::handler::recommendation::show_book_recommendations;
+use crate::handler::review::{create_review, delete_review, show_review_list, update_review};
+use crate::handler::tag::{attach_tag, detach_tag};
+use crate::handler::transfer::{
+    accept_book_transfer, create_book_transfer, delete_book_transfer, show_book_transfer_list,
 };
+use crate::model::cover::COVER_UPLOAD_BODY_LIMIT;
 
 pub fn build_book_routers() -> Router<AppRegistry> {
     let book_routers = Router::new()
         .route("/", routing::post(register_book))
         .route("/", routing::get(show_book_list))
+        .route("/import", routing::post(import_books))
+        .route("/export", routing::get(export_books))
         .route("/:book_id", routing::get(show_book))
         .route("/:book_id", routing::put(update_book))
-        .route("/:book_id", routing::delete(delete_book));
+        .route("/:book_id", routing::patch(patch_book))
+        .route("/:book_id", routing::delete(delete_book))
+        .route("/:book_id/revisions", routing::get(show_book_revision_list))
+        .route(
+            "/:book_id/revisions/:revision_number/revert",
+            routing::post(revert_book),
+        )
+        .route("/:book_id/cover", routing::get(show_book_cover))
+        .route(
+            "/:book_id/cover",
+            routing::put(upload_book_cover).layer(DefaultBodyLimit::max(COVER_UPLOAD_BODY_LIMIT)),
+        )
+        .route(
+            "/:book_id/recommendations",
+            routing::get(show_book_recommendations),
+        );
+    let trash_routers = Router::new()
+        .route("/trash", routing::get(show_deleted_book_list))
+        .route("/trash/:book_id", routing::delete(purge_book))
+        .route("/trash/:book_id/restore", routing::post(restore_book));
+    let copy_routers = Router::new()
+        .route("/:book_id/copies", routing::get(show_book_copy_list))
+        .route("/:book_id/copies", routing::post(register_book_copy))
+        .route("/:book_id/copies/:copy_id", routing::put(update_book_copy))
+        .route(
+            "/:book_id/copies/:copy_id",
+            routing::delete(delete_book_copy),
+        );
     let checkout_routers = Router::new()
         .route("/checkouts", routing::get(show_checked_out_list))
+        .route("/checkouts/overdue", routing::get(show_overdue_list))
         .route("/:book_id/checkouts", routing::post(checkout_book))
         .route(
             "/:book_id/checkouts/:checkout_id/returned",
             routing::put(return_book),
         )
+        .route(
+            "/:book_id/checkouts/:checkout_id/renewal",
+            routing::put(renew_checkout),
+        )
         .route("/:book_id/checkout-history", routing::get(checkout_history));
-    Router::new().nest("/books", book_routers.merge(checkout_routers))
+    let hold_routers = Router::new()
+        .route("/:book_id/holds", routing::post(place_hold))
+        .route("/:book_id/holds", routing::get(show_hold_list))
+        .route("/:book_id/holds/:hold_id", routing::delete(cancel_hold));
+    let review_routers = Router::new()
+        .route("/:book_id/reviews", routing::post(create_review))
+        .route("/:book_id/reviews", routing::get(show_review_list))
+        .route("/:book_id/reviews/:review_id", routing::put(update_review))
+        .route(
+            "/:book_id/reviews/:review_id",
+            routing::delete(delete_review),
+        );
+    let tag_routers = Router::new()
+        .route("/:book_id/tags/:tag_id", routing::put(attach_tag))
+        .route("/:book_id/tags/:tag_id", routing::delete(detach_tag));
+    let transfer_routers = Router::new()
+        .route("/transfers", routing::get(show_book_transfer_list))
+        .route(
+            "/transfers/:transfer_id",
+            routing::delete(delete_book_transfer),
+        )
+        .route(
+            "/transfers/:transfer_id/accept",
+            routing::post(accept_book_transfer),
+        )
+        .route("/:book_id/transfers", routing::post(create_book_transfer));
+    Router::new().nest(
+        "/books",
+        book_routers
+            .merge(trash_routers)
+            .merge(copy_routers)
+            .merge(checkout_routers)
+            .merge(hold_routers)
+            .merge(review_routers)
+            .merge(tag_routers)
+            .merge(transfer_routers),
+    )
 }
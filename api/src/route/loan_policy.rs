@@ -0,0 +1,12 @@
+use axum::routing;
+use axum::Router;
+
+use registry::AppRegistry;
+
+use crate::handler::loan_policy::{show_loan_policy_list, update_loan_policy};
+
+pub fn build_loan_policy_routers() -> Router<AppRegistry> {
+    Router::new()
+        .route("/loan-policies", routing::get(show_loan_policy_list))
+        .route("/loan-policies/:role", routing::put(update_loan_policy))
+}
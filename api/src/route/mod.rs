@@ -1,5 +1,9 @@
+pub mod audit;
 pub mod auth;
 pub mod book;
 pub mod health;
+pub mod loan_policy;
+pub mod report;
+pub mod tag;
 pub mod user;
 pub mod v1;
@@ -0,0 +1,29 @@
+use axum::routing;
+use axum::Router;
+
+use registry::AppRegistry;
+
+use crate::handler::report::{
+    show_checkouts_per_month, show_loan_duration, show_most_active_borrowers,
+    show_most_borrowed_books, show_utilisation,
+};
+
+pub fn build_report_routers() -> Router<AppRegistry> {
+    let routers = Router::new()
+        .route(
+            "/most-borrowed-books",
+            routing::get(show_most_borrowed_books),
+        )
+        .route(
+            "/most-active-borrowers",
+            routing::get(show_most_active_borrowers),
+        )
+        .route("/loan-duration", routing::get(show_loan_duration))
+        .route("/utilisation", routing::get(show_utilisation))
+        .route(
+            "/checkouts-per-month",
+            routing::get(show_checkouts_per_month),
+        );
+
+    Router::new().nest("/reports", routers)
+}
@@ -0,0 +1,14 @@
+use axum::routing;
+use axum::Router;
+
+use registry::AppRegistry;
+
+use crate::handler::tag::{delete_tag, register_tag, show_tag_list, update_tag};
+
+pub fn build_tag_routers() -> Router<AppRegistry> {
+    Router::new()
+        .route("/tags", routing::get(show_tag_list))
+        .route("/tags", routing::post(register_tag))
+        .route("/tags/:tag_id", routing::put(update_tag))
+        .route("/tags/:tag_id", routing::delete(delete_tag))
+}
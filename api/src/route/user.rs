@@ -3,17 +3,34 @@ use axum::Router;
 
 use registry::AppRegistry;
 
+use crate::handler::recommendation::get_recommendations;
 use crate::handler::user::{
-    change_password, change_role, delete_user, get_checkouts, get_current_user, list_users,
-    register_user,
+    change_password, change_privacy, change_role, delete_user, get_checkout_history, get_checkouts,
+    get_current_user, list_users, register_user, show_user, show_user_checkout_history,
 };
 
 pub fn build_user_routers() -> Router<AppRegistry> {
     Router::new()
         .route("/users/me", routing::get(get_current_user))
         .route("/users/me/password", routing::put(change_password))
+        .route("/users/me/privacy", routing::put(change_privacy))
         .route("/users/me/checkouts", routing::get(get_checkouts))
+        .route(
+            "/users/me/checkout-history",
+            routing::get(get_checkout_history),
+        )
+        .route(
+            "/users/me/recommendations",
+            routing::get(get_recommendations),
+        )
         .route("/users", routing::get(list_users).post(register_user))
-        .route("/users/:user_id", routing::delete(delete_user))
+        .route(
+            "/users/:user_id",
+            routing::get(show_user).delete(delete_user),
+        )
         .route("/users/:user_id/role", routing::put(change_role))
+        .route(
+            "/users/:user_id/checkout-history",
+            routing::get(show_user_checkout_history),
+        )
 }
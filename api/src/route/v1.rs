@@ -2,14 +2,22 @@ use axum::Router;
 
 use registry::AppRegistry;
 
+use super::audit::build_audit_routers;
 use super::book::build_book_routers;
 use super::health::build_health_check_routers;
+use super::loan_policy::build_loan_policy_routers;
+use super::report::build_report_routers;
+use super::tag::build_tag_routers;
 use super::user::build_user_routers;
 
 pub fn routers() -> Router<AppRegistry> {
     let router = Router::new()
         .merge(build_health_check_routers())
         .merge(build_user_routers())
-        .merge(build_book_routers());
+        .merge(build_book_routers())
+        .merge(build_loan_policy_routers())
+        .merge(build_tag_routers())
+        .merge(build_audit_routers())
+        .merge(build_report_routers());
     Router::new().nest("/api/v1", router)
 }
@@ -1,8 +1,8 @@
-/*
 use std::sync::Arc;
 
 use axum::body::Body;
-use axum::http::Request;
+use axum::http::header::{ETAG, IF_MATCH, IF_NONE_MATCH};
+use axum::http::{HeaderValue, Method, Request, StatusCode};
 use rstest::rstest;
 use tower::ServiceExt;
 
@@ -10,11 +10,18 @@ use api::model::book::PaginatedBookResponse;
 use kernel::model::book::Book;
 use kernel::model::id::{BookId, UserId};
 use kernel::model::list::PaginatedList;
+use kernel::model::role::Role;
 use kernel::model::user::BookOwner;
 use kernel::repository::book::MockBookRepository;
+use kernel::repository::transfer::MockBookTransferRepository;
+use registry::MockAppRegistryExt;
+use shared::error::AppError;
 
 use crate::deserialize_json;
-use crate::helper::{fixture, make_router, v1, TestRequestExt};
+use crate::helper::{
+    dummy_user_id, fixture, fixture_auth, fixture_registry, make_router, v1, with_current_user,
+    TestRequestExt,
+};
 
 #[rstest]
 #[case("/books", 20, 0)]
@@ -24,7 +31,7 @@ use crate::helper::{fixture, make_router, v1, TestRequestExt};
 #[tokio::test]
 async fn show_book_list_with_query_200(
     // 1. fixtureとしてmockオブジェクトを渡している
-    mut fixture: registry::MockAppRegistryExt,
+    mut fixture: MockAppRegistryExt,
     #[case] path: &str,
     #[case] expected_limit: i64,
     #[case] expected_offset: i64,
@@ -35,22 +42,12 @@ async fn show_book_list_with_query_200(
     fixture.expect_book_repository().returning(move || {
         let mut mock = MockBookRepository::new();
         mock.expect_find_all().returning(move |opt| {
-            let items = vec![Book {
-                id: book_id,
-                title: "RustによるWebアプリケーション開発".to_string(),
-                isbn: "".to_string(),
-                author: "Yuki Toyoda".to_string(),
-                description: "RustによるWebアプリケーション開発".to_string(),
-                owner: BookOwner {
-                    id: UserId::new(),
-                    name: "Yuki Toyoda".to_string(),
-                },
-                checkout: None,
-            }];
+            let items = vec![dummy_book(book_id)];
             Ok(PaginatedList {
                 total: 1,
                 limit: opt.limit,
                 offset: opt.offset,
+                next_cursor: None,
                 items,
             })
         });
@@ -63,7 +60,7 @@ async fn show_book_list_with_query_200(
     // 4. リクエストを作成・送信し、レスポンスのステータスコードを検証する
     let req = Request::get(&v1(path)).bearer().body(Body::empty())?;
     let resp = app.oneshot(req).await?;
-    assert_eq!(resp.status(), axum::http::StatusCode::OK);
+    assert_eq!(resp.status(), StatusCode::OK);
 
     // 5. レスポンスの値を検証する
     let result = deserialize_json!(resp, PaginatedBookResponse);
@@ -73,4 +70,420 @@ async fn show_book_list_with_query_200(
     // 6. テストが成功していることを示す
     Ok(())
 }
-*/
+
+fn dummy_book(book_id: BookId) -> Book {
+    Book {
+        id: book_id,
+        title: "RustによるWebアプリケーション開発".to_string(),
+        isbn: "".to_string(),
+        author: "Yuki Toyoda".to_string(),
+        description: "RustによるWebアプリケーション開発".to_string(),
+        owner: BookOwner {
+            id: dummy_user_id(),
+            name: "Yuki Toyoda".to_string(),
+        },
+        total_copies: 1,
+        available_copies: 1,
+        checkouts: vec![],
+        tags: vec![],
+        cover_etag: None,
+        average_rating: None,
+        review_count: 0,
+        version: 1,
+    }
+}
+
+/// 期待するステータスコードに対応する、リポジトリの結果を返す。
+fn repository_result(status: StatusCode) -> Result<(), AppError> {
+    match status {
+        StatusCode::FORBIDDEN => Err(AppError::ForbiddenOperation),
+        StatusCode::NOT_FOUND => Err(AppError::EntityNotFound("not found".into())),
+        StatusCode::UNPROCESSABLE_ENTITY => {
+            Err(AppError::UnprocessableEntity("checked out".into()))
+        }
+        _ => Ok(()),
+    }
+}
+
+// 蔵書を変更できるか(所有者か管理者か、蔵書が存在するか、貸出中か)はリポジトリが判断する。
+// ハンドラーはリクエストしたユーザーとロールをリポジトリに渡し、結果をステータスコードに変換する。
+#[rstest]
+#[case(Role::User, StatusCode::NO_CONTENT)]
+#[case(Role::Admin, StatusCode::NO_CONTENT)]
+#[case(Role::User, StatusCode::FORBIDDEN)]
+#[case(Role::User, StatusCode::NOT_FOUND)]
+#[case(Role::Admin, StatusCode::NOT_FOUND)]
+#[case(Role::User, StatusCode::UNPROCESSABLE_ENTITY)]
+#[case(Role::Admin, StatusCode::UNPROCESSABLE_ENTITY)]
+#[tokio::test]
+async fn delete_book_authorization(
+    fixture_auth: MockAppRegistryExt,
+    #[case] role: Role,
+    #[case] expected: StatusCode,
+) -> anyhow::Result<()> {
+    let book_id = BookId::new();
+    let mut fixture = with_current_user(fixture_auth, role);
+    fixture.expect_book_repository().returning(move || {
+        let mut mock = MockBookRepository::new();
+        mock.expect_delete()
+            .withf(move |event| {
+                event.book_id == book_id
+                    && event.requested_user == dummy_user_id()
+                    && event.requested_role == role
+            })
+            .times(1)
+            .returning(move |_| repository_result(expected));
+        Arc::new(mock)
+    });
+
+    let app = make_router(fixture);
+    let req = Request::delete(&v1(&format!("/books/{book_id}")))
+        .bearer()
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), expected);
+
+    Ok(())
+}
+
+#[rstest]
+#[case(Role::User, StatusCode::OK)]
+#[case(Role::Admin, StatusCode::OK)]
+#[case(Role::User, StatusCode::FORBIDDEN)]
+#[case(Role::Admin, StatusCode::NOT_FOUND)]
+#[tokio::test]
+async fn update_book_authorization(
+    fixture_auth: MockAppRegistryExt,
+    #[case] role: Role,
+    #[case] expected: StatusCode,
+) -> anyhow::Result<()> {
+    let book_id = BookId::new();
+    let mut fixture = with_current_user(fixture_auth, role);
+    fixture.expect_book_repository().returning(move || {
+        let mut mock = MockBookRepository::new();
+        mock.expect_update()
+            .withf(move |event| {
+                event.book_id == book_id
+                    && event.requested_user == dummy_user_id()
+                    && event.requested_role == role
+            })
+            .times(1)
+            .returning(move |_| repository_result(expected));
+        Arc::new(mock)
+    });
+
+    let app = make_router(fixture);
+    let body = serde_json::json!({
+        "title": "The Rust Programming Language",
+        "author": "Steve Klabnik and Carol Nichols",
+        "isbn": "978-1-7185-0310-6",
+        "description": "",
+    });
+    let req = Request::put(&v1(&format!("/books/{book_id}")))
+        .bearer()
+        .application_json()
+        .body(Body::from(body.to_string()))?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), expected);
+
+    Ok(())
+}
+
+// ゴミ箱の操作は管理者のみが行え、管理者以外の場合はリポジトリを呼び出さない。
+#[rstest]
+#[case(Request::get(v1("/books/trash")))]
+#[case(Request::post(v1(&format!("/books/trash/{}/restore", BookId::new()))))]
+#[case(Request::delete(v1(&format!("/books/trash/{}", BookId::new()))))]
+#[tokio::test]
+async fn trash_is_forbidden_for_non_admin_403(
+    fixture: MockAppRegistryExt,
+    #[case] req: axum::http::request::Builder,
+) -> anyhow::Result<()> {
+    let app = make_router(fixture);
+    let resp = app.oneshot(req.bearer().body(Body::empty())?).await?;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    Ok(())
+}
+
+/// 蔵書を取得して、レスポンスの`ETag`ヘッダーを返す。
+async fn fetch_book_etag(app: &axum::Router, book_id: BookId) -> anyhow::Result<HeaderValue> {
+    let req = Request::get(&v1(&format!("/books/{book_id}")))
+        .bearer()
+        .body(Body::empty())?;
+    let resp = app.clone().oneshot(req).await?;
+    assert_eq!(resp.status(), StatusCode::OK);
+    Ok(resp.headers().get(ETAG).unwrap().clone())
+}
+
+#[rstest]
+#[case("matching", StatusCode::NOT_MODIFIED)]
+#[case("\"stale\"", StatusCode::OK)]
+#[tokio::test]
+async fn show_book_with_if_none_match(
+    mut fixture: MockAppRegistryExt,
+    #[case] if_none_match: &str,
+    #[case] expected: StatusCode,
+) -> anyhow::Result<()> {
+    let book_id = BookId::new();
+    fixture.expect_book_repository().returning(|| {
+        let mut mock = MockBookRepository::new();
+        mock.expect_find_by_id()
+            .returning(|id| Ok(Some(dummy_book(id))));
+        Arc::new(mock)
+    });
+    let app = make_router(fixture);
+    let etag = fetch_book_etag(&app, book_id).await?;
+
+    // 蔵書が変わっていなければ、本文を返さずに304を返す
+    let if_none_match = match if_none_match {
+        "matching" => etag.clone(),
+        stale => HeaderValue::from_str(stale)?,
+    };
+    let req = Request::get(&v1(&format!("/books/{book_id}")))
+        .bearer()
+        .header(IF_NONE_MATCH, if_none_match)
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), expected);
+    assert_eq!(resp.headers().get(ETAG), Some(&etag));
+
+    Ok(())
+}
+
+// `If-Match`が現在の蔵書のエンティティタグと一致する場合のみ、確認したバージョンを指定して変更する。
+// 一致しない場合は、リポジトリを呼び出さずに412を返す。
+#[rstest]
+#[case(Method::PUT, true, StatusCode::OK)]
+#[case(Method::PUT, false, StatusCode::PRECONDITION_FAILED)]
+#[case(Method::DELETE, true, StatusCode::NO_CONTENT)]
+#[case(Method::DELETE, false, StatusCode::PRECONDITION_FAILED)]
+#[tokio::test]
+async fn mutate_book_with_if_match(
+    mut fixture: MockAppRegistryExt,
+    #[case] method: Method,
+    #[case] matches: bool,
+    #[case] expected: StatusCode,
+) -> anyhow::Result<()> {
+    let book_id = BookId::new();
+    let times = usize::from(matches);
+    fixture.expect_book_repository().returning(move || {
+        let mut mock = MockBookRepository::new();
+        mock.expect_find_by_id()
+            .returning(|id| Ok(Some(dummy_book(id))));
+        mock.expect_update()
+            .withf(|event| event.expected_version == Some(1))
+            .times(0..=times)
+            .returning(|_| Ok(()));
+        mock.expect_delete()
+            .withf(|event| event.expected_version == Some(1))
+            .times(0..=times)
+            .returning(|_| Ok(()));
+        Arc::new(mock)
+    });
+    let app = make_router(fixture);
+    let if_match = if matches {
+        fetch_book_etag(&app, book_id).await?
+    } else {
+        HeaderValue::from_static("\"stale\"")
+    };
+
+    let body = serde_json::json!({
+        "title": "The Rust Programming Language",
+        "author": "Steve Klabnik and Carol Nichols",
+        "isbn": "978-1-7185-0310-6",
+        "description": "",
+    });
+    let req = Request::builder()
+        .method(method)
+        .uri(v1(&format!("/books/{book_id}")))
+        .bearer()
+        .application_json()
+        .header(IF_MATCH, if_match)
+        .body(Body::from(body.to_string()))?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), expected);
+
+    Ok(())
+}
+
+// `If-Match`はバージョンだけを比較するため、貸出状況やレビューが変わっただけでは412を返さない。
+#[rstest]
+#[case(1, StatusCode::NO_CONTENT)]
+#[case(2, StatusCode::PRECONDITION_FAILED)]
+#[tokio::test]
+async fn if_match_compares_only_the_version(
+    mut fixture: MockAppRegistryExt,
+    #[case] current_version: i64,
+    #[case] expected: StatusCode,
+) -> anyhow::Result<()> {
+    let book_id = BookId::new();
+    let etag = {
+        // 変わる前の蔵書を取得したときの`ETag`
+        let mut fixture = crate::helper::fixture(fixture_auth(fixture_registry()));
+        fixture.expect_book_repository().returning(|| {
+            let mut mock = MockBookRepository::new();
+            mock.expect_find_by_id()
+                .returning(|id| Ok(Some(dummy_book(id))));
+            Arc::new(mock)
+        });
+        fetch_book_etag(&make_router(fixture), book_id).await?
+    };
+
+    fixture.expect_book_repository().returning(move || {
+        let mut mock = MockBookRepository::new();
+        mock.expect_find_by_id().returning(move |id| {
+            Ok(Some(Book {
+                available_copies: 0,
+                average_rating: Some(4.5),
+                review_count: 2,
+                version: current_version,
+                ..dummy_book(id)
+            }))
+        });
+        mock.expect_delete().returning(|_| Ok(()));
+        Arc::new(mock)
+    });
+    let app = make_router(fixture);
+    let req = Request::delete(&v1(&format!("/books/{book_id}")))
+        .bearer()
+        .header(IF_MATCH, etag)
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), expected);
+
+    Ok(())
+}
+
+// 指定した項目のみを検証し、省略した項目は`None`としてリポジトリに渡す。
+#[rstest]
+#[case(serde_json::json!({ "description": "" }), StatusCode::OK)]
+#[case(serde_json::json!({ "title": "Rust", "isbn": "9781718503106" }), StatusCode::OK)]
+#[case(serde_json::json!({}), StatusCode::OK)]
+#[case(serde_json::json!({ "title": "" }), StatusCode::BAD_REQUEST)]
+#[case(serde_json::json!({ "isbn": "9781718503107" }), StatusCode::BAD_REQUEST)]
+#[tokio::test]
+async fn patch_book_validates_present_fields(
+    mut fixture: MockAppRegistryExt,
+    #[case] body: serde_json::Value,
+    #[case] expected: StatusCode,
+) -> anyhow::Result<()> {
+    let book_id = BookId::new();
+    let times = usize::from(expected == StatusCode::OK);
+    let title = body["title"].as_str().map(String::from);
+    let description = body["description"].as_str().map(String::from);
+    fixture.expect_book_repository().returning(move || {
+        let mut mock = MockBookRepository::new();
+        let title = title.clone();
+        let description = description.clone();
+        mock.expect_patch()
+            .withf(move |event| {
+                event.book_id == book_id
+                    && event.title == title
+                    && event.author.is_none()
+                    && event.description == description
+                    && event.expected_version.is_none()
+            })
+            .times(times)
+            .returning(|_| Ok(()));
+        Arc::new(mock)
+    });
+
+    let app = make_router(fixture);
+    let req = Request::patch(&v1(&format!("/books/{book_id}")))
+        .bearer()
+        .application_json()
+        .body(Body::from(body.to_string()))?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), expected);
+
+    Ok(())
+}
+
+#[rstest]
+#[case(Role::User, StatusCode::OK)]
+#[case(Role::User, StatusCode::FORBIDDEN)]
+#[case(Role::Admin, StatusCode::NOT_FOUND)]
+#[tokio::test]
+async fn revert_book_to_revision(
+    fixture_auth: MockAppRegistryExt,
+    #[case] role: Role,
+    #[case] expected: StatusCode,
+) -> anyhow::Result<()> {
+    let book_id = BookId::new();
+    let mut fixture = with_current_user(fixture_auth, role);
+    fixture.expect_book_repository().returning(move || {
+        let mut mock = MockBookRepository::new();
+        mock.expect_revert()
+            .withf(move |event| {
+                event.book_id == book_id
+                    && event.revision_number == 2
+                    && event.requested_user == dummy_user_id()
+                    && event.requested_role == role
+            })
+            .times(1)
+            .returning(move |_| repository_result(expected));
+        Arc::new(mock)
+    });
+
+    let app = make_router(fixture);
+    let req = Request::post(&v1(&format!("/books/{book_id}/revisions/2/revert")))
+        .bearer()
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), expected);
+
+    Ok(())
+}
+
+// 管理者が`force`を指定した場合のみ、承諾なしに所有者を変更する。
+#[rstest]
+#[case(Role::User, false, StatusCode::CREATED)]
+#[case(Role::User, true, StatusCode::FORBIDDEN)]
+#[case(Role::Admin, false, StatusCode::CREATED)]
+#[case(Role::Admin, true, StatusCode::NO_CONTENT)]
+#[tokio::test]
+async fn create_book_transfer(
+    fixture_auth: MockAppRegistryExt,
+    #[case] role: Role,
+    #[case] force: bool,
+    #[case] expected: StatusCode,
+) -> anyhow::Result<()> {
+    let book_id = BookId::new();
+    let to_user = UserId::new();
+    let mut fixture = with_current_user(fixture_auth, role);
+    let (creates, forces) = match expected {
+        StatusCode::CREATED => (1, 0),
+        StatusCode::NO_CONTENT => (0, 1),
+        _ => (0, 0),
+    };
+    fixture
+        .expect_book_transfer_repository()
+        .returning(move || {
+            let mut mock = MockBookTransferRepository::new();
+            mock.expect_create()
+                .withf(move |event| {
+                    event.book_id == book_id
+                        && event.to_user == to_user
+                        && event.requested_user == dummy_user_id()
+                })
+                .times(creates)
+                .returning(|_| Ok(()));
+            mock.expect_force()
+                .withf(move |event| event.book_id == book_id && event.to_user == to_user)
+                .times(forces)
+                .returning(|_| Ok(()));
+            Arc::new(mock)
+        });
+
+    let app = make_router(fixture);
+    let body = serde_json::json!({ "toUserId": to_user, "force": force });
+    let req = Request::post(&v1(&format!("/books/{book_id}/transfers")))
+        .bearer()
+        .application_json()
+        .body(Body::from(body.to_string()))?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), expected);
+
+    Ok(())
+}
@@ -0,0 +1,219 @@
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use rstest::rstest;
+use tower::ServiceExt;
+
+use api::model::checkout::PaginatedCheckoutResponse;
+use kernel::model::checkout::CheckoutState;
+use kernel::model::id::{BookId, UserId};
+use kernel::model::list::PaginatedList;
+use kernel::model::role::Role;
+use kernel::repository::checkout::MockCheckoutRepository;
+use registry::MockAppRegistryExt;
+
+use crate::deserialize_json;
+use crate::helper::{
+    dummy_user_id, fixture, fixture_auth, make_router, v1, with_current_user, TestRequestExt,
+};
+
+// 返却状態を省略した場合、貸出中の一覧と自分の貸出は未返却の貸出を、貸出履歴はすべての貸出を返す。
+// 管理者以外が返却済みの貸出を含めて取得する場合は、自分の貸出のみを返す。
+#[rstest]
+#[case(
+    "/books/checkouts",
+    20,
+    0,
+    Some(CheckoutState::Unreturned),
+    false,
+    false
+)]
+#[case(
+    "/books/checkouts?state=all&limit=5&offset=10",
+    5,
+    10,
+    None,
+    false,
+    true
+)]
+#[case("/books/{book_id}/checkout-history", 20, 0, None, true, true)]
+#[case(
+    "/books/{book_id}/checkout-history?state=returned",
+    20,
+    0,
+    Some(CheckoutState::Returned),
+    true,
+    true
+)]
+#[case(
+    "/users/me/checkouts",
+    20,
+    0,
+    Some(CheckoutState::Unreturned),
+    false,
+    true
+)]
+#[tokio::test]
+async fn show_checkout_list_with_query_200(
+    mut fixture: MockAppRegistryExt,
+    #[case] path: &str,
+    #[case] expected_limit: i64,
+    #[case] expected_offset: i64,
+    #[case] expected_state: Option<CheckoutState>,
+    #[case] by_book: bool,
+    #[case] by_borrower: bool,
+) -> anyhow::Result<()> {
+    let book_id = BookId::new();
+    fixture.expect_checkout_repository().returning(move || {
+        let mut mock = MockCheckoutRepository::new();
+        mock.expect_find_all()
+            .withf(move |opt| {
+                opt.limit == expected_limit
+                    && opt.offset == expected_offset
+                    && opt.state == expected_state
+                    && opt.book_id == by_book.then_some(book_id)
+                    && opt.borrower == by_borrower.then(dummy_user_id)
+            })
+            .times(1)
+            .returning(|opt| {
+                Ok(PaginatedList {
+                    total: 0,
+                    limit: opt.limit,
+                    offset: opt.offset,
+                    next_cursor: None,
+                    items: vec![],
+                })
+            });
+        Arc::new(mock)
+    });
+
+    let app = make_router(fixture);
+    let path = path.replace("{book_id}", &book_id.to_string());
+    let req = Request::get(&v1(&path)).bearer().body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let result = deserialize_json!(resp, PaginatedCheckoutResponse);
+    assert_eq!(result.limit, expected_limit);
+    assert_eq!(result.offset, expected_offset);
+
+    Ok(())
+}
+
+#[rstest]
+#[case("/books/checkouts?limit=-1")]
+#[case("/books/checkouts?state=lost")]
+#[tokio::test]
+async fn show_checkout_list_with_invalid_query_400(
+    fixture: MockAppRegistryExt,
+    #[case] path: &str,
+) -> anyhow::Result<()> {
+    let app = make_router(fixture);
+    let req = Request::get(&v1(path)).bearer().body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
+
+// 他のユーザーの貸出は管理者のみ指定できる。
+#[rstest]
+#[case(
+    "/books/checkouts?userId={user_id}&state=returned",
+    Role::User,
+    StatusCode::FORBIDDEN
+)]
+#[case(
+    "/books/checkouts?userId={user_id}&state=returned",
+    Role::Admin,
+    StatusCode::OK
+)]
+#[case(
+    "/books/{book_id}/checkout-history?userId={user_id}",
+    Role::User,
+    StatusCode::FORBIDDEN
+)]
+#[case(
+    "/books/{book_id}/checkout-history?userId={user_id}",
+    Role::Admin,
+    StatusCode::OK
+)]
+#[tokio::test]
+async fn show_other_user_checkouts(
+    fixture_auth: MockAppRegistryExt,
+    #[case] path: &str,
+    #[case] role: Role,
+    #[case] expected: StatusCode,
+) -> anyhow::Result<()> {
+    let book_id = BookId::new();
+    let user_id = UserId::new();
+    let times = usize::from(expected == StatusCode::OK);
+    let mut fixture = with_current_user(fixture_auth, role);
+    fixture.expect_checkout_repository().returning(move || {
+        let mut mock = MockCheckoutRepository::new();
+        mock.expect_find_all()
+            .withf(move |opt| opt.borrower == Some(user_id))
+            .times(times)
+            .returning(|opt| {
+                Ok(PaginatedList {
+                    total: 0,
+                    limit: opt.limit,
+                    offset: opt.offset,
+                    next_cursor: None,
+                    items: vec![],
+                })
+            });
+        Arc::new(mock)
+    });
+
+    let app = make_router(fixture);
+    let path = path
+        .replace("{book_id}", &book_id.to_string())
+        .replace("{user_id}", &user_id.to_string());
+    let req = Request::get(&v1(&path)).bearer().body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), expected);
+
+    Ok(())
+}
+
+// 他のユーザーの貸出履歴は管理者のみ取得できる。
+#[rstest]
+#[case(Role::User, StatusCode::FORBIDDEN)]
+#[case(Role::Admin, StatusCode::OK)]
+#[tokio::test]
+async fn show_user_checkout_history(
+    fixture_auth: MockAppRegistryExt,
+    #[case] role: Role,
+    #[case] expected: StatusCode,
+) -> anyhow::Result<()> {
+    let user_id = UserId::new();
+    let times = usize::from(expected == StatusCode::OK);
+    let mut fixture = with_current_user(fixture_auth, role);
+    fixture.expect_checkout_repository().returning(move || {
+        let mut mock = MockCheckoutRepository::new();
+        mock.expect_find_history_by_user_id()
+            .withf(move |id, opt| *id == user_id && opt.state.is_none() && opt.limit == 20)
+            .times(times)
+            .returning(|_, opt| {
+                Ok(PaginatedList {
+                    total: 0,
+                    limit: opt.limit,
+                    offset: opt.offset,
+                    next_cursor: None,
+                    items: vec![],
+                })
+            });
+        Arc::new(mock)
+    });
+
+    let app = make_router(fixture);
+    let req = Request::get(&v1(&format!("/users/{user_id}/checkout-history")))
+        .bearer()
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), expected);
+
+    Ok(())
+}
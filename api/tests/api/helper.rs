@@ -1,4 +1,4 @@
-/*
+use std::str::FromStr;
 use std::sync::Arc;
 
 use axum::http::request::Builder;
@@ -18,10 +18,15 @@ pub fn v1(endpoint: &str) -> String {
     format!("/api/v1{}", endpoint)
 }
 
+/// アクセストークンに紐づくユーザーのユーザーID
+pub fn dummy_user_id() -> UserId {
+    UserId::from_str("0a1b2c3d-4e5f-4a6b-8c7d-9e0f1a2b3c4d").unwrap()
+}
+
 pub fn make_router(registry: MockAppRegistryExt) -> Router {
     Router::new()
         .merge(v1::routers())
-        .merge(auth::build_auth_routes())
+        .merge(auth::routes())
         .with_state(Arc::new(registry))
 }
 
@@ -36,10 +41,10 @@ pub fn fixture_auth(mut fixture_registry: MockAppRegistryExt) -> MockAppRegistry
         let mut mock_auth_repository = MockAuthRepository::new();
         mock_auth_repository
             .expect_fetch_user_id_from_token()
-            .returning(|_| Ok(Some(UserId::new())));
+            .returning(|_| Ok(Some(dummy_user_id())));
         mock_auth_repository
             .expect_verify_user()
-            .returning(|_, _| Ok(UserId::new()));
+            .returning(|_, _| Ok(dummy_user_id()));
         mock_auth_repository
             .expect_create_token()
             .returning(|_| Ok(AccessToken("dummy".into())));
@@ -48,23 +53,35 @@ pub fn fixture_auth(mut fixture_registry: MockAppRegistryExt) -> MockAppRegistry
     fixture_registry
 }
 
-#[fixture]
-pub fn fixture(mut fixture_auth: MockAppRegistryExt) -> MockAppRegistryExt {
-    fixture_auth.expect_user_repository().returning(|| {
+/// アクセストークンに紐づくユーザーを、指定したロールで返すようにする。
+pub fn with_current_user(mut registry: MockAppRegistryExt, role: Role) -> MockAppRegistryExt {
+    registry.expect_user_repository().returning(move || {
         let mut mock_user_repository = MockUserRepository::new();
         mock_user_repository
             .expect_find_current_user()
-            .returning(|id| {
+            .returning(move |id| {
                 Ok(Some(User {
                     id,
                     name: "dummy-user".to_string(),
                     email: "dummy@example.com".to_string(),
-                    role: Role::User,
+                    role,
+                    retain_checkout_history: true,
+                    version: 1,
                 }))
             });
         Arc::new(mock_user_repository)
     });
-    fixture_auth
+    registry
+}
+
+#[fixture]
+pub fn fixture(fixture_auth: MockAppRegistryExt) -> MockAppRegistryExt {
+    with_current_user(fixture_auth, Role::User)
+}
+
+#[fixture]
+pub fn fixture_admin(fixture_auth: MockAppRegistryExt) -> MockAppRegistryExt {
+    with_current_user(fixture_auth, Role::Admin)
 }
 
 pub trait TestRequestExt {
@@ -100,4 +117,3 @@ macro_rules! deserialize_json {
         body
     }};
 }
-*/
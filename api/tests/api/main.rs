@@ -1,2 +1,6 @@
 mod book;
+mod checkout;
 mod helper;
+mod recommendation;
+mod report;
+mod review;
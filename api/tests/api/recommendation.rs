@@ -0,0 +1,104 @@
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use rstest::rstest;
+use tower::ServiceExt;
+
+use api::model::recommendation::RecommendationsResponse;
+use kernel::model::id::BookId;
+use kernel::model::recommendation::{Recommendation, RecommendationReason};
+use kernel::repository::book::MockBookRepository;
+use kernel::service::recommendation::MockRecommendationService;
+use registry::MockAppRegistryExt;
+
+use crate::deserialize_json;
+use crate::helper::{dummy_user_id, fixture, make_router, v1, TestRequestExt};
+
+#[rstest]
+#[case("/users/me/recommendations", 10)]
+#[case("/users/me/recommendations?limit=3", 3)]
+#[tokio::test]
+async fn get_recommendations_200(
+    mut fixture: MockAppRegistryExt,
+    #[case] path: &str,
+    #[case] expected_limit: usize,
+) -> anyhow::Result<()> {
+    let book_id = BookId::new();
+    fixture.expect_recommendation_service().returning(move || {
+        let mut mock = MockRecommendationService::new();
+        mock.expect_recommend_for_user()
+            .withf(move |user_id, limit| *user_id == dummy_user_id() && *limit == expected_limit)
+            .times(1)
+            .returning(move |_, _| {
+                Ok(vec![Recommendation {
+                    book_id,
+                    title: "Programming Rust".into(),
+                    author: "Jim Blandy".into(),
+                    isbn: "9781492052593".into(),
+                    score: 5,
+                    reasons: vec![
+                        RecommendationReason::BorrowedTogether,
+                        RecommendationReason::SameAuthor,
+                    ],
+                }])
+            });
+        Arc::new(mock)
+    });
+
+    let app = make_router(fixture);
+    let req = Request::get(&v1(path)).bearer().body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let result = deserialize_json!(resp, RecommendationsResponse);
+    assert_eq!(result.items.len(), 1);
+    assert_eq!(result.items[0].book_id, book_id);
+    assert_eq!(result.items[0].reasons.len(), 2);
+
+    Ok(())
+}
+
+#[rstest]
+#[case("/users/me/recommendations?limit=0")]
+#[case("/users/me/recommendations?limit=51")]
+#[tokio::test]
+async fn get_recommendations_with_invalid_limit_400(
+    fixture: MockAppRegistryExt,
+    #[case] path: &str,
+) -> anyhow::Result<()> {
+    let app = make_router(fixture);
+    let req = Request::get(&v1(path)).bearer().body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
+
+// 存在しない蔵書の推薦は取得できない。
+#[rstest]
+#[tokio::test]
+async fn show_book_recommendations_404(mut fixture: MockAppRegistryExt) -> anyhow::Result<()> {
+    let book_id = BookId::new();
+    fixture.expect_book_repository().returning(move || {
+        let mut mock = MockBookRepository::new();
+        mock.expect_find_by_id()
+            .withf(move |id| *id == book_id)
+            .returning(|_| Ok(None));
+        Arc::new(mock)
+    });
+    fixture.expect_recommendation_service().returning(|| {
+        let mut mock = MockRecommendationService::new();
+        mock.expect_recommend_for_book().times(0);
+        Arc::new(mock)
+    });
+
+    let app = make_router(fixture);
+    let req = Request::get(&v1(&format!("/books/{book_id}/recommendations")))
+        .bearer()
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    Ok(())
+}
@@ -0,0 +1,127 @@
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::http::header::{CONTENT_DISPOSITION, CONTENT_TYPE};
+use axum::http::{Request, StatusCode};
+use rstest::rstest;
+use tokio_stream::StreamExt;
+use tower::ServiceExt;
+
+use api::model::report::MostBorrowedBooksResponse;
+use kernel::model::id::BookId;
+use kernel::model::report::BookCirculation;
+use kernel::model::role::Role;
+use kernel::repository::report::MockReportRepository;
+use registry::MockAppRegistryExt;
+
+use crate::deserialize_json;
+use crate::helper::{
+    fixture_admin, fixture_auth, make_router, v1, with_current_user, TestRequestExt,
+};
+
+fn report_fixture(mut fixture: MockAppRegistryExt, times: usize) -> MockAppRegistryExt {
+    fixture.expect_report_repository().returning(move || {
+        let mut mock = MockReportRepository::new();
+        mock.expect_most_borrowed_books()
+            .withf(|period, limit| period.since.is_some() && period.until.is_none() && *limit == 3)
+            .times(times)
+            .returning(|_, _| {
+                Ok(vec![BookCirculation {
+                    book_id: BookId::new(),
+                    title: "Rust, \"The Book\"".into(),
+                    author: "Steve Klabnik".into(),
+                    isbn: "978-1718503106".into(),
+                    checkout_count: 5,
+                }])
+            });
+        Arc::new(mock)
+    });
+    fixture
+}
+
+const MOST_BORROWED_BOOKS: &str = "/reports/most-borrowed-books?limit=3&since=2024-01-01T00:00:00Z";
+
+// 集計は管理者のみ取得できる。
+#[rstest]
+#[case(Role::User, StatusCode::FORBIDDEN)]
+#[case(Role::Admin, StatusCode::OK)]
+#[tokio::test]
+async fn show_most_borrowed_books(
+    fixture_auth: MockAppRegistryExt,
+    #[case] role: Role,
+    #[case] expected: StatusCode,
+) -> anyhow::Result<()> {
+    let times = usize::from(expected == StatusCode::OK);
+    let fixture = report_fixture(with_current_user(fixture_auth, role), times);
+
+    let app = make_router(fixture);
+    let req = Request::get(&v1(MOST_BORROWED_BOOKS))
+        .bearer()
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), expected);
+
+    if expected == StatusCode::OK {
+        let result = deserialize_json!(resp, MostBorrowedBooksResponse);
+        assert_eq!(result.items.len(), 1);
+        assert_eq!(result.items[0].checkout_count, 5);
+    }
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn show_most_borrowed_books_as_csv(fixture_admin: MockAppRegistryExt) -> anyhow::Result<()> {
+    let fixture = report_fixture(fixture_admin, 1);
+
+    let app = make_router(fixture);
+    let req = Request::get(&v1(&format!("{MOST_BORROWED_BOOKS}&format=csv")))
+        .bearer()
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers()[CONTENT_TYPE], "text/csv; charset=utf-8");
+    assert_eq!(
+        resp.headers()[CONTENT_DISPOSITION],
+        "attachment; filename=\"most-borrowed-books.csv\""
+    );
+
+    let mut bytes = Vec::new();
+    let mut stream = resp.into_body().into_data_stream();
+    while let Some(chunk) = stream.try_next().await? {
+        bytes.extend_from_slice(&chunk[..]);
+    }
+    let body = String::from_utf8(bytes)?;
+    let mut lines = body.lines();
+    assert_eq!(
+        lines.next(),
+        Some("book_id,title,author,isbn,checkout_count")
+    );
+    assert!(
+        lines.next().is_some_and(
+            |l| l.ends_with(",\"Rust, \"\"The Book\"\"\",Steve Klabnik,978-1718503106,5")
+        )
+    );
+    assert_eq!(lines.next(), None);
+
+    Ok(())
+}
+
+#[rstest]
+#[case("/reports/most-borrowed-books?limit=0")]
+#[case("/reports/most-active-borrowers?limit=101")]
+#[case("/reports/loan-duration?format=xml")]
+#[case("/reports/checkouts-per-month?since=yesterday")]
+#[tokio::test]
+async fn show_report_with_invalid_query_400(
+    fixture_admin: MockAppRegistryExt,
+    #[case] path: &str,
+) -> anyhow::Result<()> {
+    let app = make_router(fixture_admin);
+    let req = Request::get(&v1(path)).bearer().body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
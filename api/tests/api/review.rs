@@ -0,0 +1,82 @@
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use rstest::rstest;
+use tower::ServiceExt;
+
+use api::model::review::CreateReviewResponse;
+use kernel::model::id::{BookId, ReviewId};
+use kernel::repository::review::MockReviewRepository;
+use registry::MockAppRegistryExt;
+
+use crate::deserialize_json;
+use crate::helper::{dummy_user_id, fixture, make_router, v1, TestRequestExt};
+
+// 本文の前後の空白は取り除き、空白のみの本文は省略したものとみなす。
+#[rstest]
+#[case(serde_json::json!({ "rating": 5, "comment": "  Worth reading  " }), Some("Worth reading"))]
+#[case(serde_json::json!({ "rating": 1, "comment": "   " }), None)]
+#[case(serde_json::json!({ "rating": 3 }), None)]
+#[tokio::test]
+async fn create_review_201(
+    mut fixture: MockAppRegistryExt,
+    #[case] body: serde_json::Value,
+    #[case] expected_comment: Option<&'static str>,
+) -> anyhow::Result<()> {
+    let book_id = BookId::new();
+    let review_id = ReviewId::new();
+    let expected_rating = body["rating"].as_i64().unwrap() as i32;
+    fixture.expect_review_repository().returning(move || {
+        let mut mock = MockReviewRepository::new();
+        mock.expect_create()
+            .withf(move |event| {
+                event.book_id == book_id
+                    && event.reviewed_by == dummy_user_id()
+                    && event.rating == expected_rating
+                    && event.comment.as_deref() == expected_comment
+            })
+            .times(1)
+            .returning(move |_| Ok(review_id));
+        Arc::new(mock)
+    });
+
+    let app = make_router(fixture);
+    let req = Request::post(&v1(&format!("/books/{book_id}/reviews")))
+        .bearer()
+        .application_json()
+        .body(Body::from(body.to_string()))?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+
+    let result = deserialize_json!(resp, CreateReviewResponse);
+    assert_eq!(result.id, review_id);
+
+    Ok(())
+}
+
+#[rstest]
+#[case(serde_json::json!({ "rating": 0 }))]
+#[case(serde_json::json!({ "rating": 6 }))]
+#[case(serde_json::json!({ "rating": 4, "comment": "a".repeat(2001) }))]
+#[tokio::test]
+async fn create_review_with_invalid_body_400(
+    mut fixture: MockAppRegistryExt,
+    #[case] body: serde_json::Value,
+) -> anyhow::Result<()> {
+    fixture.expect_review_repository().returning(|| {
+        let mut mock = MockReviewRepository::new();
+        mock.expect_create().times(0);
+        Arc::new(mock)
+    });
+
+    let app = make_router(fixture);
+    let req = Request::post(&v1(&format!("/books/{}/reviews", BookId::new())))
+        .bearer()
+        .application_json()
+        .body(Body::from(body.to_string()))?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
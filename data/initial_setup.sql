@@ -15,3 +15,9 @@ FROM
     roles
 WHERE
     name LIKE 'Admin';
+
+INSERT INTO loan_policies (role_id, max_checkouts, loan_period_days, max_renewals)
+SELECT role_id, NULL, 28, 5 FROM roles WHERE name = 'Admin'
+UNION ALL
+SELECT role_id, 5, 14, 2 FROM roles WHERE name = 'User'
+ON CONFLICT DO NOTHING;
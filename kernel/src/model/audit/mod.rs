@@ -0,0 +1,93 @@
+use chrono::{DateTime, Utc};
+use strum::{AsRefStr, EnumString};
+
+use crate::model::id::{AuditEventId, UserId};
+
+/// 監査ログに記録した変更
+#[derive(Debug)]
+pub struct AuditEvent {
+    pub id: AuditEventId,
+    /// 操作したユーザー (ログインに失敗した場合など、操作したユーザーが認証されていない場合は`None`)
+    pub actor: Option<UserId>,
+    pub action: AuditAction,
+    pub entity_type: AuditEntityType,
+    /// 変更した対象の識別子 (貸出ポリシーの場合はロール名)
+    pub entity_id: String,
+    /// 変更前の対象 (作成した場合は`None`)
+    pub before: Option<serde_json::Value>,
+    /// 変更後の対象 (削除した場合は`None`)
+    pub after: Option<serde_json::Value>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// 監査ログに記録する操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, AsRefStr)]
+#[strum(serialize_all = "snake_case")]
+pub enum AuditAction {
+    CreateBook,
+    UpdateBook,
+    RevertBook,
+    DeleteBook,
+    RestoreBook,
+    PurgeBook,
+    TransferBook,
+    CreateBookTransfer,
+    DeleteBookTransfer,
+    UpdateBookCover,
+    AttachTag,
+    DetachTag,
+    CreateBookCopy,
+    UpdateBookCopy,
+    DeleteBookCopy,
+    CreateTag,
+    UpdateTag,
+    DeleteTag,
+    CreateUser,
+    UpdateUserPassword,
+    UpdateUserRole,
+    UpdateUserPrivacy,
+    DeleteUser,
+    CreateCheckout,
+    RenewCheckout,
+    ReturnCheckout,
+    CreateHold,
+    DeleteHold,
+    CreateReview,
+    UpdateReview,
+    DeleteReview,
+    UpdateLoanPolicy,
+    Login,
+    LoginFailed,
+    Logout,
+}
+
+/// 監査ログに記録する対象の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, AsRefStr)]
+#[strum(serialize_all = "snake_case")]
+pub enum AuditEntityType {
+    Book,
+    BookCopy,
+    Tag,
+    User,
+    Checkout,
+    Hold,
+    LoanPolicy,
+    BookTransfer,
+    Review,
+}
+
+#[derive(Debug)]
+pub struct AuditEventListOptions {
+    pub limit: i64,
+    pub offset: i64,
+    /// 操作したユーザー
+    pub actor: Option<UserId>,
+    /// 変更した対象の種類
+    pub entity_type: Option<AuditEntityType>,
+    /// 変更した対象の識別子
+    pub entity_id: Option<String>,
+    /// この日時以降に記録した変更
+    pub since: Option<DateTime<Utc>>,
+    /// この日時より前に記録した変更
+    pub until: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,75 @@
+use chrono::{DateTime, Utc};
+use strum::{AsRefStr, EnumString};
+
+/// 登録できる表紙画像のサイズの上限 (バイト)
+pub const MAX_COVER_BYTES: usize = 5 * 1024 * 1024;
+
+/// 表紙画像の形式
+///
+/// 文字列表現はMIMEタイプである。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, AsRefStr)]
+pub enum CoverImageFormat {
+    #[strum(serialize = "image/jpeg")]
+    Jpeg,
+    #[strum(serialize = "image/png")]
+    Png,
+}
+
+impl CoverImageFormat {
+    /// 先頭のシグネチャから画像の形式を判定する。
+    pub fn detect(data: &[u8]) -> Option<Self> {
+        if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            Some(Self::Jpeg)
+        } else if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Some(Self::Png)
+        } else {
+            None
+        }
+    }
+}
+
+/// 表紙画像の大きさ
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CoverSize {
+    /// 登録された画像
+    #[default]
+    Original,
+    /// 一覧表示用に縮小した画像
+    Thumbnail,
+}
+
+#[derive(Debug)]
+pub struct BookCover {
+    pub format: CoverImageFormat,
+    pub data: Vec<u8>,
+    /// 画像の内容から計算したETag
+    pub etag: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn test_detect_cover_image_format() {
+        assert_eq!(
+            CoverImageFormat::detect(&[0xFF, 0xD8, 0xFF, 0xE0, 0x00]),
+            Some(CoverImageFormat::Jpeg)
+        );
+        assert_eq!(
+            CoverImageFormat::detect(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"),
+            Some(CoverImageFormat::Png)
+        );
+        assert_eq!(CoverImageFormat::detect(b"GIF89a"), None);
+        assert_eq!(CoverImageFormat::detect(&[]), None);
+
+        assert_eq!(
+            CoverImageFormat::from_str("image/png").unwrap(),
+            CoverImageFormat::Png
+        );
+        assert_eq!(CoverImageFormat::Jpeg.as_ref(), "image/jpeg");
+    }
+}
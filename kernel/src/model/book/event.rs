@@ -1,11 +1,15 @@
-use crate::model::id::{BookId, UserId};
+use crate::model::book::{BookCopyCondition, CoverImageFormat, Isbn};
+use crate::model::id::{BookCopyId, BookId, UserId};
+use crate::model::role::Role;
 
 #[derive(Debug)]
 pub struct CreateBook {
     pub title: String,
     pub author: String,
-    pub isbn: String,
+    pub isbn: Isbn,
     pub description: String,
+    /// 同じISBNを持つ蔵書がすでに存在しても登録するか
+    pub allow_duplicate: bool,
 }
 
 #[derive(Debug)]
@@ -13,13 +17,111 @@ pub struct UpdateBook {
     pub book_id: BookId,
     pub title: String,
     pub author: String,
-    pub isbn: String,
+    pub isbn: Isbn,
     pub description: String,
+    /// 同じISBNを持つ他の蔵書がすでに存在しても更新するか
+    pub allow_duplicate: bool,
     pub requested_user: UserId,
+    pub requested_role: Role,
+    /// 更新する蔵書のバージョン (指定した場合、現在のバージョンと異なれば更新しない)
+    pub expected_version: Option<i64>,
 }
 
+/// 蔵書の一部の項目を更新する。`None`の項目は更新しない。
+#[derive(Debug)]
+pub struct PatchBook {
+    pub book_id: BookId,
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub isbn: Option<Isbn>,
+    pub description: Option<String>,
+    /// 同じISBNを持つ他の蔵書がすでに存在しても更新するか (ISBNを更新する場合のみ使用)
+    pub allow_duplicate: bool,
+    pub requested_user: UserId,
+    pub requested_role: Role,
+    /// 更新する蔵書のバージョン (指定した場合、現在のバージョンと異なれば更新しない)
+    pub expected_version: Option<i64>,
+}
+
+impl PatchBook {
+    /// 更新する項目がないか
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none()
+            && self.author.is_none()
+            && self.isbn.is_none()
+            && self.description.is_none()
+    }
+}
+
+/// 蔵書を指定した更新履歴の内容に戻す。
+#[derive(Debug)]
+pub struct RevertBook {
+    pub book_id: BookId,
+    pub revision_number: i32,
+    pub requested_user: UserId,
+    pub requested_role: Role,
+    /// 更新する蔵書のバージョン (指定した場合、現在のバージョンと異なれば更新しない)
+    pub expected_version: Option<i64>,
+}
+
+/// 蔵書を削除してゴミ箱に移す。
 #[derive(Debug)]
 pub struct DeleteBook {
     pub book_id: BookId,
     pub requested_user: UserId,
+    pub requested_role: Role,
+    /// 削除する蔵書のバージョン (指定した場合、現在のバージョンと異なれば削除しない)
+    pub expected_version: Option<i64>,
+}
+
+/// ゴミ箱にある蔵書を元に戻す。
+#[derive(Debug)]
+pub struct RestoreBook {
+    pub book_id: BookId,
+    pub requested_user: UserId,
+}
+
+/// ゴミ箱にある蔵書を、貸出履歴などの関連するデータとともに完全に削除する。
+#[derive(Debug)]
+pub struct PurgeBook {
+    pub book_id: BookId,
+    pub requested_user: UserId,
+}
+
+#[derive(Debug)]
+pub struct CreateBookCopy {
+    pub book_id: BookId,
+    pub barcode: String,
+    pub condition: BookCopyCondition,
+    pub location: String,
+    pub requested_user: UserId,
+    pub requested_role: Role,
+}
+
+#[derive(Debug)]
+pub struct UpdateBookCopy {
+    pub copy_id: BookCopyId,
+    pub book_id: BookId,
+    pub barcode: String,
+    pub condition: BookCopyCondition,
+    pub location: String,
+    pub requested_user: UserId,
+    pub requested_role: Role,
+}
+
+#[derive(Debug)]
+pub struct DeleteBookCopy {
+    pub copy_id: BookCopyId,
+    pub book_id: BookId,
+    pub requested_user: UserId,
+    pub requested_role: Role,
+}
+
+#[derive(Debug)]
+pub struct UpdateBookCover {
+    pub book_id: BookId,
+    pub format: CoverImageFormat,
+    pub data: Vec<u8>,
+    pub requested_user: UserId,
+    pub requested_role: Role,
 }
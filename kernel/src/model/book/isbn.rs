@@ -0,0 +1,142 @@
+use std::fmt;
+use std::str::FromStr;
+
+use shared::error::AppError;
+
+/// ISBN
+///
+/// ISBN-10とISBN-13のどちらも受け付け、ハイフンと空白を取り除いたISBN-13に正規化して保持する。
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Isbn(String);
+
+impl Isbn {
+    /// 正規化したISBN-13の文字列を返す。
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// ISBN-13の先頭12桁からチェックディジットを計算する。
+    fn isbn13_check_digit(digits: &[u32]) -> u32 {
+        let sum: u32 = digits
+            .iter()
+            .enumerate()
+            .map(|(i, d)| if i % 2 == 0 { *d } else { d * 3 })
+            .sum();
+        (10 - sum % 10) % 10
+    }
+
+    /// ISBN-10の先頭9桁からチェックディジットを計算する。`10`は`X`を表す。
+    fn isbn10_check_digit(digits: &[u32]) -> u32 {
+        let sum: u32 = digits
+            .iter()
+            .enumerate()
+            .map(|(i, d)| d * (10 - i as u32))
+            .sum();
+        (11 - sum % 11) % 11
+    }
+}
+
+impl FromStr for Isbn {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || AppError::UnprocessableEntity(format!("invalid ISBN: {s}"));
+        let chars: Vec<char> = s
+            .chars()
+            .filter(|c| *c != '-' && !c.is_whitespace())
+            .collect();
+        let digit = |c: &char| c.to_digit(10).ok_or_else(invalid);
+
+        match chars.len() {
+            13 => {
+                let digits = chars.iter().map(digit).collect::<Result<Vec<_>, _>>()?;
+                if !matches!(&digits[..3], [9, 7, 8] | [9, 7, 9])
+                    || Self::isbn13_check_digit(&digits[..12]) != digits[12]
+                {
+                    return Err(invalid());
+                }
+                Ok(Self(chars.into_iter().collect()))
+            }
+            10 => {
+                let digits = chars[..9]
+                    .iter()
+                    .map(digit)
+                    .collect::<Result<Vec<_>, _>>()?;
+                let check = match chars[9] {
+                    'X' | 'x' => 10,
+                    c => digit(&c)?,
+                };
+                if Self::isbn10_check_digit(&digits) != check {
+                    return Err(invalid());
+                }
+                // ISBN-10は先頭に978を付け、チェックディジットを計算し直してISBN-13に変換する。
+                let mut digits13 = vec![9, 7, 8];
+                digits13.extend(digits);
+                digits13.push(Self::isbn13_check_digit(&digits13));
+                Ok(Self(digits13.iter().map(u32::to_string).collect()))
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl TryFrom<String> for Isbn {
+    type Error = AppError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Isbn> for String {
+    fn from(value: Isbn) -> Self {
+        value.0
+    }
+}
+
+impl fmt::Display for Isbn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_isbn13() {
+        let isbn = Isbn::from_str("978-4-06-536957-9").unwrap();
+        assert_eq!(isbn.as_str(), "9784065369579");
+        assert_eq!(Isbn::from_str("9784065369579").unwrap(), isbn);
+        assert_eq!(Isbn::from_str("978 4 06 536957 9").unwrap(), isbn);
+    }
+
+    #[test]
+    fn test_convert_isbn10_to_isbn13() {
+        let isbn = Isbn::from_str("4-06-536957-X");
+        assert!(isbn.is_err());
+
+        let isbn = Isbn::from_str("1-4919-6341-7").unwrap();
+        assert_eq!(isbn.as_str(), "9781491963418");
+
+        // チェックディジットがXのISBN-10も変換できる
+        let isbn = Isbn::from_str("0-8044-2957-x").unwrap();
+        assert_eq!(isbn.as_str(), "9780804429573");
+    }
+
+    #[test]
+    fn test_reject_invalid_isbn() {
+        for s in [
+            "",
+            "Test ISBN",
+            "978-4-06-536957-0",
+            "977-4-06-536957-6",
+            "1-4919-6341-8",
+            "97840653695791",
+            "978406536957X",
+        ] {
+            assert!(Isbn::from_str(s).is_err(), "{s}");
+        }
+    }
+}
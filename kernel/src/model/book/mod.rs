@@ -1,8 +1,25 @@
+mod cover;
 pub mod event;
+mod isbn;
+mod revision;
 
+pub use cover::{BookCover, CoverImageFormat, CoverSize, MAX_COVER_BYTES};
+pub use isbn::Isbn;
+pub use revision::{BookField, BookFieldChange, BookFields, BookRevision};
+
+use std::str::FromStr;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use strum::{AsRefStr, EnumString};
+
+use shared::error::AppError;
 
-use crate::model::id::{BookId, CheckoutId};
+use crate::model::id::{BookCopyId, BookId, CheckoutId, UserId};
+use crate::model::list::SortDirection;
+use crate::model::tag::{Tag, TagFilter};
 use crate::model::user::BookOwner;
 use crate::model::user::CheckoutUser;
 
@@ -14,18 +31,236 @@ pub struct Book {
     pub isbn: String,
     pub description: String,
     pub owner: BookOwner,
-    pub checkout: Option<Checkout>,
+    /// 紛失したものを除く複本の数
+    pub total_copies: i64,
+    /// 貸出中でも紛失してもいない複本の数
+    pub available_copies: i64,
+    /// 未返却の貸出
+    pub checkouts: Vec<Checkout>,
+    /// 蔵書に付けたタグ (タグ名の順)
+    pub tags: Vec<Tag>,
+    /// 表紙画像のETag (表紙画像が登録されていない場合は`None`)
+    pub cover_etag: Option<String>,
+    /// レビューの評価の平均 (レビューがない場合は`None`)
+    pub average_rating: Option<f64>,
+    pub review_count: i64,
+    /// 蔵書を更新するたびに増えるバージョン
+    pub version: i64,
+}
+
+/// 削除されてゴミ箱にある蔵書
+#[derive(Debug)]
+pub struct DeletedBook {
+    pub id: BookId,
+    pub title: String,
+    pub author: String,
+    pub isbn: String,
+    pub description: String,
+    pub owner: BookOwner,
+    pub deleted_at: DateTime<Utc>,
+}
+
+/// ゴミ箱にある蔵書の一覧を取得するときの条件
+#[derive(Debug)]
+pub struct DeletedBookListOptions {
+    pub limit: i64,
+    pub offset: i64,
 }
 
 #[derive(Debug)]
 pub struct BookListOptions {
     pub limit: i64,
+    /// 取得対象とする蔵書一覧の開始位置
+    ///
+    /// `cursor`が指定された場合は無視される。
     pub offset: i64,
+    /// 蔵書一覧の並び順
+    pub sort: BookSort,
+    /// 前のページの最後の蔵書の次から取得するためのカーソル
+    pub cursor: Option<BookListCursor>,
+    /// タイトル、著者、ISBN及び説明を対象とした検索語
+    ///
+    /// 指定された場合、蔵書一覧は検索語との関連度が高い順に並ぶ。
+    pub query: Option<String>,
+    /// 蔵書の所有者
+    pub owner: Option<UserId>,
+    /// 蔵書の貸出状態
+    pub checkout_state: Option<BookCheckoutState>,
+    /// 蔵書に付けたタグ
+    pub tags: Option<TagFilter>,
+}
+
+/// 蔵書一覧の並び順
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BookSort {
+    pub key: BookSortKey,
+    pub direction: SortDirection,
+}
+
+/// 蔵書一覧を並べ替えるキー
+///
+/// キーの値が等しい蔵書は、蔵書IDの順に並ぶ。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, AsRefStr)]
+#[strum(serialize_all = "snake_case")]
+pub enum BookSortKey {
+    /// 検索語との関連度
+    Relevance,
+    Title,
+    Author,
+    CreatedAt,
+    UpdatedAt,
+    /// レビューの評価の平均 (レビューがない蔵書は0とみなす)
+    Rating,
+}
+
+impl BookSortKey {
+    /// 並び順が指定されなかったときの並び順を返す。
+    ///
+    /// 関連度、日時及び評価は新しいものや高いものから、文字列は辞書順に並べる。
+    pub fn default_direction(self) -> SortDirection {
+        match self {
+            Self::Title | Self::Author => SortDirection::Asc,
+            Self::Relevance | Self::CreatedAt | Self::UpdatedAt | Self::Rating => {
+                SortDirection::Desc
+            }
+        }
+    }
+}
+
+impl From<BookSortKey> for BookSort {
+    fn from(key: BookSortKey) -> Self {
+        Self {
+            key,
+            direction: key.default_direction(),
+        }
+    }
+}
+
+/// 蔵書一覧のキーセットページネーションに使用するカーソル
+///
+/// 直前のページの最後の蔵書の並べ替えキーの値と蔵書IDを保持する。
+/// クライアントには、並び順を含めてBase64でエンコードした不透明な文字列として渡す。
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct BookListCursor {
+    pub sort: BookSort,
+    /// 並べ替えキーの値をデータベースで文字列に変換した値
+    pub value: String,
+    pub id: BookId,
+}
+
+impl From<BookListCursor> for String {
+    fn from(cursor: BookListCursor) -> Self {
+        let BookListCursor { sort, value, id } = cursor;
+        let raw = format!(
+            "{}:{}:{}:{}",
+            sort.key.as_ref(),
+            sort.direction.as_ref(),
+            id,
+            value
+        );
+        URL_SAFE_NO_PAD.encode(raw)
+    }
+}
+
+impl TryFrom<String> for BookListCursor {
+    type Error = AppError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let invalid = || AppError::ConversionEntityError("invalid book list cursor".into());
+        let raw = URL_SAFE_NO_PAD
+            .decode(value)
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .ok_or_else(invalid)?;
+        let mut parts = raw.splitn(4, ':');
+        let (Some(key), Some(direction), Some(id), Some(value)) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+        Ok(Self {
+            sort: BookSort {
+                key: BookSortKey::from_str(key).map_err(|_| invalid())?,
+                direction: SortDirection::from_str(direction).map_err(|_| invalid())?,
+            },
+            value: value.into(),
+            id: BookId::from_str(id).map_err(|_| invalid())?,
+        })
+    }
+}
+
+/// 蔵書一覧を絞り込むときに指定する蔵書の貸出状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookCheckoutState {
+    /// 貸出可能
+    Available,
+    /// 貸出中
+    CheckedOut,
+}
+
+/// 蔵書を一括登録するときの方法
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum BookImportMode {
+    /// 1冊でも登録に失敗した場合は、いずれの蔵書も登録しない
+    #[default]
+    Atomic,
+    /// 登録に失敗した蔵書を除いて登録する
+    BestEffort,
+}
+
+/// 一括登録で指定された蔵書ごとの結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BookImportOutcome {
+    /// 登録した
+    Created(BookId),
+    /// 同じISBNを持つ蔵書がすでに存在したため登録しなかった
+    Skipped(String),
+    /// 登録に失敗した
+    Failed(String),
 }
 
 #[derive(Debug)]
 pub struct Checkout {
     pub checkout_id: CheckoutId,
+    pub copy_id: BookCopyId,
     pub checked_out_by: CheckoutUser,
     pub checked_out_at: DateTime<Utc>,
+    pub due_at: DateTime<Utc>,
+    /// 返却期限を延長した回数
+    pub renewal_count: i32,
+}
+
+impl Checkout {
+    /// `now`の時点で返却期限を過ぎているか確認する。
+    pub fn is_overdue(&self, now: DateTime<Utc>) -> bool {
+        now > self.due_at
+    }
+}
+
+/// 蔵書の複本
+///
+/// 蔵書は書誌情報を表し、複本は書誌情報に対応する現物を表す。
+#[derive(Debug)]
+pub struct BookCopy {
+    pub id: BookCopyId,
+    pub book_id: BookId,
+    pub barcode: String,
+    pub condition: BookCopyCondition,
+    /// 配架場所
+    pub location: String,
+    /// 貸出中か
+    pub checked_out: bool,
+}
+
+/// 複本の状態
+///
+/// 紛失した複本は貸出できない。
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, EnumString, AsRefStr)]
+pub enum BookCopyCondition {
+    #[default]
+    Good,
+    Fair,
+    Damaged,
+    Lost,
 }
@@ -0,0 +1,104 @@
+use chrono::{DateTime, Utc};
+use strum::{AsRefStr, EnumString};
+
+use crate::model::user::BookReviser;
+
+/// 更新履歴に記録する蔵書の項目の値
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BookFields {
+    pub title: String,
+    pub author: String,
+    pub isbn: String,
+    pub description: String,
+}
+
+impl BookFields {
+    /// `after`と値が異なる項目を、項目の定義順に返す。
+    pub fn diff(&self, after: &BookFields) -> Vec<BookFieldChange> {
+        [
+            (BookField::Title, &self.title, &after.title),
+            (BookField::Author, &self.author, &after.author),
+            (BookField::Isbn, &self.isbn, &after.isbn),
+            (
+                BookField::Description,
+                &self.description,
+                &after.description,
+            ),
+        ]
+        .into_iter()
+        .filter(|(_, before, after)| before != after)
+        .map(|(field, before, after)| BookFieldChange {
+            field,
+            before: before.clone(),
+            after: after.clone(),
+        })
+        .collect()
+    }
+}
+
+/// 更新履歴に記録する蔵書の項目
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, AsRefStr)]
+#[strum(serialize_all = "snake_case")]
+pub enum BookField {
+    Title,
+    Author,
+    Isbn,
+    Description,
+}
+
+/// 更新で変わった項目の値
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BookFieldChange {
+    pub field: BookField,
+    pub before: String,
+    pub after: String,
+}
+
+/// 蔵書の更新履歴
+///
+/// `fields`は更新する前の値で、`revised_by`と`revised_at`はその値を更新したユーザーと日時である。
+/// 更新履歴を指定して元に戻すと、蔵書の項目は`fields`の値になる。
+#[derive(Debug)]
+pub struct BookRevision {
+    /// 蔵書ごとに1から始まる連番
+    pub revision_number: i32,
+    pub fields: BookFields,
+    /// この更新で変わった項目
+    pub changes: Vec<BookFieldChange>,
+    pub revised_by: BookReviser,
+    pub revised_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(title: &str, isbn: &str) -> BookFields {
+        BookFields {
+            title: title.into(),
+            author: "Steve Klabnik and Carol Nichols".into(),
+            isbn: isbn.into(),
+            description: "".into(),
+        }
+    }
+
+    #[test]
+    fn diff_returns_changed_fields_only() {
+        let before = fields("The Rust Programming Language", "9781718503106");
+
+        assert!(before.diff(&before.clone()).is_empty());
+        assert_eq!(
+            before.diff(&fields("The Rust Book", "9781718503106")),
+            vec![BookFieldChange {
+                field: BookField::Title,
+                before: "The Rust Programming Language".into(),
+                after: "The Rust Book".into(),
+            }]
+        );
+        let changes = before.diff(&fields("The Rust Book", "9781718504448"));
+        assert_eq!(
+            changes.iter().map(|c| c.field).collect::<Vec<_>>(),
+            vec![BookField::Title, BookField::Isbn]
+        );
+    }
+}
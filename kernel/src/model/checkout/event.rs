@@ -1,7 +1,9 @@
 use chrono::{DateTime, Utc};
 use derive_new::new;
 
+use crate::model::checkout::CheckoutOutcome;
 use crate::model::id::{BookId, CheckoutId, UserId};
+use crate::model::role::Role;
 
 #[derive(new)]
 pub struct CreateCheckout {
@@ -10,10 +12,21 @@ pub struct CreateCheckout {
     pub checked_out_at: DateTime<Utc>,
 }
 
+#[derive(new)]
+pub struct RenewCheckout {
+    pub checkout_id: CheckoutId,
+    pub book_id: BookId,
+    pub renewed_by: UserId,
+    pub renewed_at: DateTime<Utc>,
+}
+
 #[derive(new)]
 pub struct UpdateReturned {
     pub checkout_id: CheckoutId,
     pub book_id: BookId,
     pub returned_by: UserId,
+    /// 返却を記録するユーザーのロール (管理者は他のユーザーの貸出も返却できる)
+    pub requested_role: Role,
+    pub outcome: CheckoutOutcome,
     pub returned_at: DateTime<Utc>,
 }
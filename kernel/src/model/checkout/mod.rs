@@ -1,16 +1,33 @@
 pub mod event;
 
 use chrono::{DateTime, Utc};
+use strum::{AsRefStr, EnumString};
 
-use crate::model::id::{BookId, CheckoutId, UserId};
+use crate::model::id::{BookCopyId, BookId, CheckoutId, UserId};
 
 #[derive(Debug)]
 pub struct Checkout {
     pub id: CheckoutId,
     pub book: CheckoutBook,
+    /// 貸出した複本 (返却済みの貸出で、複本が削除された場合は`None`)
+    pub copy_id: Option<BookCopyId>,
     pub checked_out_by: UserId,
     pub checked_out_at: DateTime<Utc>,
+    pub due_at: DateTime<Utc>,
+    /// 返却期限を延長した回数
+    pub renewal_count: i32,
     pub returned_at: Option<DateTime<Utc>>,
+    /// 貸出の終わり方 (未返却の場合は`None`)
+    pub outcome: Option<CheckoutOutcome>,
+}
+
+impl Checkout {
+    /// 返却期限を過ぎているか確認する。
+    ///
+    /// 返却済みの場合は返却日時が、未返却の場合は`now`が返却期限を過ぎているかを返す。
+    pub fn is_overdue(&self, now: DateTime<Utc>) -> bool {
+        self.returned_at.unwrap_or(now) > self.due_at
+    }
 }
 
 #[derive(Debug)]
@@ -20,3 +37,40 @@ pub struct CheckoutBook {
     pub author: String,
     pub isbn: String,
 }
+
+/// 貸出の終わり方
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, EnumString, AsRefStr)]
+pub enum CheckoutOutcome {
+    /// 返却された
+    #[default]
+    Returned,
+    /// 紛失した
+    Lost,
+    /// 破損した状態で返却された
+    Damaged,
+}
+
+/// 貸出の返却状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, AsRefStr)]
+pub enum CheckoutState {
+    /// 未返却
+    Unreturned,
+    /// 返却済み
+    Returned,
+}
+
+#[derive(Debug)]
+pub struct CheckoutListOptions {
+    pub limit: i64,
+    pub offset: i64,
+    /// 借りたユーザー
+    pub borrower: Option<UserId>,
+    /// 貸出した蔵書
+    pub book_id: Option<BookId>,
+    /// 返却状態 (`None`の場合は返却済みと未返却の両方)
+    pub state: Option<CheckoutState>,
+    /// この日時以降に貸出した貸出
+    pub since: Option<DateTime<Utc>>,
+    /// この日時より前に貸出した貸出
+    pub until: Option<DateTime<Utc>>,
+}
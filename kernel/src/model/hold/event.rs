@@ -0,0 +1,21 @@
+use chrono::{DateTime, Utc};
+use derive_new::new;
+
+use crate::model::id::{BookId, HoldId, UserId};
+use crate::model::role::Role;
+
+#[derive(new)]
+pub struct CreateHold {
+    pub book_id: BookId,
+    pub held_by: UserId,
+    pub held_at: DateTime<Utc>,
+}
+
+#[derive(new)]
+pub struct DeleteHold {
+    pub hold_id: HoldId,
+    pub book_id: BookId,
+    pub requested_user: UserId,
+    pub requested_role: Role,
+    pub canceled_at: DateTime<Utc>,
+}
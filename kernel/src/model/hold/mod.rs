@@ -0,0 +1,31 @@
+pub mod event;
+
+use chrono::{DateTime, Utc};
+
+use crate::model::id::{BookId, HoldId};
+use crate::model::user::HoldUser;
+
+/// 貸出中の蔵書の予約
+///
+/// 蔵書の予約は予約した日時の順に待ち行列を構成する。蔵書が返却されると待ち行列の先頭の予約が
+/// 取り置きの状態になり、取り置き期限までは予約したユーザーのみがその蔵書を借りられる。
+#[derive(Debug)]
+pub struct Hold {
+    pub id: HoldId,
+    pub book_id: BookId,
+    pub held_by: HoldUser,
+    /// 待ち行列における順番 (先頭が1)
+    pub position: i64,
+    pub held_at: DateTime<Utc>,
+    /// 取り置きを開始した日時
+    pub ready_at: Option<DateTime<Utc>>,
+    /// 取り置き期限
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl Hold {
+    /// 予約したユーザーのために蔵書が取り置かれているか確認する。
+    pub fn is_ready(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| now < expires_at)
+    }
+}
@@ -67,3 +67,9 @@ macro_rules! define_id {
 define_id!(UserId);
 define_id!(BookId);
 define_id!(CheckoutId);
+define_id!(HoldId);
+define_id!(BookCopyId);
+define_id!(TagId);
+define_id!(AuditEventId);
+define_id!(BookTransferId);
+define_id!(ReviewId);
@@ -1,8 +1,14 @@
+use strum::{AsRefStr, EnumString};
+
 #[derive(Debug)]
 pub struct PaginatedList<T> {
     pub total: i64,
     pub limit: i64,
     pub offset: i64,
+    /// 次のページを取得するためのカーソル
+    ///
+    /// キーセットページネーションに対応していない一覧や、次のページがない場合は`None`。
+    pub next_cursor: Option<String>,
     pub items: Vec<T>,
 }
 
@@ -11,3 +17,13 @@ impl<T> PaginatedList<T> {
         self.items
     }
 }
+
+/// 一覧の並び順
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumString, AsRefStr)]
+#[strum(serialize_all = "lowercase")]
+pub enum SortDirection {
+    /// 昇順
+    Asc,
+    /// 降順
+    Desc,
+}
@@ -0,0 +1,13 @@
+use derive_new::new;
+
+use crate::model::id::UserId;
+use crate::model::role::Role;
+
+#[derive(new)]
+pub struct UpdateLoanPolicy {
+    pub role: Role,
+    pub max_checkouts: Option<i32>,
+    pub loan_period_days: i32,
+    pub max_renewals: i32,
+    pub requested_user: UserId,
+}
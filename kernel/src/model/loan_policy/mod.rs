@@ -0,0 +1,15 @@
+pub mod event;
+
+use crate::model::role::Role;
+
+/// ロールごとの貸出ポリシー
+#[derive(Debug, PartialEq, Eq)]
+pub struct LoanPolicy {
+    pub role: Role,
+    /// 同時に借りられる蔵書の数の上限 (`None`の場合は制限しない)
+    pub max_checkouts: Option<i32>,
+    /// 貸出日時から返却期限までの日数
+    pub loan_period_days: i32,
+    /// 1回の貸出で返却期限を延長できる回数の上限
+    pub max_renewals: i32,
+}
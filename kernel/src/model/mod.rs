@@ -1,7 +1,15 @@
+pub mod audit;
 pub mod auth;
 pub mod book;
 pub mod checkout;
+pub mod hold;
 pub mod id;
 pub mod list;
+pub mod loan_policy;
+pub mod recommendation;
+pub mod report;
+pub mod review;
 pub mod role;
+pub mod tag;
+pub mod transfer;
 pub mod user;
@@ -0,0 +1,41 @@
+use crate::model::id::BookId;
+
+/// 蔵書を推薦する理由
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RecommendationReason {
+    /// 同じ蔵書を借りたユーザーが、この蔵書も借りている
+    BorrowedTogether,
+    /// 著者が同じ
+    SameAuthor,
+    /// 共通するタグがある
+    SharedTags,
+}
+
+/// 推薦の候補となる蔵書
+///
+/// 推薦する理由ごとに1つ作成され、同じ蔵書が複数の理由で候補になることがある。
+#[derive(Debug, Clone)]
+pub struct RecommendationCandidate {
+    pub book_id: BookId,
+    pub title: String,
+    pub author: String,
+    pub isbn: String,
+    pub reason: RecommendationReason,
+    /// 理由の強さ
+    ///
+    /// 一緒に借りられた場合はそのユーザーの数、タグが共通する場合は共通するタグの数、
+    /// 著者が同じ場合は1とする。
+    pub strength: i64,
+}
+
+/// 推薦する蔵書
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recommendation {
+    pub book_id: BookId,
+    pub title: String,
+    pub author: String,
+    pub isbn: String,
+    /// 推薦の強さ。大きいほど強く推薦する。
+    pub score: i64,
+    pub reasons: Vec<RecommendationReason>,
+}
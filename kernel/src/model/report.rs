@@ -0,0 +1,72 @@
+use chrono::{DateTime, Utc};
+
+use crate::model::id::{BookId, UserId};
+
+/// 集計する期間
+///
+/// 貸出日時が期間に含まれる貸出を、返却済みかどうかにかかわらず集計する。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReportPeriod {
+    /// この日時以降に貸出した貸出
+    pub since: Option<DateTime<Utc>>,
+    /// この日時より前に貸出した貸出
+    pub until: Option<DateTime<Utc>>,
+}
+
+/// 貸出回数の多い蔵書
+#[derive(Debug)]
+pub struct BookCirculation {
+    pub book_id: BookId,
+    pub title: String,
+    pub author: String,
+    pub isbn: String,
+    pub checkout_count: i64,
+}
+
+/// 貸出回数の多いユーザー
+#[derive(Debug)]
+pub struct BorrowerActivity {
+    pub user_id: UserId,
+    pub name: String,
+    pub checkout_count: i64,
+}
+
+/// 返却済みの貸出の貸出期間
+#[derive(Debug)]
+pub struct LoanDuration {
+    /// 集計した返却済みの貸出の数
+    pub returned_count: i64,
+    /// 貸出から返却までの平均日数 (返却済みの貸出がない場合は`None`)
+    pub average_days: Option<f64>,
+}
+
+/// 現在の複本の利用状況
+///
+/// 紛失した複本は含めない。
+#[derive(Debug)]
+pub struct Utilisation {
+    pub total_copies: i64,
+    pub checked_out_copies: i64,
+}
+
+impl Utilisation {
+    pub fn available_copies(&self) -> i64 {
+        self.total_copies - self.checked_out_copies
+    }
+
+    /// 貸出中の複本の割合 (複本がない場合は0)
+    pub fn rate(&self) -> f64 {
+        if self.total_copies == 0 {
+            return 0.0;
+        }
+        self.checked_out_copies as f64 / self.total_copies as f64
+    }
+}
+
+/// 月ごとの貸出数
+#[derive(Debug)]
+pub struct MonthlyCheckouts {
+    /// `YYYY-MM`形式の年月 (UTC)
+    pub month: String,
+    pub checkout_count: i64,
+}
@@ -0,0 +1,29 @@
+use derive_new::new;
+
+use crate::model::id::{BookId, ReviewId, UserId};
+use crate::model::role::Role;
+
+#[derive(new)]
+pub struct CreateReview {
+    pub book_id: BookId,
+    pub reviewed_by: UserId,
+    pub rating: i32,
+    pub comment: Option<String>,
+}
+
+#[derive(new)]
+pub struct UpdateReview {
+    pub review_id: ReviewId,
+    pub book_id: BookId,
+    pub requested_user: UserId,
+    pub rating: i32,
+    pub comment: Option<String>,
+}
+
+#[derive(new)]
+pub struct DeleteReview {
+    pub review_id: ReviewId,
+    pub book_id: BookId,
+    pub requested_user: UserId,
+    pub requested_role: Role,
+}
@@ -0,0 +1,26 @@
+pub mod event;
+
+use chrono::{DateTime, Utc};
+
+use crate::model::id::{BookId, ReviewId};
+use crate::model::user::ReviewUser;
+
+/// 評価の最小値
+pub const MIN_RATING: i32 = 1;
+/// 評価の最大値
+pub const MAX_RATING: i32 = 5;
+
+/// 蔵書のレビュー
+///
+/// ユーザーは、借りて返却したことのある蔵書に1件だけレビューを書ける。
+#[derive(Debug)]
+pub struct Review {
+    pub id: ReviewId,
+    pub book_id: BookId,
+    pub reviewed_by: ReviewUser,
+    /// 1から5の星の数
+    pub rating: i32,
+    pub comment: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
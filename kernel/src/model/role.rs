@@ -1,6 +1,6 @@
 use strum::{AsRefStr, EnumIter, EnumString};
 
-#[derive(Debug, Default, PartialEq, Eq, Hash, EnumString, AsRefStr, EnumIter)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, EnumString, AsRefStr, EnumIter)]
 pub enum Role {
     Admin,
     #[default]
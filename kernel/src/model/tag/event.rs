@@ -0,0 +1,39 @@
+use derive_new::new;
+
+use crate::model::id::{BookId, TagId, UserId};
+use crate::model::role::Role;
+
+#[derive(new)]
+pub struct CreateTag {
+    pub name: String,
+    pub requested_user: UserId,
+}
+
+#[derive(new)]
+pub struct UpdateTag {
+    pub tag_id: TagId,
+    pub name: String,
+    pub requested_user: UserId,
+}
+
+#[derive(new)]
+pub struct DeleteTag {
+    pub tag_id: TagId,
+    pub requested_user: UserId,
+}
+
+#[derive(new)]
+pub struct AttachTag {
+    pub book_id: BookId,
+    pub tag_id: TagId,
+    pub requested_user: UserId,
+    pub requested_role: Role,
+}
+
+#[derive(new)]
+pub struct DetachTag {
+    pub book_id: BookId,
+    pub tag_id: TagId,
+    pub requested_user: UserId,
+    pub requested_role: Role,
+}
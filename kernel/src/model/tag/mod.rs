@@ -0,0 +1,28 @@
+pub mod event;
+
+use crate::model::id::TagId;
+
+/// 蔵書を分類するタグ
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tag {
+    pub id: TagId,
+    pub name: String,
+}
+
+/// 蔵書一覧をタグで絞り込むときの照合方法
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TagMatch {
+    /// 指定したすべてのタグが付いた蔵書
+    #[default]
+    All,
+    /// 指定したいずれかのタグが付いた蔵書
+    Any,
+}
+
+/// 蔵書一覧を絞り込むタグ
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagFilter {
+    /// タグ名 (大文字と小文字を区別しない)
+    pub names: Vec<String>,
+    pub match_mode: TagMatch,
+}
@@ -0,0 +1,33 @@
+use crate::model::id::{BookId, BookTransferId, UserId};
+use crate::model::role::Role;
+
+/// 蔵書の所有者が、他のユーザーに蔵書の譲渡を申し込む。
+#[derive(Debug)]
+pub struct CreateBookTransfer {
+    pub book_id: BookId,
+    pub to_user: UserId,
+    pub requested_user: UserId,
+}
+
+/// 管理者が、譲渡先のユーザーの承諾なしに蔵書の所有者を変更する。
+#[derive(Debug)]
+pub struct ForceBookTransfer {
+    pub book_id: BookId,
+    pub to_user: UserId,
+    pub requested_user: UserId,
+}
+
+/// 譲渡先のユーザーが、蔵書の譲渡を承諾する。
+#[derive(Debug)]
+pub struct AcceptBookTransfer {
+    pub transfer_id: BookTransferId,
+    pub requested_user: UserId,
+}
+
+/// 譲渡先のユーザーが辞退するか、申し込んだユーザーまたは管理者が取り消す。
+#[derive(Debug)]
+pub struct DeleteBookTransfer {
+    pub transfer_id: BookTransferId,
+    pub requested_user: UserId,
+    pub requested_role: Role,
+}
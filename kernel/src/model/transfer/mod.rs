@@ -0,0 +1,21 @@
+pub mod event;
+
+use chrono::{DateTime, Utc};
+
+use crate::model::id::{BookId, BookTransferId};
+use crate::model::user::TransferUser;
+
+/// 承諾されていない蔵書の所有権の譲渡の申し込み
+///
+/// 譲渡先のユーザーが承諾すると蔵書の所有者が変わり、申し込みは削除される。
+#[derive(Debug)]
+pub struct BookTransfer {
+    pub id: BookTransferId,
+    pub book_id: BookId,
+    pub book_title: String,
+    /// 譲渡を申し込んだ蔵書の所有者
+    pub from: TransferUser,
+    /// 譲渡先のユーザー
+    pub to: TransferUser,
+    pub requested_at: DateTime<Utc>,
+}
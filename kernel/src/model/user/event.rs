@@ -6,12 +6,16 @@ pub struct CreateUser {
     pub name: String,
     pub email: String,
     pub password: String,
+    pub requested_user: UserId,
 }
 
 #[derive(Debug)]
 pub struct UpdateUserRole {
     pub user_id: UserId,
     pub role: Role,
+    pub requested_user: UserId,
+    /// 対象のユーザーのバージョン (指定した場合、現在のバージョンと異なれば更新しない)
+    pub expected_version: Option<i64>,
 }
 
 #[derive(Debug)]
@@ -19,9 +23,27 @@ pub struct UpdateUserPassword {
     pub user_id: UserId,
     pub current_password: String,
     pub new_password: String,
+    /// 対象のユーザーのバージョン (指定した場合、現在のバージョンと異なれば更新しない)
+    pub expected_version: Option<i64>,
+}
+
+/// 返却した貸出の履歴を残すかの設定を変更する。
+///
+/// 残さないように変更した場合は、それまでに返却した貸出の履歴も削除する。
+#[derive(Debug)]
+pub struct UpdateUserPrivacy {
+    pub user_id: UserId,
+    pub retain_checkout_history: bool,
+    /// 対象のユーザーのバージョン (指定した場合、現在のバージョンと異なれば更新しない)
+    pub expected_version: Option<i64>,
 }
 
 #[derive(Debug)]
 pub struct DeleteUser {
     pub user_id: UserId,
+    pub requested_user: UserId,
+    /// 削除するユーザーが所有する蔵書の譲渡先 (蔵書を所有している場合は必須)
+    pub reassign_to: Option<UserId>,
+    /// 対象のユーザーのバージョン (指定した場合、現在のバージョンと異なれば削除しない)
+    pub expected_version: Option<i64>,
 }
@@ -9,6 +9,10 @@ pub struct User {
     pub name: String,
     pub email: String,
     pub role: Role,
+    /// 返却した貸出の履歴を残すか
+    pub retain_checkout_history: bool,
+    /// ユーザーを更新するたびに増えるバージョン
+    pub version: i64,
 }
 
 #[derive(Debug)]
@@ -17,8 +21,34 @@ pub struct BookOwner {
     pub name: String,
 }
 
+/// 蔵書を更新したユーザー
+#[derive(Debug)]
+pub struct BookReviser {
+    pub id: UserId,
+    /// ユーザーの名前 (ユーザーが削除された場合は`None`)
+    pub name: Option<String>,
+}
+
 #[derive(Debug)]
 pub struct CheckoutUser {
     pub id: UserId,
     pub name: String,
 }
+
+#[derive(Debug)]
+pub struct HoldUser {
+    pub id: UserId,
+    pub name: String,
+}
+
+#[derive(Debug)]
+pub struct TransferUser {
+    pub id: UserId,
+    pub name: String,
+}
+
+#[derive(Debug)]
+pub struct ReviewUser {
+    pub id: UserId,
+    pub name: String,
+}
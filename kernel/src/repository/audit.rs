@@ -0,0 +1,19 @@
+use async_trait::async_trait;
+
+use shared::error::AppResult;
+
+use crate::model::audit::{AuditEvent, AuditEventListOptions};
+use crate::model::list::PaginatedList;
+
+/// 監査ログを参照する。
+///
+/// 監査ログは、変更を記録する各リポジトリが変更と同じトランザクションの中で追記する。
+#[mockall::automock]
+#[async_trait]
+pub trait AuditEventRepository: Send + Sync {
+    /// 条件に一致する監査ログを、記録した日時の新しい順に返す。
+    async fn find_all(
+        &self,
+        options: AuditEventListOptions,
+    ) -> AppResult<PaginatedList<AuditEvent>>;
+}
@@ -0,0 +1,19 @@
+use async_trait::async_trait;
+
+use shared::error::AppResult;
+
+/// 画像などのバイナリデータをキーに対応付けて保存する。
+///
+/// キーは`/`で区切った相対パスの形式とする。
+#[mockall::automock]
+#[async_trait]
+pub trait BlobStore: Send + Sync {
+    /// データを保存する。キーに対応するデータがすでに存在する場合は上書きする。
+    async fn put(&self, key: &str, data: Vec<u8>) -> AppResult<()>;
+    /// キーに対応するデータを返す。存在しない場合は`None`を返す。
+    async fn get(&self, key: &str) -> AppResult<Option<Vec<u8>>>;
+    /// キーに対応するデータを削除する。存在しない場合は何もしない。
+    async fn delete(&self, key: &str) -> AppResult<()>;
+    /// キーが`prefix/`で始まるデータをすべて削除する。存在しない場合は何もしない。
+    async fn delete_prefix(&self, prefix: &str) -> AppResult<()>;
+}
@@ -1,19 +1,66 @@
 use async_trait::async_trait;
+use futures::stream::BoxStream;
 
 use shared::error::AppResult;
 
-use crate::model::book::event::{CreateBook, DeleteBook, UpdateBook};
-use crate::model::book::Book;
-use crate::model::book::BookListOptions;
+use crate::model::book::event::{
+    CreateBook, CreateBookCopy, DeleteBook, DeleteBookCopy, PatchBook, PurgeBook, RestoreBook,
+    RevertBook, UpdateBook, UpdateBookCopy,
+};
+use crate::model::book::{Book, BookCopy, BookRevision, DeletedBook, DeletedBookListOptions};
+use crate::model::book::{BookImportMode, BookImportOutcome, BookListOptions};
 use crate::model::id::{BookId, UserId};
 use crate::model::list::PaginatedList;
 
-#[async_trait]
 #[mockall::automock]
+#[async_trait]
 pub trait BookRepository: Send + Sync {
+    /// ゴミ箱にある蔵書を除いた蔵書の一覧を返す。
     async fn find_all(&self, options: BookListOptions) -> AppResult<PaginatedList<Book>>;
+    /// 蔵書を返す。ゴミ箱にある蔵書は返さない。
     async fn find_by_id(&self, book_id: BookId) -> AppResult<Option<Book>>;
+    /// ゴミ箱にある蔵書を、削除した日時の新しい順に返す。
+    async fn find_deleted_all(
+        &self,
+        options: DeletedBookListOptions,
+    ) -> AppResult<PaginatedList<DeletedBook>>;
+    /// ゴミ箱にある蔵書を除いたすべての蔵書を蔵書IDの順に返すストリームを返す。
+    ///
+    /// 蔵書は一定の数ずつデータベースから取得するため、蔵書の数にかかわらず一度にすべてを読み込まない。
+    fn export(&self) -> BoxStream<'static, AppResult<Book>>;
     async fn create(&self, event: CreateBook, user_id: UserId) -> AppResult<()>;
+    /// 蔵書を一括で登録し、指定された順に蔵書ごとの結果を返す。
+    ///
+    /// 同じISBNを持つ蔵書がすでに存在する場合は、重複が許可されていない蔵書を登録せずに読み飛ばす。
+    /// `BookImportMode::Atomic`の場合に登録に失敗した蔵書があれば、エラーを返していずれの蔵書も登録しない。
+    async fn import(
+        &self,
+        events: Vec<CreateBook>,
+        user_id: UserId,
+        mode: BookImportMode,
+    ) -> AppResult<Vec<BookImportOutcome>>;
+    /// 蔵書を更新し、更新する前の内容を更新履歴に記録する。
     async fn update(&self, event: UpdateBook) -> AppResult<()>;
+    /// 蔵書の指定された項目のみを更新する。
+    async fn patch(&self, event: PatchBook) -> AppResult<()>;
+    /// 蔵書の更新履歴を新しい順に返す。蔵書がゴミ箱にある場合は`AppError::EntityNotFound`を返す。
+    async fn find_revisions(&self, book_id: BookId) -> AppResult<Vec<BookRevision>>;
+    /// 蔵書を指定した更新履歴の内容に戻す。元に戻す操作も更新履歴に記録する。
+    async fn revert(&self, event: RevertBook) -> AppResult<()>;
+    /// 蔵書を削除してゴミ箱に移す。貸出履歴などの関連するデータは削除しない。
     async fn delete(&self, event: DeleteBook) -> AppResult<()>;
+    /// ゴミ箱にある蔵書を元に戻す。
+    async fn restore(&self, event: RestoreBook) -> AppResult<()>;
+    /// ゴミ箱にある蔵書を完全に削除する。貸出履歴などの関連するデータも削除される。
+    ///
+    /// 貸出中の複本がある蔵書は完全に削除できない。
+    async fn purge(&self, event: PurgeBook) -> AppResult<()>;
+    /// 蔵書の複本を返す。
+    async fn find_copies_by_book_id(&self, book_id: BookId) -> AppResult<Vec<BookCopy>>;
+    /// 蔵書に複本を追加する。
+    async fn create_copy(&self, event: CreateBookCopy) -> AppResult<()>;
+    /// 蔵書の複本を更新する。
+    async fn update_copy(&self, event: UpdateBookCopy) -> AppResult<()>;
+    /// 貸出中でない蔵書の複本を削除する。
+    async fn delete_copy(&self, event: DeleteBookCopy) -> AppResult<()>;
 }
@@ -2,21 +2,30 @@ use async_trait::async_trait;
 
 use shared::error::AppResult;
 
-use crate::model::checkout::event::{CreateCheckout, UpdateReturned};
-use crate::model::checkout::Checkout;
-use crate::model::id::{BookId, UserId};
+use crate::model::checkout::event::{CreateCheckout, RenewCheckout, UpdateReturned};
+use crate::model::checkout::{Checkout, CheckoutListOptions};
+use crate::model::id::UserId;
+use crate::model::list::PaginatedList;
 
-#[async_trait]
 #[mockall::automock]
+#[async_trait]
 pub trait CheckoutRepository: Send + Sync {
     /// 蔵書を貸出する。
     async fn create(&self, event: CreateCheckout) -> AppResult<()>;
+    /// 貸出中の蔵書の返却期限を延長する。
+    async fn renew(&self, event: RenewCheckout) -> AppResult<()>;
     /// 蔵書を返却する。
     async fn update_returned(&self, event: UpdateReturned) -> AppResult<()>;
-    /// すべての未返却の貸出を返す。
-    async fn find_unreturned_all(&self) -> AppResult<Vec<Checkout>>;
-    /// ユーザーの未返却の貸出を返す。
-    async fn find_unreturned_by_user_id(&self, user_id: UserId) -> AppResult<Vec<Checkout>>;
-    /// 蔵書の返却済みを含む貸出履歴を返す。
-    async fn find_history_by_book_id(&self, book_id: BookId) -> AppResult<Vec<Checkout>>;
+    /// 条件に一致する貸出を、貸出日時の新しい順に返す。
+    async fn find_all(&self, options: CheckoutListOptions) -> AppResult<PaginatedList<Checkout>>;
+    /// ユーザーの返却済みを含む貸出履歴を、貸出日時の新しい順に返す。
+    ///
+    /// `options`の借りたユーザーは無視し、`user_id`のユーザーの貸出を返す。
+    async fn find_history_by_user_id(
+        &self,
+        user_id: UserId,
+        options: CheckoutListOptions,
+    ) -> AppResult<PaginatedList<Checkout>>;
+    /// 返却期限を過ぎたすべての未返却の貸出を返す。
+    async fn find_overdue_all(&self) -> AppResult<Vec<Checkout>>;
 }
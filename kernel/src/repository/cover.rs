@@ -0,0 +1,20 @@
+use async_trait::async_trait;
+
+use shared::error::AppResult;
+
+use crate::model::book::event::UpdateBookCover;
+use crate::model::book::{BookCover, CoverSize};
+use crate::model::id::BookId;
+
+#[mockall::automock]
+#[async_trait]
+pub trait BookCoverRepository: Send + Sync {
+    /// 蔵書の表紙画像を登録する。すでに登録されている場合は置き換える。
+    ///
+    /// 一覧表示用の縮小画像も合わせて作成する。
+    async fn update(&self, event: UpdateBookCover) -> AppResult<()>;
+    /// 蔵書の表紙画像を返す。表紙画像が登録されていない場合は`None`を返す。
+    async fn find(&self, book_id: BookId, size: CoverSize) -> AppResult<Option<BookCover>>;
+    /// 完全に削除した蔵書の表紙画像を、BlobStoreからすべて削除する。
+    async fn delete_all(&self, book_id: BookId) -> AppResult<()>;
+}
@@ -0,0 +1,18 @@
+use async_trait::async_trait;
+
+use shared::error::AppResult;
+
+use crate::model::hold::event::{CreateHold, DeleteHold};
+use crate::model::hold::Hold;
+use crate::model::id::BookId;
+
+#[mockall::automock]
+#[async_trait]
+pub trait HoldRepository: Send + Sync {
+    /// 貸出中の蔵書を予約する。
+    async fn create(&self, event: CreateHold) -> AppResult<()>;
+    /// 蔵書の予約を待ち行列の順に返す。
+    async fn find_by_book_id(&self, book_id: BookId) -> AppResult<Vec<Hold>>;
+    /// 蔵書の予約を取り消す。
+    async fn delete(&self, event: DeleteHold) -> AppResult<()>;
+}
@@ -0,0 +1,17 @@
+use async_trait::async_trait;
+
+use shared::error::AppResult;
+
+use crate::model::loan_policy::event::UpdateLoanPolicy;
+use crate::model::loan_policy::LoanPolicy;
+
+#[mockall::automock]
+#[async_trait]
+pub trait LoanPolicyRepository: Send + Sync {
+    /// すべてのロールの貸出ポリシーを返す。
+    ///
+    /// 貸出ポリシーが登録されていないロールは、設定値から作成した貸出ポリシーを返す。
+    async fn find_all(&self) -> AppResult<Vec<LoanPolicy>>;
+    /// ロールの貸出ポリシーを登録または更新する。
+    async fn update(&self, event: UpdateLoanPolicy) -> AppResult<()>;
+}
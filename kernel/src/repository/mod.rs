@@ -1,5 +1,15 @@
+pub mod audit;
 pub mod auth;
+pub mod blob;
 pub mod book;
 pub mod checkout;
+pub mod cover;
 pub mod health;
+pub mod hold;
+pub mod loan_policy;
+pub mod recommendation;
+pub mod report;
+pub mod review;
+pub mod tag;
+pub mod transfer;
 pub mod user;
@@ -0,0 +1,23 @@
+use async_trait::async_trait;
+
+use shared::error::AppResult;
+
+use crate::model::id::{BookId, UserId};
+use crate::model::recommendation::RecommendationCandidate;
+
+/// 蔵書の推薦に使う候補を、貸出履歴と蔵書の情報から取得する。
+///
+/// 候補には削除された蔵書と、`book_ids`で指定した蔵書は含まない。
+#[mockall::automock]
+#[async_trait]
+pub trait RecommendationRepository: Send + Sync {
+    /// `book_ids`のいずれかを借りたユーザーが借りた蔵書を返す。
+    async fn find_borrowed_together(
+        &self,
+        book_ids: Vec<BookId>,
+    ) -> AppResult<Vec<RecommendationCandidate>>;
+    /// `book_ids`のいずれかと著者が同じ、またはタグが共通する蔵書を返す。
+    async fn find_similar(&self, book_ids: Vec<BookId>) -> AppResult<Vec<RecommendationCandidate>>;
+    /// ユーザーが借りたことのある蔵書 (貸出中の蔵書を含む) の蔵書IDを返す。
+    async fn find_borrowed_book_ids(&self, user_id: UserId) -> AppResult<Vec<BookId>>;
+}
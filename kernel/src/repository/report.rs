@@ -0,0 +1,31 @@
+use async_trait::async_trait;
+
+use shared::error::AppResult;
+
+use crate::model::report::{
+    BookCirculation, BorrowerActivity, LoanDuration, MonthlyCheckouts, ReportPeriod, Utilisation,
+};
+
+/// 未返却と返却済みの貸出から、貸出の統計を集計する。
+#[mockall::automock]
+#[async_trait]
+pub trait ReportRepository: Send + Sync {
+    /// 期間中の貸出回数が多い順に、上位`limit`冊の蔵書を返す。
+    async fn most_borrowed_books(
+        &self,
+        period: ReportPeriod,
+        limit: i64,
+    ) -> AppResult<Vec<BookCirculation>>;
+    /// 期間中の貸出回数が多い順に、上位`limit`人のユーザーを返す。
+    async fn most_active_borrowers(
+        &self,
+        period: ReportPeriod,
+        limit: i64,
+    ) -> AppResult<Vec<BorrowerActivity>>;
+    /// 期間中に貸出して返却された貸出の、平均の貸出期間を返す。
+    async fn average_loan_duration(&self, period: ReportPeriod) -> AppResult<LoanDuration>;
+    /// 現在の複本の利用状況を返す。
+    async fn utilisation(&self) -> AppResult<Utilisation>;
+    /// 期間中の月ごとの貸出数を、古い月から順に返す。
+    async fn checkouts_per_month(&self, period: ReportPeriod) -> AppResult<Vec<MonthlyCheckouts>>;
+}
@@ -0,0 +1,20 @@
+use async_trait::async_trait;
+
+use shared::error::AppResult;
+
+use crate::model::id::{BookId, ReviewId};
+use crate::model::review::event::{CreateReview, DeleteReview, UpdateReview};
+use crate::model::review::Review;
+
+#[mockall::automock]
+#[async_trait]
+pub trait ReviewRepository: Send + Sync {
+    /// 借りて返却したことのある蔵書にレビューを書く。
+    async fn create(&self, event: CreateReview) -> AppResult<ReviewId>;
+    /// 蔵書のレビューを新しい順に返す。
+    async fn find_by_book_id(&self, book_id: BookId) -> AppResult<Vec<Review>>;
+    /// 自分が書いたレビューを編集する。
+    async fn update(&self, event: UpdateReview) -> AppResult<()>;
+    /// レビューを削除する。
+    async fn delete(&self, event: DeleteReview) -> AppResult<()>;
+}
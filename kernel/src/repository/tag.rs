@@ -0,0 +1,24 @@
+use async_trait::async_trait;
+
+use shared::error::AppResult;
+
+use crate::model::id::TagId;
+use crate::model::tag::event::{AttachTag, CreateTag, DeleteTag, DetachTag, UpdateTag};
+use crate::model::tag::Tag;
+
+#[mockall::automock]
+#[async_trait]
+pub trait TagRepository: Send + Sync {
+    /// すべてのタグをタグ名の順に返す。
+    async fn find_all(&self) -> AppResult<Vec<Tag>>;
+    /// タグを作成する。
+    async fn create(&self, event: CreateTag) -> AppResult<TagId>;
+    /// タグ名を変更する。
+    async fn update(&self, event: UpdateTag) -> AppResult<()>;
+    /// タグを削除する。蔵書に付けたタグも外れる。
+    async fn delete(&self, event: DeleteTag) -> AppResult<()>;
+    /// 蔵書にタグを付ける。すでに付いている場合は何もしない。
+    async fn attach(&self, event: AttachTag) -> AppResult<()>;
+    /// 蔵書からタグを外す。
+    async fn detach(&self, event: DetachTag) -> AppResult<()>;
+}
@@ -0,0 +1,24 @@
+use async_trait::async_trait;
+
+use shared::error::AppResult;
+
+use crate::model::id::UserId;
+use crate::model::transfer::event::{
+    AcceptBookTransfer, CreateBookTransfer, DeleteBookTransfer, ForceBookTransfer,
+};
+use crate::model::transfer::BookTransfer;
+
+#[mockall::automock]
+#[async_trait]
+pub trait BookTransferRepository: Send + Sync {
+    /// ユーザーが申し込んだ、またはユーザーに申し込まれた譲渡を、申し込んだ日時の順に返す。
+    async fn find_by_user_id(&self, user_id: UserId) -> AppResult<Vec<BookTransfer>>;
+    /// 蔵書の譲渡を申し込む。蔵書ごとに申し込めるのは1件のみである。
+    async fn create(&self, event: CreateBookTransfer) -> AppResult<()>;
+    /// 蔵書の所有者をすぐに変更する。蔵書への譲渡の申し込みは削除する。
+    async fn force(&self, event: ForceBookTransfer) -> AppResult<()>;
+    /// 蔵書の譲渡を承諾して、蔵書の所有者を変更する。
+    async fn accept(&self, event: AcceptBookTransfer) -> AppResult<()>;
+    /// 蔵書の譲渡の申し込みを削除する。
+    async fn delete(&self, event: DeleteBookTransfer) -> AppResult<()>;
+}
@@ -0,0 +1,299 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use derive_new::new;
+
+use shared::error::AppResult;
+
+use crate::model::id::{BookId, UserId};
+use crate::model::recommendation::{Recommendation, RecommendationCandidate, RecommendationReason};
+use crate::repository::recommendation::RecommendationRepository;
+
+/// 貸出履歴と蔵書の著者やタグから、次に読む蔵書を推薦する。
+#[mockall::automock]
+#[async_trait]
+pub trait RecommendationService: Send + Sync {
+    /// 指定した蔵書と一緒に借りられた蔵書や、似ている蔵書を推薦する。
+    ///
+    /// `user_id`のユーザーが借りたことのある蔵書は推薦しない。
+    async fn recommend_for_book(
+        &self,
+        book_id: BookId,
+        user_id: UserId,
+        limit: usize,
+    ) -> AppResult<Vec<Recommendation>>;
+    /// ユーザーが借りたことのある蔵書をもとに、まだ借りていない蔵書を推薦する。
+    async fn recommend_for_user(
+        &self,
+        user_id: UserId,
+        limit: usize,
+    ) -> AppResult<Vec<Recommendation>>;
+}
+
+#[derive(new)]
+pub struct RecommendationServiceImpl {
+    repository: Arc<dyn RecommendationRepository>,
+}
+
+impl RecommendationServiceImpl {
+    /// `seeds`をもとに候補を集め、`excluded`に含まれる蔵書を除いて推薦の強い順に並べる。
+    async fn recommend(
+        &self,
+        seeds: Vec<BookId>,
+        excluded: HashSet<BookId>,
+        limit: usize,
+    ) -> AppResult<Vec<Recommendation>> {
+        if seeds.is_empty() {
+            return Ok(vec![]);
+        }
+        let mut candidates = self
+            .repository
+            .find_borrowed_together(seeds.clone())
+            .await?;
+        candidates.extend(self.repository.find_similar(seeds).await?);
+
+        Ok(rank(candidates, &excluded, limit))
+    }
+}
+
+#[async_trait]
+impl RecommendationService for RecommendationServiceImpl {
+    async fn recommend_for_book(
+        &self,
+        book_id: BookId,
+        user_id: UserId,
+        limit: usize,
+    ) -> AppResult<Vec<Recommendation>> {
+        let mut excluded = self
+            .repository
+            .find_borrowed_book_ids(user_id)
+            .await?
+            .into_iter()
+            .collect::<HashSet<_>>();
+        excluded.insert(book_id);
+
+        self.recommend(vec![book_id], excluded, limit).await
+    }
+
+    async fn recommend_for_user(
+        &self,
+        user_id: UserId,
+        limit: usize,
+    ) -> AppResult<Vec<Recommendation>> {
+        let borrowed = self.repository.find_borrowed_book_ids(user_id).await?;
+        let excluded = borrowed.iter().copied().collect();
+
+        self.recommend(borrowed, excluded, limit).await
+    }
+}
+
+/// 推薦する理由ごとの重み
+///
+/// 実際に一緒に借りられたことを、著者やタグが共通することより重視する。
+fn weight(reason: RecommendationReason) -> i64 {
+    match reason {
+        RecommendationReason::BorrowedTogether => 3,
+        RecommendationReason::SameAuthor => 2,
+        RecommendationReason::SharedTags => 1,
+    }
+}
+
+/// 蔵書ごとに候補の理由の強さと重みを掛けた値を合計し、推薦の強い順に`limit`冊を返す。
+///
+/// 推薦の強さが同じ場合はタイトル順に並べる。
+fn rank(
+    candidates: Vec<RecommendationCandidate>,
+    excluded: &HashSet<BookId>,
+    limit: usize,
+) -> Vec<Recommendation> {
+    let mut books: HashMap<BookId, (Recommendation, BTreeSet<RecommendationReason>)> =
+        HashMap::new();
+    for candidate in candidates {
+        if excluded.contains(&candidate.book_id) {
+            continue;
+        }
+        let (recommendation, reasons) = books.entry(candidate.book_id).or_insert_with(|| {
+            let recommendation = Recommendation {
+                book_id: candidate.book_id,
+                title: candidate.title.clone(),
+                author: candidate.author.clone(),
+                isbn: candidate.isbn.clone(),
+                score: 0,
+                reasons: vec![],
+            };
+            (recommendation, BTreeSet::new())
+        });
+        recommendation.score += candidate.strength * weight(candidate.reason);
+        reasons.insert(candidate.reason);
+    }
+
+    let mut recommendations = books
+        .into_values()
+        .map(|(recommendation, reasons)| Recommendation {
+            reasons: reasons.into_iter().collect(),
+            ..recommendation
+        })
+        .collect::<Vec<_>>();
+    recommendations.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| a.title.cmp(&b.title))
+            .then_with(|| a.book_id.raw().cmp(&b.book_id.raw()))
+    });
+    recommendations.truncate(limit);
+    recommendations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::recommendation::MockRecommendationRepository;
+
+    fn candidate(
+        book_id: BookId,
+        title: &str,
+        reason: RecommendationReason,
+        strength: i64,
+    ) -> RecommendationCandidate {
+        RecommendationCandidate {
+            book_id,
+            title: title.into(),
+            author: "author".into(),
+            isbn: "isbn".into(),
+            reason,
+            strength,
+        }
+    }
+
+    fn titles(recommendations: &[Recommendation]) -> Vec<&str> {
+        recommendations.iter().map(|r| r.title.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn recommend_for_user_ranks_candidates_and_excludes_borrowed_books() -> anyhow::Result<()>
+    {
+        let user_id = UserId::new();
+        let (borrowed, other_borrowed) = (BookId::new(), BookId::new());
+        let (together, similar, both) = (BookId::new(), BookId::new(), BookId::new());
+
+        let mut mock = MockRecommendationRepository::new();
+        mock.expect_find_borrowed_book_ids()
+            .withf(move |id| *id == user_id)
+            .times(1)
+            .returning(move |_| Ok(vec![borrowed, other_borrowed]));
+        mock.expect_find_borrowed_together()
+            .withf(move |ids| *ids == vec![borrowed, other_borrowed])
+            .times(1)
+            .returning(move |_| {
+                Ok(vec![
+                    candidate(
+                        together,
+                        "together",
+                        RecommendationReason::BorrowedTogether,
+                        2,
+                    ),
+                    candidate(both, "both", RecommendationReason::BorrowedTogether, 1),
+                    // 借りたことのある蔵書同士も一緒に借りられた候補になるが、推薦しない。
+                    candidate(
+                        other_borrowed,
+                        "borrowed",
+                        RecommendationReason::BorrowedTogether,
+                        5,
+                    ),
+                ])
+            });
+        mock.expect_find_similar().times(1).returning(move |_| {
+            Ok(vec![
+                candidate(similar, "similar", RecommendationReason::SharedTags, 2),
+                candidate(both, "both", RecommendationReason::SameAuthor, 1),
+                candidate(both, "both", RecommendationReason::SharedTags, 1),
+            ])
+        });
+
+        let service = RecommendationServiceImpl::new(Arc::new(mock));
+        let recommendations = service.recommend_for_user(user_id, 10).await?;
+
+        // together: 2 * 3, both: 1 * 3 + 1 * 2 + 1 * 1, similar: 2 * 1
+        assert_eq!(
+            titles(&recommendations),
+            vec!["both", "together", "similar"]
+        );
+        assert_eq!(
+            recommendations.iter().map(|r| r.score).collect::<Vec<_>>(),
+            vec![6, 6, 2]
+        );
+        assert_eq!(
+            recommendations[0].reasons,
+            vec![
+                RecommendationReason::BorrowedTogether,
+                RecommendationReason::SameAuthor,
+                RecommendationReason::SharedTags,
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn recommend_for_user_without_history_returns_nothing() -> anyhow::Result<()> {
+        let mut mock = MockRecommendationRepository::new();
+        mock.expect_find_borrowed_book_ids()
+            .times(1)
+            .returning(|_| Ok(vec![]));
+        mock.expect_find_borrowed_together().times(0);
+        mock.expect_find_similar().times(0);
+
+        let service = RecommendationServiceImpl::new(Arc::new(mock));
+        assert!(service
+            .recommend_for_user(UserId::new(), 10)
+            .await?
+            .is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn recommend_for_book_excludes_books_the_user_borrowed() -> anyhow::Result<()> {
+        let (book_id, borrowed) = (BookId::new(), BookId::new());
+        let (first, second, third) = (BookId::new(), BookId::new(), BookId::new());
+
+        let mut mock = MockRecommendationRepository::new();
+        mock.expect_find_borrowed_book_ids()
+            .times(1)
+            .returning(move |_| Ok(vec![book_id, borrowed]));
+        mock.expect_find_borrowed_together()
+            .withf(move |ids| *ids == vec![book_id])
+            .times(1)
+            .returning(move |_| {
+                Ok(vec![
+                    candidate(
+                        borrowed,
+                        "borrowed",
+                        RecommendationReason::BorrowedTogether,
+                        9,
+                    ),
+                    candidate(second, "second", RecommendationReason::BorrowedTogether, 1),
+                ])
+            });
+        mock.expect_find_similar()
+            .withf(move |ids| *ids == vec![book_id])
+            .times(1)
+            .returning(move |_| {
+                Ok(vec![
+                    candidate(third, "third", RecommendationReason::SharedTags, 1),
+                    candidate(first, "first", RecommendationReason::SameAuthor, 1),
+                    candidate(first, "first", RecommendationReason::SharedTags, 3),
+                ])
+            });
+
+        let service = RecommendationServiceImpl::new(Arc::new(mock));
+        let recommendations = service
+            .recommend_for_book(book_id, UserId::new(), 2)
+            .await?;
+
+        assert_eq!(titles(&recommendations), vec!["first", "second"]);
+
+        Ok(())
+    }
+}
@@ -1,17 +1,37 @@
 use std::sync::Arc;
 
+use adapter::blob::LocalBlobStore;
 use adapter::database::ConnectionPool;
 use adapter::redis::RedisClient;
+use adapter::repository::audit::AuditEventRepositoryImpl;
 use adapter::repository::auth::AuthRepositoryImpl;
 use adapter::repository::book::BookRepositoryImpl;
 use adapter::repository::checkout::CheckoutRepositoryImpl;
+use adapter::repository::cover::BookCoverRepositoryImpl;
 use adapter::repository::health::HealthCheckRepositoryImpl;
+use adapter::repository::hold::HoldRepositoryImpl;
+use adapter::repository::loan_policy::LoanPolicyRepositoryImpl;
+use adapter::repository::recommendation::RecommendationRepositoryImpl;
+use adapter::repository::report::ReportRepositoryImpl;
+use adapter::repository::review::ReviewRepositoryImpl;
+use adapter::repository::tag::TagRepositoryImpl;
+use adapter::repository::transfer::BookTransferRepositoryImpl;
 use adapter::repository::user::UserRepositoryImpl;
+use kernel::repository::audit::AuditEventRepository;
 use kernel::repository::auth::AuthRepository;
+use kernel::repository::blob::BlobStore;
 use kernel::repository::book::BookRepository;
 use kernel::repository::checkout::CheckoutRepository;
+use kernel::repository::cover::BookCoverRepository;
 use kernel::repository::health::HealthCheckRepository;
+use kernel::repository::hold::HoldRepository;
+use kernel::repository::loan_policy::LoanPolicyRepository;
+use kernel::repository::report::ReportRepository;
+use kernel::repository::review::ReviewRepository;
+use kernel::repository::tag::TagRepository;
+use kernel::repository::transfer::BookTransferRepository;
 use kernel::repository::user::UserRepository;
+use kernel::service::recommendation::{RecommendationService, RecommendationServiceImpl};
 use shared::config::AppConfig;
 
 pub type AppRegistry = Arc<dyn AppRegistryExt + Send + Sync + 'static>;
@@ -20,9 +40,18 @@ pub type AppRegistry = Arc<dyn AppRegistryExt + Send + Sync + 'static>;
 pub trait AppRegistryExt {
     fn health_check_repository(&self) -> Arc<dyn HealthCheckRepository>;
     fn book_repository(&self) -> Arc<dyn BookRepository>;
+    fn book_cover_repository(&self) -> Arc<dyn BookCoverRepository>;
+    fn book_transfer_repository(&self) -> Arc<dyn BookTransferRepository>;
     fn auth_repository(&self) -> Arc<dyn AuthRepository>;
     fn checkout_repository(&self) -> Arc<dyn CheckoutRepository>;
+    fn hold_repository(&self) -> Arc<dyn HoldRepository>;
+    fn review_repository(&self) -> Arc<dyn ReviewRepository>;
+    fn loan_policy_repository(&self) -> Arc<dyn LoanPolicyRepository>;
+    fn tag_repository(&self) -> Arc<dyn TagRepository>;
     fn user_repository(&self) -> Arc<dyn UserRepository>;
+    fn audit_event_repository(&self) -> Arc<dyn AuditEventRepository>;
+    fn report_repository(&self) -> Arc<dyn ReportRepository>;
+    fn recommendation_service(&self) -> Arc<dyn RecommendationService>;
 }
 
 /// DIコンテナ
@@ -30,9 +59,18 @@ pub trait AppRegistryExt {
 pub struct AppRegistryImpl {
     health_check_repository: Arc<dyn HealthCheckRepository>,
     book_repository: Arc<dyn BookRepository>,
+    book_cover_repository: Arc<dyn BookCoverRepository>,
+    book_transfer_repository: Arc<dyn BookTransferRepository>,
     auth_repository: Arc<dyn AuthRepository>,
     user_repository: Arc<dyn UserRepository>,
     checkout_repository: Arc<dyn CheckoutRepository>,
+    hold_repository: Arc<dyn HoldRepository>,
+    review_repository: Arc<dyn ReviewRepository>,
+    loan_policy_repository: Arc<dyn LoanPolicyRepository>,
+    tag_repository: Arc<dyn TagRepository>,
+    audit_event_repository: Arc<dyn AuditEventRepository>,
+    report_repository: Arc<dyn ReportRepository>,
+    recommendation_service: Arc<dyn RecommendationService>,
 }
 
 impl AppRegistryImpl {
@@ -43,16 +81,39 @@ impl AppRegistryImpl {
     ) -> Self {
         let health_check_repository = HealthCheckRepositoryImpl::new(pool.clone());
         let book_repository = BookRepositoryImpl::new(pool.clone());
+        let blob_store: Arc<dyn BlobStore> = Arc::new(LocalBlobStore::new(&app_config.blob_store));
+        let book_cover_repository = BookCoverRepositoryImpl::new(pool.clone(), blob_store);
+        let book_transfer_repository = BookTransferRepositoryImpl::new(pool.clone());
         let auth_repository =
             AuthRepositoryImpl::new(pool.clone(), Arc::clone(&redis_client), app_config.auth.ttl);
         let user_repository = UserRepositoryImpl::new(pool.clone());
-        let checkout_repository = CheckoutRepositoryImpl::new(pool.clone());
+        let checkout_repository =
+            CheckoutRepositoryImpl::new(pool.clone(), app_config.checkout.clone());
+        let hold_repository = HoldRepositoryImpl::new(pool.clone(), app_config.checkout.clone());
+        let loan_policy_repository =
+            LoanPolicyRepositoryImpl::new(pool.clone(), app_config.checkout);
+        let review_repository = ReviewRepositoryImpl::new(pool.clone());
+        let tag_repository = TagRepositoryImpl::new(pool.clone());
+        let audit_event_repository = AuditEventRepositoryImpl::new(pool.clone());
+        let report_repository = ReportRepositoryImpl::new(pool.clone());
+        let recommendation_service = RecommendationServiceImpl::new(Arc::new(
+            RecommendationRepositoryImpl::new(pool.clone()),
+        ));
         Self {
             health_check_repository: Arc::new(health_check_repository),
             book_repository: Arc::new(book_repository),
+            book_cover_repository: Arc::new(book_cover_repository),
+            book_transfer_repository: Arc::new(book_transfer_repository),
             auth_repository: Arc::new(auth_repository),
             user_repository: Arc::new(user_repository),
             checkout_repository: Arc::new(checkout_repository),
+            hold_repository: Arc::new(hold_repository),
+            review_repository: Arc::new(review_repository),
+            loan_policy_repository: Arc::new(loan_policy_repository),
+            tag_repository: Arc::new(tag_repository),
+            audit_event_repository: Arc::new(audit_event_repository),
+            report_repository: Arc::new(report_repository),
+            recommendation_service: Arc::new(recommendation_service),
         }
     }
 }
@@ -66,6 +127,14 @@ impl AppRegistryExt for AppRegistryImpl {
         Arc::clone(&self.book_repository)
     }
 
+    fn book_cover_repository(&self) -> Arc<dyn BookCoverRepository> {
+        Arc::clone(&self.book_cover_repository)
+    }
+
+    fn book_transfer_repository(&self) -> Arc<dyn BookTransferRepository> {
+        Arc::clone(&self.book_transfer_repository)
+    }
+
     fn auth_repository(&self) -> Arc<dyn AuthRepository> {
         Arc::clone(&self.auth_repository)
     }
@@ -74,7 +143,35 @@ impl AppRegistryExt for AppRegistryImpl {
         Arc::clone(&self.checkout_repository)
     }
 
+    fn hold_repository(&self) -> Arc<dyn HoldRepository> {
+        Arc::clone(&self.hold_repository)
+    }
+
+    fn review_repository(&self) -> Arc<dyn ReviewRepository> {
+        Arc::clone(&self.review_repository)
+    }
+
+    fn loan_policy_repository(&self) -> Arc<dyn LoanPolicyRepository> {
+        Arc::clone(&self.loan_policy_repository)
+    }
+
+    fn tag_repository(&self) -> Arc<dyn TagRepository> {
+        Arc::clone(&self.tag_repository)
+    }
+
     fn user_repository(&self) -> Arc<dyn UserRepository> {
         Arc::clone(&self.user_repository)
     }
+
+    fn audit_event_repository(&self) -> Arc<dyn AuditEventRepository> {
+        Arc::clone(&self.audit_event_repository)
+    }
+
+    fn report_repository(&self) -> Arc<dyn ReportRepository> {
+        Arc::clone(&self.report_repository)
+    }
+
+    fn recommendation_service(&self) -> Arc<dyn RecommendationService> {
+        Arc::clone(&self.recommendation_service)
+    }
 }
@@ -2,6 +2,8 @@ pub struct AppConfig {
     pub database: DatabaseConfig,
     pub redis: RedisConfig,
     pub auth: AuthConfig,
+    pub checkout: CheckoutConfig,
+    pub blob_store: BlobStoreConfig,
 }
 
 impl AppConfig {
@@ -20,10 +22,20 @@ impl AppConfig {
         let auth = AuthConfig {
             ttl: std::env::var("AUTH_TOKEN_TTL")?.parse::<u64>()?,
         };
+        let checkout = CheckoutConfig {
+            loan_period_days: std::env::var("CHECKOUT_LOAN_PERIOD_DAYS")?.parse::<i32>()?,
+            max_renewals: std::env::var("CHECKOUT_MAX_RENEWALS")?.parse::<i32>()?,
+            hold_pickup_hours: std::env::var("CHECKOUT_HOLD_PICKUP_HOURS")?.parse::<i64>()?,
+        };
+        let blob_store = BlobStoreConfig {
+            local_root: std::env::var("BLOB_STORE_LOCAL_ROOT")?,
+        };
         Ok(Self {
             database,
             redis,
             auth,
+            checkout,
+            blob_store,
         })
     }
 }
@@ -44,3 +56,18 @@ pub struct RedisConfig {
 pub struct AuthConfig {
     pub ttl: u64,
 }
+
+#[derive(Clone)]
+pub struct CheckoutConfig {
+    /// 貸出日時から返却期限までの日数 (ロールに貸出ポリシーが登録されていない場合に使用)
+    pub loan_period_days: i32,
+    /// 1回の貸出で返却期限を延長できる回数の上限 (ロールに貸出ポリシーが登録されていない場合に使用)
+    pub max_renewals: i32,
+    /// 返却された蔵書を予約の待ち行列の先頭のユーザーのために取り置く時間
+    pub hold_pickup_hours: i64,
+}
+
+pub struct BlobStoreConfig {
+    /// 表紙画像などを保存するディレクトリ
+    pub local_root: String,
+}